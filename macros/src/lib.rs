@@ -0,0 +1,391 @@
+//! Attribute macro for [safe_index](https://docs.rs/safe_index), see [`macro@index`].
+//!
+//! Do not depend on this crate directly, activate the `macros` feature of safe_index instead.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{
+    parse::ParseStream, parse_macro_input, punctuated::Punctuated, spanned::Spanned, Expr, ExprLit,
+    Fields, Ident, ItemStruct, Lit, LitStr, Meta, Token, Visibility,
+};
+
+/// Options of the attribute: key, keywords of the corresponding `new` option, and whether the
+/// option takes a capacity.
+const OPTIONS: &[(&str, &str, bool)] = &[
+    ("map", "map", false),
+    ("small_map", "small map", true),
+    ("array_map", "array map", true),
+    ("slice", "slice", false),
+    ("range", "range", false),
+    ("heap", "heap", false),
+    ("union_find", "union find", false),
+    ("interner", "interner", false),
+    ("sparse_map", "sparse map", false),
+    ("deque_map", "deque map", false),
+    ("shared_map", "shared map", false),
+    ("slot_map", "slot map", false),
+    ("counter", "counter", false),
+    ("atomic_counter", "atomic counter", false),
+    ("btree_set", "btree set", false),
+    ("btree_set_struct", "btree set struct", false),
+    ("btree_map", "btree map", false),
+    ("btree_map_struct", "btree map struct", false),
+    ("bit_set", "bit set", false),
+    ("interval_set", "interval set", false),
+    ("hash_set", "hash set", false),
+    ("hash_map", "hash map", false),
+];
+
+/// Options generated by `full`: key, and suffix appended to the prefix to get the name.
+const FULL: &[(&str, &str)] = &[
+    ("map", "Map"),
+    ("btree_set", "BSet"),
+    ("btree_map", "BMap"),
+    ("range", "Range"),
+];
+
+/// Turns a unit struct into an index type, equivalent to safe_index's `new` macro.
+///
+/// Each argument is an option of `new` written in snake case (`btree set` becomes `btree_set`),
+/// with the name of the type to generate as a string. Options `small_map` and `array_map` expect
+/// a capacity after the name (`"Name[4]"`). The attributes of the struct, including its
+/// documentation, go to the index type.
+///
+/// ```rust,ignore
+/// #[safe_index::index(map = "Clients", btree_set = "ClientSet", range = "ClientRange")]
+/// /// Indices of clients.
+/// pub struct Client;
+/// ```
+///
+/// is the same as
+///
+/// ```rust,ignore
+/// safe_index::new! {
+///     /// Indices of clients.
+///     Client,
+///     map: Clients,
+///     btree set: ClientSet,
+///     range: ClientRange,
+/// }
+/// ```
+///
+/// Argument `full` is a shorthand for the options most index families need, with conventional
+/// names derived from the name of the index type: `#[safe_index::index(full)] pub struct Client;`
+/// generates map `ClientMap`, btree set `ClientBSet`, btree map `ClientBMap` and range
+/// `ClientRange`. Use `full = "Prefix"` to derive the names from another prefix. Other options
+/// can appear next to `full`, as long as their names are different and there is no other `range`.
+///
+/// Unlike `new`, this attribute checks its options before generating anything: unknown options,
+/// invalid names and names used twice are reported on the faulty argument.
+#[proc_macro_attribute]
+pub fn index(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr with Punctuated::<Meta, Token![,]>::parse_terminated);
+    let item = parse_macro_input!(item as ItemStruct);
+    match expand(args, item) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Expands the `full: Prefix` option of safe_index's `new` macro, do not use it directly.
+///
+/// Takes the index declaration between brackets, the prefix, and the other options between
+/// brackets, and invokes `new` with the options of [`FULL`] named after the prefix first.
+#[doc(hidden)]
+#[proc_macro]
+pub fn __new_full(input: TokenStream) -> TokenStream {
+    match syn::parse::Parser::parse(expand_full, input) {
+        Ok(tokens) => tokens.into(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Generates the invocation of `new` corresponding to `full: Prefix`.
+fn expand_full(input: ParseStream) -> syn::Result<TokenStream2> {
+    let idx;
+    syn::bracketed!(idx in input);
+    let idx: TokenStream2 = idx.parse()?;
+    let prefix: Ident = input.parse()?;
+    let tail;
+    syn::bracketed!(tail in input);
+    let tail: TokenStream2 = tail.parse()?;
+
+    let options = FULL.iter().map(|(key, suffix)| {
+        let keywords = match OPTIONS.iter().find(|(name, _, _)| name == key) {
+            Some((_, keywords, _)) => keywords,
+            None => unreachable!("unknown option `{}`", key),
+        };
+        let keywords = keywords.split(' ').map(|kw| Ident::new(kw, prefix.span()));
+        let name = Ident::new(&format!("{}{}", prefix, suffix), prefix.span());
+        quote!(#(#keywords)* : #name,)
+    });
+    Ok(quote! {
+        ::safe_index::new! {
+            #idx,
+            #(#options)*
+            #tail
+        }
+    })
+}
+
+/// Accumulates errors so that all of them are reported at once.
+#[derive(Default)]
+struct Errors {
+    errors: Option<syn::Error>,
+}
+impl Errors {
+    /// Adds an error.
+    fn push(&mut self, error: syn::Error) {
+        match &mut self.errors {
+            Some(errors) => errors.combine(error),
+            None => self.errors = Some(error),
+        }
+    }
+    /// Fails if any error was pushed.
+    fn check(self) -> syn::Result<()> {
+        match self.errors {
+            Some(errors) => Err(errors),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Generates the invocation of `new` corresponding to an attribute and its struct.
+fn expand(args: Punctuated<Meta, Token![,]>, item: ItemStruct) -> syn::Result<TokenStream2> {
+    let mut errors = Errors::default();
+
+    if !matches!(item.vis, Visibility::Public(_)) {
+        errors.push(syn::Error::new_spanned(
+            item.struct_token,
+            "index types are always public, use `pub struct`",
+        ))
+    }
+    if !item.generics.params.is_empty() || item.generics.where_clause.is_some() {
+        errors.push(syn::Error::new_spanned(
+            &item.generics,
+            "index types cannot have generic parameters",
+        ))
+    }
+    if !matches!(item.fields, Fields::Unit) {
+        errors.push(syn::Error::new_spanned(
+            &item.fields,
+            "index types wrap a `usize` themselves, expected a unit struct such as `pub struct Idx;`",
+        ))
+    }
+
+    let idx = &item.ident;
+    let mut options = Options {
+        idx,
+        names: vec![idx.clone()],
+        range: None,
+        tokens: Vec::with_capacity(args.len()),
+        errors,
+    };
+
+    for arg in &args {
+        let (path, value) = match arg {
+            Meta::NameValue(arg) => (&arg.path, Some(&arg.value)),
+            Meta::Path(path) => (path, None),
+            Meta::List(list) => (&list.path, None),
+        };
+        let key = match path.get_ident() {
+            Some(key) => key,
+            None => {
+                options
+                    .errors
+                    .push(syn::Error::new_spanned(path, unknown_option_msg()));
+                continue;
+            }
+        };
+
+        if key == "full" {
+            let prefix = match (arg, value) {
+                (Meta::Path(_), _) => idx.clone(),
+                (_, Some(value)) => {
+                    match name_of(key, value).and_then(|lit| match parse_name(lit)? {
+                        (prefix, None) => Ok(prefix),
+                        (_, Some(capacity)) => Err(syn::Error::new_spanned(
+                            capacity,
+                            "`full` does not take a capacity",
+                        )),
+                    }) {
+                        Ok(prefix) => prefix,
+                        Err(e) => {
+                            options.errors.push(e);
+                            continue;
+                        }
+                    }
+                }
+                _ => {
+                    options.errors.push(syn::Error::new_spanned(
+                        arg,
+                        "expected `full` or `full = \"Prefix\"`",
+                    ));
+                    continue;
+                }
+            };
+            for (key, suffix) in FULL {
+                let name = Ident::new(&format!("{}{}", prefix, suffix), prefix.span());
+                options.add(&Ident::new(key, path.span()), path.span(), name, None);
+            }
+            continue;
+        }
+
+        if !OPTIONS.iter().any(|(name, _, _)| key == name) {
+            options
+                .errors
+                .push(syn::Error::new_spanned(key, unknown_option_msg()));
+            continue;
+        }
+        let lit = match value {
+            Some(value) => match name_of(key, value) {
+                Ok(lit) => lit,
+                Err(e) => {
+                    options.errors.push(e);
+                    continue;
+                }
+            },
+            None => {
+                options.errors.push(syn::Error::new_spanned(
+                    arg,
+                    format!("expected `{} = \"Name\"`", key),
+                ));
+                continue;
+            }
+        };
+        match parse_name(lit) {
+            Ok((name, capacity)) => options.add(key, lit.span(), name, capacity),
+            Err(e) => options.errors.push(e),
+        }
+    }
+
+    let Options {
+        tokens: options,
+        errors,
+        ..
+    } = options;
+    errors.check()?;
+
+    let attrs = &item.attrs;
+    Ok(quote! {
+        ::safe_index::new! {
+            #(#attrs)*
+            #idx,
+            #(#options)*
+        }
+    })
+}
+
+/// Options checked so far, and the `new` options they produce.
+struct Options<'a> {
+    /// Index type.
+    idx: &'a Ident,
+    /// Names of the index type and of the options so far.
+    names: Vec<Ident>,
+    /// Key of the first `range`, if any.
+    range: Option<Ident>,
+    /// Options of `new`.
+    tokens: Vec<TokenStream2>,
+    /// Errors so far.
+    errors: Errors,
+}
+impl Options<'_> {
+    /// Checks an option and adds it, `span` is where errors about its name go.
+    fn add(&mut self, key: &Ident, span: Span, name: Ident, capacity: Option<Expr>) {
+        let (keywords, has_capacity) = match OPTIONS.iter().find(|(name, _, _)| key == name) {
+            Some((_, keywords, has_capacity)) => (*keywords, *has_capacity),
+            None => unreachable!("unknown option `{}`", key),
+        };
+        if key == "range" {
+            if let Some(first) = &self.range {
+                let mut error = syn::Error::new_spanned(key, "`range` can only appear once");
+                error.combine(syn::Error::new_spanned(first, "first `range` here"));
+                self.errors.push(error);
+                return;
+            }
+            self.range = Some(key.clone());
+        }
+
+        match (has_capacity, &capacity) {
+            (true, None) => self.errors.push(syn::Error::new(
+                span,
+                format!(
+                    "`{}` expects a capacity after the name, for instance `\"{}[4]\"`",
+                    key, name,
+                ),
+            )),
+            (false, Some(capacity)) => self.errors.push(syn::Error::new_spanned(
+                capacity,
+                format!("`{}` does not take a capacity", key),
+            )),
+            _ => (),
+        }
+        if let Some(prev) = self.names.iter().find(|prev| **prev == name) {
+            let what = if prev == self.idx {
+                "the index type"
+            } else {
+                "another option"
+            };
+            self.errors.push(syn::Error::new(
+                span,
+                format!("`{}` is already the name of {}", name, what),
+            ));
+            return;
+        }
+        self.names.push(name.clone());
+
+        let keywords = keywords.split(' ').map(|kw| Ident::new(kw, key.span()));
+        let capacity = capacity.map(|capacity| quote!([#capacity]));
+        self.tokens.push(quote!(#(#keywords)* : #name #capacity,));
+    }
+}
+
+/// Value of an option, which must be a string.
+fn name_of<'a>(key: &Ident, value: &'a Expr) -> syn::Result<&'a LitStr> {
+    match value {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(lit), ..
+        }) => Ok(lit),
+        value => Err(syn::Error::new_spanned(
+            value,
+            format!("expected the name of the `{}` as a string", key),
+        )),
+    }
+}
+
+/// Parses the value of an option: a name and an optional capacity between brackets.
+fn parse_name(lit: &LitStr) -> syn::Result<(Ident, Option<Expr>)> {
+    lit.parse_with(|input: ParseStream| {
+        let name: Ident = input.parse()?;
+        let capacity = if input.peek(syn::token::Bracket) {
+            let content;
+            syn::bracketed!(content in input);
+            Some(content.parse()?)
+        } else {
+            None
+        };
+        if !input.is_empty() {
+            return Err(
+                input.error("expected a single type name, optionally followed by `[capacity]`")
+            );
+        }
+        Ok((name, capacity))
+    })
+}
+
+/// Error message for unknown options.
+fn unknown_option_msg() -> String {
+    let mut msg = String::from("unknown option, expected `full`, ");
+    for (i, (key, _, _)) in OPTIONS.iter().enumerate() {
+        if i + 1 == OPTIONS.len() {
+            msg.push_str(" or ");
+        } else if i > 0 {
+            msg.push_str(", ");
+        }
+        msg.push('`');
+        msg.push_str(key);
+        msg.push('`');
+    }
+    msg
+}
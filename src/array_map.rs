@@ -0,0 +1,282 @@
+//! Handles array map code generation.
+
+/// Array map code generation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! array_map_codegen {
+    ($t:ident,
+        $(#[$meta:meta])*
+        $map:ident [$n:expr]
+        $($tail:tt)*
+    ) => {
+        $(#[$meta])*
+        ///
+        /// Map storing at most
+        #[doc = stringify!($n)]
+        /// elements in an array, never allocates. Only relies on `core`.
+        pub struct $map<T> {
+            /// Elements, only the first `len` ones are initialized.
+            buf: [core::mem::MaybeUninit<T>; $n],
+            /// Number of elements.
+            len: usize,
+        }
+
+        impl<T> core::default::Default for $map<T> {
+            #[inline]
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<T> $map<T> {
+            /// Maximum number of elements in the map.
+            pub const CAPACITY: usize = $n;
+
+            /// Creates an empty map.
+            #[inline]
+            pub const fn new() -> Self {
+                $map {
+                    // Sound: an array of `MaybeUninit`s does not need to be initialized.
+                    buf: unsafe {
+                        core::mem::MaybeUninit::<[core::mem::MaybeUninit<T>; $n]>::uninit()
+                            .assume_init()
+                    },
+                    len: 0,
+                }
+            }
+
+            /// Number of elements in the map.
+            #[inline]
+            pub const fn len(&self) -> usize {
+                self.len
+            }
+            /// True if the map is empty.
+            #[inline]
+            pub const fn is_empty(&self) -> bool {
+                self.len == 0
+            }
+            /// True if the map is full, *i.e.* pushing fails.
+            #[inline]
+            pub const fn is_full(&self) -> bool {
+                self.len == $n
+            }
+
+            /// Generates an index from a [`usize`] when it is a legal index.
+            #[inline]
+            pub fn index_from_usize(&self, n: usize) -> Option<$t> {
+                if n < self.len {
+                    Some($t { val: n })
+                } else {
+                    None
+                }
+            }
+            /// Index of the last element in the map.
+            #[inline]
+            pub fn last_index(&self) -> Option<$t> {
+                if self.len > 0 { Some($t { val: self.len - 1 }) } else { None }
+            }
+
+            /// Pushes an element, yields its index, or gives the element back if the map is full.
+            #[inline]
+            pub fn push(&mut self, elem: T) -> Result<$t, T> {
+                if self.len == $n {
+                    return Err(elem);
+                }
+                self.buf[self.len] = core::mem::MaybeUninit::new(elem);
+                let idx = $t { val: self.len };
+                self.len += 1;
+                Ok(idx)
+            }
+            /// Removes the last element, yields it with its index.
+            #[inline]
+            pub fn pop(&mut self) -> Option<($t, T)> {
+                if self.len == 0 {
+                    return None;
+                }
+                self.len -= 1;
+                // Sound: the element was initialized, and is not part of the map anymore.
+                let elem = unsafe { self.buf[self.len].as_ptr().read() };
+                Some(($t { val: self.len }, elem))
+            }
+            /// Removes and drops all the elements.
+            #[inline]
+            pub fn clear(&mut self) {
+                let elems: *mut [T] = self.as_mut_slice();
+                // Forget the elements first so that a panicking `drop` cannot cause a double drop.
+                self.len = 0;
+                // Sound: the elements were initialized, and are not part of the map anymore.
+                unsafe { core::ptr::drop_in_place(elems) }
+            }
+
+            /// Slice of the elements.
+            #[inline]
+            pub fn as_slice(&self) -> &[T] {
+                // Sound: the first `len` elements are initialized.
+                unsafe { core::slice::from_raw_parts(self.buf.as_ptr() as *const T, self.len) }
+            }
+            /// Mutable slice of the elements.
+            #[inline]
+            pub fn as_mut_slice(&mut self) -> &mut [T] {
+                // Sound: the first `len` elements are initialized.
+                unsafe {
+                    core::slice::from_raw_parts_mut(self.buf.as_mut_ptr() as *mut T, self.len)
+                }
+            }
+
+            /// Retrieves an entry in the map.
+            #[inline]
+            pub fn get(&self, idx: impl core::borrow::Borrow<$t>) -> Option<&T> {
+                self.as_slice().get(idx.borrow().val)
+            }
+            /// Retrieves an entry in the map.
+            #[inline]
+            pub fn get_mut(&mut self, idx: impl core::borrow::Borrow<$t>) -> Option<&mut T> {
+                self.as_mut_slice().get_mut(idx.borrow().val)
+            }
+
+            /// Iterator over all the indices.
+            #[inline]
+            pub fn indices(&self) ->
+                impl core::iter::DoubleEndedIterator<Item = $t>
+                + core::iter::ExactSizeIterator
+                + core::iter::FusedIterator
+            {
+                (0..self.len).map(|val| $t { val })
+            }
+            /// Ref-iterator over the elements.
+            #[inline]
+            pub fn iter(&self) -> core::slice::Iter<'_, T> {
+                self.as_slice().iter()
+            }
+            /// Ref-mut-iterator over the elements.
+            #[inline]
+            pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, T> {
+                self.as_mut_slice().iter_mut()
+            }
+            /// Ref-iterator over the index/element pairs.
+            #[inline]
+            pub fn index_iter<'a>(&'a self) ->
+                impl core::iter::DoubleEndedIterator<Item = ($t, &'a T)>
+                + core::iter::ExactSizeIterator
+                + core::iter::FusedIterator
+            where T: 'a {
+                self.iter().enumerate().map(|(val, elem)| ($t { val }, elem))
+            }
+            /// Ref-mut-iterator over the index/element pairs.
+            #[inline]
+            pub fn index_iter_mut<'a>(&'a mut self) ->
+                impl core::iter::DoubleEndedIterator<Item = ($t, &'a mut T)>
+                + core::iter::ExactSizeIterator
+                + core::iter::FusedIterator
+            where T: 'a {
+                self.iter_mut().enumerate().map(|(val, elem)| ($t { val }, elem))
+            }
+        }
+
+        impl<T> core::ops::Drop for $map<T> {
+            #[inline]
+            fn drop(&mut self) {
+                self.clear()
+            }
+        }
+        impl<T: Clone> core::clone::Clone for $map<T> {
+            fn clone(&self) -> Self {
+                let mut map = Self::new();
+                for elem in self.iter() {
+                    let _ = map.push(elem.clone());
+                }
+                map
+            }
+        }
+        impl<T: core::fmt::Debug> core::fmt::Debug for $map<T> {
+            fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+                fmt.debug_list().entries(self.iter()).finish()
+            }
+        }
+        impl<T: PartialEq> PartialEq for $map<T> {
+            #[inline]
+            fn eq(&self, other: &Self) -> bool {
+                self.as_slice() == other.as_slice()
+            }
+        }
+        impl<T: Eq> Eq for $map<T> {}
+        impl<T: core::hash::Hash> core::hash::Hash for $map<T> {
+            #[inline]
+            fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+                self.as_slice().hash(state)
+            }
+        }
+
+        impl<'a, T> core::iter::IntoIterator for &'a $map<T> {
+            type Item = &'a T;
+            type IntoIter = core::slice::Iter<'a, T>;
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+        impl<'a, T> core::iter::IntoIterator for &'a mut $map<T> {
+            type Item = &'a mut T;
+            type IntoIter = core::slice::IterMut<'a, T>;
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter_mut()
+            }
+        }
+
+        impl<T> core::ops::Index<$t> for $map<T> {
+            type Output = T;
+            #[inline]
+            #[track_caller]
+            fn index(&self, index: $t) -> &T {
+                match self.as_slice().get(index.val) {
+                    Some(elem) => elem,
+                    None => $crate::oob::index(
+                        stringify!($t), index.val, stringify!($map), self.len
+                    ),
+                }
+            }
+        }
+        impl<T> core::ops::IndexMut<$t> for $map<T> {
+            #[inline]
+            #[track_caller]
+            fn index_mut(&mut self, index: $t) -> &mut T {
+                let len = self.len;
+                match self.as_mut_slice().get_mut(index.val) {
+                    Some(elem) => elem,
+                    None => $crate::oob::index(stringify!($t), index.val, stringify!($map), len),
+                }
+            }
+        }
+        impl<'a, T> core::ops::Index<&'a $t> for $map<T> {
+            type Output = T;
+            #[inline]
+            #[track_caller]
+            fn index(&self, index: &'a $t) -> &T {
+                &self[*index]
+            }
+        }
+        impl<'a, T> core::ops::IndexMut<&'a $t> for $map<T> {
+            #[inline]
+            #[track_caller]
+            fn index_mut(&mut self, index: &'a $t) -> &mut T {
+                &mut self[*index]
+            }
+        }
+
+        $crate::handle!{ $t $($tail)* }
+    };
+    ($t:ident,
+        $(#[$meta:meta])*
+        $map:ident
+        $($tail:tt)*
+    ) => {
+        compile_error!(concat!(
+            "`array map: ",
+            stringify!($map),
+            "` expects a capacity after the name, for instance `array map: ",
+            stringify!($map),
+            "[4]`",
+        ));
+    };
+}
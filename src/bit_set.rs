@@ -0,0 +1,213 @@
+//! Handles bit set code generation.
+
+/// Bit set code generation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! bit_set_codegen {
+    ($t:ident,
+        $(#[$meta:meta])*
+        $set:ident
+        $($tail:tt)*
+    ) => {
+        $(#[$meta])*
+        #[derive(Default, Clone)]
+        pub struct $set {
+            words: $crate::alloc::vec::Vec<u64>,
+        }
+
+        impl $set {
+            /// Creates an empty set.
+            #[inline]
+            pub const fn new() -> Self {
+                $set {
+                    words: $crate::alloc::vec::Vec::new(),
+                }
+            }
+            /// Word and bit mask of an index.
+            #[inline]
+            fn locate(idx: $t) -> (usize, u64) {
+                (idx.val / 64, 1 << (idx.val % 64))
+            }
+            /// Makes room for all indices up to `idx` included.
+            pub fn grow_to(&mut self, idx: $t) {
+                let (word, _) = Self::locate(idx);
+                if word >= self.words.len() {
+                    self.words.resize(word + 1, 0)
+                }
+            }
+            /// Number of indices in the set.
+            #[inline]
+            pub fn len(&self) -> usize {
+                self.words.iter().map(|word| word.count_ones() as usize).sum()
+            }
+            /// True if the set is empty.
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.words.iter().all(|word| *word == 0)
+            }
+            /// Inserts an index, returns `true` if it was not already there.
+            #[inline]
+            pub fn insert(&mut self, idx: $t) -> bool {
+                self.grow_to(idx);
+                let (word, mask) = Self::locate(idx);
+                let is_new = self.words[word] & mask == 0;
+                self.words[word] |= mask;
+                is_new
+            }
+            /// Removes an index, returns `true` if it was there.
+            #[inline]
+            pub fn remove(&mut self, idx: impl core::borrow::Borrow<$t>) -> bool {
+                let (word, mask) = Self::locate(*idx.borrow());
+                match self.words.get_mut(word) {
+                    Some(word) if *word & mask != 0 => {
+                        *word &= !mask;
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            /// True if the set contains an index.
+            #[inline]
+            pub fn contains(&self, idx: impl core::borrow::Borrow<$t>) -> bool {
+                let (word, mask) = Self::locate(*idx.borrow());
+                self.words.get(word).map_or(false, |word| word & mask != 0)
+            }
+            /// Removes all the indices, keeps the memory.
+            #[inline]
+            pub fn clear(&mut self) {
+                self.words.iter_mut().for_each(|word| *word = 0)
+            }
+            /// Iterator over the indices, in ascending order.
+            pub fn iter(&self) -> impl Iterator<Item = $t> + '_ {
+                self.words.iter().enumerate().flat_map(|(word_idx, word)| {
+                    let mut word = *word;
+                    core::iter::from_fn(move || {
+                        if word == 0 {
+                            None
+                        } else {
+                            let bit = word.trailing_zeros() as usize;
+                            // Clears the lowest set bit.
+                            word &= word - 1;
+                            Some($t { val: word_idx * 64 + bit })
+                        }
+                    })
+                })
+            }
+            /// `k`-th smallest index of the set, starting at zero.
+            ///
+            /// Linear in the capacity of the set, skips entire words.
+            pub fn select(&self, mut k: usize) -> Option<$t> {
+                for (word_idx, word) in self.words.iter().enumerate() {
+                    let ones = word.count_ones() as usize;
+                    if k >= ones {
+                        k -= ones;
+                        continue;
+                    }
+                    let mut word = *word;
+                    for _ in 0..k {
+                        // Clears the lowest set bit.
+                        word &= word - 1
+                    }
+                    return Some($t { val: word_idx * 64 + word.trailing_zeros() as usize });
+                }
+                None
+            }
+            /// Number of indices of the set smaller than `idx`.
+            ///
+            /// Linear in `idx`, skips entire words.
+            pub fn rank(&self, idx: impl core::borrow::Borrow<$t>) -> usize {
+                let (word, mask) = Self::locate(*idx.borrow());
+                let below: usize = self.words.iter().take(word).map(|word| word.count_ones() as usize).sum();
+                below + self.words.get(word).map_or(0, |word| (word & (mask - 1)).count_ones() as usize)
+            }
+            /// Position of an index among the indices of the set in ascending order, if it is in the
+            /// set.
+            #[inline]
+            pub fn position_of(&self, idx: impl core::borrow::Borrow<$t>) -> Option<usize> {
+                let idx = *idx.borrow();
+                if self.contains(idx) {
+                    Some(self.rank(idx))
+                } else {
+                    None
+                }
+            }
+            $crate::rand_only! {
+                /// Uniformly random index of the set, `None` if the set is empty.
+                ///
+                /// Linear in the capacity of the set, see [`Self::select`].
+                pub fn choose<R: $crate::rand::Rng + ?Sized>(&self, rng: &mut R) -> Option<$t> {
+                    let len = self.len();
+                    if len == 0 {
+                        return None;
+                    }
+                    self.select(rng.gen_range(0..len))
+                }
+                /// `k` distinct uniformly random indices of the set in ascending order, all of them
+                /// if `k >= self.len()`.
+                pub fn sample<R: $crate::rand::Rng + ?Sized>(
+                    &self,
+                    rng: &mut R,
+                    k: usize,
+                ) -> $crate::alloc::vec::Vec<$t> {
+                    $crate::sample_iter(self.iter(), self.len(), rng, k)
+                }
+            }
+            /// Adds all the indices of `other` to `self`.
+            pub fn union_with(&mut self, other: &Self) {
+                if self.words.len() < other.words.len() {
+                    self.words.resize(other.words.len(), 0)
+                }
+                for (word, other) in self.words.iter_mut().zip(&other.words) {
+                    *word |= other
+                }
+            }
+            /// Removes all the indices of `self` that are not in `other`.
+            pub fn intersect_with(&mut self, other: &Self) {
+                for (idx, word) in self.words.iter_mut().enumerate() {
+                    *word &= other.words.get(idx).copied().unwrap_or(0)
+                }
+            }
+            /// Removes all the indices of `other` from `self`.
+            pub fn difference_with(&mut self, other: &Self) {
+                for (word, other) in self.words.iter_mut().zip(&other.words) {
+                    *word &= !other
+                }
+            }
+        }
+
+        impl core::cmp::PartialEq for $set {
+            /// Ignores the capacity of the sets.
+            fn eq(&self, other: &Self) -> bool {
+                let (short, long) = if self.words.len() <= other.words.len() {
+                    (&self.words, &other.words)
+                } else {
+                    (&other.words, &self.words)
+                };
+                short[..] == long[..short.len()] && long[short.len()..].iter().all(|word| *word == 0)
+            }
+        }
+        impl core::cmp::Eq for $set {}
+        impl core::fmt::Debug for $set {
+            /// Formats the set as `{0, 3, 7}`.
+            fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+                fmt.debug_set().entries(self.iter().map(|idx| idx.val)).finish()
+            }
+        }
+        impl core::iter::FromIterator<$t> for $set {
+            fn from_iter<I: core::iter::IntoIterator<Item = $t>>(iter: I) -> Self {
+                let mut set = Self::new();
+                set.extend(iter);
+                set
+            }
+        }
+        impl core::iter::Extend<$t> for $set {
+            fn extend<I: core::iter::IntoIterator<Item = $t>>(&mut self, iter: I) {
+                for idx in iter {
+                    self.insert(idx);
+                }
+            }
+        }
+
+        $crate::handle!{ $t $($tail)* }
+    };
+}
@@ -0,0 +1,281 @@
+//! Handles bounded index code generation.
+
+/// A [`usize`] known to be smaller than `N`.
+///
+/// Representation of the indices generated by the `bounded` option of [`new`](crate::new): the
+/// bound is checked once on construction, which lets maps of exactly `N` elements skip it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Below<const N: usize> {
+    val: usize,
+}
+impl<const N: usize> Below<N> {
+    /// Wraps a [`usize`], `None` if it is not smaller than `N`.
+    #[inline]
+    pub const fn new(val: usize) -> Option<Self> {
+        if val < N {
+            Some(Below { val })
+        } else {
+            None
+        }
+    }
+    /// Underlying [`usize`], smaller than `N`.
+    #[inline]
+    pub const fn get(self) -> usize {
+        self.val
+    }
+}
+impl<const N: usize> core::fmt::Display for Below<N> {
+    #[inline]
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.val.fmt(fmt)
+    }
+}
+
+/// Bounded index code generation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! bounded_codegen {
+    (@map $bounded:ident [$n:expr] $map:ident) => {
+        /// Map with exactly one element for each
+        #[doc = concat!("[`", stringify!($bounded), "`],")]
+        /// indexing it never checks bounds.
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub struct $map<T> {
+            elems: [T; $n],
+        }
+
+        impl<T> $map<T> {
+            /// Number of elements in the map.
+            pub const LEN: usize = $n;
+
+            $crate::non_strict! {
+                /// Creates a map from the element of each index.
+                #[inline]
+                pub fn from_fn(mut f: impl FnMut($bounded) -> T) -> Self {
+                    $map {
+                        elems: core::array::from_fn(|val| match $bounded::try_new(val) {
+                            Some(idx) => f(idx),
+                            None => unreachable!(),
+                        }),
+                    }
+                }
+            }
+            /// Creates a map from an array, element `i` goes to index `i`.
+            #[inline]
+            pub const fn from_array(elems: [T; $n]) -> Self {
+                $map { elems }
+            }
+            /// Turns the map into an array, element `i` is the one of index `i`.
+            #[inline]
+            pub fn into_array(self) -> [T; $n] {
+                self.elems
+            }
+            /// Creates a map where all indices have the same element.
+            #[inline]
+            pub fn filled(elem: T) -> Self
+            where
+                T: Clone,
+            {
+                $map {
+                    elems: core::array::from_fn(|_| elem.clone()),
+                }
+            }
+
+            /// Number of elements in the map, always [`Self::LEN`].
+            #[inline]
+            pub const fn len(&self) -> usize {
+                $n
+            }
+            /// True if the map has no elements, *i.e.* if the bound is zero.
+            #[inline]
+            pub const fn is_empty(&self) -> bool {
+                $n == 0
+            }
+
+            /// Slice of the elements.
+            #[inline]
+            pub fn as_slice(&self) -> &[T] {
+                &self.elems
+            }
+            /// Mutable slice of the elements.
+            #[inline]
+            pub fn as_mut_slice(&mut self) -> &mut [T] {
+                &mut self.elems
+            }
+            /// Iterator over the elements.
+            #[inline]
+            pub fn iter(&self) -> core::slice::Iter<'_, T> {
+                self.elems.iter()
+            }
+            /// Iterator over the elements, mutable version.
+            #[inline]
+            pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, T> {
+                self.elems.iter_mut()
+            }
+            $crate::non_strict! {
+                /// Iterator over the indices and the elements.
+                #[inline]
+                pub fn index_iter(&self) -> impl Iterator<Item = ($bounded, &T)> + '_ {
+                    $bounded::all().zip(self.elems.iter())
+                }
+                /// Iterator over the indices and the elements, mutable version.
+                #[inline]
+                pub fn index_iter_mut(
+                    &mut self,
+                ) -> impl Iterator<Item = ($bounded, &mut T)> + '_ {
+                    $bounded::all().zip(self.elems.iter_mut())
+                }
+            }
+        }
+
+        impl<T: core::default::Default> core::default::Default for $map<T> {
+            #[inline]
+            fn default() -> Self {
+                $map {
+                    elems: core::array::from_fn(|_| T::default()),
+                }
+            }
+        }
+
+        impl<T> core::ops::Index<$bounded> for $map<T> {
+            type Output = T;
+            #[inline]
+            fn index(&self, idx: $bounded) -> &T {
+                // Sound: `idx` is smaller than the bound, the length of the array.
+                unsafe { self.elems.get_unchecked(idx.get()) }
+            }
+        }
+        impl<T> core::ops::IndexMut<$bounded> for $map<T> {
+            #[inline]
+            fn index_mut(&mut self, idx: $bounded) -> &mut T {
+                // Sound: `idx` is smaller than the bound, the length of the array.
+                unsafe { self.elems.get_unchecked_mut(idx.get()) }
+            }
+        }
+
+        impl<T> core::iter::IntoIterator for $map<T> {
+            type Item = T;
+            type IntoIter = core::array::IntoIter<T, { $n }>;
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                core::iter::IntoIterator::into_iter(self.elems)
+            }
+        }
+        impl<'a, T> core::iter::IntoIterator for &'a $map<T> {
+            type Item = &'a T;
+            type IntoIter = core::slice::Iter<'a, T>;
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.elems.iter()
+            }
+        }
+        impl<'a, T> core::iter::IntoIterator for &'a mut $map<T> {
+            type Item = &'a mut T;
+            type IntoIter = core::slice::IterMut<'a, T>;
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.elems.iter_mut()
+            }
+        }
+    };
+    ($t:ident,
+        $(#[$meta:meta])*
+        $bounded:ident [$n:expr]
+        with map: $map:ident
+        $($tail:tt)*
+    ) => {
+        $crate::bounded_codegen! { $t, $(#[$meta])* $bounded [$n] }
+        $crate::bounded_codegen! { @map $bounded [$n] $map }
+        $crate::handle!{ $t $($tail)* }
+    };
+    ($t:ident,
+        $(#[$meta:meta])*
+        $bounded:ident [$n:expr]
+        $($tail:tt)*
+    ) => {
+        $(#[$meta])*
+        ///
+        /// Index smaller than
+        #[doc = stringify!($n)]
+        /// validated once on construction, converts to the unbounded index.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $bounded {
+            val: $crate::Below<{ $n }>,
+        }
+
+        impl $bounded {
+            /// Number of legal values, every index is smaller.
+            pub const BOUND: usize = $n;
+
+            $crate::non_strict! {
+                /// Index corresponding to a [`usize`], `None` if it is not smaller than the bound.
+                #[inline]
+                pub const fn try_new(val: usize) -> Option<Self> {
+                    match $crate::Below::new(val) {
+                        Some(val) => Some($bounded { val }),
+                        None => None,
+                    }
+                }
+            }
+            /// Bounded version of an index, `None` if it is not smaller than the bound.
+            #[inline]
+            pub const fn try_from_idx(idx: $t) -> Option<Self> {
+                match $crate::Below::new(idx.val) {
+                    Some(val) => Some($bounded { val }),
+                    None => None,
+                }
+            }
+            /// Underlying index accessor.
+            #[inline]
+            pub const fn get(self) -> usize {
+                self.val.get()
+            }
+            /// Unbounded version of the index.
+            #[inline]
+            pub const fn idx(self) -> $t {
+                $t { val: self.val.get() }
+            }
+            $crate::non_strict! {
+                /// Iterator over all the indices smaller than the bound, in increasing order.
+                #[inline]
+                pub fn all() -> impl Iterator<Item = Self> + Clone {
+                    (0..Self::BOUND).filter_map(Self::try_new)
+                }
+            }
+        }
+
+        impl core::convert::From<$bounded> for $t {
+            #[inline]
+            fn from(bounded: $bounded) -> $t {
+                bounded.idx()
+            }
+        }
+        impl core::convert::From<$bounded> for usize {
+            #[inline]
+            fn from(bounded: $bounded) -> usize {
+                bounded.get()
+            }
+        }
+        impl core::fmt::Display for $bounded {
+            #[inline]
+            fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+                self.val.fmt(fmt)
+            }
+        }
+
+        $crate::handle!{ $t $($tail)* }
+    };
+    ($t:ident,
+        $(#[$meta:meta])*
+        $bounded:ident
+        $($tail:tt)*
+    ) => {
+        compile_error!(concat!(
+            "`bounded: ",
+            stringify!($bounded),
+            "` expects a bound after the name, for instance `bounded: ",
+            stringify!($bounded),
+            "[4]`",
+        ));
+    };
+}
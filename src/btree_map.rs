@@ -0,0 +1,287 @@
+//! Handles btree map newtype code generation.
+
+/// Btree map newtype code generation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! btree_map_struct_codegen {
+    ($t:ident,
+        $(#[$meta:meta])*
+        $map:ident
+        $($tail:tt)*
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[repr(transparent)]
+        pub struct $map<T> {
+            map: $crate::alloc::collections::BTreeMap<$t, T>,
+        }
+
+        impl<T> $map<T> {
+            /// Creates an empty map.
+            #[inline]
+            pub const fn new() -> Self {
+                $map {
+                    map: $crate::alloc::collections::BTreeMap::new(),
+                }
+            }
+            /// Number of elements in the map.
+            #[inline]
+            pub fn len(&self) -> usize {
+                self.map.len()
+            }
+            /// True if the map is empty.
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.map.is_empty()
+            }
+            /// Inserts an element, returns the previous element of that index if any.
+            #[inline]
+            pub fn insert(&mut self, idx: $t, elem: T) -> Option<T> {
+                self.map.insert(idx, elem)
+            }
+            /// Removes the element of an index, if any.
+            #[inline]
+            pub fn remove(&mut self, idx: impl core::borrow::Borrow<$t>) -> Option<T> {
+                self.map.remove(idx.borrow())
+            }
+            /// Removes all the elements.
+            #[inline]
+            pub fn clear(&mut self) {
+                self.map.clear()
+            }
+            /// Element of an index, if any.
+            #[inline]
+            pub fn get(&self, idx: impl core::borrow::Borrow<$t>) -> Option<&T> {
+                self.map.get(idx.borrow())
+            }
+            /// Mutable element of an index, if any.
+            #[inline]
+            pub fn get_mut(&mut self, idx: impl core::borrow::Borrow<$t>) -> Option<&mut T> {
+                self.map.get_mut(idx.borrow())
+            }
+            /// True if an index has an element.
+            #[inline]
+            pub fn contains_index(&self, idx: impl core::borrow::Borrow<$t>) -> bool {
+                self.map.contains_key(idx.borrow())
+            }
+            /// Smallest index with an element.
+            #[inline]
+            pub fn first_index(&self) -> Option<$t> {
+                self.map.keys().next().copied()
+            }
+            /// Greatest index with an element.
+            #[inline]
+            pub fn last_index(&self) -> Option<$t> {
+                self.map.keys().next_back().copied()
+            }
+            /// Iterator over the indices with an element, in ascending order.
+            #[inline]
+            pub fn indices(&self) -> core::iter::Copied<
+                $crate::alloc::collections::btree_map::Keys<'_, $t, T>
+            > {
+                self.map.keys().copied()
+            }
+            /// Set of the indices with an element.
+            #[inline]
+            pub fn keys_as_set(&self) -> $crate::alloc::collections::BTreeSet<$t> {
+                self.map.keys().copied().collect()
+            }
+            /// Ref-iterator over the elements, in index order.
+            #[inline]
+            pub fn iter(&self) -> $crate::alloc::collections::btree_map::Values<'_, $t, T> {
+                self.map.values()
+            }
+            /// Mutable ref-iterator over the elements, in index order.
+            #[inline]
+            pub fn iter_mut(&mut self) -> $crate::alloc::collections::btree_map::ValuesMut<'_, $t, T> {
+                self.map.values_mut()
+            }
+            /// Ref-iterator over the index-element pairs, in index order.
+            #[inline]
+            pub fn index_iter(&self) -> impl DoubleEndedIterator<Item = ($t, &T)>
+                + ExactSizeIterator
+                + core::iter::FusedIterator
+            {
+                self.map.iter().map(|(idx, elem)| (*idx, elem))
+            }
+            /// Mutable ref-iterator over the index-element pairs, in index order.
+            #[inline]
+            pub fn index_iter_mut(&mut self) -> impl DoubleEndedIterator<Item = ($t, &mut T)>
+                + ExactSizeIterator
+                + core::iter::FusedIterator
+            {
+                self.map.iter_mut().map(|(idx, elem)| (*idx, elem))
+            }
+            /// Owning iterator over the index-element pairs, in index order.
+            #[inline]
+            pub fn into_index_iter(self) -> $crate::alloc::collections::btree_map::IntoIter<$t, T> {
+                self.map.into_iter()
+            }
+            /// Ref-iterator over the index-element pairs with an index in a range, in index order.
+            ///
+            /// Inverted ranges, such as `b..a` with `a < b`, are empty.
+            #[inline]
+            pub fn range_query(
+                &self,
+                range: impl core::ops::RangeBounds<$t>,
+            ) -> impl DoubleEndedIterator<Item = ($t, &T)> + core::iter::FusedIterator {
+                self.map
+                    .range($crate::non_inverted_bounds(&range))
+                    .map(|(idx, elem)| (*idx, elem))
+            }
+            /// Underlying btree map.
+            #[inline]
+            pub fn as_btree_map(&self) -> &$crate::alloc::collections::BTreeMap<$t, T> {
+                &self.map
+            }
+            /// Underlying btree map.
+            #[inline]
+            pub fn into_btree_map(self) -> $crate::alloc::collections::BTreeMap<$t, T> {
+                self.map
+            }
+        }
+
+        impl<T> core::default::Default for $map<T> {
+            #[inline]
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+        impl<T> core::convert::From<$crate::alloc::collections::BTreeMap<$t, T>> for $map<T> {
+            #[inline]
+            fn from(map: $crate::alloc::collections::BTreeMap<$t, T>) -> Self {
+                $map { map }
+            }
+        }
+        impl<T: core::fmt::Debug> core::fmt::Debug for $map<T> {
+            /// Formats the map as `{0: elem, 3: elem}`.
+            fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+                fmt.debug_map()
+                    .entries(self.map.iter().map(|(idx, elem)| (idx.val, elem)))
+                    .finish()
+            }
+        }
+
+        impl<T> core::ops::Index<$t> for $map<T> {
+            type Output = T;
+            /// Panics if the index has no element.
+            #[inline]
+            #[track_caller]
+            fn index(&self, idx: $t) -> &T {
+                &self[&idx]
+            }
+        }
+        impl<'a, T> core::ops::Index<&'a $t> for $map<T> {
+            type Output = T;
+            /// Panics if the index has no element.
+            #[inline]
+            #[track_caller]
+            fn index(&self, idx: &'a $t) -> &T {
+                match self.map.get(idx) {
+                    Some(elem) => elem,
+                    None => panic!(
+                        "index {}({}) has no element in {}",
+                        stringify!($t),
+                        idx.val,
+                        stringify!($map),
+                    ),
+                }
+            }
+        }
+        impl<T> core::ops::IndexMut<$t> for $map<T> {
+            /// Panics if the index has no element.
+            #[inline]
+            #[track_caller]
+            fn index_mut(&mut self, idx: $t) -> &mut T {
+                &mut self[&idx]
+            }
+        }
+        impl<'a, T> core::ops::IndexMut<&'a $t> for $map<T> {
+            /// Panics if the index has no element.
+            #[inline]
+            #[track_caller]
+            fn index_mut(&mut self, idx: &'a $t) -> &mut T {
+                match self.map.get_mut(idx) {
+                    Some(elem) => elem,
+                    None => panic!(
+                        "index {}({}) has no element in {}",
+                        stringify!($t),
+                        idx.val,
+                        stringify!($map),
+                    ),
+                }
+            }
+        }
+
+        impl<T> core::iter::FromIterator<($t, T)> for $map<T> {
+            #[inline]
+            fn from_iter<I: core::iter::IntoIterator<Item = ($t, T)>>(iter: I) -> Self {
+                $map {
+                    map: iter.into_iter().collect(),
+                }
+            }
+        }
+        impl<T> core::iter::Extend<($t, T)> for $map<T> {
+            #[inline]
+            fn extend<I: core::iter::IntoIterator<Item = ($t, T)>>(&mut self, iter: I) {
+                self.map.extend(iter)
+            }
+        }
+        impl<T> core::iter::IntoIterator for $map<T> {
+            type Item = T;
+            type IntoIter = $crate::alloc::collections::btree_map::IntoValues<$t, T>;
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.map.into_values()
+            }
+        }
+        impl<'a, T> core::iter::IntoIterator for &'a $map<T> {
+            type Item = &'a T;
+            type IntoIter = $crate::alloc::collections::btree_map::Values<'a, $t, T>;
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.map.values()
+            }
+        }
+        impl<'a, T> core::iter::IntoIterator for &'a mut $map<T> {
+            type Item = &'a mut T;
+            type IntoIter = $crate::alloc::collections::btree_map::ValuesMut<'a, $t, T>;
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.map.values_mut()
+            }
+        }
+
+        $crate::handle!{ $t $($tail)* }
+    };
+}
+
+/// Btree map newtype code generation involving a map.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! btree_map_map_codegen {
+    ($t:ident, $btree_map:ident, $map:ident) => {
+        impl<T> $btree_map<T> {
+            /// Turns the map into a dense map, if its indices are exactly `0..self.len()`.
+            ///
+            /// Fails on the first index missing otherwise.
+            #[inline]
+            pub fn to_dense(self) -> Result<$map<T>, $crate::MissingIndex<$t>> {
+                $map::from_dense_btree_map(self.map)
+            }
+        }
+        impl<T: Clone> $btree_map<T> {
+            /// Turns the map into a dense map with an element for all indices up to the greatest one,
+            /// filling the gaps with `default`.
+            pub fn densify_with(self, default: T) -> $map<T> {
+                let len = self.last_index().map_or(0, |idx| idx.val + 1);
+                let mut vec = $crate::alloc::vec::Vec::with_capacity(len);
+                for (idx, elem) in self.map {
+                    vec.resize(idx.val, default.clone());
+                    vec.push(elem);
+                }
+                $map { vec }
+            }
+        }
+    };
+}
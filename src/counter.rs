@@ -0,0 +1,112 @@
+//! Handles index counter code generation.
+
+/// Index counter code generation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! counter_codegen {
+    ($t:ident,
+        $(#[$meta:meta])*
+        $counter:ident
+        $($tail:tt)*
+    ) => {
+        $(#[$meta])*
+        ///
+        /// Generates fresh indices, starting from zero, without any collection. Available in strict
+        /// mode.
+        #[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+        pub struct $counter {
+            /// Next index generated.
+            next: usize,
+        }
+
+        impl $counter {
+            /// Creates a counter generating indices from zero.
+            #[inline]
+            pub const fn new() -> Self {
+                $counter { next: 0 }
+            }
+
+            /// Generates a fresh index.
+            #[inline]
+            #[allow(clippy::should_implement_trait)]
+            pub fn next(&mut self) -> $t {
+                let idx = $t { val: self.next };
+                self.next += 1;
+                idx
+            }
+            /// Index the next call to [`Self::next`] generates.
+            #[inline]
+            pub const fn peek(&self) -> $t {
+                $t { val: self.next }
+            }
+            /// Number of indices generated so far, or skipped by [`Self::set_floor`].
+            #[inline]
+            pub const fn count(&self) -> usize {
+                self.next
+            }
+            /// Makes sure the indices generated from now on are greater than or equal to `floor`.
+            #[inline]
+            pub fn set_floor(&mut self, floor: $t) {
+                self.next = self.next.max(floor.val)
+            }
+        }
+
+        $crate::handle!{ $t $($tail)* }
+    };
+}
+
+/// Atomic index counter code generation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! atomic_counter_codegen {
+    ($t:ident,
+        $(#[$meta:meta])*
+        $counter:ident
+        $($tail:tt)*
+    ) => {
+        $(#[$meta])*
+        ///
+        /// Generates fresh indices, starting from zero, without any collection. Can be shared
+        /// between threads and used in `static`s. Available in strict mode.
+        #[derive(Debug, Default)]
+        pub struct $counter {
+            /// Next index generated.
+            next: core::sync::atomic::AtomicUsize,
+        }
+
+        impl $counter {
+            /// Creates a counter generating indices from zero.
+            #[inline]
+            pub const fn new() -> Self {
+                $counter {
+                    next: core::sync::atomic::AtomicUsize::new(0),
+                }
+            }
+
+            /// Generates a fresh index, distinct from all the other indices generated.
+            #[inline]
+            pub fn fetch_next(&self) -> $t {
+                let val = self
+                    .next
+                    .fetch_add(1, core::sync::atomic::Ordering::Relaxed);
+                $t { val }
+            }
+            /// Index the next call to [`Self::fetch_next`] would generate if no other thread
+            /// generates one first.
+            #[inline]
+            pub fn peek(&self) -> $t {
+                $t {
+                    val: self.next.load(core::sync::atomic::Ordering::Relaxed),
+                }
+            }
+            /// Makes sure the indices generated from now on are greater than or equal to `floor`.
+            #[inline]
+            pub fn set_floor(&self, floor: $t) {
+                self.next
+                    .fetch_max(floor.val, core::sync::atomic::Ordering::Relaxed);
+            }
+        }
+
+        $crate::handle!{ $t $($tail)* }
+    };
+}
@@ -0,0 +1,235 @@
+//! Handles deque map code generation.
+
+/// Deque map code generation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! deque_map_codegen {
+    (@oob $t:ident, $deque:ident, $slf:expr, $idx:expr) => {
+        if $idx.val < $slf.front {
+            panic!(
+                "index {}({}) was evicted from {}, front index is {}",
+                stringify!($t),
+                $idx.val,
+                stringify!($deque),
+                $slf.front,
+            )
+        } else {
+            $crate::oob::index(
+                stringify!($t),
+                $idx.val,
+                stringify!($deque),
+                $slf.front + $slf.deque.len(),
+            )
+        }
+    };
+
+    ($t:ident,
+        $(#[$meta:meta])*
+        $deque:ident
+        $($tail:tt)*
+    ) => {
+        $(#[$meta])*
+        ///
+        /// Double-ended queue where elements are pushed at the back and evicted from the front.
+        /// Indices increase with each push and are never reused, evicting elements does not change
+        /// the indices of the remaining ones.
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub struct $deque<T> {
+            /// Elements, from front to back.
+            deque: $crate::alloc::collections::VecDeque<T>,
+            /// Index of the front element, *i.e.* number of elements evicted so far.
+            front: usize,
+        }
+
+        impl<T> core::default::Default for $deque<T> {
+            #[inline]
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<T> $deque<T> {
+            /// Creates an empty deque map.
+            #[inline]
+            pub const fn new() -> Self {
+                $deque {
+                    deque: $crate::alloc::collections::VecDeque::new(),
+                    front: 0,
+                }
+            }
+            /// Creates an empty deque map with some capacity.
+            #[inline]
+            pub fn with_capacity(capacity: usize) -> Self {
+                $deque {
+                    deque: $crate::alloc::collections::VecDeque::with_capacity(capacity),
+                    front: 0,
+                }
+            }
+
+            /// Number of elements currently in the map.
+            #[inline]
+            pub fn len(&self) -> usize {
+                self.deque.len()
+            }
+            /// True if the map currently has no elements.
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.deque.is_empty()
+            }
+
+            /// Index of the front element, `None` if the map is empty.
+            #[inline]
+            pub fn front_index(&self) -> Option<$t> {
+                if self.deque.is_empty() {
+                    None
+                } else {
+                    Some($t { val: self.front })
+                }
+            }
+            /// Index of the back element, `None` if the map is empty.
+            #[inline]
+            pub fn back_index(&self) -> Option<$t> {
+                if self.deque.is_empty() {
+                    None
+                } else {
+                    Some($t { val: self.front + self.deque.len() - 1 })
+                }
+            }
+
+            /// Pushes an element at the back, yields its index.
+            #[inline]
+            pub fn push_back(&mut self, elem: T) -> $t {
+                let idx = $t { val: self.front + self.deque.len() };
+                self.deque.push_back(elem);
+                idx
+            }
+            /// Evicts the front element, `None` if the map is empty.
+            #[inline]
+            pub fn pop_front(&mut self) -> Option<($t, T)> {
+                let elem = self.deque.pop_front()?;
+                let idx = $t { val: self.front };
+                self.front += 1;
+                Some((idx, elem))
+            }
+            /// Evicts all the elements with an index strictly lower than `idx`, yields the number
+            /// of elements evicted.
+            pub fn evict_before(&mut self, idx: $t) -> usize {
+                let count = idx.val.saturating_sub(self.front).min(self.deque.len());
+                self.deque.drain(..count);
+                self.front += count;
+                count
+            }
+            /// Evicts all the elements, indices keep increasing afterwards.
+            #[inline]
+            pub fn clear(&mut self) {
+                self.front += self.deque.len();
+                self.deque.clear()
+            }
+
+            /// Position of an index in the deque, `None` if evicted or not pushed yet.
+            #[inline]
+            fn position(&self, idx: $t) -> Option<usize> {
+                idx.val
+                    .checked_sub(self.front)
+                    .filter(|pos| *pos < self.deque.len())
+            }
+            /// Retrieves an element, `None` if it was evicted or not pushed yet.
+            #[inline]
+            pub fn get(&self, idx: impl core::borrow::Borrow<$t>) -> Option<&T> {
+                self.position(*idx.borrow()).and_then(|pos| self.deque.get(pos))
+            }
+            /// Retrieves an element, `None` if it was evicted or not pushed yet.
+            #[inline]
+            pub fn get_mut(&mut self, idx: impl core::borrow::Borrow<$t>) -> Option<&mut T> {
+                let pos = self.position(*idx.borrow())?;
+                self.deque.get_mut(pos)
+            }
+            /// True if the index has an element, *i.e.* it was pushed and not evicted yet.
+            #[inline]
+            pub fn contains_index(&self, idx: impl core::borrow::Borrow<$t>) -> bool {
+                self.position(*idx.borrow()).is_some()
+            }
+
+            /// Iterator over the indices of the elements, from front to back.
+            #[inline]
+            pub fn indices(&self) ->
+                impl core::iter::DoubleEndedIterator<Item = $t>
+                + core::iter::ExactSizeIterator
+                + core::iter::FusedIterator
+            {
+                (self.front..self.front + self.deque.len()).map(|val| $t { val })
+            }
+            /// Iterator over the elements, from front to back.
+            #[inline]
+            pub fn iter(&self) -> $crate::alloc::collections::vec_deque::Iter<'_, T> {
+                self.deque.iter()
+            }
+            /// Mutable iterator over the elements, from front to back.
+            #[inline]
+            pub fn iter_mut(&mut self) -> $crate::alloc::collections::vec_deque::IterMut<'_, T> {
+                self.deque.iter_mut()
+            }
+            /// Iterator over the index/element pairs, from front to back.
+            #[inline]
+            pub fn index_iter<'a>(&'a self) ->
+                impl core::iter::DoubleEndedIterator<Item = ($t, &'a T)>
+                + core::iter::ExactSizeIterator
+                + core::iter::FusedIterator
+            where T: 'a {
+                self.indices().zip(self.deque.iter())
+            }
+            /// Mutable iterator over the index/element pairs, from front to back.
+            #[inline]
+            pub fn index_iter_mut<'a>(&'a mut self) ->
+                impl core::iter::DoubleEndedIterator<Item = ($t, &'a mut T)>
+                + core::iter::ExactSizeIterator
+                + core::iter::FusedIterator
+            where T: 'a {
+                let front = self.front;
+                self.deque
+                    .iter_mut()
+                    .enumerate()
+                    .map(move |(pos, elem)| ($t { val: front + pos }, elem))
+            }
+        }
+
+        impl<T> core::ops::Index<$t> for $deque<T> {
+            type Output = T;
+            #[inline]
+            #[track_caller]
+            fn index(&self, idx: $t) -> &T {
+                match self.position(idx) {
+                    Some(pos) => &self.deque[pos],
+                    None => $crate::deque_map_codegen!(@oob $t, $deque, self, idx),
+                }
+            }
+        }
+        impl<T> core::ops::IndexMut<$t> for $deque<T> {
+            #[inline]
+            #[track_caller]
+            fn index_mut(&mut self, idx: $t) -> &mut T {
+                match self.position(idx) {
+                    Some(pos) => &mut self.deque[pos],
+                    None => $crate::deque_map_codegen!(@oob $t, $deque, self, idx),
+                }
+            }
+        }
+
+        impl<'a, T> core::iter::IntoIterator for &'a $deque<T> {
+            type Item = &'a T;
+            type IntoIter = $crate::alloc::collections::vec_deque::Iter<'a, T>;
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+        impl<T> core::iter::Extend<T> for $deque<T> {
+            #[inline]
+            fn extend<I: core::iter::IntoIterator<Item = T>>(&mut self, iter: I) {
+                self.deque.extend(iter)
+            }
+        }
+
+        $crate::handle!{ $t $($tail)* }
+    };
+}
@@ -0,0 +1,165 @@
+//! The [clients example](super::clients) with the [`index`](crate::index) attribute, requires the
+//! `macros` feature.
+//!
+//! The code is the same as in the clients example, only the declaration of the indices differs.
+//! The attribute has no `cfg` for a single option, the interner of client names, which needs the
+//! `std` or `hashbrown` feature, comes from a `cfg_attr` instead.
+//!
+//! ```rust
+//! use safe_index::examples::attr::{*, idx::{FileSet, ClientSet}};
+//!
+//! let mut data = Data::new();
+//!
+//! let c_1 = data.add_client("client 1");
+//! let c_2 = data.add_client("client 2");
+//! let c_3 = data.add_client("client 3");
+//! assert_eq!(c_2, data.add_client("client 2"));
+//!
+//! let f_1 = data.add_file(FileInfo::new("file 1"), vec![c_1, c_2]);
+//! let f_2 = data.add_file(FileInfo::new("file 2"), vec![c_3]);
+//!
+//! let classes = data.client_clusters();
+//! let expected: Vec<(ClientSet, FileSet)> = vec![
+//!     (vec![c_1, c_2].into_iter().collect(), vec![f_1].into_iter().collect()),
+//!     (vec![c_3].into_iter().collect(), vec![f_2].into_iter().collect()),
+//! ];
+//! assert_eq! { classes, expected }
+//! ```
+
+/// Indices.
+pub mod idx {
+    /// Indices of clients.
+    #[cfg_attr(
+        any(feature = "std", feature = "hashbrown"),
+        crate::index(map = "Clients", btree_set = "ClientSet", interner = "ClientNames")
+    )]
+    #[cfg_attr(
+        not(any(feature = "std", feature = "hashbrown")),
+        crate::index(map = "Clients", btree_set = "ClientSet")
+    )]
+    pub struct Client;
+
+    /// Indices of files.
+    #[crate::index(
+        map = "Files",
+        btree_set_struct = "FileSet",
+        union_find = "FileClasses",
+        range = "FileRange"
+    )]
+    pub struct File;
+}
+
+use idx::*;
+
+super::clients::clients_example! {}
+
+#[test]
+fn attribute_items() {
+    let mut files = Files::new();
+    let f_0 = files.push("file 0");
+    let f_2 = {
+        files.push("file 1");
+        files.push("file 2")
+    };
+    let range = FileRange::new(f_0, f_2);
+    assert_eq! { range.len(), 2 }
+    let set: FileSet = range.into_iter().collect();
+    assert!(set.contains(f_0));
+    assert!(!set.contains(f_2));
+}
+
+#[test]
+fn full() {
+    #[allow(dead_code)]
+    mod idx {
+        #[crate::index(full)]
+        pub struct Job;
+        #[crate::index(full = "Task", bit_set = "TaskBits")]
+        pub struct Step;
+        #[crate::index(
+            map = "Works",
+            btree_set = "WorkSet",
+            btree_map = "WorkMap",
+            range = "WorkRange"
+        )]
+        pub struct Work;
+    }
+    use idx::*;
+
+    let (mut jobs, mut steps, mut works) = (JobMap::new(), TaskMap::new(), Works::new());
+    let (j_0, s_0, w_0) = (jobs.push("0"), steps.push("0"), works.push("0"));
+    let (j_1, s_1, w_1) = (jobs.push("1"), steps.push("1"), works.push("1"));
+    let (j_2, s_2, w_2) = (jobs.push("2"), steps.push("2"), works.push("2"));
+
+    let jobs_range = JobRange::new(j_0, j_2);
+    let steps_range = TaskRange::new(s_0, s_2);
+    let works_range = WorkRange::new(w_0, w_2);
+    assert_eq! { jobs_range.len(), works_range.len() }
+    assert_eq! { steps_range.len(), works_range.len() }
+
+    let job_set: JobBSet = jobs_range.into_iter().collect();
+    let step_set: TaskBSet = steps_range.into_iter().collect();
+    let work_set: WorkSet = works_range.into_iter().collect();
+    assert!(job_set.contains(&j_1) && !job_set.contains(&j_2));
+    assert!(step_set.contains(&s_1) && !step_set.contains(&s_2));
+    assert!(work_set.contains(&w_1) && !work_set.contains(&w_2));
+
+    let mut job_map = JobBMap::new();
+    let mut step_map = TaskBMap::new();
+    let mut work_map = WorkMap::new();
+    job_map.insert(j_1, jobs[j_1]);
+    step_map.insert(s_1, steps[s_1]);
+    work_map.insert(w_1, works[w_1]);
+    assert_eq! { job_map.get(&j_1), work_map.get(&w_1) }
+    assert_eq! { step_map.get(&s_1), work_map.get(&w_1) }
+
+    let bits: TaskBits = steps.indices().collect();
+    assert_eq! { bits.len(), 3 }
+}
+
+#[test]
+fn full_new() {
+    use alloc::vec::Vec;
+    #[allow(dead_code)]
+    mod idx {
+        crate::new! {
+            /// Indices of jobs, with the names `full` derives.
+            Job,
+            full: Job,
+            /// Bit set of jobs.
+            bit set: JobBits,
+        }
+        crate::new! {
+            /// Indices of steps, with the same options declared explicitly.
+            Step,
+            map: StepMap,
+            btree set: StepBSet,
+            btree map: StepBMap,
+            range: StepRange,
+        }
+    }
+    use idx::*;
+
+    let (mut jobs, mut steps) = (JobMap::new(), StepMap::new());
+    let (j_0, s_0) = (jobs.push('a'), steps.push('a'));
+    let (j_1, s_1) = (jobs.push('b'), steps.push('b'));
+    let (j_2, s_2) = (jobs.push('c'), steps.push('c'));
+
+    let (job_range, step_range): (JobRange, StepRange) = (j_0.up_to(j_2), s_0.up_to(s_2));
+    assert_eq! { job_range.len(), step_range.len() }
+    assert_eq! {
+        job_range.into_iter().map(|job| jobs[job]).collect::<Vec<_>>(),
+        step_range.into_iter().map(|step| steps[step]).collect::<Vec<_>>()
+    }
+    let job_set: JobBSet = job_range.into_iter().collect();
+    let step_set: StepBSet = step_range.into_iter().collect();
+    assert!(job_set.contains(&j_1) && !job_set.contains(&j_2));
+    assert!(step_set.contains(&s_1) && !step_set.contains(&s_2));
+    let mut job_map = JobBMap::new();
+    let mut step_map = StepBMap::new();
+    job_map.insert(j_1, jobs[j_1]);
+    step_map.insert(s_1, steps[s_1]);
+    assert_eq! { job_map.get(&j_1), step_map.get(&s_1) }
+    let bits: JobBits = jobs.indices().collect();
+    assert_eq! { bits.len(), 3 }
+}
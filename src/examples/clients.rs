@@ -9,6 +9,8 @@
 //! let c_2 = data.add_client("client 2");
 //! let c_3 = data.add_client("client 3");
 //! let c_4 = data.add_client("client 4");
+//! assert_eq!(c_2, data.add_client("client 2"));
+//! assert_eq!(data.clients.len(), 4);
 //!
 //! let c_1_too = data.clients.index_from_usize(0);
 //! assert_eq!(Some(c_1), c_1_too);
@@ -21,10 +23,10 @@
 //! let none_because_only_four_clients = data.clients.index_from_usize(4);
 //! assert_eq!(None, none_because_only_four_clients);
 //!
-//! let f_1 = data.add_file(FileInfo::new("file 1", vec![c_1, c_2]));
-//! let f_2 = data.add_file(FileInfo::new("file 2", vec![c_3]));
-//! let f_3 = data.add_file(FileInfo::new("file 3", vec![c_2]));
-//! let f_4 = data.add_file(FileInfo::new("file 4", vec![c_4]));
+//! let f_1 = data.add_file(FileInfo::new("file 1"), vec![c_1, c_2]);
+//! let f_2 = data.add_file(FileInfo::new("file 2"), vec![c_3]);
+//! let f_3 = data.add_file(FileInfo::new("file 3"), vec![c_2]);
+//! let f_4 = data.add_file(FileInfo::new("file 4"), vec![c_4]);
 //!
 //! let classes = data.client_clusters();
 //! let expected: Vec<(ClientSet, FileSet)> = vec![
@@ -43,7 +45,8 @@
 //! ];
 //! assert_eq! { classes, expected }
 //!
-//! data.add_client_to_file(c_3, f_3);
+//! assert!(data.add_client_to_file(c_3, f_3));
+//! assert!(!data.add_client_to_file(c_3, f_3));
 //!
 //! let classes = data.client_clusters();
 //! let expected: Vec<(ClientSet, FileSet)> = vec![
@@ -57,207 +60,1007 @@
 //!     ),
 //! ];
 //! assert_eq! { classes, expected }
+//!
+//! // Removing a file compacts the files, the remap says where the other ones went.
+//! let remap = data.remove_file(f_2);
+//! assert_eq! { remap[f_2], None }
+//! let (f_1, f_3, f_4) = (remap[f_1].unwrap(), remap[f_3].unwrap(), remap[f_4].unwrap());
+//! assert_eq! { data.files.len(), 3 }
+//! assert_eq! { data.files[f_3].name, "file 3" }
+//! assert_eq! { data.links.left_of(f_3).collect::<Vec<_>>(), vec![c_2, c_3] }
+//! assert_eq! { data.links.right_of(c_3).collect::<Vec<_>>(), vec![f_3] }
+//!
+//! // `client 2` was the only link between files 1 and 3.
+//! let remap = data.remove_client(c_2);
+//! let (c_1, c_3, c_4) = (remap[c_1].unwrap(), remap[c_3].unwrap(), remap[c_4].unwrap());
+//! assert_eq! { data.clients.len(), 3 }
+//! assert_eq! { data.add_client("client 3"), c_3 }
+//! assert_eq! { data.links.left_of(f_1).collect::<Vec<_>>(), vec![c_1] }
+//!
+//! let classes = data.client_clusters();
+//! let expected: Vec<(ClientSet, FileSet)> = vec![
+//!     (vec![c_1].into_iter().collect(), vec![f_1].into_iter().collect()),
+//!     (vec![c_3].into_iter().collect(), vec![f_3].into_iter().collect()),
+//!     (vec![c_4].into_iter().collect(), vec![f_4].into_iter().collect()),
+//! ];
+//! assert_eq! { classes, expected }
 //! ```
+//!
+//! # Removal
+//!
+//! Removing a client or a file compacts the clients or files: the ones after it move down, so
+//! that maps stay dense and all their indices stay legal, in strict mode too. Indices held outside
+//! of [`Data`] are stale after that, removal returns a map from the old indices to the new ones
+//! (`None` for the removed one) to update them, *e.g.* `set.iter().filter_map(|file|
+//! remap[*file]).collect::<FileSet>()`.
+//!
+//! Compaction is linear in the size of the data, so removing many entities one by one is
+//! quadratic. The alternative is tombstones, *e.g.* `Files<Option<FileInfo>>`: indices never
+//! change, but every traversal has to skip dead entries and the memory of removed entities is
+//! never reclaimed.
 
-/// Indices.
-pub mod idx {
-    new! {
-        /// Indices of clients.
-        Client,
-        /// Map from clients to something.
-        map: Clients,
-        /// Set of clients.
-        btree set: ClientSet,
-    }
+/// Generates the data structures, algorithms and tests of the example over the index types in
+/// scope: `Client` with `Clients`, `ClientSet` and `ClientNames`, `File` with `Files`, `FileSet` and
+/// `FileClasses`.
+///
+/// Only the declaration of the indices differs between the [clients](self),
+/// [attribute](super::attr) and [tagged](super::tagged) examples.
+macro_rules! clients_example {
+    () => {
+        crate::relation! {
+            /// Relates clients and the files about them.
+            ClientFiles: Client <-> File
+        }
 
-    new! {
-        /// Indices of files.
-        File,
-        /// Map from files to something.
-        map: Files,
-        /// Set of files.
-        btree set: FileSet,
-    }
-}
+        /// Client information.
+        pub struct ClientInfo {
+            /// Name of the client.
+            pub name: alloc::string::String,
+        }
+        /// File information.
+        pub struct FileInfo {
+            /// Name of the file.
+            pub name: alloc::string::String,
+        }
+        impl FileInfo {
+            /// Constructor.
+            pub fn new<S>(name: S) -> Self
+            where
+                S: Into<alloc::string::String>,
+            {
+                FileInfo { name: name.into() }
+            }
+        }
 
-use idx::*;
+        impl core::ops::Index<Client> for Data {
+            type Output = ClientInfo;
+            fn index(&self, client: Client) -> &ClientInfo {
+                &self.clients[client]
+            }
+        }
 
-/// Client information.
-pub struct ClientInfo {
-    /// Name of the client.
-    pub name: alloc::string::String,
-    /// Files associated with the client.
-    pub files: FileSet,
-}
-/// File information.
-pub struct FileInfo {
-    /// Name of the file.
-    pub name: alloc::string::String,
-    /// Clients concerned by the file.
-    pub clients: ClientSet,
-}
-impl FileInfo {
-    /// Constructor.
-    pub fn new<S, I>(name: S, clients: I) -> Self
-    where
-        S: Into<alloc::string::String>,
-        I: core::iter::IntoIterator<Item = Client>,
-    {
-        FileInfo {
-            name: name.into(),
-            clients: clients.into_iter().collect(),
+        /// Aggregates client and file info.
+        pub struct Data {
+            /// Map from client indexes to client information.
+            pub clients: Clients<ClientInfo>,
+            /// Map from file indexes to file information.
+            pub files: Files<FileInfo>,
+            /// Relates clients and the files about them.
+            pub links: ClientFiles,
+            /// Index of each client name, derived from `clients`.
+            #[cfg(any(feature = "std", feature = "hashbrown"))]
+            client_names: ClientNames<alloc::string::String>,
         }
-    }
-}
+        #[allow(clippy::new_without_default)]
+        impl Data {
+            /// Constructor.
+            pub fn new() -> Data {
+                Data {
+                    clients: Clients::with_capacity(103),
+                    files: Files::with_capacity(103),
+                    links: ClientFiles::new(),
+                    #[cfg(any(feature = "std", feature = "hashbrown"))]
+                    client_names: ClientNames::new(),
+                }
+            }
 
-impl core::ops::Index<Client> for Data {
-    type Output = ClientInfo;
-    fn index(&self, client: Client) -> &ClientInfo {
-        &self.clients[client]
-    }
-}
+            /// Adds a client.
+            ///
+            /// Does not add the client again if it's already there (by name).
+            pub fn add_client<S: Into<alloc::string::String>>(&mut self, name: S) -> Client {
+                let name = name.into();
+                if let Some(client) = self.client_named(&name) {
+                    return client;
+                }
+                #[cfg(any(feature = "std", feature = "hashbrown"))]
+                self.client_names.intern(name.clone());
+                self.clients.push(ClientInfo { name })
+            }
 
-/// Aggregates client and file info.
-pub struct Data {
-    /// Map from client indexes to client information.
-    pub clients: Clients<ClientInfo>,
-    /// Map from file indexes to file information.
-    pub files: Files<FileInfo>,
-}
-impl Data {
-    /// Constructor.
-    pub fn new() -> Data {
-        Data {
-            clients: Clients::with_capacity(103),
-            files: Files::with_capacity(103),
-        }
-    }
+            /// Client with some name, if any.
+            ///
+            /// Looks the name up in the interner of client names, scans the clients without the `std` and
+            /// `hashbrown` features.
+            pub fn client_named(&self, name: &str) -> Option<Client> {
+                #[cfg(any(feature = "std", feature = "hashbrown"))]
+                let client = self.client_names.get(name);
+                #[cfg(not(any(feature = "std", feature = "hashbrown")))]
+                let client = position(&self.clients, |info| info.name == name);
+                client
+            }
+
+            /// Adds a file about some clients.
+            pub fn add_file<I>(&mut self, file: FileInfo, clients: I) -> File
+            where
+                I: core::iter::IntoIterator<Item = Client>,
+            {
+                let idx = self.files.push(file);
+                self.links
+                    .insert_all(clients.into_iter().map(|client| (client, idx)));
+                idx
+            }
 
-    /// Adds a client.
-    ///
-    /// Does not add the client again if it's already there (by name).
-    pub fn add_client<S: Into<alloc::string::String>>(&mut self, name: S) -> Client {
-        let name = name.into();
-        for (client, info) in self.clients.index_iter() {
-            if info.name == name {
-                return client;
+            /// Removes a file, returns the new index of each file, `None` for the removed one.
+            ///
+            /// Files after the removed one move down, see
+            /// [the clients example](crate::examples::clients#removal).
+            pub fn remove_file(&mut self, file: File) -> Files<Option<File>> {
+                let mut remap = Files::new_like(&self.files);
+                let files = core::mem::replace(&mut self.files, Files::with_capacity(remap.len()));
+                for (old, info) in files.into_index_iter() {
+                    if old != file {
+                        remap[old] = Some(self.files.push(info));
+                    }
+                }
+                self.links = self
+                    .links
+                    .iter()
+                    .filter_map(|(client, file)| Some((client, remap[file]?)))
+                    .collect();
+                remap
+            }
+
+            /// Removes a client, returns the new index of each client, `None` for the removed one.
+            ///
+            /// Clients after the removed one move down, see
+            /// [the clients example](crate::examples::clients#removal).
+            pub fn remove_client(&mut self, client: Client) -> Clients<Option<Client>> {
+                let mut remap = Clients::new_like(&self.clients);
+                let clients = core::mem::replace(&mut self.clients, Clients::with_capacity(remap.len()));
+                for (old, info) in clients.into_index_iter() {
+                    if old != client {
+                        remap[old] = Some(self.clients.push(info));
+                    }
+                }
+                // Interners cannot forget names, intern the remaining ones again in the new order.
+                #[cfg(any(feature = "std", feature = "hashbrown"))]
+                {
+                    self.client_names = self.clients.iter().map(|info| info.name.clone()).collect();
+                }
+                self.links = self
+                    .links
+                    .iter()
+                    .filter_map(|(client, file)| Some((remap[client]?, file)))
+                    .collect();
+                remap
+            }
+
+            /// Retrieves information about a file.
+            pub fn get_file(&mut self, file: File) -> &FileInfo {
+                &self.files[file]
+            }
+
+            /// Adds a client to a file, false if the file was already about the client.
+            pub fn add_client_to_file(&mut self, client: Client, file: File) -> bool {
+                self.links.insert(client, file)
+            }
+
+            /// Returns the client equivalence classes.
+            ///
+            /// Two clients are in the same equivalence class if they are associated to the same file,
+            /// transitively.
+            pub fn client_clusters(&self) -> alloc::vec::Vec<(ClientSet, FileSet)> {
+                // Files are in the same class if they are about the same client.
+                let mut classes = FileClasses::new_like(&self.files);
+                for client in self.clients.indices() {
+                    let mut files = self.links.right_of(client);
+                    if let Some(first) = files.next() {
+                        for file in files {
+                            classes.union(first, file);
+                        }
+                    }
+                }
+                classes
+                    .into_groups::<FileSet>()
+                    .into_iter()
+                    .map(|files| {
+                        let clients = files
+                            .iter()
+                            .flat_map(|file| self.links.left_of(*file))
+                            .collect();
+                        (clients, files)
+                    })
+                    .collect()
             }
         }
-        self.clients.push(ClientInfo {
-            name,
-            files: FileSet::new(),
-        })
-    }
 
-    /// Adds a file, updates the clients concerned.
-    pub fn add_file(&mut self, file: FileInfo) -> File {
-        let idx = self.files.push(file);
-        let file = &self.files[idx];
-        for client in &file.clients {
-            let is_new = self.clients[*client].files.insert(idx);
-            debug_assert! { is_new }
+        /// Index of the first element of a map verifying a predicate.
+        ///
+        /// Generic over the map, works for both [`Clients`] and [`Files`].
+        pub fn position<M: crate::SafeMap>(
+            map: &M,
+            mut pred: impl FnMut(&M::Elem) -> bool,
+        ) -> Option<M::Idx> {
+            (0..map.len())
+                .filter_map(|n| map.index_from_usize(n))
+                .find(|idx| map.get(*idx).is_some_and(&mut pred))
         }
-        idx
-    }
 
-    /// Retrieves information about a file.
-    pub fn get_file(&mut self, file: File) -> &FileInfo {
-        &self.files[file]
-    }
+        #[test]
+        fn run() {
+            let mut data = Data::new();
 
-    /// Adds a client to a file.
-    pub fn add_client_to_file(&mut self, client: Client, file: File) {
-        let is_new = self.files[file].clients.insert(client);
-        debug_assert! { is_new }
-        let is_new = self.clients[client].files.insert(file);
-        debug_assert! { is_new }
-    }
+            let c_1 = data.add_client("client 1");
+            let c_2 = data.add_client("client 2");
+            let c_3 = data.add_client("client 3");
+            let c_4 = data.add_client("client 4");
+
+            let f_1 = data.add_file(FileInfo::new("file 1"), alloc::vec![c_1, c_2]);
+            let f_2 = data.add_file(FileInfo::new("file 2"), alloc::vec![c_3]);
+            let f_3 = data.add_file(FileInfo::new("file 3"), alloc::vec![c_2]);
+            let f_4 = data.add_file(FileInfo::new("file 4"), alloc::vec![c_4]);
+
+            let classes = data.client_clusters();
+            let expected: alloc::vec::Vec<(ClientSet, FileSet)> = alloc::vec![
+                (
+                    alloc::vec![c_1, c_2].into_iter().collect(),
+                    alloc::vec![f_1, f_3].into_iter().collect(),
+                ),
+                (
+                    alloc::vec![c_3].into_iter().collect(),
+                    alloc::vec![f_2].into_iter().collect(),
+                ),
+                (
+                    alloc::vec![c_4].into_iter().collect(),
+                    alloc::vec![f_4].into_iter().collect(),
+                ),
+            ];
+            assert_eq! { classes, expected }
 
-    /// Returns the client equivalence classes.
-    ///
-    /// Two clients are in the same equivalence class if they are associated to the same file,
-    /// transitively.
-    pub fn client_clusters(&self) -> alloc::vec::Vec<(ClientSet, FileSet)> {
-        let mut res: alloc::vec::Vec<(ClientSet, FileSet)> = alloc::vec![];
-        macro_rules! is_known {
-            ($file:expr) => {
-                res.iter().any(|(_, files)| files.contains(&$file))
-            };
+            assert!(data.add_client_to_file(c_3, f_3));
+            assert!(!data.add_client_to_file(c_3, f_3));
+
+            let classes = data.client_clusters();
+            let expected: alloc::vec::Vec<(ClientSet, FileSet)> = alloc::vec![
+                (
+                    alloc::vec![c_1, c_2, c_3].into_iter().collect(),
+                    alloc::vec![f_1, f_2, f_3].into_iter().collect(),
+                ),
+                (
+                    alloc::vec![c_4].into_iter().collect(),
+                    alloc::vec![f_4].into_iter().collect(),
+                ),
+            ];
+            assert_eq! { classes, expected }
         }
 
-        'all_files: for (file, file_info) in self.files.index_iter() {
-            if is_known!(file) {
-                continue 'all_files;
+        #[test]
+        fn removal() {
+            use alloc::{collections::BTreeSet, string::String, vec::Vec};
+
+            /// Links of the data, by name.
+            fn named_links(data: &Data) -> BTreeSet<(String, String)> {
+                data.links
+                    .iter()
+                    .map(|(client, file)| {
+                        let client = data.clients[client].name.clone();
+                        (client, data.files[file].name.clone())
+                    })
+                    .collect()
+            }
+
+            let mut data = Data::new();
+            let clients: Vec<Client> = (0..5)
+                .map(|n| data.add_client(alloc::format!("client {}", n)))
+                .collect();
+            for n in 0..6 {
+                let about = clients
+                    .iter()
+                    .copied()
+                    .filter(|client| client.get() % 3 != n % 3);
+                data.add_file(FileInfo::new(alloc::format!("file {}", n)), about);
             }
+            let mut links = named_links(&data);
 
-            let (mut clients, mut files) = (ClientSet::new(), FileSet::new());
-            files.insert(file);
+            while let Some(file) = data.files.indices().nth(1) {
+                let name = data.files[file].name.clone();
+                let remap = data.remove_file(file);
+                assert_eq! { remap.iter().filter(|new| new.is_none()).count(), 1 }
+                links.retain(|(_, file)| *file != name);
+                assert_eq! { named_links(&data), links }
+            }
+            while let Some(client) = data.clients.last_index() {
+                let name = data.clients[client].name.clone();
+                let remap = data.remove_client(client);
+                assert_eq! { remap[client], None }
+                assert_eq! { data.client_named(&name), None }
+                links.retain(|(client, _)| *client != name);
+                assert_eq! { named_links(&data), links }
+            }
+            assert!(data.links.is_empty());
+            // The last file is about no client.
+            let file = data.files.last_index().unwrap();
+            let expected = (
+                ClientSet::new(),
+                core::iter::once(file).collect::<FileSet>(),
+            );
+            assert_eq! { data.client_clusters(), [expected] }
+        }
 
-            let mut to_dos = alloc::vec![&file_info.clients];
+        #[test]
+        fn generic_position() {
+            use crate::SafeIndex;
+            let mut data = Data::new();
+            let c_1 = data.add_client("client 1");
+            let c_2 = data.add_client("client 2");
+            let f_1 = data.add_file(FileInfo::new("file 1"), alloc::vec![c_1]);
+            let f_2 = data.add_file(FileInfo::new("file 2"), alloc::vec![c_2]);
 
-            while let Some(to_do) = to_dos.pop() {
-                for client in to_do {
-                    let is_new = clients.insert(*client);
-                    if is_new {
-                        for file in &self.clients[*client].files {
-                            let is_new = files.insert(*file);
-                            if is_new {
-                                to_dos.push(&self.files[*file].clients)
+            assert_eq! { position(&data.clients, |info| info.name == "client 2"), Some(c_2) }
+            assert_eq! { position(&data.clients, |info| info.name == "client 3"), None }
+            assert_eq! { position(&data.files, |info| info.name.ends_with('1')), Some(f_1) }
+            assert_eq! { position(&data.files, |info| info.name.ends_with('2')), Some(f_2) }
+            assert_eq! { (c_2.as_usize(), f_2.as_usize()), (1, 1) }
+            #[cfg(not(feature = "strict"))]
+            {
+                assert_eq! { Client::ZERO, c_1 }
+                assert_eq! { File::from_usize(1), f_2 }
+            }
+        }
+
+        #[test]
+        fn idx_vec() {
+            use crate::IdxVec;
+            use alloc::{string::String, vec::Vec};
+            let mut data = Data::new();
+            let clients: Vec<Client> = ["a", "b", "c"]
+                .iter()
+                .map(|name| data.add_client(*name))
+                .collect();
+
+            let mut names: IdxVec<Client, char> = IdxVec::with_capacity(3);
+            assert!(names.is_empty());
+            for client in &clients {
+                let idx = names.push(data[*client].name.chars().next().unwrap());
+                assert_eq! { idx, *client }
+            }
+            assert_eq! { names.len(), 3 }
+            assert_eq! { names[clients[1]], 'b' }
+            assert_eq! { names[&clients[1]], 'b' }
+            assert_eq! { names[clients[1]..], ['b', 'c'] }
+            assert_eq! { names[..=clients[0]], ['a'] }
+            assert_eq! { names.get(clients[2]), Some(&'c') }
+            names[clients[2]] = 'C';
+            assert_eq! { names.replace(clients[0], 'A'), 'a' }
+            names.swap(clients[0], clients[1]);
+            assert_eq! { names.iter().collect::<String>(), "bAC" }
+            assert_eq! { names.index_from_usize(2), Some(clients[2]) }
+            assert_eq! { names.index_from_usize(3), None }
+            assert_eq! { names.indices().collect::<Vec<_>>(), clients }
+            assert_eq! { names.last(), Some((clients[2], &'C')) }
+            assert_eq! {
+                names.index_iter().map(|(idx, c)| (idx, *c)).collect::<Vec<_>>(),
+                [(clients[0], 'b'), (clients[1], 'A'), (clients[2], 'C')]
+            }
+            for (idx, c) in names.index_iter_mut() {
+                if idx == clients[1] {
+                    *c = 'a'
+                }
+            }
+            assert_eq! { names.as_slice(), ['b', 'a', 'C'] }
+            assert_eq! { names.select(&clients[1..]).map(|(_, c)| *c).collect::<String>(), "aC" }
+            assert_eq! { position(&names, |c| *c == 'C'), Some(clients[2]) }
+
+            // Conversions with the generated map, keeping the indices.
+            let map: Clients<char> = names.clone().into();
+            assert_eq! { map[clients[1]], 'a' }
+            let back: IdxVec<Client, char> = map.into();
+            assert_eq! { back, names }
+            assert_eq! {
+                core::mem::size_of::<IdxVec<Client, char>>(),
+                core::mem::size_of::<Vec<char>>()
+            }
+        }
+
+        #[test]
+        #[should_panic(expected = "index Client(2) out of bounds for IdxVec of length 1")]
+        fn idx_vec_out_of_bounds() {
+            let mut data = Data::new();
+            data.add_client("client 1");
+            data.add_client("client 2");
+            let c_3 = data.add_client("client 3");
+            let mut names = crate::IdxVec::<Client, _>::new();
+            names.push(());
+            names[c_3]
+        }
+
+        #[test]
+        fn union_find() {
+            use alloc::vec::Vec;
+            use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+            let mut classes = FileClasses::default();
+            assert!(classes.is_empty());
+            let files: Vec<File> = (0..5).map(|_| classes.make_set()).collect();
+            assert_eq! { classes.len(), 5 }
+            assert_eq! { classes.set_count(), 5 }
+            assert!(classes.union(files[0], files[3]));
+            assert!(classes.union(files[3], files[4]));
+            assert!(!classes.union(files[4], files[0]));
+            assert!(classes.same_set(files[0], files[4]));
+            assert!(!classes.same_set(files[0], files[1]));
+            assert_eq! { classes.find(files[4]), classes.find(files[0]) }
+            assert_eq! { classes.set_count(), 3 }
+            let groups: Vec<FileSet> = classes.into_groups();
+            let expected: Vec<FileSet> = alloc::vec![
+                [files[0], files[3], files[4]].iter().cloned().collect(),
+                [files[1]].iter().cloned().collect(),
+                [files[2]].iter().cloned().collect(),
+            ];
+            assert_eq! { groups, expected }
+
+            // Random unions against a naive partition, where merging relabels one of the sets.
+            let mut rng = SmallRng::seed_from_u64(42);
+            for len in [1, 2, 10, 100] {
+                let mut classes = FileClasses::default();
+                let files: Vec<File> = (0..len).map(|_| classes.make_set()).collect();
+                let mut labels: Vec<usize> = (0..len).collect();
+                for _ in 0..2 * len {
+                    let (lft, rgt) = (rng.gen_range(0..len), rng.gen_range(0..len));
+                    let merged = labels[lft] != labels[rgt];
+                    assert_eq! { classes.union(files[lft], files[rgt]), merged }
+                    if merged {
+                        let (old, new) = (labels[rgt], labels[lft]);
+                        for label in &mut labels {
+                            if *label == old {
+                                *label = new
                             }
                         }
                     }
+                    let (lft, rgt) = (rng.gen_range(0..len), rng.gen_range(0..len));
+                    assert_eq! { classes.same_set(files[lft], files[rgt]), labels[lft] == labels[rgt] }
+                }
+                let mut distinct = labels.clone();
+                distinct.sort_unstable();
+                distinct.dedup();
+                assert_eq! { classes.set_count(), distinct.len() }
+
+                let groups: Vec<Vec<File>> = classes.clone().into_groups();
+                assert_eq! { groups.len(), distinct.len() }
+                for group in &groups {
+                    assert!(group.windows(2).all(|pair| pair[0] < pair[1]));
+                    assert!(group
+                        .iter()
+                        .all(|file| labels[file.get()] == labels[group[0].get()]));
+                }
+                assert!(groups.windows(2).all(|pair| pair[0][0] < pair[1][0]));
+                for (lft, rgt) in files.iter().zip(files.iter().skip(1)) {
+                    assert_eq! {
+                        classes.find(*lft) == classes.find(*rgt),
+                        labels[lft.get()] == labels[rgt.get()]
+                    }
                 }
             }
+        }
 
-            res.push((clients, files))
+        #[test]
+        #[should_panic(expected = "index File(3) out of bounds for FileClasses of length 3")]
+        fn union_find_out_of_bounds() {
+            let mut files: Files<_> = ["a", "b", "c"].iter().collect();
+            let mut classes = FileClasses::new_like(&files);
+            files.push(&"d");
+            classes.find(files.last_index().unwrap());
         }
-        res
+
+        #[test]
+        #[cfg(feature = "petgraph")]
+        fn petgraph() {
+            use alloc::vec::Vec;
+            use petgraph::{graph::NodeIndex, visit::Dfs, Directed, Graph};
+
+            // Clients refer others, the graph has one node per client in the same order.
+            let mut clients = Clients::new();
+            let mut graph: Graph<&str, (), Directed, Client> = Graph::default();
+            let mut ids = Vec::new();
+            for name in ["a", "b", "c", "d", "e"] {
+                let client = clients.push(name);
+                let node = graph.add_node(name);
+                assert_eq! { Client::from(node), client }
+                ids.push(client)
+            }
+            for (src, tgt) in [(0, 1), (1, 2), (0, 3), (4, 0)] {
+                let edge = graph.add_edge(ids[src].into(), ids[tgt].into(), ());
+                assert_eq! { edge.index(), graph.edge_count() - 1 }
+            }
+            assert_eq! { graph[NodeIndex::from(ids[2])], clients[ids[2]] }
+
+            let mut reached: Vec<Client> = Vec::new();
+            let mut dfs = Dfs::new(&graph, ids[0].into());
+            while let Some(node) = dfs.next(&graph) {
+                reached.push(node.into())
+            }
+            reached.sort();
+            assert_eq! { reached, [ids[0], ids[1], ids[2], ids[3]] }
+
+            let referrers: Vec<Client> = graph
+                .neighbors_directed(ids[0].into(), petgraph::Incoming)
+                .map(Client::from)
+                .collect();
+            assert_eq! { referrers, [ids[4]] }
+        }
+
+        #[test]
+        #[cfg(feature = "borsh")]
+        fn borsh() {
+            use alloc::vec::Vec;
+
+            let mut owners: Files<Client> = Files::new();
+            let mut clients = Clients::new();
+            let c_0 = clients.push(7u16);
+            let c_1 = clients.push(0x0102);
+            owners.push(c_1);
+            owners.push(c_0);
+
+            // Golden bytes, changing them breaks the hashes of serialized states.
+            let bytes = borsh::to_vec(&c_1).unwrap();
+            assert_eq! { bytes, [1, 0, 0, 0, 0, 0, 0, 0] }
+            let bytes = borsh::to_vec(&clients).unwrap();
+            assert_eq! { bytes, [2, 0, 0, 0, 7, 0, 2, 1] }
+            let bytes = borsh::to_vec(&owners).unwrap();
+            let mut expected = Vec::from([2, 0, 0, 0]);
+            expected.extend([1, 0, 0, 0, 0, 0, 0, 0]);
+            expected.extend([0, 0, 0, 0, 0, 0, 0, 0]);
+            assert_eq! { bytes, expected }
+            // Same encoding as the vector of the elements.
+            assert_eq! { bytes, borsh::to_vec(&Vec::from([1usize, 0])).unwrap() }
+
+            #[cfg(not(feature = "strict"))]
+            {
+                let read: Files<Client> = borsh::from_slice(&bytes).unwrap();
+                assert_eq! { read, owners }
+                let read: Clients<u16> = borsh::from_slice(&borsh::to_vec(&clients).unwrap()).unwrap();
+                assert_eq! { read, clients }
+                assert_eq! { borsh::from_slice::<Client>(&[1, 0, 0, 0, 0, 0, 0, 0]).unwrap(), c_1 }
+                let empty: Clients<u16> = borsh::from_slice(&[0, 0, 0, 0]).unwrap();
+                assert!(empty.is_empty());
+                // Truncated input.
+                assert!(borsh::from_slice::<Clients<u16>>(&[2, 0, 0, 0, 7, 0, 2]).is_err());
+                assert!(borsh::from_slice::<Client>(&[1, 0, 0]).is_err());
+            }
+        }
+
+        #[test]
+        #[cfg(feature = "schemars")]
+        fn schemars() {
+            use alloc::{format, string::String};
+
+            /// Owner of some files.
+            #[derive(schemars::JsonSchema)]
+            #[allow(dead_code)]
+            struct Owner {
+                client: Client,
+                files: Files<String>,
+                owners: Files<Client>,
+            }
+
+            let schema = serde_json::to_value(schemars::schema_for!(Owner)).unwrap();
+            let expected = serde_json::json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "title": "Owner",
+                "description": "Owner of some files.",
+                "type": "object",
+                "properties": {
+                    "client": { "$ref": "#/$defs/Client" },
+                    "files": { "$ref": "#/$defs/Files_of_string" },
+                    "owners": { "$ref": "#/$defs/Files_of_Client" },
+                },
+                "required": ["client", "files", "owners"],
+                "$defs": {
+                    "Client": {
+                        "type": "integer",
+                        "format": format!("uint{}", usize::BITS),
+                        "minimum": 0,
+                    },
+                    "Files_of_string": {
+                        "type": "array",
+                        "description": "Map indexed by `File`.",
+                        "items": { "type": "string" },
+                    },
+                    "Files_of_Client": {
+                        "type": "array",
+                        "description": "Map indexed by `File`.",
+                        "items": { "$ref": "#/$defs/Client" },
+                    },
+                },
+            });
+            assert_eq! { schema, expected }
+        }
+
+        #[test]
+        #[cfg(feature = "index_vec")]
+        fn index_vec() {
+            use index_vec::{Idx, IndexVec};
+
+            let mut clients = Clients::new();
+            let c_0 = clients.push("client 0");
+            let c_1 = clients.push("client 1");
+            let elems: *const &str = &clients[c_0];
+
+            let mut vec: IndexVec<Client, &str> = clients.into();
+            assert_eq! { vec.as_raw_slice().as_ptr(), elems }
+            assert_eq! { vec[c_1], "client 1" }
+            assert_eq! { vec.last_idx(), c_1 }
+            assert_eq! { c_1.index(), 1 }
+            let c_2 = vec.push("client 2");
+            assert_eq! { Client::from_usize(2), c_2 }
+            // Pushing may reallocate.
+            let elems = vec.as_raw_slice().as_ptr();
+
+            let clients: Clients<&str> = vec.into();
+            assert_eq! { &clients[c_0] as *const &str, elems }
+            assert_eq! { clients[c_2], "client 2" }
+        }
+
+        #[test]
+        #[cfg(feature = "typed-index-collections")]
+        fn typed_index_collections() {
+            use typed_index_collections::TiVec;
+
+            let mut clients = Clients::new();
+            let c_0 = clients.push("client 0");
+            let c_1 = clients.push("client 1");
+            let elems: *const &str = &clients[c_0];
+
+            let vec: TiVec<Client, &str> = clients.into();
+            assert_eq! { vec.raw.as_ptr(), elems }
+            assert_eq! { vec[c_0], "client 0" }
+            assert_eq! { vec[c_1], "client 1" }
+            assert_eq! { vec.len(), 2 }
+
+            #[cfg(not(feature = "strict"))]
+            {
+                let keys: alloc::vec::Vec<Client> = vec.keys().collect();
+                assert_eq! { keys, [c_0, c_1] }
+                let clients: Clients<&str> = vec.into();
+                assert_eq! { &clients[c_0] as *const &str, elems }
+                assert_eq! { clients[c_1], "client 1" }
+            }
+        }
+
+        #[test]
+        #[cfg(all(feature = "num-traits", not(feature = "strict")))]
+        fn num_traits() {
+            use alloc::vec::Vec;
+            use num_traits::{Bounded, CheckedAdd, CheckedSub, One, SaturatingAdd, SaturatingSub, Zero};
+
+            /// Steps from zero by `step` until `end`, then back down from `end`, without overflowing.
+            fn steps<I>(step: I, end: I) -> Vec<I>
+            where
+                I: Zero
+                    + One
+                    + Bounded
+                    + CheckedAdd
+                    + CheckedSub
+                    + SaturatingAdd
+                    + SaturatingSub
+                    + Ord
+                    + Copy,
+            {
+                let mut res = Vec::new();
+                let mut current = I::zero();
+                while current < end {
+                    res.push(current);
+                    current = current.saturating_add(&step);
+                }
+                while let Some(prev) = current.checked_sub(&step) {
+                    res.push(prev);
+                    current = prev;
+                }
+                assert!(I::max_value().checked_add(&I::one()).is_none());
+                assert!(I::min_value().saturating_sub(&I::one()).is_zero());
+                res
+            }
+
+            let by_usize = steps(3usize, 10);
+            let by_client = steps(Client::new(3), Client::new(10));
+            assert_eq! { by_client.len(), by_usize.len() }
+            assert!(by_client
+                .iter()
+                .zip(&by_usize)
+                .all(|(client, n)| client == n));
+            assert_eq! { Client::new(5) - Client::new(2), Client::new(3) }
+            assert_eq! { Client::new(5) * Client::new(2), Client::new(10) }
+            assert_eq! { <Client as One>::one(), Client::one() }
+            assert_eq! { <Client as Zero>::zero(), Client::zero() }
+        }
+
+        #[test]
+        fn client_files_random_operations() {
+            use alloc::{collections::BTreeSet, vec::Vec};
+            use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+            let mut data = Data::new();
+            let clients: Vec<Client> = (0..5)
+                .map(|n| data.add_client(alloc::format!("client {}", n)))
+                .collect();
+            let files: Vec<File> = (0..4)
+                .map(|n| data.add_file(FileInfo::new(alloc::format!("file {}", n)), None))
+                .collect();
+
+            let mut rng = SmallRng::seed_from_u64(42);
+            let mut reference = BTreeSet::new();
+            let mut links = ClientFiles::new();
+            for _ in 0..500 {
+                let client = clients[rng.gen_range(0..clients.len())];
+                let file = files[rng.gen_range(0..files.len())];
+                match rng.gen_range(0..3) {
+                    0 => assert_eq! { links.remove(client, file), reference.remove(&(client, file)) },
+                    1 => {
+                        let pairs = [(client, file), (clients[0], file)];
+                        let expected = pairs.iter().filter(|pair| reference.insert(**pair)).count();
+                        assert_eq! { links.insert_all(pairs.iter().cloned()), expected }
+                    }
+                    _ => assert_eq! { links.insert(client, file), reference.insert((client, file)) },
+                }
+
+                assert_eq! { links.len(), reference.len() }
+                assert_eq! { links.is_empty(), reference.is_empty() }
+                assert_eq! { links.iter().collect::<BTreeSet<_>>(), reference }
+                for client in &clients {
+                    for file in &files {
+                        let related = links.contains(*client, *file);
+                        assert_eq! { related, reference.contains(&(*client, *file)) }
+                        assert_eq! { related, links.right_of(*client).any(|f| f == *file) }
+                        assert_eq! { related, links.left_of(*file).any(|c| c == *client) }
+                    }
+                }
+                assert_eq! {
+                    clients.iter().map(|client| links.right_of(*client).count()).sum::<usize>(),
+                    files.iter().map(|file| links.left_of(*file).count()).sum::<usize>()
+                }
+            }
+
+            assert_eq! { links.iter().collect::<ClientFiles>(), links }
+            links.clear();
+            assert!(links.is_empty());
+            assert_eq! { links.iter().count(), 0 }
+        }
+    };
+}
+pub(crate) use clients_example;
+
+/// Indices.
+pub mod idx {
+    new! {
+        /// Indices of clients.
+        Client {
+            /// Map from clients to something.
+            map: Clients,
+            /// Set of clients.
+            btree set: ClientSet,
+            /// Interner of client names.
+            #[cfg(any(feature = "std", feature = "hashbrown"))]
+            interner: ClientNames,
+        }
+
+        /// Indices of files.
+        File {
+            /// Map from files to something.
+            map: Files,
+            /// Set of files, a wrapper around a btree set.
+            btree set struct: FileSet,
+            /// Slice of things indexed by files.
+            slice: FileSlice,
+            /// Partition of files into classes.
+            union find: FileClasses,
+        }
+
+        /// Matrix from clients and files to something.
+        matrix ClientFileMatrix<Client, File>, row: FileSlice;
     }
 }
 
+use idx::*;
+
+clients_example! {}
+
 #[test]
-fn run() {
-    let mut data = Data::new();
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+fn fixed_map() {
+    use alloc::{string::String, vec::Vec};
+    crate::new! {
+        /// Indices of clients.
+        Client,
+        /// Client information.
+        map: ClientInfos of ClientInfo,
+        /// Client names.
+        interner: ClientNames,
+    }
 
+    /// Adds a client if not already there.
+    fn add_client(infos: &mut ClientInfos, names: &mut ClientNames<String>, name: &str) -> Client {
+        if let Some(client) = names.get(name) {
+            return client;
+        }
+        let client = names.intern(name.into());
+        let pushed = infos.push(ClientInfo { name: name.into() });
+        assert_eq! { client, pushed }
+        client
+    }
+
+    let mut infos = ClientInfos::with_capacity(3);
+    let mut names = ClientNames::new();
+    let c_1 = add_client(&mut infos, &mut names, "client 1");
+    let c_2 = add_client(&mut infos, &mut names, "client 2");
+    assert_eq! { add_client(&mut infos, &mut names, "client 1"), c_1 }
+    assert_eq! { infos.len(), 2 }
+    assert_eq! { infos[c_2].name, "client 2" }
+    infos[c_1].name.push_str(" (renamed)");
+    assert_eq! { infos.index_from_usize(1), Some(c_2) }
+    assert_eq! { position(&*infos, |info| info.name == "client 2"), Some(c_2) }
+    assert_eq! {
+        (&infos).into_iter().map(|info| info.name.as_str()).collect::<Vec<_>>(),
+        ["client 1 (renamed)", "client 2"]
+    }
+
+    let generic: crate::IdxVec<Client, ClientInfo> = infos.into();
+    assert_eq! { generic.len(), 2 }
+    let infos = ClientInfos::from(generic);
+    assert_eq! { infos[c_1].name, "client 1 (renamed)" }
+    let collected: ClientInfos = infos.into_iter().collect();
+    assert_eq! { collected.into_generic().last().map(|(idx, _)| idx), Some(c_2) }
+}
+
+#[test]
+#[cfg(all(feature = "zerocopy", not(feature = "strict")))]
+fn zerocopy() {
+    use alloc::vec;
+    use zerocopy::{CastError, FromBytes, IntoBytes};
+
+    let size = core::mem::size_of::<usize>();
+    let owners: Files<Client> = vec![Client::new(3), Client::new(0), Client::new(7)].into();
+    let bytes = owners.as_bytes();
+    assert_eq! { bytes.len(), 3 * size }
+    assert_eq! { Client::read_from_bytes(&bytes[..size]).unwrap(), Client::new(3) }
+    assert_eq! { Client::new(7).as_bytes(), 7usize.as_bytes() }
+
+    let slice = FileSlice::<Client>::from_bytes(bytes).unwrap();
+    assert_eq! { slice.len(), 3 }
+    assert_eq! { slice.as_slice(), owners.as_slice() }
+    assert_eq! { slice.as_bytes(), bytes }
+
+    // Going through `usize`s guarantees the alignment `from_bytes` checks.
+    let words = [0usize; 3];
+    let misaligned = &words.as_bytes()[1..1 + size];
+    assert!(matches!(
+        FileSlice::<Client>::from_bytes(misaligned),
+        Err(CastError::Alignment(_))
+    ));
+    let truncated = &bytes[..bytes.len() - 1];
+    assert!(matches!(
+        FileSlice::<Client>::from_bytes(truncated),
+        Err(CastError::Size(_))
+    ));
+}
+
+#[test]
+fn file_set() {
+    use alloc::{format, vec::Vec};
+    let mut files = Files::new();
+    let f_0 = files.push("file 0");
+    let f_1 = files.push("file 1");
+    let f_2 = files.push("file 2");
+
+    let mut set = FileSet::new();
+    assert!(set.is_empty());
+    assert!(set.insert(f_2));
+    assert!(set.insert(f_0));
+    assert!(!set.insert(f_2));
+    assert_eq! { set.len(), 2 }
+    assert!(set.contains(f_0));
+    assert!(set.contains(f_2));
+    assert!(!set.contains(f_1));
+    assert_eq! { format!("{:?}", set), "{0, 2}" }
+    assert_eq! { set.iter().cloned().collect::<Vec<_>>(), [f_0, f_2] }
+    assert_eq! { (&set).into_iter().count(), 2 }
+
+    let mut other: FileSet = files.indices().collect();
+    assert_eq! { other.len(), 3 }
+    assert!(other.remove(f_1));
+    assert!(!other.remove(f_1));
+    assert_eq! { other, set }
+
+    let mut extended = FileSet::new();
+    extended.extend(&set);
+    extended.extend(Some(f_1));
+    assert_eq! { extended.clone().into_iter().collect::<Vec<_>>(), [f_0, f_1, f_2] }
+    let mut appended: FileSet = set.iter().collect();
+    appended.append(&mut FileSet::from(alloc::collections::BTreeSet::from([
+        f_1,
+    ])));
+    assert_eq! { appended, extended }
+    assert_eq! { appended.as_btree_set().len(), 3 }
+    appended.clear();
+    assert!(appended.into_btree_set().is_empty());
+}
+
+#[test]
+fn client_file_matrix() {
+    use alloc::vec::Vec;
+    let mut data = Data::new();
     let c_1 = data.add_client("client 1");
     let c_2 = data.add_client("client 2");
-    let c_3 = data.add_client("client 3");
-    let c_4 = data.add_client("client 4");
-
-    let f_1 = data.add_file(FileInfo::new("file 1", alloc::vec![c_1, c_2]));
-    let f_2 = data.add_file(FileInfo::new("file 2", alloc::vec![c_3]));
-    let f_3 = data.add_file(FileInfo::new("file 3", alloc::vec![c_2]));
-    let f_4 = data.add_file(FileInfo::new("file 4", alloc::vec![c_4]));
-
-    let classes = data.client_clusters();
-    let expected: alloc::vec::Vec<(ClientSet, FileSet)> = alloc::vec![
-        (
-            alloc::vec![c_1, c_2].into_iter().collect(),
-            alloc::vec![f_1, f_3].into_iter().collect(),
-        ),
-        (
-            alloc::vec![c_3].into_iter().collect(),
-            alloc::vec![f_2].into_iter().collect(),
-        ),
-        (
-            alloc::vec![c_4].into_iter().collect(),
-            alloc::vec![f_4].into_iter().collect(),
-        ),
-    ];
-    assert_eq! { classes, expected }
-
-    data.add_client_to_file(c_3, f_3);
-
-    let classes = data.client_clusters();
-    let expected: alloc::vec::Vec<(ClientSet, FileSet)> = alloc::vec![
-        (
-            alloc::vec![c_1, c_2, c_3].into_iter().collect(),
-            alloc::vec![f_1, f_2, f_3].into_iter().collect(),
-        ),
-        (
-            alloc::vec![c_4].into_iter().collect(),
-            alloc::vec![f_4].into_iter().collect(),
-        ),
-    ];
-    assert_eq! { classes, expected }
+    let f_1 = data.add_file(FileInfo::new("file 1"), alloc::vec![c_1]);
+    let f_2 = data.add_file(FileInfo::new("file 2"), alloc::vec![c_1, c_2]);
+    let f_3 = data.add_file(FileInfo::new("file 3"), alloc::vec![c_2]);
+
+    let mut matrix = ClientFileMatrix::new(data.clients.len(), data.files.len(), 0);
+    assert_eq! { matrix.dims(), (2, 3) }
+    assert_eq! { (matrix.row_count(), matrix.col_count()), (2, 3) }
+    for (client, file) in data.links.iter() {
+        matrix[(client, file)] = 10 * (client.get() + 1) + file.get() + 1;
+    }
+    assert_eq! { matrix[(c_1, f_1)], 11 }
+    assert_eq! { matrix[(c_1, f_2)], 12 }
+    assert_eq! { matrix[(c_1, f_3)], 0 }
+    assert_eq! { matrix[(c_2, f_1)], 0 }
+    assert_eq! { matrix[(c_2, f_3)], 23 }
+    assert_eq! { matrix.get(c_2, f_2), Some(&22) }
+    assert_eq! { matrix.row(c_1).as_slice(), [11, 12, 0] }
+    assert_eq! { matrix.row(c_2)[f_3], 23 }
+    assert_eq! {
+        matrix.rows().map(|row| row.as_slice().to_vec()).collect::<Vec<_>>(),
+        [[11, 12, 0], [0, 22, 23]]
+    }
+    assert_eq! { matrix.rows().rev().count(), 2 }
+
+    matrix.row_mut(c_2)[f_1] = 21;
+    *matrix.get_mut(c_1, f_3).unwrap() = 13;
+    assert_eq! { matrix.row(c_2).as_slice(), [21, 22, 23] }
+    assert_eq! { matrix.row(c_1).as_slice(), [11, 12, 13] }
+
+    // Out of bounds on both axes in a smaller matrix.
+    let small = ClientFileMatrix::new(1, 2, 'x');
+    assert_eq! { small.get(c_1, f_2), Some(&'x') }
+    assert_eq! { small.get(c_2, f_1), None }
+    assert_eq! { small.get(c_1, f_3), None }
+    matrix.fill(7);
+    assert!(matrix.rows().all(|row| row.iter().all(|n| *n == 7)));
+    assert!(ClientFileMatrix::new(0, 3, ()).is_empty());
+}
+
+#[test]
+#[should_panic(
+    expected = "index Client(1) out of bounds for the rows of ClientFileMatrix of length 1"
+)]
+fn client_file_matrix_row_out_of_bounds() {
+    let mut data = Data::new();
+    data.add_client("client 1");
+    let c_2 = data.add_client("client 2");
+    let f_1 = data.add_file(FileInfo::new("file 1"), alloc::vec![c_2]);
+    let matrix = ClientFileMatrix::new(1, 1, ());
+    matrix[(c_2, f_1)]
+}
+
+#[test]
+#[should_panic(
+    expected = "index File(1) out of bounds for the columns of ClientFileMatrix of length 1"
+)]
+fn client_file_matrix_col_out_of_bounds() {
+    let mut data = Data::new();
+    let c_1 = data.add_client("client 1");
+    data.add_file(FileInfo::new("file 1"), alloc::vec![c_1]);
+    let f_2 = data.add_file(FileInfo::new("file 2"), alloc::vec![c_1]);
+    let matrix = ClientFileMatrix::new(1, 1, ());
+    matrix[(c_1, f_2)]
 }
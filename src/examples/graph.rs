@@ -0,0 +1,252 @@
+//! A directed graph with typed node and edge indices.
+//!
+//! ```rust
+//! use safe_index::examples::graph::{idx::*, *};
+//!
+//! let mut graph = Graph::new();
+//!
+//! let shirt = graph.add_node("shirt");
+//! let tie = graph.add_node("tie");
+//! let jacket = graph.add_node("jacket");
+//! let belt = graph.add_node("belt");
+//! let trousers = graph.add_node("trousers");
+//!
+//! graph.add_edge(shirt, tie);
+//! graph.add_edge(tie, jacket);
+//! graph.add_edge(shirt, belt);
+//! graph.add_edge(belt, jacket);
+//! let trousers_belt = graph.add_edge(trousers, belt);
+//! assert_eq! { graph.nodes.len(), 5 }
+//! assert_eq! { graph.edges.len(), 5 }
+//! assert_eq! { graph.edges[trousers_belt].src, trousers }
+//! assert_eq! { graph[belt].name, "belt" }
+//!
+//! let succs: Vec<_> = graph.successors(shirt).collect();
+//! assert_eq! { succs, vec![tie, belt] }
+//!
+//! // Everything reachable from `shirt`, closest first.
+//! assert_eq! { graph.bfs(shirt), vec![shirt, tie, belt, jacket] }
+//! assert_eq! { graph.bfs(jacket), vec![jacket] }
+//!
+//! let order = graph.topological_sort().unwrap();
+//! assert_eq! { order, vec![trousers, shirt, belt, tie, jacket] }
+//! let names: Vec<_> = order.iter().map(|node| graph[*node].name.as_str()).collect();
+//! assert_eq! { names, ["trousers", "shirt", "belt", "tie", "jacket"] }
+//! assert_eq! { graph.find_cycle(), None }
+//!
+//! // Now nothing can go first.
+//! graph.add_edge(jacket, shirt);
+//! assert_eq! { graph.find_cycle(), Some(vec![shirt, tie, jacket]) }
+//! assert_eq! { graph.topological_sort(), Err(vec![shirt, tie, jacket]) }
+//! ```
+
+use alloc::{collections::VecDeque, vec::Vec};
+
+/// Indices.
+pub mod idx {
+    new! {
+        /// Indices of nodes.
+        Node {
+            /// Map from nodes to something.
+            map: Nodes,
+            /// Set of nodes.
+            bit set: NodeSet,
+        }
+
+        /// Indices of edges.
+        Edge {
+            /// Map from edges to something.
+            map: Edges,
+            /// Set of edges.
+            btree set: EdgeSet,
+        }
+    }
+}
+
+use idx::*;
+
+/// Node information.
+pub struct NodeInfo {
+    /// Name of the node.
+    pub name: alloc::string::String,
+}
+
+/// Edge information.
+pub struct EdgeInfo {
+    /// Source of the edge.
+    pub src: Node,
+    /// Target of the edge.
+    pub tgt: Node,
+}
+
+impl core::ops::Index<Node> for Graph {
+    type Output = NodeInfo;
+    fn index(&self, node: Node) -> &NodeInfo {
+        &self.nodes[node]
+    }
+}
+
+/// Progress of a node in a depth-first traversal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Mark {
+    /// Not visited yet.
+    #[default]
+    New,
+    /// Being visited, its successors are not all done.
+    Active,
+    /// Visited along with all its successors.
+    Done,
+}
+
+/// A directed graph.
+pub struct Graph {
+    /// Map from node indexes to node information.
+    pub nodes: Nodes<NodeInfo>,
+    /// Map from edge indexes to edge information.
+    pub edges: Edges<EdgeInfo>,
+    /// Edges leaving each node.
+    pub out_edges: Nodes<EdgeSet>,
+}
+impl Default for Graph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Graph {
+    /// Constructor.
+    pub fn new() -> Graph {
+        Graph {
+            nodes: Nodes::new(),
+            edges: Edges::new(),
+            out_edges: Nodes::new(),
+        }
+    }
+
+    /// Adds a node.
+    pub fn add_node<S: Into<alloc::string::String>>(&mut self, name: S) -> Node {
+        let node = self.nodes.push(NodeInfo { name: name.into() });
+        let pushed = self.out_edges.push(EdgeSet::new());
+        debug_assert_eq! { node, pushed }
+        node
+    }
+
+    /// Adds an edge from `src` to `tgt`.
+    pub fn add_edge(&mut self, src: Node, tgt: Node) -> Edge {
+        let edge = self.edges.push(EdgeInfo { src, tgt });
+        self.out_edges[src].insert(edge);
+        edge
+    }
+
+    /// Targets of the edges leaving a node, in the order the edges were added.
+    pub fn successors(&self, node: Node) -> impl Iterator<Item = Node> + '_ {
+        self.out_edges[node]
+            .iter()
+            .map(move |edge| self.edges[*edge].tgt)
+    }
+
+    /// Nodes reachable from `start`, in breadth-first order.
+    pub fn bfs(&self, start: Node) -> Vec<Node> {
+        let mut seen = NodeSet::new();
+        seen.insert(start);
+        let mut queue = VecDeque::new();
+        queue.push_back(start);
+        let mut order = Vec::new();
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            for succ in self.successors(node) {
+                if seen.insert(succ) {
+                    queue.push_back(succ);
+                }
+            }
+        }
+        order
+    }
+
+    /// Orders the nodes so that edges only go forward.
+    ///
+    /// Fails with a cycle, as the nodes it goes through starting from the first one reached, if
+    /// there is no such order.
+    pub fn topological_sort(&self) -> Result<Vec<Node>, Vec<Node>> {
+        let mut marks: Nodes<Mark> = Nodes::new_like(&self.nodes);
+        let mut path = Vec::new();
+        let mut order = Vec::with_capacity(self.nodes.len());
+        for node in self.nodes.indices() {
+            self.visit(node, &mut marks, &mut path, &mut order)?;
+        }
+        order.reverse();
+        Ok(order)
+    }
+
+    /// A cycle of the graph, if any.
+    pub fn find_cycle(&self) -> Option<Vec<Node>> {
+        self.topological_sort().err()
+    }
+
+    /// Depth-first traversal from `node`, pushes nodes to `order` once all their successors are.
+    ///
+    /// `path` holds the active nodes, fails with the cycle if `node` is one of them.
+    fn visit(
+        &self,
+        node: Node,
+        marks: &mut Nodes<Mark>,
+        path: &mut Vec<Node>,
+        order: &mut Vec<Node>,
+    ) -> Result<(), Vec<Node>> {
+        match marks[node] {
+            Mark::Done => return Ok(()),
+            Mark::Active => {
+                let cycle = path.iter().skip_while(|active| **active != node);
+                return Err(cycle.cloned().collect());
+            }
+            Mark::New => (),
+        }
+        marks[node] = Mark::Active;
+        path.push(node);
+        for succ in self.successors(node) {
+            self.visit(succ, marks, path, order)?;
+        }
+        path.pop();
+        marks[node] = Mark::Done;
+        order.push(node);
+        Ok(())
+    }
+}
+
+#[test]
+fn run() {
+    let mut graph = Graph::new();
+    let a = graph.add_node("a");
+    let b = graph.add_node("b");
+    let c = graph.add_node("c");
+    let d = graph.add_node("d");
+
+    graph.add_edge(a, b);
+    graph.add_edge(a, c);
+    graph.add_edge(b, d);
+    graph.add_edge(c, d);
+    assert_eq! { graph.bfs(a), alloc::vec![a, b, c, d] }
+    assert_eq! { graph.bfs(c), alloc::vec![c, d] }
+    assert_eq! { graph.topological_sort(), Ok(alloc::vec![a, c, b, d]) }
+
+    // Self loops are cycles too.
+    let loop_d = graph.add_edge(d, d);
+    assert_eq! { graph.edges[loop_d].tgt, d }
+    assert_eq! { graph.find_cycle(), Some(alloc::vec![d]) }
+    assert_eq! { graph.bfs(d), alloc::vec![d] }
+}
+
+#[test]
+fn cycle_away_from_first_node() {
+    let mut graph = Graph::new();
+    let nodes: Vec<Node> = ["a", "b", "c", "d"]
+        .iter()
+        .map(|name| graph.add_node(*name))
+        .collect();
+    graph.add_edge(nodes[0], nodes[1]);
+    graph.add_edge(nodes[1], nodes[2]);
+    graph.add_edge(nodes[2], nodes[3]);
+    graph.add_edge(nodes[3], nodes[1]);
+    assert_eq! { graph.find_cycle(), Some(alloc::vec![nodes[1], nodes[2], nodes[3]]) }
+    assert_eq! { graph.bfs(nodes[2]), alloc::vec![nodes[2], nodes[3], nodes[1]] }
+    assert!(Graph::new().topological_sort().unwrap().is_empty());
+}
@@ -0,0 +1,244 @@
+//! A board addressed by rows and columns, stored as a flat map of cells.
+//!
+//! ```rust
+//! use safe_index::examples::grid::{idx::*, *};
+//!
+//! let mut grid = Grid::parse("..#..\n..#..\n###..\n....#").unwrap();
+//! assert_eq! { (grid.height(), grid.width()), (4, 5) }
+//!
+//! let corner = grid.at(0, 0).unwrap();
+//! let (row, col) = grid.row_col(corner);
+//! assert_eq! { grid.cell(row, col), corner }
+//! // Borders have fewer neighbors.
+//! let neighbors: Vec<_> = grid.neighbors(corner).collect();
+//! assert_eq! { neighbors, [grid.at(0, 1).unwrap(), grid.at(1, 0).unwrap()] }
+//! assert_eq! { grid.neighbors(grid.at(1, 1).unwrap()).count(), 4 }
+//! assert_eq! { grid.at(4, 0), None }
+//! assert_eq! { grid.at(0, 5), None }
+//!
+//! let filled = grid.flood_fill(corner);
+//! assert_eq! { filled.len(), 4 }
+//! assert_eq! { grid.to_string(), "~~#..\n~~#..\n###..\n....#\n" }
+//! // Nothing left to fill there.
+//! assert!(grid.flood_fill(corner).is_empty());
+//!
+//! let filled = grid.flood_fill(grid.at(3, 0).unwrap());
+//! assert_eq! { filled.len(), 10 }
+//! assert!(filled.contains(grid.at(0, 4).unwrap()));
+//! assert_eq! { grid.to_string(), "~~#~~\n~~#~~\n###~~\n~~~~#\n" }
+//! ```
+//!
+//! Cells are computed as `row * width + col`, and rows and columns are recovered from cells by
+//! division and remainder. Index types do not support these operations with `usize`s, so this
+//! arithmetic goes through [`get`](Cell::get) and the map's `index_from_usize`, which checks the
+//! result is a legal index, in strict mode too.
+
+use alloc::vec::Vec;
+
+/// Indices.
+pub mod idx {
+    new! {
+        /// Indices of rows.
+        Row {
+            /// Map from rows to something.
+            map: Rows,
+        }
+
+        /// Indices of columns.
+        Col {
+            /// Map from columns to something.
+            map: Cols,
+        }
+
+        /// Indices of cells, row after row.
+        Cell {
+            /// Map from cells to something.
+            map: Cells,
+            /// Set of cells.
+            bit set: CellSet,
+        }
+    }
+}
+
+use idx::*;
+
+/// Content of a cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellInfo {
+    /// Empty cell, `.`.
+    Empty,
+    /// Wall, `#`.
+    Wall,
+    /// Filled cell, `~`.
+    Filled,
+}
+impl CellInfo {
+    /// Parses a cell.
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '.' => Some(Self::Empty),
+            '#' => Some(Self::Wall),
+            '~' => Some(Self::Filled),
+            _ => None,
+        }
+    }
+    /// Character of a cell.
+    pub fn to_char(self) -> char {
+        match self {
+            Self::Empty => '.',
+            Self::Wall => '#',
+            Self::Filled => '~',
+        }
+    }
+}
+
+/// A board.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid {
+    /// Cells, row after row.
+    pub cells: Cells<CellInfo>,
+    /// Rows, they carry no information but make `Row`s legal.
+    rows: Rows<()>,
+    /// Columns, they carry no information but make `Col`s legal.
+    cols: Cols<()>,
+}
+impl Grid {
+    /// Parses a board, one line per row.
+    ///
+    /// Fails if the rows do not have the same length or on unknown characters.
+    pub fn parse(board: &str) -> Option<Grid> {
+        let width = board.lines().next().map_or(0, |line| line.chars().count());
+        let mut cells = Cells::with_capacity(board.len());
+        let mut height = 0;
+        for line in board.lines() {
+            let mut len = 0;
+            for c in line.chars() {
+                cells.push(CellInfo::from_char(c)?);
+                len += 1;
+            }
+            if len != width {
+                return None;
+            }
+            height += 1;
+        }
+        Some(Grid {
+            cells,
+            rows: Rows::of_elems((), height),
+            cols: Cols::of_elems((), width),
+        })
+    }
+
+    /// Number of columns.
+    pub fn width(&self) -> usize {
+        self.cols.len()
+    }
+    /// Number of rows.
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+    /// Iterator over the rows.
+    pub fn rows(&self) -> impl Iterator<Item = Row> {
+        self.rows.indices()
+    }
+    /// Iterator over the columns.
+    pub fn cols(&self) -> impl Iterator<Item = Col> {
+        self.cols.indices()
+    }
+
+    /// Cell at a row and a column.
+    ///
+    /// Panics if `row` or `col` comes from a bigger grid.
+    pub fn cell(&self, row: Row, col: Col) -> Cell {
+        self.cells
+            .index_from_usize(row.get() * self.width() + col.get())
+            .expect("row or column out of the grid")
+    }
+    /// Row and column of a cell.
+    ///
+    /// Panics if `cell` comes from a bigger grid.
+    pub fn row_col(&self, cell: Cell) -> (Row, Col) {
+        let row = self.rows.index_from_usize(cell.get() / self.width());
+        let col = self.cols.index_from_usize(cell.get() % self.width());
+        row.zip(col).expect("cell out of the grid")
+    }
+    /// Cell at a row and a column given as `usize`s, `None` if out of the grid.
+    pub fn at(&self, row: usize, col: usize) -> Option<Cell> {
+        let row = self.rows.index_from_usize(row)?;
+        let col = self.cols.index_from_usize(col)?;
+        Some(self.cell(row, col))
+    }
+
+    /// Cells above, left of, right of and below a cell, when they are in the grid.
+    pub fn neighbors(&self, cell: Cell) -> impl Iterator<Item = Cell> + '_ {
+        let (row, col) = self.row_col(cell);
+        let (r, c) = (row.get(), col.get());
+        let above = r.checked_sub(1).and_then(|r| self.rows.index_from_usize(r));
+        let below = self.rows.index_from_usize(r + 1);
+        let left = c.checked_sub(1).and_then(|c| self.cols.index_from_usize(c));
+        let right = self.cols.index_from_usize(c + 1);
+        IntoIterator::into_iter([
+            above.map(|row| (row, col)),
+            left.map(|col| (row, col)),
+            right.map(|col| (row, col)),
+            below.map(|row| (row, col)),
+        ])
+        .flatten()
+        .map(move |(row, col)| self.cell(row, col))
+    }
+
+    /// Fills the empty cells connected to `start`, returns the cells filled.
+    pub fn flood_fill(&mut self, start: Cell) -> CellSet {
+        let mut filled = CellSet::new();
+        let mut todo = Vec::new();
+        todo.push(start);
+        while let Some(cell) = todo.pop() {
+            if self.cells[cell] == CellInfo::Empty {
+                self.cells[cell] = CellInfo::Filled;
+                filled.insert(cell);
+                todo.extend(self.neighbors(cell));
+            }
+        }
+        filled
+    }
+}
+impl core::fmt::Display for Grid {
+    /// One line per row, each ending with a newline.
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        for row in self.rows() {
+            for col in self.cols() {
+                write!(fmt, "{}", self.cells[self.cell(row, col)].to_char())?;
+            }
+            writeln!(fmt)?;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn run() {
+    use alloc::string::ToString;
+    let mut grid = Grid::parse(".#.\n#..\n...").unwrap();
+    for cell in grid.cells.indices() {
+        let (row, col) = grid.row_col(cell);
+        assert_eq! { grid.cell(row, col), cell }
+        assert_eq! { grid.at(row.get(), col.get()), Some(cell) }
+    }
+    let counts: Vec<usize> = grid
+        .cells
+        .indices()
+        .map(|cell| grid.neighbors(cell).count())
+        .collect();
+    assert_eq! { counts, [2, 3, 2, 3, 4, 3, 2, 3, 2] }
+
+    // Walls are not filled and stop the fill.
+    assert!(grid.flood_fill(grid.at(0, 1).unwrap()).is_empty());
+    assert_eq! { grid.flood_fill(grid.at(0, 0).unwrap()).len(), 1 }
+    assert_eq! { grid.flood_fill(grid.at(2, 2).unwrap()).len(), 6 }
+    assert_eq! { grid.to_string(), "~#~\n#~~\n~~~\n" }
+
+    assert_eq! { Grid::parse("..\n."), None }
+    assert_eq! { Grid::parse("..\n.x"), None }
+    let empty = Grid::parse("").unwrap();
+    assert_eq! { (empty.height(), empty.width()), (0, 0) }
+    assert_eq! { empty.to_string(), "" }
+}
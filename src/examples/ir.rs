@@ -0,0 +1,377 @@
+//! A tiny compiler IR: functions made of blocks, whose instructions live in one flat map.
+//!
+//! Blocks only store a range of instruction indices for their body and the index of their
+//! terminator, so splitting a block never moves instructions around.
+//!
+//! ```rust
+//! use safe_index::examples::ir::{idx::*, *};
+//!
+//! let mut prog = Program::new();
+//! let max = prog.add_fun("max");
+//!
+//! let mut vals = None;
+//! let entry = prog.add_block(max, |block| {
+//!     let lhs = block.push(InstrInfo::Const(3));
+//!     let rhs = block.push(InstrInfo::Const(7));
+//!     vals = Some((lhs, rhs, block.push(InstrInfo::Lt(lhs, rhs))));
+//!     // Patched below, once the blocks the branch goes to exist.
+//!     InstrInfo::Unreachable
+//! });
+//! let (lhs, rhs, lt) = vals.unwrap();
+//! let then = prog.add_block(max, |_| InstrInfo::Return(rhs));
+//! let other = prog.add_block(max, |_| InstrInfo::Return(lhs));
+//! prog.set_terminator(max, entry, InstrInfo::Branch(lt, then, other));
+//!
+//! assert_eq! {
+//!     prog.pretty(max),
+//! "\
+//! f0 max:
+//!   b0:
+//!     i0 = const 3
+//!     i1 = const 7
+//!     i2 = lt i0 i1
+//!     branch i2 b1 b2
+//!   b1:
+//!     return i1
+//!   b2:
+//!     return i0
+//! "
+//! }
+//!
+//! // Splitting before `i2` moves it and the branch to a new block.
+//! let tail = prog.split_block(max, lt).unwrap();
+//! assert_eq! { prog.funs[max].blocks[tail].body.len(), 1 }
+//! assert_eq! {
+//!     prog.pretty(max),
+//! "\
+//! f0 max:
+//!   b0:
+//!     i0 = const 3
+//!     i1 = const 7
+//!     jump b3
+//!   b1:
+//!     return i1
+//!   b2:
+//!     return i0
+//!   b3:
+//!     i2 = lt i0 i1
+//!     branch i2 b1 b2
+//! "
+//! }
+//!
+//! // A block nothing jumps to.
+//! let dead = prog.add_block(max, |_| InstrInfo::Return(lhs));
+//! let reachable = prog.reachable(max);
+//! assert_eq! { reachable.iter().collect::<Vec<_>>(), [entry, then, other, tail] }
+//! assert!(!reachable.contains(dead));
+//! ```
+
+use alloc::{string::String, vec::Vec};
+use core::fmt::Write;
+
+/// Indices.
+pub mod idx {
+    new! {
+        /// Indices of functions.
+        Fun {
+            /// Map from functions to something.
+            map: Funs,
+        }
+
+        /// Indices of blocks, relative to the function they belong to.
+        Block {
+            /// Map from blocks to something.
+            map: Blocks,
+            /// Set of blocks.
+            bit set: BlockSet,
+        }
+
+        /// Indices of instructions.
+        Instr {
+            /// Map from instructions to something.
+            map: Instrs,
+            /// Range of instructions.
+            range: InstrRange,
+        }
+    }
+}
+
+use idx::*;
+
+/// An instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstrInfo {
+    /// Integer constant.
+    Const(i64),
+    /// Sum of two instructions.
+    Add(Instr, Instr),
+    /// Compares two instructions.
+    Lt(Instr, Instr),
+    /// Calls a function.
+    Call(Fun, Vec<Instr>),
+    /// Terminator, goes to a block.
+    Jump(Block),
+    /// Terminator, goes to the first block if the instruction holds, to the second otherwise.
+    Branch(Instr, Block, Block),
+    /// Terminator, returns from the function.
+    Return(Instr),
+    /// Terminator, never reached.
+    Unreachable,
+}
+impl InstrInfo {
+    /// True if the instruction ends a block.
+    pub fn is_terminator(&self) -> bool {
+        matches!(
+            self,
+            Self::Jump(_) | Self::Branch(..) | Self::Return(_) | Self::Unreachable
+        )
+    }
+    /// Blocks a terminator goes to.
+    pub fn targets(&self) -> impl Iterator<Item = Block> {
+        let (first, second) = match *self {
+            Self::Jump(tgt) => (Some(tgt), None),
+            Self::Branch(_, thn, els) => (Some(thn), Some(els)),
+            _ => (None, None),
+        };
+        first.into_iter().chain(second)
+    }
+}
+impl core::fmt::Display for InstrInfo {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Self::Const(val) => write!(fmt, "const {}", val),
+            Self::Add(lhs, rhs) => write!(fmt, "add i{} i{}", lhs, rhs),
+            Self::Lt(lhs, rhs) => write!(fmt, "lt i{} i{}", lhs, rhs),
+            Self::Call(fun, args) => {
+                write!(fmt, "call f{}(", fun)?;
+                for (n, arg) in args.iter().enumerate() {
+                    let sep = if n > 0 { ", " } else { "" };
+                    write!(fmt, "{}i{}", sep, arg)?;
+                }
+                write!(fmt, ")")
+            }
+            Self::Jump(tgt) => write!(fmt, "jump b{}", tgt),
+            Self::Branch(cnd, thn, els) => write!(fmt, "branch i{} b{} b{}", cnd, thn, els),
+            Self::Return(val) => write!(fmt, "return i{}", val),
+            Self::Unreachable => write!(fmt, "unreachable"),
+        }
+    }
+}
+
+/// Block information.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockInfo {
+    /// Instructions of the block, its terminator excluded.
+    pub body: InstrRange,
+    /// Terminator of the block.
+    pub term: Instr,
+}
+
+/// Function information.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunInfo {
+    /// Name of the function.
+    pub name: String,
+    /// Blocks of the function, the first one is its entry point.
+    pub blocks: Blocks<BlockInfo>,
+}
+
+/// Pushes the body of a block, see [`Program::add_block`].
+pub struct BlockBuilder<'a> {
+    /// Instructions of the program.
+    instrs: &'a mut Instrs<InstrInfo>,
+    /// First instruction pushed, if any.
+    first: Option<Instr>,
+}
+impl BlockBuilder<'_> {
+    /// Pushes an instruction, panics on terminators.
+    pub fn push(&mut self, instr: InstrInfo) -> Instr {
+        assert!(
+            !instr.is_terminator(),
+            "illegal terminator `{}` in block body",
+            instr
+        );
+        let idx = self.instrs.push(instr);
+        self.first.get_or_insert(idx);
+        idx
+    }
+}
+
+/// A program.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Program {
+    /// Map from function indexes to function information.
+    pub funs: Funs<FunInfo>,
+    /// Instructions of all the functions.
+    pub instrs: Instrs<InstrInfo>,
+}
+impl Default for Program {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Program {
+    /// Constructor.
+    pub fn new() -> Program {
+        Program {
+            funs: Funs::new(),
+            instrs: Instrs::new(),
+        }
+    }
+
+    /// Adds a function without blocks.
+    pub fn add_fun<S: Into<String>>(&mut self, name: S) -> Fun {
+        self.funs.push(FunInfo {
+            name: name.into(),
+            blocks: Blocks::new(),
+        })
+    }
+
+    /// Adds a block to a function.
+    ///
+    /// `build` pushes the body of the block and returns its terminator, which can be patched with
+    /// [`Self::set_terminator`] once the blocks it goes to exist.
+    pub fn add_block(
+        &mut self,
+        fun: Fun,
+        build: impl FnOnce(&mut BlockBuilder) -> InstrInfo,
+    ) -> Block {
+        let mut builder = BlockBuilder {
+            instrs: &mut self.instrs,
+            first: None,
+        };
+        let term = build(&mut builder);
+        assert!(term.is_terminator(), "`{}` is not a terminator", term);
+        let first = builder.first;
+        let term = self.instrs.push(term);
+        let body = first.unwrap_or(term).up_to(term);
+        self.funs[fun].blocks.push(BlockInfo { body, term })
+    }
+
+    /// Replaces the terminator of a block, returns the previous one.
+    pub fn set_terminator(&mut self, fun: Fun, block: Block, term: InstrInfo) -> InstrInfo {
+        assert!(term.is_terminator(), "`{}` is not a terminator", term);
+        let idx = self.funs[fun].blocks[block].term;
+        core::mem::replace(&mut self.instrs[idx], term)
+    }
+
+    /// Splits the block of `fun` whose body contains `at`, returns the new block.
+    ///
+    /// The new block starts at `at` and takes the terminator of the original block, which now
+    /// jumps to it. No instruction moves: the original block gets a fresh `jump` terminator at the
+    /// end of the instruction map. Returns `None` if no block body contains `at`.
+    pub fn split_block(&mut self, fun: Fun, at: Instr) -> Option<Block> {
+        let block = self.funs[fun]
+            .blocks
+            .index_iter()
+            .find(|(_, info)| info.body.contains(at))
+            .map(|(block, _)| block)?;
+        let BlockInfo { body, term } = self.funs[fun].blocks[block];
+        let tail = self.funs[fun].blocks.push(BlockInfo {
+            body: at.up_to(body.end()),
+            term,
+        });
+        let jump = self.instrs.push(InstrInfo::Jump(tail));
+        self.funs[fun].blocks[block] = BlockInfo {
+            body: body.start().up_to(at),
+            term: jump,
+        };
+        Some(tail)
+    }
+
+    /// Blocks a block goes to.
+    pub fn successors(&self, fun: Fun, block: Block) -> impl Iterator<Item = Block> {
+        self.instrs[self.funs[fun].blocks[block].term].targets()
+    }
+
+    /// Blocks of a function reachable from its entry point.
+    pub fn reachable(&self, fun: Fun) -> BlockSet {
+        let mut reachable = BlockSet::new();
+        let mut todo: Vec<Block> = self.funs[fun].blocks.indices().take(1).collect();
+        while let Some(block) = todo.pop() {
+            if reachable.insert(block) {
+                todo.extend(self.successors(fun, block));
+            }
+        }
+        reachable
+    }
+
+    /// Pretty-prints a function.
+    pub fn pretty(&self, fun: Fun) -> String {
+        let mut res = String::new();
+        let info = &self.funs[fun];
+        // Writing to a `String` never fails.
+        let _ = writeln!(res, "f{} {}:", fun, info.name);
+        for (block, BlockInfo { body, term }) in info.blocks.index_iter() {
+            let _ = writeln!(res, "  b{}:", block);
+            for instr in *body {
+                let _ = writeln!(res, "    i{} = {}", instr, self.instrs[instr]);
+            }
+            let _ = writeln!(res, "    {}", self.instrs[*term]);
+        }
+        res
+    }
+}
+
+#[test]
+fn run() {
+    let mut prog = Program::new();
+    let double = prog.add_fun("double");
+    let mut arg = None;
+    prog.add_block(double, |block| {
+        let val = block.push(InstrInfo::Const(21));
+        arg = Some(val);
+        InstrInfo::Return(block.push(InstrInfo::Add(val, val)))
+    });
+    let arg = arg.unwrap();
+
+    let main = prog.add_fun("main");
+    let entry = prog.add_block(main, |_| InstrInfo::Unreachable);
+    let call = prog.add_block(main, |block| {
+        InstrInfo::Return(block.push(InstrInfo::Call(double, alloc::vec![arg, arg])))
+    });
+    assert_eq! { prog.reachable(main).iter().collect::<Vec<_>>(), [entry] }
+    assert_eq! {
+        prog.set_terminator(main, entry, InstrInfo::Jump(call)),
+        InstrInfo::Unreachable
+    }
+    assert_eq! { prog.reachable(main).iter().collect::<Vec<_>>(), [entry, call] }
+    // Loop back to the entry block.
+    prog.set_terminator(main, call, InstrInfo::Jump(entry));
+    assert_eq! { prog.successors(main, call).collect::<Vec<_>>(), [entry] }
+    assert_eq! { prog.reachable(main).len(), 2 }
+
+    assert_eq! {
+        prog.pretty(double),
+        "f0 double:\n  b0:\n    i0 = const 21\n    i1 = add i0 i0\n    return i1\n"
+    }
+    assert_eq! {
+        prog.pretty(main),
+        "f1 main:\n  b0:\n    jump b1\n  b1:\n    i4 = call f0(i0, i0)\n    jump b0\n"
+    }
+
+    // Instructions of other functions or terminators are not in any block body.
+    assert_eq! { prog.split_block(main, arg), None }
+    let term = prog.funs[main].blocks[call].term;
+    assert_eq! { prog.split_block(main, term), None }
+    // Splitting at the start of a body leaves an empty block behind.
+    let body = prog.funs[main].blocks[call].body;
+    let tail = prog.split_block(main, body.start()).unwrap();
+    assert!(prog.funs[main].blocks[call].body.is_empty());
+    assert_eq! { prog.funs[main].blocks[tail].body, body }
+    assert_eq! { prog.successors(main, call).collect::<Vec<_>>(), [tail] }
+    assert_eq! { prog.successors(main, tail).collect::<Vec<_>>(), [entry] }
+    assert_eq! { prog.reachable(main).len(), 3 }
+}
+
+#[test]
+#[should_panic(expected = "illegal terminator `return i0` in block body")]
+fn terminator_in_body() {
+    let mut prog = Program::new();
+    let fun = prog.add_fun("f");
+    prog.add_block(fun, |block| {
+        let val = block.push(InstrInfo::Const(0));
+        block.push(InstrInfo::Return(val));
+        InstrInfo::Return(val)
+    });
+}
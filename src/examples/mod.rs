@@ -14,6 +14,8 @@
 ///     btree map: VarBMap,
 ///     /// Vector indexed by variable indexes.
 ///     map: VarMap,
+///     /// Slice indexed by variable indexes.
+///     slice: VarSlice,
 /// }
 /// fn main() {
 ///     use std::mem::size_of;
@@ -81,9 +83,40 @@ pub mod basic {
         btree map: VarBMap,
         /// Vector indexed by variable indexes.
         map: VarMap,
+        /// Slice indexed by variable indexes.
+        slice: VarSlice,
+        /// Bit set of variable indexes.
+        bit set: VarBitSet,
+        /// Priority queue of variable indexes.
+        heap: VarHeap,
+        /// Variable names, interned.
+        #[cfg(any(feature = "std", feature = "hashbrown"))]
+        interner: VarNames,
+        /// Slot map of variables, detecting accesses to removed variables.
+        slot map: VarSlots,
+        /// Sparse map of variables, removing leaves holes.
+        sparse map: VarSparse,
+        /// Sliding window of variables.
+        deque map: VarWindow,
+        /// Generator of fresh variable indexes.
+        counter: VarCounter,
+        /// Thread-safe generator of fresh variable indexes.
+        atomic counter: AtomicVarCounter,
+        /// Map of variable indexes storing at most three elements, without allocating.
+        array map: VarArray[3],
+        /// Map of variable indexes storing up to four elements inline.
+        #[cfg(feature = "smallvec")]
+        small map: VarOps[4],
+        /// Hash set of variable indexes.
+        #[cfg(any(feature = "std", feature = "hashbrown"))]
+        hash set: VarHSet,
+        /// Hash map from variable indexes to something.
+        #[cfg(any(feature = "std", feature = "hashbrown"))]
+        hash map: VarHMap,
     }
 
     #[test]
+    #[allow(clippy::into_iter_on_ref)]
     fn run() {
         use core::mem::size_of;
         assert_eq!(size_of::<VarIndex>(), size_of::<usize>());
@@ -142,6 +175,2013 @@ pub mod basic {
         assert_eq! { var_values[v_1], 3  }
         assert_eq! { var_values[v_2], 7  }
     }
+
+    #[test]
+    #[cfg(not(feature = "strict"))]
+    fn views() {
+        let mut vec = alloc::vec![7, 3, 11];
+        let map = VarMap::from_vec_ref(&vec);
+        assert!(core::ptr::eq(map.iter().as_slice(), vec.as_slice()));
+        assert_eq! { map[VarIndex::new(2)], 11 }
+
+        let map = VarMap::from_vec_mut(&mut vec);
+        let v_3 = map.push(5);
+        map[v_3] += 1;
+        assert_eq! { vec, alloc::vec![7, 3, 11, 6] }
+
+        let slice = VarSlice::from_slice(&vec[1..]);
+        assert!(core::ptr::eq(slice.as_slice(), &vec[1..]));
+        assert_eq! { slice.len(), 3 }
+        assert_eq! { slice[VarIndex::new(0)], 3 }
+        assert_eq! { slice.get(VarIndex::new(3)), None }
+
+        let slice = VarSlice::from_slice_mut(&mut vec[..2]);
+        slice[VarIndex::new(1)] = 42;
+        assert_eq! { vec, alloc::vec![7, 42, 11, 6] }
+    }
+
+    #[test]
+    fn slice_of_map() {
+        let mut map: VarMap<_> = [7, 3, 11].iter().cloned().collect();
+        let v_2 = map.last_index().unwrap();
+        let slice = VarSlice::from_map(&map);
+        assert!(core::ptr::eq(slice.as_slice(), map.iter().as_slice()));
+        assert_eq! { slice.indices().collect::<alloc::vec::Vec<_>>(), map.indices().collect::<alloc::vec::Vec<_>>() }
+        assert_eq! { slice[v_2], 11 }
+
+        let slice = VarSlice::from_map_mut(&mut map);
+        slice[v_2] = 42;
+        assert_eq! { map[v_2], 42 }
+    }
+
+    #[test]
+    fn select_nth() {
+        let mut map: VarMap<isize> = [9, -3, 5, 0, 12, 5, -8, 1].iter().cloned().collect();
+        let mut sorted: alloc::vec::Vec<isize> = map.iter().cloned().collect();
+        sorted.sort_by_key(|n| n.abs());
+        let n = map.index_from_usize(4).unwrap();
+        let (before, (pivot_idx, pivot), after) = map.select_nth_unstable_by_key(n, |n| n.abs());
+        assert_eq! { pivot_idx, n }
+        assert_eq! { pivot.abs(), sorted[4].abs() }
+        assert_eq! { before.len(), 4 }
+        assert_eq! { after.len(), 3 }
+        assert!(before.iter().all(|b| b.abs() <= pivot.abs()));
+        assert!(after.iter().all(|a| a.abs() >= pivot.abs()));
+    }
+
+    #[test]
+    fn range_index_mut() {
+        let mut map: VarMap<usize> = (0..6).collect();
+        let (v_1, v_4) = (
+            map.index_from_usize(1).unwrap(),
+            map.index_from_usize(4).unwrap(),
+        );
+        map[v_1..v_4].fill(42);
+        assert_eq! { map.iter().cloned().collect::<alloc::vec::Vec<_>>(), [0, 42, 42, 42, 4, 5] }
+        map[v_1..=v_4].fill(7);
+        assert_eq! { map.iter().cloned().collect::<alloc::vec::Vec<_>>(), [0, 7, 7, 7, 7, 5] }
+        map[..v_1].fill(1);
+        map[v_4..].fill(2);
+        assert_eq! { map.iter().cloned().collect::<alloc::vec::Vec<_>>(), [1, 7, 7, 7, 2, 2] }
+        map[..=v_1].fill(3);
+        assert_eq! { map.iter().cloned().collect::<alloc::vec::Vec<_>>(), [3, 3, 7, 7, 2, 2] }
+        map[..].fill(0);
+        assert!(map.iter().all(|n| *n == 0));
+    }
+
+    #[test]
+    fn index_by_ref() {
+        let mut map = VarMap::new();
+        let v_0 = map.push(1);
+        let _ = map.push(2);
+        let v_2 = map.push(3);
+        let set: VarBSet = [v_0, v_2].iter().cloned().collect();
+        for idx in &set {
+            map[idx] *= 10;
+            assert_eq! { map.get(idx), Some(&map[idx]) }
+            assert!(map.get_mut(idx).is_some());
+        }
+        assert_eq! { map.iter().cloned().collect::<alloc::vec::Vec<_>>(), [10, 2, 30] }
+    }
+
+    #[test]
+    fn runs() {
+        use alloc::vec::Vec;
+        let runs = |map: &VarMap<usize>| -> Vec<(usize, Vec<usize>)> {
+            map.runs_by(|a, b| a == b)
+                .map(|(idx, run)| (idx.get(), run.to_vec()))
+                .collect()
+        };
+
+        let empty: VarMap<usize> = VarMap::new();
+        assert_eq! { runs(&empty), [] }
+        let same: VarMap<usize> = [3, 3, 3].iter().cloned().collect();
+        assert_eq! { runs(&same), [(0, alloc::vec![3, 3, 3])] }
+        let distinct: VarMap<usize> = [1, 2, 3].iter().cloned().collect();
+        assert_eq! {
+            runs(&distinct),
+            [(0, alloc::vec![1]), (1, alloc::vec![2]), (2, alloc::vec![3])]
+        }
+        let mixed: VarMap<usize> = [1, 1, 2, 3, 3, 3, 1].iter().cloned().collect();
+        assert_eq! {
+            runs(&mixed),
+            [
+                (0, alloc::vec![1, 1]),
+                (2, alloc::vec![2]),
+                (3, alloc::vec![3, 3, 3]),
+                (6, alloc::vec![1]),
+            ]
+        }
+
+        let parity: Vec<(usize, usize)> = mixed
+            .runs_by_key(|n| n % 2)
+            .map(|(idx, run)| (idx.get(), run.len()))
+            .collect();
+        assert_eq! { parity, [(0, 2), (2, 1), (3, 4)] }
+    }
+
+    #[test]
+    fn fill() {
+        let mut map: VarMap<usize> = (0..6).collect();
+        let (v_2, v_4) = (
+            map.index_from_usize(2).unwrap(),
+            map.index_from_usize(4).unwrap(),
+        );
+        map.fill_range(v_2..v_4, 7);
+        assert_eq! { map.iter().cloned().collect::<alloc::vec::Vec<_>>(), [0, 1, 7, 7, 4, 5] }
+        map.fill_range(v_4.., 9);
+        assert_eq! { map.iter().cloned().collect::<alloc::vec::Vec<_>>(), [0, 1, 7, 7, 9, 9] }
+        map.fill_range(..=v_2, 3);
+        assert_eq! { map.iter().cloned().collect::<alloc::vec::Vec<_>>(), [3, 3, 3, 7, 9, 9] }
+
+        let mut count = 0;
+        map.fill_with(|| {
+            count += 1;
+            count
+        });
+        assert_eq! { map.iter().cloned().collect::<alloc::vec::Vec<_>>(), [1, 2, 3, 4, 5, 6] }
+        map.fill(0);
+        assert_eq! { map.iter().cloned().collect::<alloc::vec::Vec<_>>(), [0; 6] }
+    }
+
+    #[test]
+    #[cfg(not(feature = "strict"))]
+    fn successors() {
+        use alloc::vec::Vec;
+        // Allocates ids until one is not taken.
+        let taken = [0, 1, 2, 4];
+        let fresh = VarIndex::zero_onwards()
+            .find(|idx| !taken.contains(&idx.get()))
+            .unwrap();
+        assert_eq! { fresh, VarIndex::new(3) }
+
+        let from_5 = VarIndex::new(5).successors();
+        let prefix: Vec<usize> = from_5
+            .clone()
+            .take_while(|idx| *idx < 9)
+            .map(|idx| idx.get())
+            .collect();
+        assert_eq! { prefix, [5, 6, 7, 8] }
+        // Clones restart where the original was.
+        assert_eq! { from_5.clone().next(), Some(VarIndex::new(5)) }
+
+        let mut last = VarIndex::new(usize::MAX - 1).successors();
+        assert_eq! { last.next(), Some(VarIndex::new(usize::MAX - 1)) }
+        assert_eq! { last.next(), Some(VarIndex::new(usize::MAX)) }
+        assert_eq! { last.next(), None }
+        assert_eq! { last.next(), None }
+        assert_eq! { VarIndex::new(usize::MAX).successors().count(), 1 }
+    }
+
+    #[test]
+    #[cfg(not(feature = "strict"))]
+    fn retain_mut() {
+        let mut map: VarMap<usize> = [3, 1, 2, 1].iter().cloned().collect();
+        map.retain_mut(|count| {
+            *count -= 1;
+            *count > 0
+        });
+        assert_eq! { map.iter().cloned().collect::<alloc::vec::Vec<_>>(), [2, 1] }
+
+        let mut map: VarMap<usize> = [3, 1, 2, 1].iter().cloned().collect();
+        let side: VarMap<&str> = ["a", "b", "c", "d"].iter().cloned().collect();
+        let mut seen = alloc::vec![];
+        let remap = map.retain_indexed_mut(|idx, count| {
+            seen.push(idx.get());
+            *count *= 10;
+            idx.get() % 2 == 0
+        });
+        assert_eq! { seen, [0, 1, 2, 3] }
+        assert_eq! { map.iter().cloned().collect::<alloc::vec::Vec<_>>(), [30, 20] }
+        assert_eq! {
+            remap.iter().map(|new| new.map(|idx| idx.get())).collect::<alloc::vec::Vec<_>>(),
+            [Some(0), None, Some(1), None]
+        }
+        // Repair a side table with the remap.
+        let mut repaired = VarMap::new();
+        for (old, new) in remap.index_iter() {
+            if let Some(new) = new {
+                assert_eq! { repaired.push(side[old]), *new }
+            }
+        }
+        assert_eq! { repaired.iter().cloned().collect::<alloc::vec::Vec<_>>(), ["a", "c"] }
+    }
+
+    #[test]
+    fn sort_by_cached_key() {
+        use alloc::{string::String, vec::Vec};
+        let original: VarMap<String> = ["Delta", "alpha", "charlie", "Bravo", "ALPHA", "echo"]
+            .iter()
+            .map(|s| String::from(*s))
+            .collect();
+        let mut map = original.clone();
+        let mut calls = 0;
+        let perm = map.sort_by_cached_key(|s| {
+            calls += 1;
+            s.to_lowercase()
+        });
+        assert_eq! { calls, original.len() }
+        assert_eq! {
+            map.iter().map(String::as_str).collect::<Vec<_>>(),
+            ["alpha", "ALPHA", "Bravo", "charlie", "Delta", "echo"]
+        }
+        assert_eq! { perm.len(), original.len() }
+        for (old, new) in perm.index_iter() {
+            assert_eq! { original[old], map[*new] }
+        }
+    }
+
+    #[test]
+    #[cfg(not(feature = "strict"))]
+    fn extract_if() {
+        use alloc::vec::Vec;
+        let new = || -> VarMap<usize> { (0..6).map(|n| n * 10).collect() };
+        let content = |map: &VarMap<usize>| map.iter().cloned().collect::<Vec<_>>();
+        let extracted = |iter: &mut dyn Iterator<Item = (VarIndex, usize)>| {
+            iter.map(|(idx, n)| (idx.get(), n)).collect::<Vec<_>>()
+        };
+
+        let mut map = new();
+        assert_eq! { extracted(&mut map.extract_if(|_, _| false)), [] }
+        assert_eq! { content(&map), [0, 10, 20, 30, 40, 50] }
+
+        let mut map = new();
+        assert_eq! { extracted(&mut map.extract_if(|_, _| true)).len(), 6 }
+        assert!(map.is_empty());
+
+        let mut map = new();
+        assert_eq! {
+            extracted(&mut map.extract_if(|idx, n| {
+                *n += 1;
+                idx.get() % 2 == 1
+            })),
+            [(1, 11), (3, 31), (5, 51)]
+        }
+        assert_eq! { content(&map), [1, 21, 41] }
+
+        let mut map = new();
+        {
+            let mut iter = map.extract_if(|_, n| *n >= 20);
+            assert_eq! { iter.next().map(|(idx, n)| (idx.get(), n)), Some((2, 20)) }
+        }
+        assert_eq! { content(&map), [0, 10, 30, 40, 50] }
+
+        #[cfg(feature = "std")]
+        {
+            let mut map = new();
+            let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                map.extract_if(|idx, n| match idx.get() {
+                    3 => panic!("pred"),
+                    _ => *n == 10,
+                })
+                .count()
+            }));
+            assert!(res.is_err());
+            assert_eq! { content(&map), [0, 20, 30, 40, 50] }
+        }
+    }
+
+    #[test]
+    fn push_within_capacity() {
+        let mut map = VarMap::with_capacity(2);
+        let capacity = map.capacity();
+        assert_eq! { map.remaining_capacity(), capacity }
+        let mut pushed = alloc::vec![];
+        for n in 0..capacity {
+            pushed.push(map.push_within_capacity(n).unwrap());
+        }
+        assert_eq! { map.remaining_capacity(), 0 }
+        assert_eq! { map.push_within_capacity(42), Err(42) }
+        assert_eq! { map.capacity(), capacity }
+        assert_eq! { map.len(), capacity }
+        for (n, idx) in pushed.into_iter().enumerate() {
+            assert_eq! { map[idx], n }
+        }
+    }
+
+    #[test]
+    fn btree_map_conversions() {
+        let map: VarMap<char> = "abc".chars().collect();
+        let b_map: VarBMap<char> = map.to_btree_map();
+        assert_eq! { b_map.len(), 3 }
+        for (idx, c) in map.index_iter() {
+            assert_eq! { b_map[&idx], *c }
+        }
+        assert_eq! { VarMap::from_dense_btree_map(b_map.clone()), Ok(map.clone()) }
+        assert_eq! { map.clone().into_btree_map(), b_map }
+
+        let mut gapped = b_map;
+        let v_1 = map.index_from_usize(1).unwrap();
+        gapped.remove(&v_1);
+        assert_eq! {
+            VarMap::from_dense_btree_map(gapped),
+            Err(crate::MissingIndex { idx: v_1 })
+        }
+    }
+
+    #[test]
+    fn keys_as_set() {
+        let map: VarMap<usize> = (0..5).collect();
+        let all: VarBSet = map.keys_as_set();
+        assert_eq! { all.len(), 5 }
+        let even: VarBSet = map.indices_matching(|n| n % 2 == 0);
+        let mut some = VarBSet::new();
+        some.insert(map.index_from_usize(1).unwrap());
+        some.insert(map.index_from_usize(2).unwrap());
+        assert_eq! { all.intersection(&some).count(), 2 }
+        assert_eq! {
+            even.intersection(&some).cloned().collect::<VarBSet>(),
+            core::iter::once(map.index_from_usize(2).unwrap()).collect()
+        }
+    }
+
+    #[test]
+    fn diff() {
+        let indices = |set: VarBSet| {
+            set.into_iter()
+                .map(|idx| idx.get())
+                .collect::<alloc::vec::Vec<_>>()
+        };
+        let map: VarMap<usize> = (0..4).collect();
+        assert!(map.diff_indices(&map.clone()).is_empty());
+
+        let mut changed = map.clone();
+        changed[map.index_from_usize(2).unwrap()] = 42;
+        assert_eq! { indices(map.diff_indices(&changed)), [2] }
+        assert_eq! {
+            map.diff(&changed).map(|(idx, a, b)| (idx.get(), a, b)).collect::<alloc::vec::Vec<_>>(),
+            [(2, Some(&2), Some(&42))]
+        }
+
+        let longer: VarMap<usize> = (0..6).collect();
+        assert_eq! { indices(map.diff_indices(&longer)), [4, 5] }
+        assert_eq! { indices(longer.diff_indices(&map)), [4, 5] }
+        assert_eq! {
+            longer.diff(&changed).map(|(idx, a, b)| (idx.get(), a, b)).collect::<alloc::vec::Vec<_>>(),
+            [(2, Some(&2), Some(&42)), (4, Some(&4), None), (5, Some(&5), None)]
+        }
+    }
+
+    #[test]
+    fn for_each_in() {
+        let mut map: VarMap<usize> = (0..5).collect();
+        let set: VarBSet = map.indices_matching(|n| n % 2 == 1);
+        map.for_each_in(&set, |idx, n| {
+            assert_eq! { idx.get(), *n }
+            *n *= 10
+        });
+        assert_eq! { map.iter().cloned().collect::<alloc::vec::Vec<_>>(), [0, 10, 2, 30, 4] }
+
+        let res = map.try_for_each_in(set.iter().rev(), |idx, n| {
+            if idx.get() == 1 {
+                Err(idx)
+            } else {
+                *n += 1;
+                Ok(())
+            }
+        });
+        assert_eq! { res, map.index_from_usize(1).ok_or(()).map(Err).unwrap() }
+        assert_eq! { map.iter().cloned().collect::<alloc::vec::Vec<_>>(), [0, 10, 2, 31, 4] }
+    }
+
+    #[test]
+    fn select() {
+        let map: VarMap<usize> = (0..5).map(|n| n * 10).collect();
+        let set = map.indices_matching(|n| *n >= 20);
+        assert_eq! {
+            map.select(&set).map(|(idx, n)| (idx.get(), *n)).collect::<alloc::vec::Vec<_>>(),
+            [(2, 20), (3, 30), (4, 40)]
+        }
+        let (v_1, v_3) = (
+            map.index_from_usize(1).unwrap(),
+            map.index_from_usize(3).unwrap(),
+        );
+        assert_eq! {
+            map.select(alloc::vec![v_3, v_1, v_3]).map(|(idx, n)| (idx.get(), *n)).collect::<alloc::vec::Vec<_>>(),
+            [(3, 30), (1, 10), (3, 30)]
+        }
+    }
+
+    #[test]
+    fn gather() {
+        let map: VarMap<char> = "abcd".chars().collect();
+        let (same, sources) = map.gather(map.indices());
+        assert_eq! { same, map }
+        assert_eq! { sources.into_iter().collect::<alloc::vec::Vec<_>>(), map.indices().collect::<alloc::vec::Vec<_>>() }
+
+        let (v_0, v_3) = (
+            map.index_from_usize(0).unwrap(),
+            map.index_from_usize(3).unwrap(),
+        );
+        let (gathered, sources) = map.gather([v_3, v_0, v_3, v_3]);
+        assert_eq! { gathered.iter().collect::<alloc::string::String>(), "dadd" }
+        for (new, old) in sources.index_iter() {
+            assert_eq! { gathered[new], map[old] }
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn gather_out_of_bounds() {
+        let set = (0..5).collect::<VarMap<usize>>().keys_as_set();
+        let map: VarMap<usize> = (0..2).collect();
+        let _ = map.gather(set);
+    }
+
+    #[test]
+    fn get_range() {
+        let big: VarMap<usize> = (0..6).collect();
+        let idx = |n: usize| big.index_from_usize(n).unwrap();
+        let mut map: VarMap<usize> = (0..4).collect();
+        assert!(map.contains_index(idx(3)));
+        assert!(!map.contains_index(idx(4)));
+
+        assert_eq! { map.get_range(idx(1)..idx(3)), Some(&[1, 2][..]) }
+        assert_eq! { map.get_range(idx(1)..=idx(3)), Some(&[1, 2, 3][..]) }
+        assert_eq! { map.get_range(idx(2)..idx(2)), Some(&[][..]) }
+        assert_eq! { map.get_range(..), Some(&[0, 1, 2, 3][..]) }
+        // End exactly at the length.
+        assert_eq! { map.get_range(idx(2)..idx(4)), Some(&[2, 3][..]) }
+        assert_eq! { map.get_range(idx(4)..), Some(&[][..]) }
+        // One past the end.
+        assert_eq! { map.get_range(idx(2)..idx(5)), None }
+        assert_eq! { map.get_range(idx(2)..=idx(4)), None }
+        assert_eq! { map.get_range(idx(5)..), None }
+        // Inverted.
+        assert_eq! { map.get_range(idx(3)..idx(1)), None }
+
+        map.get_range_mut(idx(2)..).unwrap().fill(7);
+        assert_eq! { map.get_range(..), Some(&[0, 1, 7, 7][..]) }
+        assert!(map.get_range_mut(..=idx(4)).is_none());
+    }
+
+    #[test]
+    #[cfg(not(feature = "strict"))]
+    fn splice() {
+        use alloc::vec::Vec;
+        let new = || -> VarMap<usize> { (0..5).collect() };
+        let idx = VarIndex::new;
+
+        // Shrinking.
+        let mut map = new();
+        let (shift, removed) = map.splice(idx(1)..idx(4), [10]);
+        assert_eq! { removed.collect::<Vec<_>>(), [1, 2, 3] }
+        assert_eq! { shift, -2 }
+        assert_eq! { map.iter().cloned().collect::<Vec<_>>(), [0, 10, 4] }
+
+        // Growing.
+        let mut map = new();
+        let (shift, removed) = map.splice(idx(1)..=idx(1), [10, 11, 12]);
+        assert_eq! { removed.collect::<Vec<_>>(), [1] }
+        assert_eq! { shift, 2 }
+        assert_eq! { map.iter().cloned().collect::<Vec<_>>(), [0, 10, 11, 12, 2, 3, 4] }
+
+        // Same length, removed elements not consumed.
+        let mut map = new();
+        let (shift, _) = map.splice(idx(3).., [30, 40]);
+        assert_eq! { shift, 0 }
+        assert_eq! { map.iter().cloned().collect::<Vec<_>>(), [0, 1, 2, 30, 40] }
+
+        // Insertion with an empty range.
+        let mut map = new();
+        let (shift, removed) = map.splice(idx(2)..idx(2), [7, 7]);
+        assert_eq! { removed.count(), 0 }
+        assert_eq! { shift, 2 }
+        assert_eq! { map.iter().cloned().collect::<Vec<_>>(), [0, 1, 7, 7, 2, 3, 4] }
+        // Fixing an index after the range.
+        let old = idx(3);
+        let new = idx((old.get() as isize + shift) as usize);
+        assert_eq! { map[new], 3 }
+    }
+
+    #[test]
+    fn extend_pairs() {
+        let source: VarMap<usize> = (0..6).collect();
+        let mut left: VarMap<usize> = source.get_range(..).unwrap()[..3].iter().cloned().collect();
+        left.extend(source.index_iter().skip(3).map(|(idx, n)| (idx, *n)));
+        assert_eq! { left, source }
+
+        let mut map: VarMap<usize> = VarMap::new();
+        let pairs = source
+            .clone()
+            .into_index_iter()
+            .filter(|(idx, _)| idx.get() != 2);
+        let err = map.try_extend_pairs(pairs).unwrap_err();
+        assert_eq! { (err.expected.get(), err.found.get()), (2, 3) }
+        assert_eq! { map.len(), 2 }
+
+        let mut map: VarMap<usize> = VarMap::new();
+        let err = map
+            .try_extend_pairs(source.clone().into_index_iter().rev())
+            .unwrap_err();
+        assert_eq! { (err.expected.get(), err.found.get()), (0, 5) }
+        assert_eq! { map.len(), 0 }
+    }
+
+    #[test]
+    #[should_panic(expected = "expected index 0, found 1")]
+    fn extend_pairs_gap() {
+        let source: VarMap<usize> = (0..6).collect();
+        let mut map = VarMap::new();
+        map.extend(source.into_index_iter().skip(1));
+    }
+
+    #[test]
+    fn get_unchecked() {
+        let mut map: VarMap<usize> = (0..6).collect();
+        for idx in map.indices() {
+            assert_eq! { unsafe { map.get_unchecked(idx) }, &map[idx] }
+        }
+        for idx in map.indices() {
+            *unsafe { map.get_unchecked_mut(idx) } += 1;
+        }
+        assert_eq! { map.iter().cloned().collect::<alloc::vec::Vec<_>>(), [1, 2, 3, 4, 5, 6] }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic]
+    fn get_unchecked_debug_check() {
+        let big: VarMap<usize> = (0..6).collect();
+        let map: VarMap<usize> = (0..2).collect();
+        let _ = unsafe { map.get_unchecked(big.last_index().unwrap()) };
+    }
+
+    #[test]
+    #[should_panic(expected = "index VarIndex(4) out of bounds for VarMap of length 2")]
+    fn index_out_of_bounds() {
+        let big: VarMap<usize> = (0..5).collect();
+        let map: VarMap<usize> = (0..2).collect();
+        let _ = map[big.last_index().unwrap()];
+    }
+
+    #[test]
+    #[should_panic(expected = "index VarIndex(4) out of bounds for VarMap of length 2")]
+    fn index_mut_out_of_bounds() {
+        let big: VarMap<usize> = (0..5).collect();
+        let mut map: VarMap<usize> = (0..2).collect();
+        map[big.last_index().unwrap()] = 0;
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "range VarIndex(1)..=VarIndex(4) out of bounds for VarMap of length 2"
+    )]
+    fn range_out_of_bounds() {
+        let big: VarMap<usize> = (0..5).collect();
+        let map: VarMap<usize> = (0..2).collect();
+        let _ = &map[big.index_from_usize(1).unwrap()..=big.last_index().unwrap()];
+    }
+
+    #[test]
+    #[should_panic(expected = "range ..VarIndex(4) out of bounds for VarMap of length 2")]
+    fn range_mut_out_of_bounds() {
+        let big: VarMap<usize> = (0..5).collect();
+        let mut map: VarMap<usize> = (0..2).collect();
+        map[..big.last_index().unwrap()].fill(0);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "range VarIndex(4)..VarIndex(1) is inverted, indexing VarMap of length 5"
+    )]
+    fn range_inverted() {
+        let map: VarMap<usize> = (0..5).collect();
+        let _ = &map[map.last_index().unwrap()..map.index_from_usize(1).unwrap()];
+    }
+
+    #[test]
+    #[should_panic(expected = "index VarIndex(4) out of bounds for VarSlice of length 2")]
+    #[cfg(not(feature = "strict"))]
+    fn slice_index_out_of_bounds() {
+        let _ = VarSlice::from_slice(&[0, 1])[VarIndex::new(4)];
+    }
+
+    #[test]
+    fn copy_within() {
+        use alloc::{string::String, vec::Vec};
+        let new = || -> VarMap<usize> { (0..6).collect() };
+        let content = |map: &VarMap<usize>| map.iter().cloned().collect::<Vec<_>>();
+        let idx = |n: usize| new().index_from_usize(n).unwrap();
+
+        // Forward overlap.
+        let mut map = new();
+        map.copy_within(idx(0)..idx(3), idx(2));
+        assert_eq! { content(&map), [0, 1, 0, 1, 2, 5] }
+        let mut strings: VarMap<String> = new().iter().map(|n| alloc::format!("{}", n)).collect();
+        strings.clone_within(idx(0)..idx(3), idx(2));
+        assert_eq! { strings.iter().map(String::as_str).collect::<Vec<_>>(), ["0", "1", "0", "1", "2", "5"] }
+
+        // Backward overlap.
+        let mut map = new();
+        map.copy_within(idx(2)..=idx(4), idx(1));
+        assert_eq! { content(&map), [0, 2, 3, 4, 4, 5] }
+        let mut strings: VarMap<String> = new().iter().map(|n| alloc::format!("{}", n)).collect();
+        strings.clone_within(idx(2)..=idx(4), idx(1));
+        assert_eq! { strings.iter().map(String::as_str).collect::<Vec<_>>(), ["0", "2", "3", "4", "4", "5"] }
+
+        // Up to the end.
+        let mut map = new();
+        map.copy_within(..idx(2), idx(4));
+        assert_eq! { content(&map), [0, 1, 2, 3, 0, 1] }
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "range VarIndex(5)..VarIndex(7) out of bounds for VarMap of length 6"
+    )]
+    fn copy_within_out_of_bounds() {
+        let mut map: VarMap<usize> = (0..6).collect();
+        map.copy_within(
+            ..map.index_from_usize(2).unwrap(),
+            map.last_index().unwrap(),
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "range VarIndex(5)..VarIndex(7) out of bounds for VarMap of length 6"
+    )]
+    fn clone_within_out_of_bounds() {
+        let mut map: VarMap<usize> = (0..6).collect();
+        map.clone_within(
+            ..map.index_from_usize(2).unwrap(),
+            map.last_index().unwrap(),
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn par_sort() {
+        use rand::{rngs::SmallRng, Rng, SeedableRng};
+        let mut rng = SmallRng::seed_from_u64(0x5eed);
+        let map: VarMap<u64> = (0..10_000).map(|_| rng.gen_range(0..1 << 31)).collect();
+        let key = |n: &u64| n % 1_000;
+
+        let mut expected = map.clone();
+        let expected_perm = expected.sort_by_cached_key(key);
+
+        let mut stable = map.clone();
+        let perm = stable.par_sort_by_key(key);
+        assert_eq! { stable, expected }
+        assert_eq! { perm, expected_perm }
+
+        let mut unstable = map.clone();
+        let perm = unstable.par_sort_unstable_by_key(key);
+        assert!(unstable
+            .iter()
+            .zip(expected.iter())
+            .all(|(a, b)| key(a) == key(b)));
+        assert_eq! { perm.len(), map.len() }
+        for (old, new) in perm.index_iter() {
+            assert_eq! { map[old], unstable[*new] }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn par_sort_panicking_key() {
+        extern crate std;
+        let mut map: VarMap<usize> = (0..1_000).rev().collect();
+        let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            map.par_sort_by_key(|n| if *n == 500 { panic!("key") } else { *n })
+        }));
+        assert!(res.is_err());
+        assert_eq! { map.len(), 1_000 }
+        let mut elems: alloc::vec::Vec<usize> = map.iter().copied().collect();
+        elems.sort_unstable();
+        assert!(elems.into_iter().eq(0..1_000));
+    }
+
+    #[test]
+    fn iterator_traits() {
+        use alloc::vec::Vec;
+        let mut map: VarMap<usize> = (0..4).collect();
+
+        let mut indices = map.indices();
+        assert_eq! { indices.len(), 4 }
+        assert_eq! { indices.size_hint(), (4, Some(4)) }
+        assert_eq! { indices.next_back().map(|idx| idx.get()), Some(3) }
+        assert_eq! { indices.nth(1).map(|idx| idx.get()), Some(1) }
+        assert_eq! { indices.len(), 1 }
+        assert_eq! { indices.next().map(|idx| idx.get()), Some(2) }
+        assert_eq! { indices.next(), None }
+        assert_eq! { indices.next(), None }
+        assert_eq! { map.indices().rev().map(|idx| idx.get()).collect::<Vec<_>>(), [3, 2, 1, 0] }
+
+        {
+            let mut iter = map.index_iter();
+            assert_eq! { iter.len(), 4 }
+            assert_eq! { iter.next_back().map(|(idx, n)| (idx.get(), *n)), Some((3, 3)) }
+            assert_eq! { iter.len(), 3 }
+            assert_eq! { iter.by_ref().count(), 3 }
+            assert!(iter.next().is_none());
+            assert!(iter.next().is_none());
+        }
+
+        for (idx, n) in map.index_iter_mut().rev().take(2) {
+            *n = idx.get() * 10
+        }
+        assert_eq! { map.clone().into_index_iter().len(), 4 }
+        assert_eq! {
+            map.into_index_iter().rev().map(|(idx, n)| (idx.get(), n)).collect::<Vec<_>>(),
+            [(3, 30), (2, 20), (1, 1), (0, 0)]
+        }
+    }
+
+    #[test]
+    fn new_like() {
+        let names: VarMap<&str> = ["x", "y", "z"].iter().cloned().collect();
+        let counts: VarMap<usize> = VarMap::new_like(&names);
+        assert_eq! { counts.len(), names.len() }
+        assert!(counts.iter().all(|n| *n == 0));
+
+        let mut seen = alloc::vec![];
+        let labels = VarMap::new_like_with(&names, |idx, name| {
+            seen.push(idx);
+            alloc::format!("{}_{}", name, idx)
+        });
+        assert_eq! { seen, names.indices().collect::<alloc::vec::Vec<_>>() }
+        for idx in names.indices() {
+            assert_eq! { labels[idx], alloc::format!("{}_{}", names[idx], idx) }
+        }
+    }
+
+    #[test]
+    fn take_replace() {
+        let mut map: VarMap<alloc::string::String> = ["a", "b"]
+            .iter()
+            .map(|s| alloc::string::String::from(*s))
+            .collect();
+        let (v_0, v_1) = (
+            map.index_from_usize(0).unwrap(),
+            map.index_from_usize(1).unwrap(),
+        );
+        assert_eq! { map.take(v_0), "a" }
+        assert_eq! { map[v_0], "" }
+        assert_eq! { map.replace(v_1, "c".into()), "b" }
+        assert_eq! { map[v_1], "c" }
+    }
+
+    #[test]
+    #[should_panic(expected = "index VarIndex(4) out of bounds for VarMap of length 2")]
+    fn take_out_of_bounds() {
+        let big: VarMap<usize> = (0..5).collect();
+        let mut map: VarMap<usize> = (0..2).collect();
+        map.take(big.last_index().unwrap());
+    }
+
+    #[test]
+    fn rposition() {
+        let map: VarMap<usize> = [4, 1, 2, 1, 3].iter().cloned().collect();
+        let found = |pred: fn(&usize) -> bool| map.rposition(pred).map(|idx| idx.get());
+        assert_eq! { found(|n| *n == 3), Some(4) }
+        assert_eq! { found(|n| *n == 1), Some(3) }
+        assert_eq! { found(|n| *n == 4), Some(0) }
+        assert_eq! { found(|n| *n == 0), None }
+
+        let mut visited = 0;
+        let last_odd_before_3 = map.rfind_indexed(|idx, n| {
+            visited += 1;
+            idx.get() < 3 && n % 2 == 1
+        });
+        assert_eq! { last_odd_before_3.map(|(idx, n)| (idx.get(), *n)), Some((1, 1)) }
+        assert_eq! { visited, 4 }
+        assert_eq! { map.rfind_indexed(|_, n| *n == 4).map(|(idx, _)| idx.get()), Some(0) }
+        assert!(map.rfind_indexed(|_, n| *n > 4).is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn hash_set() {
+        use std::collections::{hash_map::DefaultHasher, BTreeSet};
+        use std::hash::BuildHasherDefault;
+
+        let map: VarMap<usize> = (0..10).collect();
+        let even: VarHSet = map
+            .index_iter()
+            .filter(|(_, n)| *n % 2 == 0)
+            .map(|(idx, _)| idx)
+            .collect();
+        assert_eq! { even.len(), 5 }
+        for (idx, n) in map.index_iter() {
+            assert_eq! { even.contains(&idx), n % 2 == 0 }
+        }
+        let as_btree: BTreeSet<_> = even.iter().cloned().collect();
+        assert_eq! { as_btree, map.indices_matching(|n| n % 2 == 0) }
+
+        let mut custom: VarHSet<BuildHasherDefault<DefaultHasher>> = VarHSet::default();
+        let first = map.indices().next().unwrap();
+        assert!(custom.insert(first));
+        assert!(!custom.insert(first));
+        custom.extend(even.iter().cloned());
+        assert_eq! { custom.len(), 5 }
+        assert!(custom.remove(&first));
+        assert_eq! { custom.len(), 4 }
+        assert!(custom.iter().all(|idx| even.contains(idx)));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn hash_map() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::BuildHasherDefault;
+
+        let map: VarMap<usize> = (0..100).collect();
+        // Only multiples of 25 carry a name.
+        let mut names = VarHMap::new();
+        for (idx, n) in map.index_iter() {
+            if n % 25 == 0 {
+                names.insert(idx, alloc::format!("v_{}", n));
+            }
+        }
+        assert_eq! { names.len(), 4 }
+        for (idx, n) in map.index_iter() {
+            assert_eq! { names.contains_key(&idx), n % 25 == 0 }
+        }
+        let last = map.last_index().unwrap();
+        assert_eq! { names.get(&last), None }
+        names.insert(last, "last".into());
+        assert_eq! { names[&last], "last" }
+
+        let mut custom: VarHMap<&str, BuildHasherDefault<DefaultHasher>> = VarHMap::default();
+        custom.insert(last, "last");
+        assert_eq! { custom.get(&last), Some(&"last") }
+        assert_eq! { custom.remove(&last), Some("last") }
+        assert!(custom.is_empty());
+    }
+
+    #[test]
+    fn bit_set() {
+        use alloc::{collections::BTreeSet, vec::Vec};
+        use rand::{rngs::SmallRng, Rng, SeedableRng};
+        let mut rng = SmallRng::seed_from_u64(0xb175e7);
+        let mut rand = |max: usize| rng.gen_range(0..max);
+        let map: VarMap<()> = VarMap::of_elems((), 300);
+        let idx = |n: usize| map.index_from_usize(n).unwrap();
+
+        let (mut set, mut oracle) = (VarBitSet::new(), BTreeSet::new());
+        let (mut other, mut other_oracle) = (VarBitSet::new(), BTreeSet::new());
+        for _ in 0..2_000 {
+            let i = idx(rand(300));
+            match rand(10) {
+                0..=3 => assert_eq! { set.insert(i), oracle.insert(i) },
+                4 | 5 => assert_eq! { set.remove(i), oracle.remove(&i) },
+                6 | 7 => {
+                    other.insert(i);
+                    other_oracle.insert(i);
+                }
+                8 => match rand(3) {
+                    0 => {
+                        set.union_with(&other);
+                        oracle = oracle.union(&other_oracle).cloned().collect();
+                    }
+                    1 => {
+                        set.intersect_with(&other);
+                        oracle = oracle.intersection(&other_oracle).cloned().collect();
+                    }
+                    _ => {
+                        set.difference_with(&other);
+                        oracle = oracle.difference(&other_oracle).cloned().collect();
+                    }
+                },
+                _ => {
+                    if rand(20) == 0 {
+                        set.clear();
+                        oracle.clear();
+                    }
+                    set.grow_to(i);
+                }
+            }
+            assert_eq! { set.contains(i), oracle.contains(&i) }
+            assert_eq! { set.len(), oracle.len() }
+            assert_eq! { set.is_empty(), oracle.is_empty() }
+        }
+        assert_eq! { set.iter().collect::<Vec<_>>(), oracle.iter().cloned().collect::<Vec<_>>() }
+        assert_eq! { set, oracle.iter().cloned().collect() }
+
+        // Equality ignores capacity.
+        let mut big = VarBitSet::new();
+        big.grow_to(idx(299));
+        assert_eq! { big, VarBitSet::new() }
+        big.insert(idx(64));
+        assert_eq! { alloc::format!("{:?}", big), "{64}" }
+    }
+
+    #[test]
+    fn heap() {
+        use alloc::vec::Vec;
+        use core::cmp::Reverse;
+        use rand::{rngs::SmallRng, Rng, SeedableRng};
+        let mut rng = SmallRng::seed_from_u64(0xd1325);
+        let mut rand = |max: u64| rng.gen_range(0..max);
+
+        // Random graph, maps nodes to their weighted successors.
+        let mut graph: VarMap<Vec<(VarIndex, u64)>> = VarMap::of_elems(Vec::new(), 40);
+        let nodes: Vec<VarIndex> = graph.indices().collect();
+        for _ in 0..150 {
+            let (src, tgt) = (nodes[rand(40) as usize], nodes[rand(40) as usize]);
+            graph[src].push((tgt, rand(100)))
+        }
+        let src = nodes[0];
+
+        // Dijkstra.
+        let mut dist: VarMap<Option<u64>> = VarMap::of_elems(None, graph.len());
+        let mut heap = VarHeap::new();
+        heap.push(src, Reverse(0));
+        while let Some((node, Reverse(d))) = heap.pop() {
+            dist[node] = Some(d);
+            for (succ, w) in &graph[node] {
+                if dist[succ].is_some() {
+                    continue;
+                }
+                match heap.priority(succ) {
+                    Some(Reverse(old)) if *old <= d + w => (),
+                    Some(_) => {
+                        heap.change_priority(*succ, Reverse(d + w));
+                    }
+                    None => {
+                        heap.push(*succ, Reverse(d + w));
+                    }
+                }
+            }
+        }
+
+        // Brute force: Bellman-Ford.
+        let mut expected: VarMap<Option<u64>> = VarMap::of_elems(None, graph.len());
+        expected[src] = Some(0);
+        for _ in 0..graph.len() {
+            for (node, succs) in graph.index_iter() {
+                if let Some(d) = expected[node] {
+                    for (succ, w) in succs {
+                        if expected[succ].is_none_or(|old| d + w < old) {
+                            expected[succ] = Some(d + w)
+                        }
+                    }
+                }
+            }
+        }
+        assert_eq! { dist, expected }
+
+        let mut heap = VarHeap::new();
+        for (idx, prio) in nodes.iter().zip([5, 1, 7, 3]) {
+            assert_eq! { heap.push(*idx, prio), None }
+        }
+        assert_eq! { heap.push(nodes[1], 4), Some(1) }
+        assert_eq! { heap.change_priority(nodes[2], 0), Some(7) }
+        assert_eq! { heap.change_priority(nodes[9], 0), None }
+        assert!(heap.contains(nodes[3]) && !heap.contains(nodes[9]));
+        assert_eq! { heap.remove(nodes[3]), Some(3) }
+        assert_eq! { heap.peek(), Some((nodes[0], &5)) }
+        let order: Vec<_> = core::iter::from_fn(|| heap.pop()).collect();
+        assert_eq! { order, [(nodes[0], 5), (nodes[1], 4), (nodes[2], 0)] }
+        assert!(heap.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn bit_set_random_members() {
+        use alloc::{collections::BTreeMap, vec::Vec};
+        use rand::{rngs::SmallRng, SeedableRng};
+
+        let map: VarMap<()> = VarMap::of_elems((), 300);
+        let members = [0, 63, 64, 65, 200, 299];
+        let set: VarBitSet = members
+            .iter()
+            .map(|n| map.index_from_usize(*n).unwrap())
+            .collect();
+        let mut rng = SmallRng::seed_from_u64(42);
+
+        let mut counts = BTreeMap::new();
+        for _ in 0..6_000 {
+            *counts.entry(set.choose(&mut rng).unwrap()).or_insert(0) += 1;
+        }
+        assert!(counts.keys().cloned().eq(set.iter()));
+        assert!(
+            counts.values().all(|n| (800..1200).contains(n)),
+            "{:?}",
+            counts
+        );
+        assert_eq! { VarBitSet::new().choose(&mut rng), None }
+
+        for k in 0..8 {
+            let sample = set.sample(&mut rng, k);
+            assert_eq! { sample.len(), k.min(6) }
+            assert!(sample.windows(2).all(|pair| pair[0] < pair[1]));
+            assert!(sample.iter().all(|idx| set.contains(idx)));
+        }
+
+        let run = |seed| {
+            let mut rng = SmallRng::seed_from_u64(seed);
+            let picks: Vec<_> = (0..10).map(|_| set.choose(&mut rng)).collect();
+            (picks, set.sample(&mut rng, 3))
+        };
+        assert_eq! { run(7), run(7) }
+    }
+
+    #[test]
+    #[cfg(all(feature = "rand", any(feature = "std", feature = "hashbrown")))]
+    fn alias_set_random_members() {
+        use crate::ChooseIndex;
+        use alloc::collections::BTreeMap;
+        use rand::{rngs::SmallRng, SeedableRng};
+
+        let map: VarMap<()> = VarMap::of_elems((), 10);
+        let bset: VarBSet = map.indices().filter(|idx| idx.get() % 3 == 0).collect();
+        let hset: VarHSet = bset.iter().copied().collect();
+        let mut rng = SmallRng::seed_from_u64(42);
+
+        for _ in 0..100 {
+            assert!(bset.contains(&bset.choose(&mut rng).unwrap()));
+        }
+        let mut counts = BTreeMap::new();
+        for _ in 0..4_000 {
+            *counts.entry(hset.choose(&mut rng).unwrap()).or_insert(0) += 1;
+        }
+        assert!(counts.keys().eq(bset.iter()));
+        assert!(
+            counts.values().all(|n| (800..1200).contains(n)),
+            "{:?}",
+            counts
+        );
+        assert_eq! { <VarHSet>::default().choose(&mut rng), None }
+        assert_eq! { VarBSet::new().choose(&mut rng), None }
+
+        for k in 0..6 {
+            let sample = hset.sample(&mut rng, k);
+            assert_eq! { sample.len(), k.min(4) }
+            assert!(sample.iter().all(|idx| hset.contains(idx)));
+            let sample = bset.sample(&mut rng, k);
+            assert!(sample.windows(2).all(|pair| pair[0] < pair[1]));
+        }
+    }
+
+    #[test]
+    fn bit_set_rank_select() {
+        use alloc::vec::Vec;
+        let map: VarMap<()> = VarMap::of_elems((), 300);
+        let idx = |n: usize| map.index_from_usize(n).unwrap();
+        let members = [0, 1, 63, 64, 65, 127, 128, 200, 299];
+        let set: VarBitSet = members.iter().map(|n| idx(*n)).collect();
+        // Sorted-vec oracle.
+        let oracle: Vec<VarIndex> = members.iter().map(|n| idx(*n)).collect();
+
+        for k in 0..12 {
+            assert_eq! { set.select(k), oracle.get(k).copied() }
+        }
+        for i in map.indices() {
+            let rank = oracle.iter().filter(|m| **m < i).count();
+            assert_eq! { set.rank(i), rank }
+            assert_eq! { set.position_of(i), oracle.binary_search(&i).ok() }
+        }
+        assert_eq! { VarBitSet::new().select(0), None }
+        assert_eq! { VarBitSet::new().rank(idx(200)), 0 }
+    }
+
+    #[test]
+    #[cfg(any(feature = "std", feature = "hashbrown"))]
+    fn interner() {
+        use alloc::{string::String, vec::Vec};
+        let mut names = VarNames::new();
+        assert!(names.is_empty());
+        let x = names.intern(String::from("x"));
+        let y = names.intern(String::from("y"));
+        assert_ne! { x, y }
+        assert_eq! { names.intern(String::from("x")), x }
+        assert_eq! { names.intern(String::from("y")), y }
+        let z = names.intern(String::from("z"));
+        assert_eq! { names.len(), 3 }
+
+        for idx in names.indices() {
+            assert_eq! { names.intern(names[idx].clone()), idx }
+            assert_eq! { names.get(names.resolve(idx).as_str()), Some(idx) }
+        }
+        assert_eq! { names.get("w"), None }
+        assert!(names.contains("z"));
+        assert_eq! { names.try_resolve(z).map(String::as_str), Some("z") }
+        assert_eq! { names.iter().map(String::as_str).collect::<Vec<_>>(), ["x", "y", "z"] }
+        assert_eq! { names.index_iter().map(|(idx, _)| idx).collect::<Vec<_>>(), [x, y, z] }
+
+        let chars: VarNames<char> = "abracadabra".chars().collect();
+        assert_eq! { chars.as_slice(), ['a', 'b', 'r', 'c', 'd'] }
+        assert_eq! { chars.into_vec().len(), 5 }
+    }
+
+    #[test]
+    #[cfg(any(feature = "std", feature = "hashbrown"))]
+    #[should_panic(expected = "index VarIndex(1) out of bounds for VarNames of length 1")]
+    fn interner_out_of_bounds() {
+        let mut names = VarNames::new();
+        names.intern('a');
+        let map: VarMap<()> = VarMap::of_elems((), 2);
+        let _ = names[map.last_index().unwrap()];
+    }
+
+    #[test]
+    fn slot_map() {
+        use alloc::vec::Vec;
+        let mut slots = VarSlots::new();
+        let a = slots.insert('a');
+        let b = slots.insert('b');
+        let c = slots.insert('c');
+        assert_eq! { slots.len(), 3 }
+        assert_eq! { (slots[a], slots[b], slots[c]), ('a', 'b', 'c') }
+
+        assert_eq! { slots.remove(b), Some('b') }
+        assert_eq! { slots.remove(b), None }
+        assert_eq! { slots.get(b), None }
+        assert!(!slots.contains(b));
+        assert_eq! { slots.len(), 2 }
+
+        // The slot of `b` is reused, `b` is stale and does not see the new element.
+        let d = slots.insert('d');
+        assert_eq! { d.index(), b.index() }
+        assert_ne! { d, b }
+        assert_eq! { slots.slot_count(), 3 }
+        assert_eq! { slots.get(b), None }
+        assert_eq! { slots.get_mut(b), None }
+        assert_eq! { slots.remove(b), None }
+        assert_eq! { slots.get(d), Some(&'d') }
+
+        slots[d] = 'D';
+        *slots.get_mut(a).unwrap() = 'A';
+        for (_, elem) in slots.iter_mut() {
+            elem.make_ascii_lowercase()
+        }
+        assert_eq! { slots.iter().collect::<Vec<_>>(), [(a, &'a'), (d, &'d'), (c, &'c')] }
+        assert_eq! { slots.handles().collect::<Vec<_>>(), [a, d, c] }
+        assert_eq! { slots.values().collect::<alloc::string::String>(), "adc" }
+
+        slots.clear();
+        assert!(slots.is_empty());
+        assert!([a, c, d].iter().all(|handle| !slots.contains(*handle)));
+        let e = slots.insert('e');
+        assert!(e != a && e != c && e != d);
+        assert_eq! { slots.len(), 1 }
+    }
+
+    #[test]
+    #[should_panic(expected = "handle VarIndex(0)@0 is stale in VarSlots")]
+    fn slot_map_stale_index() {
+        let mut slots = VarSlots::new();
+        let a = slots.insert(1);
+        slots.remove(a);
+        slots.insert(2);
+        let _ = slots[a];
+    }
+
+    #[test]
+    fn sparse_map() {
+        use alloc::vec::Vec;
+        let mut vars: VarSparse<char> = "abcd".chars().collect();
+        let idx: Vec<VarIndex> = vars.indices_present().collect();
+        assert_eq! { (vars.len(), vars.len_present()), (4, 4) }
+
+        assert_eq! { vars.remove(idx[1]), Some('b') }
+        assert_eq! { vars.remove(idx[1]), None }
+        assert_eq! { vars.get(idx[1]), None }
+        assert!(!vars.contains_index(idx[1]));
+        // Other indices are not shifted.
+        assert_eq! { (vars[idx[0]], vars[idx[2]], vars[idx[3]]), ('a', 'c', 'd') }
+        assert_eq! { (vars.len(), vars.len_present(), vars.hole_count()), (4, 3, 1) }
+
+        // Pushing never reuses holes, inserting fills them.
+        let e = vars.push('e');
+        assert_eq! { e.get(), 4 }
+        assert_eq! { vars.insert(idx[1], 'B'), None }
+        assert_eq! { vars.insert(idx[1], 'b'), Some('B') }
+        assert_eq! { vars.len_present(), 5 }
+        assert_eq! { vars.remove(idx[0]), Some('a') }
+        *vars.get_mut(idx[3]).unwrap() = 'D';
+        vars[idx[2]] = 'C';
+        assert_eq! {
+            vars.iter_present().collect::<Vec<_>>(),
+            [(idx[1], &'b'), (idx[2], &'C'), (idx[3], &'D'), (e, &'e')]
+        }
+        for (_, c) in vars.iter_present_mut() {
+            c.make_ascii_uppercase()
+        }
+        assert_eq! { vars.iter_present().rev().map(|(_, c)| *c).collect::<Vec<_>>(), ['E', 'D', 'C', 'B'] }
+    }
+
+    #[test]
+    #[cfg(not(feature = "strict"))]
+    fn sparse_map_compact() {
+        use alloc::vec::Vec;
+        let mut vars: VarSparse<char> = "abcde".chars().collect();
+        let idx: Vec<VarIndex> = vars.indices_present().collect();
+        // Side table over the indices of `vars`.
+        let side: VarMap<usize> = idx.iter().map(|idx| idx.get() * 10).collect();
+        vars.remove(idx[0]);
+        vars.remove(idx[3]);
+
+        let remap = vars.compact();
+        assert_eq! { remap[..], [None, Some(idx[0]), Some(idx[1]), None, Some(idx[2])] }
+        assert_eq! { (vars.len(), vars.hole_count()), (3, 0) }
+        assert_eq! { vars.iter_present().map(|(_, c)| *c).collect::<alloc::string::String>(), "bce" }
+
+        // Repair the side table.
+        let mut repaired = VarMap::of_elems(0, vars.len());
+        for (old, new) in remap.index_iter() {
+            if let Some(new) = new {
+                repaired[*new] = side[old]
+            }
+        }
+        assert_eq! { repaired[..], [10, 20, 40] }
+        for (new, c) in vars.iter_present() {
+            assert_eq! { repaired[new], (*c as usize - 'a' as usize) * 10 }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "index VarIndex(0) has no element in VarSparse")]
+    fn sparse_map_hole() {
+        let mut vars: VarSparse<char> = "ab".chars().collect();
+        let a = vars.indices_present().next().unwrap();
+        vars.remove(a);
+        let _ = vars[a];
+    }
+
+    #[test]
+    fn deque_map() {
+        use alloc::vec::Vec;
+        const WINDOW: usize = 3;
+        let mut window = VarWindow::new();
+        assert_eq! { (window.front_index(), window.back_index()), (None, None) }
+
+        let mut pushed = Vec::new();
+        for n in 0..7 {
+            let idx = window.push_back(n * 10);
+            if let Some(last) = pushed.last() {
+                assert!(*last < idx)
+            }
+            pushed.push(idx);
+            if window.len() > WINDOW {
+                let (evicted, val) = window.pop_front().unwrap();
+                assert_eq! { (evicted, val), (pushed[n - WINDOW], (n - WINDOW) * 10) }
+            }
+        }
+        assert_eq! { window.len(), WINDOW }
+        assert_eq! { window.front_index(), Some(pushed[4]) }
+        assert_eq! { window.back_index(), Some(pushed[6]) }
+        // Evicted indices report `None`, live ones still resolve.
+        for idx in &pushed[..4] {
+            assert_eq! { window.get(idx), None }
+            assert!(!window.contains_index(idx));
+        }
+        for (n, idx) in pushed.iter().enumerate().skip(4) {
+            assert_eq! { window[*idx], n * 10 }
+        }
+        assert_eq! { window.index_iter().map(|(idx, _)| idx).collect::<Vec<_>>(), pushed[4..] }
+
+        *window.get_mut(pushed[5]).unwrap() += 1;
+        for (idx, val) in window.index_iter_mut() {
+            *val += idx.get()
+        }
+        assert_eq! { window.iter().cloned().collect::<Vec<_>>(), [44, 56, 66] }
+
+        assert_eq! { window.evict_before(pushed[6]), 2 }
+        assert_eq! { window.evict_before(pushed[6]), 0 }
+        assert_eq! { window.indices().collect::<Vec<_>>(), [pushed[6]] }
+        window.clear();
+        assert!(window.is_empty());
+        assert_eq! { window.pop_front(), None }
+        // Indices are never reused.
+        assert!(window.push_back(0) > pushed[6]);
+    }
+
+    #[test]
+    #[should_panic(expected = "index VarIndex(0) was evicted from VarWindow, front index is 1")]
+    fn deque_map_evicted() {
+        let mut window = VarWindow::new();
+        let idx = window.push_back('a');
+        window.push_back('b');
+        window.pop_front();
+        let _ = window[idx];
+    }
+
+    #[test]
+    fn counter() {
+        let mut counter = VarCounter::new();
+        let v_0 = counter.peek();
+        assert_eq! { counter.next(), v_0 }
+        let v_1 = counter.next();
+        assert!(v_0 < v_1);
+        assert_eq! { counter.count(), 2 }
+
+        let mut other = VarCounter::default();
+        for _ in 0..5 {
+            other.next();
+        }
+        let floor = other.next();
+        counter.set_floor(floor);
+        assert_eq! { counter.next(), floor }
+        // Floors below the next index are ignored.
+        counter.set_floor(v_1);
+        assert!(counter.next() > floor);
+
+        static COUNTER: AtomicVarCounter = AtomicVarCounter::new();
+        let v_0 = COUNTER.peek();
+        assert_eq! { COUNTER.fetch_next(), v_0 }
+        COUNTER.set_floor(floor);
+        assert_eq! { COUNTER.fetch_next(), floor }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn atomic_counter_threads() {
+        use crate::std::{sync::Arc, thread, vec::Vec};
+        use alloc::collections::BTreeSet;
+        let counter = Arc::new(AtomicVarCounter::new());
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let counter = counter.clone();
+                thread::spawn(move || (0..1_000).map(|_| counter.fetch_next()).collect::<Vec<_>>())
+            })
+            .collect();
+        let mut all = BTreeSet::new();
+        for handle in handles {
+            for idx in handle.join().unwrap() {
+                assert!(all.insert(idx), "{} generated twice", idx)
+            }
+        }
+        assert_eq! { all.len(), 8_000 }
+        assert_eq! { counter.peek().get(), 8_000 }
+    }
+
+    #[test]
+    #[cfg(feature = "smallvec")]
+    fn small_map() {
+        use alloc::vec::Vec;
+        use core::mem::size_of;
+        // Four `u32`s inline take no more room than a vector and the elements it points to.
+        assert!(size_of::<VarOps<u32>>() <= size_of::<Vec<u32>>() + 4 * size_of::<u32>());
+        assert_eq! { VarOps::<u32>::INLINE_CAPACITY, 4 }
+
+        let mut ops = VarOps::new();
+        let idx: Vec<VarIndex> = (0..4).map(|n| ops.push(n * 10)).collect();
+        assert!(!ops.spilled());
+        assert_eq! { ops[idx[2]], 20 }
+        assert_eq! { ops[idx[1]..=idx[2]], [10, 20] }
+        assert_eq! { ops[..idx[1]], [0] }
+
+        let v_4 = ops.push(40);
+        assert!(ops.spilled());
+        assert_eq! { ops.last_index(), Some(v_4) }
+        assert_eq! { ops[idx[3]..], [30, 40] }
+        for (idx, op) in ops.index_iter_mut() {
+            *op += idx.get()
+        }
+        assert_eq! { ops.as_slice(), [0, 11, 22, 33, 44] }
+        assert_eq! { ops.indices().collect::<Vec<_>>(), ops.index_iter().map(|(i, _)| i).collect::<Vec<_>>() }
+        assert_eq! { ops.pop(), Some((v_4, 44)) }
+        assert_eq! { ops.get(v_4), None }
+        assert_eq! { ops.index_from_usize(3), Some(idx[3]) }
+
+        let collected: VarOps<_> = ops.iter().map(|op| op % 2).collect();
+        assert_eq! { collected.into_vec(), [0, 1, 0, 1] }
+        assert!(!(0..3).collect::<VarOps<u8>>().spilled());
+    }
+
+    #[test]
+    fn array_map() {
+        use alloc::vec::Vec;
+        let mut vars = VarArray::new();
+        assert_eq! { VarArray::<char>::CAPACITY, 3 }
+        let idx: Vec<VarIndex> = "abc".chars().map(|c| vars.push(c).unwrap()).collect();
+        assert!(vars.is_full());
+        assert_eq! { vars.push('d'), Err('d') }
+        assert_eq! { vars.len(), 3 }
+        assert_eq! { (vars[idx[0]], vars[&idx[2]]), ('a', 'c') }
+        assert_eq! { vars.last_index(), Some(idx[2]) }
+
+        vars[idx[1]] = 'B';
+        for (_, c) in vars.index_iter_mut() {
+            c.make_ascii_uppercase()
+        }
+        assert_eq! { vars.as_slice(), ['A', 'B', 'C'] }
+        assert_eq! { vars.index_iter().map(|(i, _)| i).collect::<Vec<_>>(), idx }
+        assert_eq! { vars.clone(), vars }
+        assert_eq! { alloc::format!("{:?}", vars), "['A', 'B', 'C']" }
+
+        assert_eq! { vars.pop(), Some((idx[2], 'C')) }
+        assert_eq! { vars.get(idx[2]), None }
+        assert_eq! { vars.push('c'), Ok(idx[2]) }
+        vars.clear();
+        assert!(vars.is_empty());
+        assert_eq! { vars.index_from_usize(0), None }
+    }
+
+    #[test]
+    fn array_map_drops() {
+        use core::cell::Cell;
+        struct Canary<'a>(&'a Cell<usize>);
+        impl Drop for Canary<'_> {
+            fn drop(&mut self) {
+                self.0.set(self.0.get() + 1)
+            }
+        }
+
+        let drops = Cell::new(0);
+        {
+            let mut vars = VarArray::new();
+            for _ in 0..3 {
+                assert!(vars.push(Canary(&drops)).is_ok());
+            }
+            // Rejected elements are given back, then dropped by the caller.
+            assert!(vars.push(Canary(&drops)).is_err());
+            assert_eq! { drops.get(), 1 }
+            drop(vars.pop());
+            assert_eq! { drops.get(), 2 }
+        }
+        // Only the two remaining elements are dropped with the map.
+        assert_eq! { drops.get(), 4 }
+
+        let drops = Cell::new(0);
+        let mut vars = VarArray::new();
+        let _ = vars.push(Canary(&drops));
+        let _ = vars.push(Canary(&drops));
+        vars.clear();
+        assert_eq! { drops.get(), 2 }
+        let _ = vars.push(Canary(&drops));
+        drop(vars);
+        assert_eq! { drops.get(), 3 }
+    }
+
+    #[test]
+    fn attribute_passthrough() {
+        new! {
+            /// Index with derives from an external crate.
+            #[derive(serde::Serialize, serde::Deserialize)]
+            #[serde(transparent)]
+            Id,
+            /// Map with derives from an external crate.
+            #[cfg_attr(test, derive(serde::Serialize, serde::Deserialize))]
+            #[serde(transparent)]
+            #[non_exhaustive]
+            map: Ids,
+            /// Range with extra derives.
+            #[derive(PartialOrd, Ord)]
+            range: IdRange,
+        }
+
+        let mut ids = Ids::new();
+        let id_0 = ids.push(7);
+        let id_1 = ids.push(3);
+        assert_eq! { serde_json::to_string(&ids).unwrap(), "[7,3]" }
+        assert_eq! { serde_json::to_string(&id_1).unwrap(), "1" }
+
+        let ids: Ids<u8> = serde_json::from_str("[7,3,11]").unwrap();
+        let id_2: Id = serde_json::from_str("2").unwrap();
+        assert_eq! { ids[id_0], 7 }
+        assert_eq! { ids[id_2], 11 }
+        assert!(IdRange::new(id_0, id_1) < IdRange::new(id_0, id_2));
+    }
+
+    #[test]
+    fn index_derives() {
+        use alloc::vec::Vec;
+        new! {
+            /// Index with derives given in several attributes, by path or by name.
+            #[derive(serde::Serialize)]
+            #[derive(serde::Deserialize)]
+            #[cfg_attr(all(feature = "strict", not(feature = "petgraph")), derive(Default))]
+            Id,
+            /// Map of indices.
+            map: Ids,
+        }
+
+        let mut ids = Ids::new();
+        let id_0 = ids.push('a');
+        let id_1 = ids.push('b');
+        // Derives see the private field of the index.
+        let pairs: Vec<(Id, char)> = ids.index_iter().map(|(id, c)| (id, *c)).collect();
+        let json = serde_json::to_string(&pairs).unwrap();
+        assert_eq! { json, r#"[[{"val":0},"a"],[{"val":1},"b"]]"# }
+        let pairs: Vec<(Id, char)> = serde_json::from_str(&json).unwrap();
+        assert_eq! { pairs, [(id_0, 'a'), (id_1, 'b')] }
+        assert_eq! { Id::default(), id_0 }
+    }
+
+    #[test]
+    #[cfg(all(feature = "strict", not(feature = "petgraph")))]
+    fn index_derive_default() {
+        new! {
+            /// Index with a derived default, only possible in strict mode.
+            #[derive(Default)]
+            Id,
+        }
+        assert_eq! { Id::default().get(), 0 }
+    }
+
+    #[test]
+    fn cfg_options() {
+        new! {
+            /// Indices of items.
+            Item,
+            /// Range of items.
+            range: ItemRange,
+            /// Map of items, only with the `std` feature.
+            #[cfg(feature = "std")]
+            map: ItemMap,
+            #[cfg(feature = "std")]
+            /// Set of items, only with the `std` feature.
+            btree set struct: ItemSet,
+            /// Never generated.
+            #[cfg(any())]
+            #[cfg(all())]
+            slice: Missing,
+            /// Small maps of items, only when both features are active.
+            #[cfg(feature = "std")]
+            #[cfg(feature = "smallvec")]
+            small map: ItemOps[2],
+            #[cfg(any())]
+            base: 1,
+        }
+        // Would clash with the generated items if they existed.
+        #[allow(dead_code)]
+        struct Missing;
+        #[cfg(not(feature = "std"))]
+        #[allow(dead_code)]
+        struct ItemMap;
+        #[cfg(not(feature = "std"))]
+        #[allow(dead_code)]
+        struct ItemSet;
+        #[cfg(not(all(feature = "std", feature = "smallvec")))]
+        #[allow(dead_code)]
+        struct ItemOps;
+
+        #[cfg(feature = "std")]
+        {
+            let mut map = ItemMap::new();
+            let i_0 = map.push('a');
+            let i_1 = map.push('b');
+            let i_2 = map.push('c');
+            // Cross implementations with the range.
+            let range = ItemRange::new(i_1, i_2);
+            assert_eq! { map[range], ['b'] }
+            let set: ItemSet = [i_0, i_1, i_2].iter().cloned().collect();
+            assert_eq! { set.iter_ranges().count(), 1 }
+            assert!(set.as_single_range().is_some());
+            assert!(set.to_membership_map(3)[i_2]);
+        }
+        new! {
+            /// Indices without range.
+            Other,
+            /// Never generated, nor are the methods it adds to `Other`.
+            #[cfg(any())]
+            range: OtherRange,
+        }
+        trait UpTo: Sized {
+            fn up_to(self, _end: Self) -> u8 {
+                0
+            }
+        }
+        impl UpTo for Other {}
+        // Would be the inherent method if the range existed.
+        let _: fn(Other, Other) -> u8 = Other::up_to;
+
+        #[cfg(all(feature = "std", feature = "smallvec"))]
+        {
+            let mut ops = ItemOps::new();
+            ops.push(7);
+            assert!(!ops.spilled());
+        }
+    }
+
+    #[test]
+    fn set_and_set_struct() {
+        new! {
+            /// Indices of items.
+            Item,
+            /// Range of items.
+            range: ItemRange,
+            /// Map of items.
+            map: ItemMap,
+            /// Set of items.
+            btree set: ItemSet,
+            /// Set of items, with the methods of the newtype.
+            btree set struct: ItemSetStruct,
+        }
+        let mut map = ItemMap::new();
+        let i_0 = map.push('a');
+        let i_1 = map.push('b');
+        // The range and the map build the first set.
+        let set: ItemSet = ItemRange::new(i_0, i_1).to_set();
+        let flags: ItemMap<bool> = map.iter().map(|c| *c == 'a').collect();
+        assert_eq! { flags.to_set(), set }
+        // The newtype still has its cross implementations.
+        let set_struct: ItemSetStruct = [i_0, i_1].iter().cloned().collect();
+        assert_eq! { set_struct.as_single_range().map(Iterator::count), Some(2) }
+        assert!(set_struct.to_membership_map(2)[i_1]);
+    }
+
+    #[test]
+    fn bounded() {
+        use alloc::{format, vec::Vec};
+        use core::mem::size_of;
+        new! {
+            /// Squares of a board.
+            Square,
+            /// Map of squares.
+            map: Squares,
+            /// Squares of an eight by eight board.
+            bounded: Board[8 * 8] with map: BoardMap,
+            /// Registers.
+            bounded: Register[4],
+        }
+
+        assert_eq! { Board::BOUND, 64 }
+        assert_eq! { Register::BOUND, 4 }
+        assert_eq! { size_of::<Board>(), size_of::<Square>() }
+        assert_eq! { size_of::<Option<Board>>(), size_of::<Option<Square>>() }
+        assert_eq! { size_of::<BoardMap<u8>>(), 64 }
+
+        // Same indices as the unbounded type.
+        let mut squares = Squares::new();
+        for i in 0..65 {
+            squares.push(i);
+        }
+        let unbounded: Vec<Square> = squares.indices().collect();
+        let bounded: Vec<Board> = unbounded
+            .iter()
+            .filter_map(|sq| Board::try_from_idx(*sq))
+            .collect();
+        assert_eq! { bounded.len(), 64 }
+        for (sq, b) in unbounded.iter().zip(&bounded) {
+            assert_eq! { Square::from(*b), *sq }
+            assert_eq! { b.idx(), *sq }
+            assert_eq! { usize::from(*b), sq.get() }
+            assert_eq! { format!("{}", b), format!("{}", sq) }
+            assert_eq! { squares[b.idx()], sq.get() }
+        }
+        // Rejection path.
+        assert!(Board::try_from_idx(unbounded[64]).is_none());
+        assert!(Register::try_from_idx(unbounded[4]).is_none());
+        assert!(bounded[0] < bounded[1]);
+        let last = bounded[63];
+        assert_eq! { last.get(), 63 }
+        assert_eq! { format!("{}", last), "63" }
+
+        let mut board = BoardMap::from_array(core::array::from_fn(|i| i % 8));
+        assert_eq! { board.len(), 64 }
+        assert_eq! { board[last], 7 }
+        board[last] = 42;
+        assert_eq! { board.as_slice()[63], 42 }
+        assert_eq! { BoardMap::filled('.').iter().filter(|c| **c == '.').count(), 64 }
+        assert_eq! { BoardMap::<u8>::default().into_iter().sum::<u8>(), 0 }
+
+        #[cfg(not(feature = "strict"))]
+        {
+            assert!(Board::try_new(64).is_none());
+            assert!(Board::try_new(usize::MAX).is_none());
+            assert!(Register::try_new(4).is_none());
+            assert_eq! { Board::try_new(63), Some(last) }
+            assert_eq! { Board::all().collect::<Vec<_>>(), bounded }
+
+            let mut board = BoardMap::from_fn(|b| b.get() % 8);
+            assert_eq! { board.index_iter().filter(|(_, v)| **v == 0).count(), 8 }
+            for (b, v) in board.index_iter_mut() {
+                *v = b.get();
+            }
+            assert_eq! { board, BoardMap::from_array(core::array::from_fn(|i| i)) }
+            let regs: Vec<Register> = Register::all().collect();
+            assert_eq! { regs.len(), 4 }
+        }
+    }
+
+    #[test]
+    fn shared_map() {
+        use alloc::{string::String, vec::Vec};
+        new! {
+            /// Indices of nodes.
+            Node,
+            /// Map of nodes.
+            map: Nodes,
+            /// Read-only map of nodes.
+            shared map: SharedNodes,
+        }
+
+        let mut nodes = Nodes::new();
+        let n_0 = nodes.push(String::from("a"));
+        let n_1 = nodes.push(String::from("b"));
+        let n_2 = nodes.push(String::from("c"));
+        let data = nodes[n_1].as_ptr();
+        let shared = nodes.into_shared();
+        // Elements moved, not copied.
+        assert_eq! { shared[n_1].as_ptr(), data }
+        assert_eq! { shared.len(), 3 }
+        assert_eq! { shared.get(n_2).map(String::as_str), Some("c") }
+        assert_eq! { shared[n_0..n_2], ["a", "b"] }
+        assert_eq! { shared[..=n_0], ["a"] }
+        assert_eq! { shared.last(), Some((n_2, &String::from("c"))) }
+        assert_eq! { shared.indices().collect::<Vec<_>>(), [n_0, n_1, n_2] }
+        assert_eq! {
+            shared.index_iter().map(|(n, s)| (n, s.as_str())).collect::<Vec<_>>(),
+            [(n_0, "a"), (n_1, "b"), (n_2, "c")]
+        }
+        assert_eq! { (&shared).into_iter().cloned().collect::<String>(), "abc" }
+
+        fn send_sync<T: Send + Sync>(_: &T) {}
+        send_sync(&shared);
+        let clone = shared.clone();
+        assert!(clone.ptr_eq(&shared));
+        assert_eq! { shared.share_count(), 2 }
+        assert_eq! { clone[n_1].as_ptr(), data }
+        drop(clone);
+        assert_eq! { shared.share_count(), 1 }
+
+        let mut copy = shared.to_map();
+        copy[n_0].push('!');
+        assert_eq! { copy[n_0], "a!" }
+        assert_eq! { shared[n_0], "a" }
+        assert!(!SharedNodes::from(copy).ptr_eq(&shared));
+        assert!(SharedNodes::<u8>::default().is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn shared_map_threads() {
+        use crate::std::{thread, vec::Vec};
+        new! {
+            /// Indices of nodes.
+            Node,
+            /// Map of nodes.
+            map: Nodes,
+            /// Read-only map of nodes.
+            shared map: SharedNodes,
+        }
+
+        let shared: SharedNodes<usize> = (0..1_000).collect::<Nodes<_>>().into();
+        let clones: Vec<_> = (0..8).map(|_| shared.clone()).collect();
+        assert_eq! { shared.share_count(), 9 }
+        let handles: Vec<_> = clones
+            .into_iter()
+            .map(|shared| {
+                thread::spawn(move || {
+                    assert!(shared.share_count() > 1);
+                    shared
+                        .index_iter()
+                        .map(|(n, v)| {
+                            assert_eq! { n.get(), *v };
+                            *v
+                        })
+                        .sum::<usize>()
+                })
+            })
+            .collect();
+        for handle in handles {
+            assert_eq! { handle.join().unwrap(), 999 * 1_000 / 2 }
+        }
+        assert_eq! { shared.share_count(), 1 }
+    }
+
+    #[test]
+    fn generated_module() {
+        new! {
+            /// Task indices and collections.
+            mod task_idx, use;
+            /// Indices of tasks.
+            Task,
+            /// Map from tasks to something.
+            map: Tasks,
+            /// Set of tasks.
+            btree set: TaskSet,
+        }
+        new! {
+            /// Worker and job indices and collections.
+            #[allow(dead_code)]
+            pub mod work_idx, pub(crate) use;
+            /// Indices of workers.
+            tagged Worker: WorkerTag {
+                /// Map from workers to something.
+                map: Workers,
+            }
+            /// Indices of jobs.
+            Job {
+                /// Map from jobs to something.
+                map: Jobs,
+            }
+            /// Relates workers and jobs.
+            relation Assignments: Worker <-> Job;
+        }
+
+        let mut tasks = task_idx::Tasks::new();
+        let t_0: Task = tasks.push("task 0");
+        let t_1: task_idx::Task = tasks.push("task 1");
+        let set: task_idx::TaskSet = tasks.indices().collect();
+        assert_eq! { set.len(), 2 }
+        assert_eq! { tasks[t_1], "task 1" }
+        assert!(t_0 < t_1);
+
+        let mut workers = work_idx::Workers::new();
+        let w_0: Worker = workers.push("worker 0");
+        let mut jobs = work_idx::Jobs::new();
+        let j_0: Job = jobs.push("job 0");
+        let mut assignments = work_idx::Assignments::new();
+        assert!(assignments.insert(w_0, j_0));
+        assert!(assignments.contains(w_0, j_0));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn tagged_options() {
+        use crate::tagged::Family;
+        new! {
+            /// Indices of workers.
+            tagged Worker: WorkerTag,
+            /// Map from workers to something.
+            map: Workers,
+            /// Hash set of workers.
+            hash set: WorkerHashSet,
+            /// Hash map from workers to something.
+            hash map: WorkerHashMap,
+            /// Btree map from workers to something.
+            btree map: WorkerBTreeMap,
+        }
+        new! {
+            /// Indices of jobs.
+            tagged Job: JobTag,
+        }
+
+        let workers: Workers<&str> = ["a", "b"].iter().cloned().collect();
+        let set: WorkerHashSet = workers.indices().collect();
+        let hash_map: WorkerHashMap<&str> = workers.index_iter().map(|(w, n)| (w, *n)).collect();
+        let btree_map: WorkerBTreeMap<&str> = hash_map.clone().into_iter().collect();
+        assert_eq! { set.len(), 2 }
+        assert_eq! { btree_map.values().cloned().collect::<alloc::vec::Vec<_>>(), ["a", "b"] }
+        assert_eq! { (WorkerTag::IDX, WorkerTag::MAP), ("Worker", "Workers") }
+        // Without a `map` option, the map is named after the tag.
+        assert_eq! { (JobTag::IDX, JobTag::MAP), ("Job", "Map<JobTag>") }
+        assert_eq! { <Job as crate::SafeIndex>::NAME, "Job" }
+    }
+
+    #[test]
+    #[should_panic]
+    fn select_out_of_bounds() {
+        let set = (0..5).collect::<VarMap<usize>>().keys_as_set();
+        let map: VarMap<usize> = (0..2).collect();
+        let _ = map.select(set).count();
+    }
+
+    #[test]
+    #[should_panic]
+    fn for_each_in_out_of_bounds() {
+        let set = (0..5).collect::<VarMap<usize>>().keys_as_set();
+        let mut map: VarMap<usize> = (0..2).collect();
+        map.for_each_in(set, |_, _| ())
+    }
+
+    #[test]
+    #[cfg(all(feature = "hashbrown", not(feature = "std")))]
+    fn hashbrown_collections() {
+        use core::hash::{BuildHasherDefault, Hasher};
+
+        /// FNV-1a, any hasher works without `std`.
+        struct Fnv(u64);
+        impl Default for Fnv {
+            fn default() -> Self {
+                Fnv(0xcbf29ce484222325)
+            }
+        }
+        impl Hasher for Fnv {
+            fn finish(&self) -> u64 {
+                self.0
+            }
+            fn write(&mut self, bytes: &[u8]) {
+                for byte in bytes {
+                    self.0 = (self.0 ^ u64::from(*byte)).wrapping_mul(0x100000001b3);
+                }
+            }
+        }
+
+        let map: VarMap<usize> = (0..10).collect();
+        let even: hashbrown::HashSet<VarIndex> =
+            map.indices_matching(|n| n % 2 == 0).into_iter().collect();
+        let even: VarHSet = even;
+        assert_eq! { even.len(), 5 }
+
+        let mut custom: VarHMap<usize, BuildHasherDefault<Fnv>> = VarHMap::default();
+        custom.extend(map.index_iter().map(|(idx, n)| (idx, *n)));
+        let custom: hashbrown::HashMap<VarIndex, usize, BuildHasherDefault<Fnv>> = custom;
+        assert!(even.iter().all(|idx| custom[idx] == map[*idx]));
+    }
+
+    #[test]
+    #[cfg(all(feature = "hashbrown", feature = "std"))]
+    fn std_wins_over_hashbrown() {
+        let set: std::collections::HashSet<VarIndex> = VarHSet::default();
+        let map: std::collections::HashMap<VarIndex, char> = VarHMap::new();
+        assert!(set.is_empty() && map.is_empty());
+    }
+
+    #[test]
+    #[cfg(any(feature = "std", feature = "hashbrown"))]
+    fn hash_map_conversions() {
+        let map: VarMap<char> = "abc".chars().collect();
+        let h_map = map.to_hash_map();
+        assert_eq! { VarMap::from_dense_hash_map(h_map.clone()), Ok(map.clone()) }
+        assert_eq! { map.clone().into_hash_map(), h_map }
+
+        let mut gapped = h_map;
+        gapped.remove(&map.index_from_usize(0).unwrap());
+        assert_eq! {
+            VarMap::from_dense_hash_map(gapped).map_err(|e| e.idx.get()),
+            Err(0)
+        }
+    }
+
+    #[test]
+    fn one_based() {
+        use alloc::{format, string::String, vec::Vec};
+        new! {
+            /// Nodes of a graph read from a DIMACS-like file.
+            Node,
+            /// Map of nodes.
+            map: Nodes,
+            base: 1,
+        }
+
+        let mut nodes = Nodes::new();
+        let first = nodes.push("a");
+        let last = nodes.push("b");
+        assert_eq! { first.get(), 0 }
+        assert_eq! { first.to_external(), 1 }
+        assert_eq! { last.to_external(), nodes.len() }
+        assert_eq! { format!("{}", last.to_external()), "2" }
+        assert_eq! {
+            nodes.indices().map(|node| node.to_external()).collect::<Vec<_>>(),
+            [1, 2]
+        }
+
+        // Edges of the file, read back as internal indices.
+        let edges: Vec<(Node, Node)> = "1 2\n2 1\n2 2"
+            .lines()
+            .map(|line| {
+                let mut ends = line.split(' ').map(|end| {
+                    let end: usize = end.parse().unwrap();
+                    nodes.index_from_usize(end - 1).unwrap()
+                });
+                (ends.next().unwrap(), ends.next().unwrap())
+            })
+            .collect();
+        assert_eq! { edges, [(first, last), (last, first), (last, last)] }
+        let exported: Vec<String> = edges
+            .iter()
+            .map(|(src, tgt)| format!("{} {}", src.to_external(), tgt.to_external()))
+            .collect();
+        assert_eq! { exported.join("\n"), "1 2\n2 1\n2 2" }
+    }
+
+    #[test]
+    #[cfg(not(feature = "strict"))]
+    fn one_based_round_trip() {
+        new! {
+            /// Indices starting at one outside.
+            Id,
+            base: 1,
+        }
+
+        assert_eq! { Id::from_external(0), None }
+        assert_eq! { Id::from_external(1), Some(Id::zero()) }
+        assert_eq! { Id::from_external(2), Some(Id::one()) }
+        for external in 1..100 {
+            assert_eq! { Id::from_external(external).unwrap().to_external(), external }
+        }
+        for val in 0..100 {
+            assert_eq! { Id::from_external(Id::new(val).to_external()), Some(Id::new(val)) }
+        }
+        assert_eq! { Id::from_external(usize::MAX), Some(Id::new(usize::MAX - 1)) }
+    }
 }
 
+#[cfg(feature = "macros")]
+pub mod attr;
 pub mod clients;
+pub mod graph;
+pub mod grid;
+pub mod ir;
+pub mod range;
+pub mod scopes;
+pub mod tagged;
+pub mod tasks;
@@ -0,0 +1,900 @@
+//! Ranges of indices.
+//!
+//! ```rust
+//! use safe_index::examples::range::*;
+//!
+//! let mut map = IdxMap::new();
+//! let start = map.push('a');
+//! map.push('b');
+//! map.push('c');
+//! let end = map.push('d');
+//!
+//! let range = start.up_to(end);
+//! assert_eq!(range.len(), 3);
+//! let chars: String = range.rev().map(|idx| map[idx]).collect();
+//! assert_eq!(chars, "cba");
+//! ```
+
+new! {
+    /// Indices.
+    Idx,
+    /// Range of indices.
+    range: IdxRange,
+    /// Map from indices to something.
+    map: IdxMap,
+    /// Set of indices.
+    btree set struct: IdxSet,
+    /// Sparse map from indices to something.
+    btree map struct: IdxBMap,
+    /// Set of indices stored as intervals.
+    interval set: IdxIntervals,
+}
+
+/// Builds an index, works in strict mode.
+#[cfg(test)]
+fn idx(n: usize) -> Idx {
+    IdxMap::of_elems((), n + 1).last_index().unwrap()
+}
+
+#[test]
+fn iter() {
+    use alloc::vec::Vec;
+    let range = |start, end| IdxRange::new(idx(start), idx(end));
+    fn vals(range: impl Iterator<Item = Idx>) -> Vec<usize> {
+        range.map(|i| i.get()).collect()
+    }
+
+    assert_eq! { vals(range(2, 5)), [2, 3, 4] }
+    assert_eq! { vals(range(2, 5).rev()), [4, 3, 2] }
+    assert!(vals(range(5, 5)).is_empty());
+    assert!(vals(range(7, 5)).is_empty());
+    assert_eq! { range(7, 5).len(), 0 }
+    assert_eq! { range(7, 5).next_back(), None }
+
+    let mut r = range(2, 6);
+    assert_eq! { r.len(), 4 }
+    assert_eq! { r.next(), Some(idx(2)) }
+    assert_eq! { r.next_back(), Some(idx(5)) }
+    assert_eq! { r.len(), 2 }
+    assert_eq! { r.size_hint(), (2, Some(2)) }
+    assert_eq! { r.next_back(), Some(idx(4)) }
+    assert_eq! { r.next(), Some(idx(3)) }
+    assert_eq! { r.len(), 0 }
+    assert_eq! { r.next(), None }
+    assert_eq! { r.next_back(), None }
+    assert_eq! { r.next(), None }
+
+    let mut r = range(2, 8);
+    assert_eq! { r.nth(2), Some(idx(4)) }
+    assert_eq! { r.len(), 3 }
+    assert_eq! { r.nth(3), None }
+    assert_eq! { r.next(), None }
+    assert_eq! { range(2, 8).count(), 6 }
+    assert_eq! { range(2, 8).last(), Some(idx(7)) }
+    assert_eq! { range(8, 2).last(), None }
+    assert_eq! { idx(1).up_to(idx(3)).collect::<Vec<_>>(), [idx(1), idx(2)] }
+}
+
+#[test]
+fn copy_eq() {
+    use alloc::vec::Vec;
+    let range = idx(1).up_to(idx(4));
+    let mut copy = range;
+    assert_eq! { copy.next(), Some(idx(1)) }
+    assert_ne! { copy, range }
+    assert_eq! { copy, idx(2).up_to(idx(4)) }
+    // Iterating through a mutable reference leaves `range` itself untouched.
+    let mut other = range;
+    let firsts: Vec<_> = (&mut other).take(2).collect();
+    assert_eq! { firsts, [idx(1), idx(2)] }
+    assert_eq! { other, idx(3).up_to(idx(4)) }
+    assert_eq! { range, IdxRange::new(idx(1), idx(4)) }
+    assert_eq! { range.collect::<Vec<_>>(), range.collect::<Vec<_>>() }
+}
+
+#[test]
+fn accessors() {
+    let range = idx(2).up_to(idx(5));
+    assert_eq! { range.start(), idx(2) }
+    assert_eq! { range.end(), idx(5) }
+    assert_eq! { range.len(), 3 }
+    assert!(!range.is_empty());
+    assert!(!range.contains(idx(1)));
+    assert!(range.contains(idx(2)));
+    assert!(range.contains(idx(4)));
+    assert!(!range.contains(idx(5)));
+
+    let single = idx(3).up_to(idx(4));
+    assert_eq! { single.len(), 1 }
+    assert!(!single.is_empty());
+    assert!(single.contains(idx(3)));
+    assert!(!single.contains(idx(4)));
+
+    for empty in [idx(3).up_to(idx(3)), idx(5).up_to(idx(2))] {
+        assert_eq! { empty.len(), 0 }
+        assert!(empty.is_empty());
+        assert!(!empty.contains(empty.start()));
+        assert!(!empty.contains(empty.end()));
+        assert!(!empty.contains(idx(4)));
+    }
+}
+
+#[test]
+fn down_to() {
+    use alloc::vec::Vec;
+    let vals: Vec<_> = idx(5).down_to(idx(0)).map(|i| i.get()).collect();
+    assert_eq! { vals, [4, 3, 2, 1, 0] }
+    let vals: Vec<_> = idx(5).down_to(idx(3)).map(|i| i.get()).collect();
+    assert_eq! { vals, [4, 3] }
+    assert_eq! { idx(5).down_to(idx(4)).collect::<Vec<_>>(), [idx(4)] }
+    assert_eq! { idx(5).down_to(idx(5)).next(), None }
+    assert_eq! { idx(2).down_to(idx(5)).next(), None }
+    assert_eq! { idx(5).down_to(idx(0)).len(), 5 }
+    assert!(idx(5)
+        .down_to(idx(1))
+        .eq(idx(1).up_to(idx(5)).collect::<Vec<_>>().into_iter().rev()));
+
+    // Walking a map from the back.
+    let map: IdxMap<usize> = (10..14).collect();
+    let vals: Vec<_> = idx(map.len()).down_to(idx(0)).map(|i| map[i]).collect();
+    assert_eq! { vals, [13, 12, 11, 10] }
+}
+
+#[test]
+fn step_by_typed() {
+    use alloc::vec::Vec;
+    fn vals(range: impl Iterator<Item = Idx>) -> Vec<usize> {
+        range.map(|i| i.get()).collect()
+    }
+    let range = |start, end| IdxRange::new(idx(start), idx(end));
+
+    assert_eq! { vals(range(2, 6).step_by_typed(1)), [2, 3, 4, 5] }
+    assert_eq! { vals(range(2, 6).step_by_typed(1).rev()), [5, 4, 3, 2] }
+    assert_eq! { vals(range(2, 6).step_by_typed(10)), [2] }
+    assert_eq! { vals(range(2, 6).step_by_typed(10).rev()), [2] }
+    assert!(vals(range(2, 2).step_by_typed(3)).is_empty());
+    assert!(vals(range(6, 2).step_by_typed(3).rev()).is_empty());
+
+    // Length divisible by the step.
+    assert_eq! { vals(range(0, 6).step_by_typed(3)), [0, 3] }
+    assert_eq! { vals(range(0, 6).step_by_typed(3).rev()), [3, 0] }
+    assert_eq! { range(0, 6).step_by_typed(3).len(), 2 }
+    // Length not divisible by the step.
+    assert_eq! { vals(range(0, 7).step_by_typed(3)), [0, 3, 6] }
+    assert_eq! { vals(range(0, 7).step_by_typed(3).rev()), [6, 3, 0] }
+    assert_eq! { vals(range(0, 8).step_by_typed(3).rev()), [6, 3, 0] }
+    assert_eq! { range(0, 8).step_by_typed(3).len(), 3 }
+
+    let mut stepped = range(1, 12).step_by_typed(4);
+    let copy = stepped;
+    assert_eq! { stepped.step(), 4 }
+    assert_eq! { stepped.len(), 3 }
+    assert_eq! { stepped.next_back(), Some(idx(9)) }
+    assert_eq! { stepped.next(), Some(idx(1)) }
+    assert_eq! { stepped.len(), 1 }
+    assert_eq! { stepped.next_back(), Some(idx(5)) }
+    assert_eq! { stepped.next(), None }
+    assert_eq! { copy.collect::<Vec<_>>(), [idx(1), idx(5), idx(9)] }
+
+    let mut stepped = range(0, 20).step_by_typed(3);
+    assert_eq! { stepped.nth(2), Some(idx(6)) }
+    assert_eq! { stepped.next(), Some(idx(9)) }
+    assert_eq! { stepped.nth(10), None }
+    assert_eq! { stepped.next(), None }
+}
+
+#[test]
+#[should_panic(expected = "range step must be non-zero")]
+fn step_by_typed_zero() {
+    idx(1).up_to(idx(3)).step_by_typed(0);
+}
+
+#[test]
+fn nth_back() {
+    let mut range = idx(2).up_to(idx(8));
+    assert_eq! { range.nth_back(0), Some(idx(7)) }
+    assert_eq! { range.nth_back(2), Some(idx(4)) }
+    assert_eq! { range, idx(2).up_to(idx(4)) }
+    assert_eq! { range.nth_back(2), None }
+    assert!(range.is_empty());
+    assert_eq! { range.next(), None }
+    assert_eq! { idx(2).up_to(idx(8)).nth_back(usize::MAX), None }
+}
+
+#[test]
+fn constructors() {
+    use crate::InvalidRange;
+    let (two, five) = (idx(2), idx(5));
+
+    assert_eq! { IdxRange::try_new(two, five), Ok(IdxRange::new(two, five)) }
+    assert_eq! { IdxRange::try_new(two, two), Ok(IdxRange::new(two, two)) }
+    assert!(IdxRange::try_new(two, two).unwrap().is_empty());
+    assert_eq! {
+        IdxRange::try_new(five, two),
+        Err(InvalidRange { start: five, end: two })
+    }
+    assert_eq! {
+        alloc::format!("{}", IdxRange::try_new(five, two).unwrap_err()),
+        "invalid range 5..2, start is greater than end"
+    }
+
+    assert_eq! { IdxRange::from(two..five), IdxRange::new(two, five) }
+    assert_eq! { IdxRange::from(two..two).len(), 0 }
+    assert!(IdxRange::from(five..two).is_empty());
+}
+
+#[test]
+#[cfg(not(feature = "strict"))]
+fn from_usize_range() {
+    let (two, five) = (2, 5);
+    assert_eq! { IdxRange::from(two..five), IdxRange::new(idx(2), idx(5)) }
+    assert_eq! { IdxRange::from(two..two).len(), 0 }
+    assert!(IdxRange::from(five..two).is_empty());
+}
+
+#[test]
+fn iter_by_ref() {
+    use alloc::vec::Vec;
+    struct Block {
+        range: IdxRange,
+    }
+    let block = Block {
+        range: idx(3).up_to(idx(6)),
+    };
+    let mut vals = Vec::new();
+    for i in &block.range {
+        vals.push(i.get())
+    }
+    for i in &block.range {
+        vals.push(i.get() * 10)
+    }
+    assert_eq! { vals, [3, 4, 5, 30, 40, 50] }
+    assert_eq! { block.range.len(), 3 }
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn iter_in_threads() {
+    use alloc::vec::Vec;
+    let range = idx(3).up_to(idx(6));
+    let handles: Vec<_> = (0..2)
+        // `range` is `Copy`, each thread gets its own.
+        .map(|_| std::thread::spawn(move || (&range).into_iter().map(|i| i.get()).sum::<usize>()))
+        .collect();
+    for handle in handles {
+        assert_eq! { handle.join().unwrap(), 12 }
+    }
+    assert_eq! { range.len(), 3 }
+}
+
+#[test]
+fn fmt() {
+    use alloc::format;
+    let range = idx(2).up_to(idx(5));
+    assert_eq! { format!("{}", range), "2..5" }
+    assert_eq! { format!("{:?}", range), "2..5" }
+    assert_eq! { format!("{}", idx(3).up_to(idx(3))), "3..3" }
+    assert_eq! { format!("{:?}", idx(5).up_to(idx(2))), "5..2" }
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn serde() {
+    let range = idx(2).up_to(idx(5));
+    assert_eq! { serde_json::to_string(&range).unwrap(), "[2,5]" }
+    assert_eq! { serde_json::to_string(&idx(3).up_to(idx(3))).unwrap(), "[3,3]" }
+}
+
+#[test]
+#[cfg(all(feature = "serde", not(feature = "strict")))]
+fn serde_round_trip() {
+    for range in [idx(2).up_to(idx(5)), idx(3).up_to(idx(3))] {
+        let json = serde_json::to_string(&range).unwrap();
+        assert_eq! { serde_json::from_str::<IdxRange>(&json).unwrap(), range }
+    }
+    let err = serde_json::from_str::<IdxRange>("[5,2]").unwrap_err();
+    assert_eq! {
+        alloc::format!("{}", err),
+        "invalid range 5..2, start is greater than end"
+    }
+    assert!(serde_json::from_str::<IdxRange>("[5]").is_err());
+}
+
+#[test]
+fn map_collect() {
+    let map = idx(0).up_to(idx(4)).map_collect(|i| i.get() * 10);
+    assert_eq! { map.len(), 4 }
+    for (i, val) in map.index_iter() {
+        assert_eq! { *val, i.get() * 10 }
+    }
+    assert_eq! { idx(0).up_to(idx(0)).map_collect(|i| i).len(), 0 }
+}
+
+#[test]
+#[should_panic(expected = "cannot collect range 2..4 into a IdxMap, it does not start at zero")]
+fn map_collect_not_zero() {
+    idx(2).up_to(idx(4)).map_collect(|i| i);
+}
+
+#[test]
+fn combine() {
+    let range = |start, end| IdxRange::new(idx(start), idx(end));
+    // (lhs, rhs, intersection, hull)
+    let cases = [
+        // disjoint
+        (range(0, 2), range(4, 6), None, range(0, 6)),
+        // touching
+        (range(0, 2), range(2, 4), None, range(0, 4)),
+        // nested
+        (range(0, 6), range(2, 4), Some(range(2, 4)), range(0, 6)),
+        (range(2, 4), range(2, 4), Some(range(2, 4)), range(2, 4)),
+        // partially overlapping
+        (range(0, 4), range(2, 6), Some(range(2, 4)), range(0, 6)),
+        (range(0, 3), range(2, 6), Some(range(2, 3)), range(0, 6)),
+        // empty
+        (range(3, 3), range(0, 6), None, range(0, 6)),
+        (range(5, 1), range(2, 4), None, range(2, 4)),
+        (range(5, 1), range(3, 3), None, range(5, 1)),
+    ];
+    for (lhs, rhs, inter, hull) in cases {
+        for (lhs, rhs) in [(lhs, rhs), (rhs, lhs)] {
+            assert_eq! { lhs.intersect(rhs), inter, "{} /\\ {}", lhs, rhs }
+            assert_eq! { lhs.overlaps(&rhs), inter.is_some(), "{} /\\ {}", lhs, rhs }
+            if !(lhs.is_empty() && rhs.is_empty()) {
+                assert_eq! { lhs.hull(rhs), hull, "{} \\/ {}", lhs, rhs }
+            }
+        }
+    }
+    // Hull of two empty ranges is the first one.
+    assert_eq! { range(5, 1).hull(range(3, 3)), range(5, 1) }
+    assert_eq! { range(3, 3).hull(range(5, 1)), range(3, 3) }
+}
+
+#[test]
+fn len_pair() {
+    assert_eq! { idx(2).up_to(idx(5)).len_pair(), (idx(2), 3) }
+    assert_eq! { idx(2).up_to(idx(2)).len_pair(), (idx(2), 0) }
+    assert_eq! { idx(5).up_to(idx(2)).len_pair(), (idx(5), 0) }
+}
+
+#[test]
+#[cfg(not(feature = "strict"))]
+fn with_len() {
+    assert_eq! { IdxRange::with_len(idx(2), 3), idx(2).up_to(idx(5)) }
+    assert_eq! { idx(2).span(3), idx(2).up_to(idx(5)) }
+    assert_eq! { idx(2).span(3).len_pair(), (idx(2), 3) }
+    assert!(idx(2).span(0).is_empty());
+    assert_eq! { IdxRange::with_len(idx(7), 0).len_pair(), (idx(7), 0) }
+    assert_eq! { Idx::new(usize::MAX).span(0).len(), 0 }
+    assert_eq! { Idx::new(usize::MAX - 1).span(1).len(), 1 }
+}
+
+#[test]
+#[cfg(not(feature = "strict"))]
+#[should_panic(expected = "range length overflows the index type")]
+fn with_len_overflow() {
+    Idx::new(usize::MAX - 1).span(2);
+}
+
+#[test]
+fn split_into() {
+    use alloc::vec::Vec;
+    let range = |start, end| IdxRange::new(idx(start), idx(end));
+    let split = |r: IdxRange, n| r.split_into(n).collect::<Vec<_>>();
+
+    // exact division
+    assert_eq! { split(range(2, 8), 3), [range(2, 4), range(4, 6), range(6, 8)] }
+    assert_eq! { split(range(2, 8), 1), [range(2, 8)] }
+    // remainder distribution
+    assert_eq! { split(range(0, 8), 3), [range(0, 3), range(3, 6), range(6, 8)] }
+    assert_eq! { split(range(0, 11), 4), [range(0, 3), range(3, 6), range(6, 9), range(9, 11)] }
+    // more chunks than indices
+    assert_eq! { split(range(1, 4), 5), [range(1, 2), range(2, 3), range(3, 4)] }
+    // empty ranges
+    assert!(split(range(3, 3), 2).is_empty());
+    assert!(split(range(5, 3), 2).is_empty());
+}
+
+#[test]
+#[should_panic(expected = "cannot split a range into zero sub-ranges")]
+fn split_into_zero() {
+    let _ = idx(1).up_to(idx(3)).split_into(0);
+}
+
+#[test]
+fn chunks() {
+    use alloc::vec::Vec;
+    let range = |start, end| IdxRange::new(idx(start), idx(end));
+    let chunks = |r: IdxRange, n| r.chunks(n).collect::<Vec<_>>();
+
+    assert_eq! { chunks(range(2, 8), 3), [range(2, 5), range(5, 8)] }
+    assert_eq! { chunks(range(2, 8), 4), [range(2, 6), range(6, 8)] }
+    assert_eq! { chunks(range(2, 8), 10), [range(2, 8)] }
+    assert_eq! { chunks(range(2, 5), 1), [range(2, 3), range(3, 4), range(4, 5)] }
+    assert!(chunks(range(3, 3), 2).is_empty());
+    assert!(chunks(range(5, 3), 2).is_empty());
+}
+
+#[test]
+#[should_panic(expected = "cannot split a range into chunks of length zero")]
+fn chunks_zero() {
+    let _ = idx(1).up_to(idx(3)).chunks(0);
+}
+
+#[test]
+#[cfg(feature = "rayon")]
+fn par_iter() {
+    use alloc::vec::Vec;
+    use rayon::prelude::*;
+    let map: IdxMap<usize> = idx(0).up_to(idx(1000)).map_collect(|i| i.get() * 3 % 17);
+    let range = idx(100).up_to(idx(900));
+
+    let par: usize = range.into_par_iter().map(|i| map[i]).sum();
+    let seq: usize = range.map(|i| map[i]).sum();
+    assert_eq! { par, seq }
+
+    let par = range.into_par_iter().len();
+    assert_eq! { par, 800 }
+    let pairs: Vec<_> = range
+        .into_par_iter()
+        .zip(idx(0).up_to(idx(800)).into_par_iter())
+        .collect();
+    assert!(pairs.into_iter().eq(range.zip(idx(0).up_to(idx(800)))));
+    assert_eq! { idx(5).up_to(idx(2)).into_par_iter().count(), 0 }
+}
+
+#[test]
+fn contains_range() {
+    let range = |start, end| IdxRange::new(idx(start), idx(end));
+    let outer = range(2, 6);
+    // (inner, contained)
+    let cases = [
+        // empty inner
+        (range(0, 0), true),
+        (range(9, 9), true),
+        (range(9, 1), true),
+        // equal
+        (range(2, 6), true),
+        // boundaries at the start
+        (range(1, 6), false),
+        (range(2, 5), true),
+        (range(3, 6), true),
+        // boundaries at the end
+        (range(2, 7), false),
+        (range(5, 6), true),
+        (range(6, 7), false),
+        (range(1, 2), false),
+    ];
+    for (inner, contained) in cases {
+        assert_eq! { outer.contains_range(inner), contained, "{} in {}", inner, outer }
+        assert_eq! { inner.is_subrange_of(&outer), contained, "{} in {}", inner, outer }
+    }
+    assert!(range(3, 3).contains_range(range(3, 3)));
+    assert!(!range(3, 3).contains_range(range(3, 4)));
+}
+
+#[test]
+fn index_map() {
+    let mut map: IdxMap<usize> = idx(0).up_to(idx(6)).map_collect(|i| i.get() * 10);
+    let range = idx(2).up_to(idx(5));
+    assert_eq! { map[range], map[idx(2)..idx(5)] }
+    assert_eq! { map.slice(range), [20, 30, 40] }
+    assert!(map[idx(6).up_to(idx(6))].is_empty());
+    assert!(map[idx(5).up_to(idx(2))].is_empty());
+    assert_eq! { map[idx(0).up_to(idx(6))], map[..] }
+
+    for val in &mut map[range] {
+        *val += 1
+    }
+    map.slice_mut(range)[0] = 0;
+    assert_eq! { map[..], [0, 10, 0, 31, 41, 50] }
+}
+
+#[test]
+#[should_panic(expected = "range Idx(4)..Idx(7) out of bounds for IdxMap of length 6")]
+fn index_map_out_of_bounds() {
+    let map: IdxMap<usize> = idx(0).up_to(idx(6)).map_collect(|i| i.get());
+    let _ = &map[idx(4).up_to(idx(7))];
+}
+
+#[test]
+fn to_set() {
+    use crate::InsertRange;
+    let range = idx(2).up_to(idx(6));
+    let set = range.to_set();
+    assert_eq! { set, range.collect::<IdxSet>() }
+    assert!(idx(3).up_to(idx(3)).to_set().is_empty());
+    assert!(idx(5).up_to(idx(1)).to_set().is_empty());
+
+    let mut set = IdxSet::new();
+    set.insert(idx(0));
+    set.insert(idx(4));
+    set.insert(idx(9));
+    set.insert_range(range);
+    set.insert_range(idx(7).up_to(idx(7)));
+    set.insert_range(idx(8).up_to(idx(1)));
+    let mut expected: IdxSet = [idx(0), idx(9)].iter().collect();
+    expected.extend(range);
+    assert_eq! { set, expected }
+}
+
+#[test]
+fn arithmetic_iteration() {
+    use rand::{rngs::SmallRng, Rng, SeedableRng};
+    let mut rng = SmallRng::seed_from_u64(0x5eed);
+    let mut rand = |max: usize| rng.gen_range(0..max);
+    let get = |idx: Option<Idx>| idx.map(|i| i.get());
+
+    for _ in 0..200 {
+        let (start, end) = (rand(40), rand(40));
+        let mut range = idx(start).up_to(idx(end));
+        // Reference implementation.
+        let mut reference = start..end;
+
+        assert_eq! { range.count(), reference.clone().count() }
+        assert_eq! { get(range.last()), reference.clone().last() }
+        assert_eq! { get(range.min()), reference.clone().min() }
+        assert_eq! { get(range.max()), reference.clone().max() }
+
+        for _ in 0..6 {
+            // Offsets may go past the end.
+            let n = rand(15);
+            if rand(2) == 0 {
+                assert_eq! { get(range.nth(n)), reference.nth(n), "nth({})", n }
+            } else {
+                assert_eq! { get(range.nth_back(n)), reference.nth_back(n), "nth_back({})", n }
+            }
+            assert_eq! { range.len(), reference.len() }
+            assert_eq! { get(range.next()), reference.next() }
+            assert_eq! { get(range.next_back()), reference.next_back() }
+        }
+    }
+}
+
+#[test]
+fn iter_ranges() {
+    use alloc::vec::Vec;
+    let range = |start, end| IdxRange::new(idx(start), idx(end));
+    let set = |indices: &[usize]| indices.iter().map(|n| idx(*n)).collect::<IdxSet>();
+    let ranges = |set: &IdxSet| set.iter_ranges().collect::<Vec<_>>();
+
+    // gaps
+    let gaps = set(&[1, 2, 3, 5, 6, 9]);
+    assert_eq! { ranges(&gaps), [range(1, 4), range(5, 7), range(9, 10)] }
+    assert_eq! { gaps.as_single_range(), None }
+    // singletons
+    let singletons = set(&[0, 2, 4]);
+    assert_eq! { ranges(&singletons), [range(0, 1), range(2, 3), range(4, 5)] }
+    assert_eq! { singletons.as_single_range(), None }
+    assert_eq! { set(&[7]).as_single_range(), Some(range(7, 8)) }
+    // contiguous
+    let contiguous = set(&[3, 4, 5, 6]);
+    assert_eq! { ranges(&contiguous), [range(3, 7)] }
+    assert_eq! { contiguous.as_single_range(), Some(range(3, 7)) }
+    assert_eq! { range(3, 7).to_set(), contiguous }
+    // empty
+    assert!(ranges(&IdxSet::new()).is_empty());
+    assert_eq! { IdxSet::new().as_single_range(), None }
+    // last index
+    #[cfg(not(feature = "strict"))]
+    {
+        let max = |n: usize| Idx::new(usize::MAX - n);
+        let last = [max(2), max(1), max(0)].iter().copied().collect::<IdxSet>();
+        assert_eq! { ranges(&last), [IdxRange::new(max(2), max(0))] }
+        assert_eq! { last.as_single_range(), None }
+        let last = [max(0)].iter().copied().collect::<IdxSet>();
+        assert!(ranges(&last).is_empty());
+        assert_eq! { last.as_single_range(), None }
+        let mut full = set(&[0]);
+        full.insert(max(0));
+        assert_eq! { ranges(&full), [range(0, 1)] }
+        assert_eq! { full.as_single_range(), None }
+    }
+}
+
+#[test]
+fn range_query() {
+    use alloc::vec::Vec;
+    let set: IdxSet = [2, 4, 5, 8].iter().map(|n| idx(*n)).collect();
+    let query = |range| set.range_query(range).map(|i| i.get()).collect::<Vec<_>>();
+    let (two, four, five, eight) = (idx(2), idx(4), idx(5), idx(8));
+
+    // boundaries on set members
+    assert_eq! { query(two.up_to(eight)), [2, 4, 5] }
+    assert_eq! { query(two.up_to(five)), [2, 4] }
+    assert_eq! { set.range_query(two..=eight).count(), 4 }
+    assert_eq! { set.range_query(four..).count(), 3 }
+    assert_eq! { set.range_query(..four).count(), 1 }
+    assert_eq! { set.range_query(..).count(), 4 }
+    assert_eq! { set.first_in(idx(3).up_to(eight)), Some(four) }
+    assert_eq! { set.last_in(idx(3).up_to(eight)), Some(five) }
+    assert_eq! { set.first_in(four..=four), Some(four) }
+    assert_eq! { set.last_in(..=eight), Some(eight) }
+
+    // empty query ranges
+    assert!(query(four.up_to(four)).is_empty());
+    assert!(query(eight.up_to(two)).is_empty());
+    assert_eq! { set.first_in(idx(6).up_to(eight)), None }
+    assert_eq! { set.last_in(five..five), None }
+    assert_eq! { set.first_in(eight..=two), None }
+    let excluded = (
+        core::ops::Bound::Excluded(four),
+        core::ops::Bound::Excluded(four),
+    );
+    assert_eq! { set.first_in(excluded), None }
+}
+
+#[test]
+fn membership_map() {
+    let set: IdxSet = [1, 2, 5].iter().map(|n| idx(*n)).collect();
+    let map = set.to_membership_map(7);
+    assert_eq! { map[..], [false, true, true, false, false, true, false] }
+    assert_eq! { map.to_set(), set }
+    assert_eq! { set.to_membership_map(6).to_set(), set }
+    assert_eq! { set.to_map_with(6, 'x', '.')[..], ['.', 'x', 'x', '.', '.', 'x'] }
+
+    let empty = IdxSet::new();
+    assert_eq! { empty.to_membership_map(3)[..], [false; 3] }
+    assert_eq! { empty.to_membership_map(3).to_set(), empty }
+    assert_eq! { empty.to_membership_map(0).len(), 0 }
+}
+
+#[test]
+#[should_panic(expected = "index Idx(5) out of bounds for IdxMap of length 5")]
+fn membership_map_out_of_bounds() {
+    let set: IdxSet = [1, 2, 5].iter().map(|n| idx(*n)).collect();
+    set.to_membership_map(5);
+}
+
+#[test]
+fn complement() {
+    use alloc::vec::Vec;
+    let set = |indices: &[usize]| indices.iter().map(|n| idx(*n)).collect::<IdxSet>();
+    let absent = |set: &IdxSet, end| {
+        set.absent_up_to(idx(end))
+            .map(|i| i.get())
+            .collect::<Vec<_>>()
+    };
+
+    let some = set(&[1, 2, 5]);
+    assert_eq! { absent(&some, 7), [0, 3, 4, 6] }
+    assert_eq! { some.complement_up_to(idx(7)), set(&[0, 3, 4, 6]) }
+    // members beyond the bound
+    assert_eq! { absent(&some, 4), [0, 3] }
+    assert_eq! { some.complement_up_to(idx(2)), set(&[0]) }
+    assert!(some.complement_up_to(idx(0)).is_empty());
+    // empty set
+    assert_eq! { absent(&IdxSet::new(), 3), [0, 1, 2] }
+    // full set
+    let full = idx(0).up_to(idx(5)).to_set();
+    assert!(absent(&full, 5).is_empty());
+    assert_eq! { absent(&full, 7), [5, 6] }
+
+    let map: IdxMap<char> = "abcdef".chars().collect();
+    assert_eq! { some.complement_in(&map), set(&[0, 3, 4]) }
+    assert_eq! { full.complement_in(&map), set(&[5]) }
+}
+
+#[test]
+fn btree_map_struct() {
+    use alloc::{collections::BTreeMap, format, vec::Vec};
+    let pairs = [(idx(5), 'b'), (idx(1), 'a'), (idx(9), 'c')];
+    let alias: BTreeMap<Idx, char> = pairs.iter().cloned().collect();
+    let mut map: IdxBMap<char> = pairs.iter().cloned().collect();
+
+    assert_eq! { map.len(), alias.len() }
+    assert_eq! { map.first_index(), alias.keys().next().copied() }
+    assert_eq! { map.last_index(), alias.keys().next_back().copied() }
+    assert_eq! { map.indices().collect::<Vec<_>>(), alias.keys().copied().collect::<Vec<_>>() }
+    assert_eq! { map.keys_as_set(), alias.keys().copied().collect() }
+    assert_eq! { map.iter().collect::<Vec<_>>(), alias.values().collect::<Vec<_>>() }
+    assert!(map.index_iter().eq(alias.iter().map(|(idx, c)| (*idx, c))));
+    assert!(map
+        .index_iter()
+        .rev()
+        .eq(alias.iter().rev().map(|(idx, c)| (*idx, c))));
+    assert!(map
+        .range_query(idx(1).up_to(idx(9)))
+        .eq(alias.range(idx(1)..idx(9)).map(|(idx, c)| (*idx, c))));
+    assert!(map
+        .range_query(idx(5)..)
+        .eq(alias.range(idx(5)..).map(|(idx, c)| (*idx, c))));
+    assert_eq! { map.range_query(idx(9).up_to(idx(1))).count(), 0 }
+    assert_eq! { format!("{:?}", map), "{1: 'a', 5: 'b', 9: 'c'}" }
+
+    assert_eq! { map[idx(5)], alias[&idx(5)] }
+    assert_eq! { map.get(idx(2)), alias.get(&idx(2)) }
+    assert!(map.contains_index(idx(9)));
+    map[idx(5)] = 'B';
+    for (_, c) in map.index_iter_mut() {
+        *c = c.to_ascii_uppercase()
+    }
+    assert_eq! { map.insert(idx(2), 'z'), None }
+    assert_eq! { map.remove(idx(9)), Some('C') }
+    assert_eq! { map.into_iter().collect::<alloc::string::String>(), "AzB" }
+
+    // Zero-cost conversions.
+    let map = IdxBMap::from(alias.clone());
+    assert_eq! { map.as_btree_map(), &alias }
+    assert_eq! { map.into_btree_map(), alias }
+}
+
+#[test]
+#[should_panic(expected = "index Idx(2) has no element in IdxBMap")]
+fn btree_map_struct_missing_index() {
+    let map: IdxBMap<char> = [(idx(1), 'a')].iter().cloned().collect();
+    let _ = map[idx(2)];
+}
+
+#[test]
+fn interval_set() {
+    use alloc::{format, vec::Vec};
+    let range = |start, end| IdxRange::new(idx(start), idx(end));
+    let ranges = |set: &IdxIntervals| {
+        set.iter_ranges()
+            .map(|r| (r.start.get(), r.end.get()))
+            .collect::<Vec<_>>()
+    };
+
+    let mut set = IdxIntervals::new();
+    set.insert_range(range(2, 4));
+    set.insert_range(idx(8)..idx(10));
+    assert_eq! { ranges(&set), [(2, 4), (8, 10)] }
+    // adjacent, on both sides
+    set.insert_range(range(4, 5));
+    set.insert_range(range(7, 8));
+    assert_eq! { ranges(&set), [(2, 5), (7, 10)] }
+    // overlapping
+    set.insert_range(range(9, 12));
+    set.insert_range(range(1, 3));
+    assert_eq! { ranges(&set), [(1, 5), (7, 12)] }
+    // nested
+    set.insert_range(range(8, 10));
+    assert_eq! { ranges(&set), [(1, 5), (7, 12)] }
+    // bridging and covering
+    set.insert_range(range(5, 7));
+    assert_eq! { ranges(&set), [(1, 12)] }
+    set.insert_range(range(0, 20));
+    assert_eq! { ranges(&set), [(0, 20)] }
+    // empty
+    set.insert_range(range(30, 30));
+    set.insert_range(range(40, 30));
+    assert_eq! { ranges(&set), [(0, 20)] }
+    assert_eq! { set.covered_len(), 20 }
+
+    // removals splitting an interval
+    set.remove_range(range(5, 8));
+    assert_eq! { ranges(&set), [(0, 5), (8, 20)] }
+    set.remove_range(range(4, 9));
+    assert_eq! { ranges(&set), [(0, 4), (9, 20)] }
+    set.remove_range(range(15, 25));
+    set.remove_range(range(0, 1));
+    assert_eq! { ranges(&set), [(1, 4), (9, 15)] }
+    set.remove_range(range(12, 3));
+    assert_eq! { set.covered_len(), 9 }
+    assert_eq! { format!("{:?}", set), "{1..4, 9..15}" }
+
+    for n in 0..20 {
+        assert_eq! { set.contains(idx(n)), (1..4).contains(&n) || (9..15).contains(&n) }
+    }
+    assert_eq! { set.iter().count(), set.covered_len() }
+
+    let other: IdxIntervals = [range(0, 2), range(3, 10), range(14, 30)]
+        .iter()
+        .cloned()
+        .collect();
+    let mut union = set.clone();
+    union.union_with(&other);
+    assert_eq! { ranges(&union), [(0, 30)] }
+    let mut inter = set.clone();
+    inter.intersect_with(&other);
+    assert_eq! { ranges(&inter), [(1, 2), (3, 4), (9, 10), (14, 15)] }
+    let mut diff = set.clone();
+    diff.difference_with(&other);
+    assert_eq! { ranges(&diff), [(2, 3), (10, 14)] }
+    assert_eq! { inter.covered_len() + diff.covered_len(), set.covered_len() }
+}
+
+#[test]
+#[cfg(feature = "rand")]
+fn random_members() {
+    use alloc::{collections::BTreeMap, vec::Vec};
+    use rand::{rngs::SmallRng, SeedableRng};
+
+    let set: IdxSet = [1, 4, 5, 9, 12, 20].iter().map(|n| idx(*n)).collect();
+    let intervals: IdxIntervals = [1..2, 4..6, 9..10, 12..13, 20..21]
+        .iter()
+        .map(|r| idx(r.start)..idx(r.end))
+        .collect();
+    let mut rng = SmallRng::seed_from_u64(42);
+
+    // Uniformity smoke test.
+    let mut set_counts = BTreeMap::new();
+    let mut interval_counts = BTreeMap::new();
+    for _ in 0..6_000 {
+        *set_counts.entry(set.choose(&mut rng).unwrap()).or_insert(0) += 1;
+        *interval_counts
+            .entry(intervals.choose(&mut rng).unwrap())
+            .or_insert(0) += 1;
+    }
+    for counts in [set_counts, interval_counts] {
+        assert!(counts.keys().eq(set.iter()));
+        assert!(
+            counts.values().all(|n| (800..1200).contains(n)),
+            "{:?}",
+            counts
+        );
+    }
+    assert_eq! { IdxSet::new().choose(&mut rng), None }
+    assert_eq! { IdxIntervals::new().choose(&mut rng), None }
+
+    for k in 0..8 {
+        for sample in [set.sample(&mut rng, k), intervals.sample(&mut rng, k)] {
+            assert_eq! { sample.len(), k.min(6) }
+            assert!(sample.windows(2).all(|pair| pair[0] < pair[1]));
+            assert!(sample.iter().all(|idx| set.contains(idx)));
+        }
+    }
+
+    // Determinism under a seeded RNG.
+    let run = |seed| {
+        let mut rng = SmallRng::seed_from_u64(seed);
+        let picks: Vec<_> = (0..10).map(|_| set.choose(&mut rng)).collect();
+        (
+            picks,
+            set.sample(&mut rng, 3),
+            intervals.sample(&mut rng, 3),
+        )
+    };
+    assert_eq! { run(7), run(7) }
+}
+
+#[test]
+fn rank_select() {
+    use alloc::vec::Vec;
+    let members = [0, 3, 4, 8, 15, 16, 42];
+    let set: IdxSet = members.iter().map(|n| idx(*n)).collect();
+    // Sorted-vec oracle.
+    let oracle: Vec<Idx> = members.iter().map(|n| idx(*n)).collect();
+
+    for k in 0..10 {
+        assert_eq! { set.select(k), oracle.get(k).copied() }
+    }
+    for n in 0..50 {
+        let i = idx(n);
+        let rank = oracle.iter().filter(|m| **m < i).count();
+        assert_eq! { set.rank(i), rank }
+        assert_eq! { set.position_of(i), oracle.binary_search(&i).ok() }
+    }
+    assert_eq! { IdxSet::new().select(0), None }
+    assert_eq! { IdxSet::new().rank(idx(3)), 0 }
+}
+
+#[test]
+fn btree_map_to_dense() {
+    use crate::MissingIndex;
+    let bmap = |pairs: &[(usize, char)]| {
+        pairs
+            .iter()
+            .map(|(n, c)| (idx(*n), *c))
+            .collect::<IdxBMap<_>>()
+    };
+
+    // exact prefix
+    let dense = bmap(&[(2, 'c'), (0, 'a'), (1, 'b')]).to_dense().unwrap();
+    assert_eq! { dense[..], ['a', 'b', 'c'] }
+    assert_eq! { bmap(&[]).to_dense().unwrap().len(), 0 }
+    // gap
+    assert_eq! { bmap(&[(0, 'a'), (2, 'c')]).to_dense(), Err(MissingIndex { idx: idx(1) }) }
+    assert_eq! { bmap(&[(1, 'b')]).to_dense(), Err(MissingIndex { idx: idx(0) }) }
+    // trailing extra key
+    assert_eq! {
+        bmap(&[(0, 'a'), (1, 'b'), (7, 'h')]).to_dense(),
+        Err(MissingIndex { idx: idx(2) })
+    }
+
+    assert_eq! { bmap(&[(0, 'a'), (2, 'c')]).densify_with('.')[..], ['a', '.', 'c'] }
+    assert_eq! { bmap(&[(1, 'b'), (4, 'e')]).densify_with('.')[..], ['.', 'b', '.', '.', 'e'] }
+    assert_eq! { bmap(&[(0, 'a'), (1, 'b')]).densify_with('.')[..], ['a', 'b'] }
+    assert_eq! { bmap(&[]).densify_with('.').len(), 0 }
+}
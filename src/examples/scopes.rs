@@ -0,0 +1,201 @@
+//! A symbol table for nested lexical scopes.
+//!
+//! ```rust
+//! use safe_index::examples::scopes::{idx::*, *};
+//!
+//! let mut table = SymTable::new();
+//! let root = table.root;
+//! let x = table.declare(root, "x");
+//! let y = table.declare(root, "y");
+//!
+//! // fn f(x) { let z; { let y; } }
+//! let f = table.open(root);
+//! let f_x = table.declare(f, "x");
+//! let z = table.declare(f, "z");
+//! let block = table.open(f);
+//! let block_y = table.declare(block, "y");
+//!
+//! assert_eq! { table.resolve(block, "x"), Some(f_x) }
+//! assert_eq! { table.resolve(block, "y"), Some(block_y) }
+//! assert_eq! { table.resolve(block, "z"), Some(z) }
+//! assert_eq! { table.resolve(f, "y"), Some(y) }
+//! assert_eq! { table.resolve(root, "x"), Some(x) }
+//! assert_eq! { table.resolve(root, "z"), None }
+//! assert_eq! { table.parents(block).collect::<Vec<_>>(), [block, f, root] }
+//!
+//! // Redeclaring in the same scope shadows the previous symbol, which still exists.
+//! let z_again = table.declare(f, "z");
+//! assert_ne! { z, z_again }
+//! assert_eq! { table.resolve(block, "z"), Some(z_again) }
+//! assert_eq! { table.syms[z].name, "z" }
+//!
+//! // Fresh symbols are never bound to their name, they cannot capture anything.
+//! let tmp = table.fresh(block, "x");
+//! assert_eq! { table.resolve(block, "x"), Some(f_x) }
+//! assert_eq! { table.syms[tmp].scope, block }
+//! assert_eq! { table.display(tmp), format!("x#{}", tmp) }
+//! assert_eq! { table.display(f_x), "x" }
+//!
+//! // Index types wrap a whole `usize`: there is no niche for `None`.
+//! use std::mem::size_of;
+//! assert_eq! { size_of::<Scope>(), size_of::<usize>() }
+//! assert_eq! { size_of::<Option<Scope>>(), 2 * size_of::<usize>() }
+//! ```
+//!
+//! Storing `parent: Option<Scope>` thus doubles the size of the parent link compared to a
+//! `Scope`. Making the root its own parent would save that word, but every walk up the scope
+//! chain would then have to compare scopes to know when to stop, which `Option` makes explicit.
+
+use alloc::{collections::BTreeMap, string::String};
+
+/// Indices.
+pub mod idx {
+    new! {
+        /// Indices of symbols.
+        Sym {
+            /// Map from symbols to something.
+            map: Syms,
+        }
+
+        /// Indices of scopes.
+        Scope {
+            /// Map from scopes to something.
+            map: Scopes,
+        }
+    }
+}
+
+use idx::*;
+
+/// Symbol information.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymInfo {
+    /// Name of the symbol, as written by the user or the hint of a fresh symbol.
+    pub name: String,
+    /// Scope the symbol is declared in.
+    pub scope: Scope,
+}
+
+/// Scope information.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScopeInfo {
+    /// Parent scope, `None` for the root.
+    pub parent: Option<Scope>,
+    /// Symbols bound in this scope, by name.
+    pub names: BTreeMap<String, Sym>,
+}
+
+/// Symbol table for nested scopes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymTable {
+    /// Map from symbol indexes to symbol information.
+    pub syms: Syms<SymInfo>,
+    /// Map from scope indexes to scope information.
+    pub scopes: Scopes<ScopeInfo>,
+    /// Root scope.
+    pub root: Scope,
+}
+impl Default for SymTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl SymTable {
+    /// Constructor, creates the root scope.
+    pub fn new() -> SymTable {
+        let mut scopes = Scopes::new();
+        let root = scopes.push(ScopeInfo {
+            parent: None,
+            names: BTreeMap::new(),
+        });
+        SymTable {
+            syms: Syms::new(),
+            scopes,
+            root,
+        }
+    }
+
+    /// Opens a scope nested in `parent`.
+    pub fn open(&mut self, parent: Scope) -> Scope {
+        self.scopes.push(ScopeInfo {
+            parent: Some(parent),
+            names: BTreeMap::new(),
+        })
+    }
+
+    /// Declares a symbol in a scope.
+    ///
+    /// Shadows the symbols with the same name in the scope and its parents.
+    pub fn declare<S: Into<String>>(&mut self, scope: Scope, name: S) -> Sym {
+        let name = name.into();
+        let sym = self.syms.push(SymInfo {
+            name: name.clone(),
+            scope,
+        });
+        self.scopes[scope].names.insert(name, sym);
+        sym
+    }
+
+    /// Creates a symbol in a scope without binding its name, resolution never yields it.
+    pub fn fresh<S: Into<String>>(&mut self, scope: Scope, hint: S) -> Sym {
+        self.syms.push(SymInfo {
+            name: hint.into(),
+            scope,
+        })
+    }
+
+    /// Iterator over a scope and its parents, innermost first.
+    pub fn parents(&self, scope: Scope) -> impl Iterator<Item = Scope> + '_ {
+        core::iter::successors(Some(scope), move |scope| self.scopes[*scope].parent)
+    }
+
+    /// Symbol a name refers to in a scope, if any.
+    pub fn resolve(&self, scope: Scope, name: &str) -> Option<Sym> {
+        self.parents(scope)
+            .find_map(|scope| self.scopes[scope].names.get(name).cloned())
+    }
+
+    /// Name of a symbol, suffixed with `#` and its index unless its scope binds the name to it.
+    ///
+    /// Such symbols are fresh ones and the ones shadowed in their own scope.
+    pub fn display(&self, sym: Sym) -> String {
+        let SymInfo { name, scope } = &self.syms[sym];
+        if self.scopes[*scope].names.get(name) == Some(&sym) {
+            name.clone()
+        } else {
+            alloc::format!("{}#{}", name, sym)
+        }
+    }
+}
+
+#[test]
+fn run() {
+    let mut table = SymTable::new();
+    let root = table.root;
+    let mut scope = root;
+    let mut decls = alloc::vec::Vec::new();
+    // Ten nested scopes, each declaring `v` and every other one `w`.
+    for with_w in [true, false].iter().cycle().take(10) {
+        scope = table.open(scope);
+        decls.push((scope, table.declare(scope, "v")));
+        if *with_w {
+            table.declare(scope, "w");
+        }
+    }
+    assert_eq! { table.parents(scope).count(), 11 }
+    assert_eq! { table.parents(scope).last(), Some(root) }
+    for (scope, v) in &decls {
+        assert_eq! { table.resolve(*scope, "v"), Some(*v) }
+        let w = table.resolve(*scope, "w").unwrap();
+        // Scopes without a `w` see the one of their parent.
+        let w_scope = Some(table.syms[w].scope);
+        assert!(w_scope == Some(*scope) || w_scope == table.scopes[*scope].parent);
+    }
+    assert_eq! { table.resolve(root, "v"), None }
+
+    // Symbols shadowed in their own scope display like fresh ones.
+    let (inner, v) = decls[9];
+    let shadowing = table.declare(inner, "v");
+    assert_eq! { table.display(shadowing), "v" }
+    assert_eq! { table.display(v), alloc::format!("v#{}", v) }
+}
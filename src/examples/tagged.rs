@@ -0,0 +1,144 @@
+//! The [clients example](super::clients) with tagged indices.
+//!
+//! The code is the same as in the clients example, only the declaration of the indices differs:
+//! here `Client` and `File` are aliases of [`tagged::Idx`](crate::tagged::Idx) over two different
+//! tags, they share their implementation but are still distinct types. Tagged indices do not
+//! support `btree set struct`, `slice` or matrices, so `FileSet` is a `btree set` and the tests
+//! relying on the other options only run in the clients example.
+//!
+//! ```rust
+//! use safe_index::examples::tagged::{*, idx::{FileSet, ClientSet}};
+//!
+//! let mut data = Data::new();
+//!
+//! let c_1 = data.add_client("client 1");
+//! let c_2 = data.add_client("client 2");
+//! let c_3 = data.add_client("client 3");
+//! assert_eq!(c_2, data.add_client("client 2"));
+//!
+//! let f_1 = data.add_file(FileInfo::new("file 1"), vec![c_1, c_2]);
+//! let f_2 = data.add_file(FileInfo::new("file 2"), vec![c_3]);
+//!
+//! let classes = data.client_clusters();
+//! let expected: Vec<(ClientSet, FileSet)> = vec![
+//!     (vec![c_1, c_2].into_iter().collect(), vec![f_1].into_iter().collect()),
+//!     (vec![c_3].into_iter().collect(), vec![f_2].into_iter().collect()),
+//! ];
+//! assert_eq! { classes, expected }
+//! ```
+
+/// Indices.
+pub mod idx {
+    new! {
+        /// Indices of clients.
+        tagged Client: ClientTag,
+        /// Map from clients to something.
+        map: Clients,
+        /// Set of clients.
+        btree set: ClientSet,
+        /// Interner of client names.
+        #[cfg(any(feature = "std", feature = "hashbrown"))]
+        interner: ClientNames,
+    }
+
+    new! {
+        /// Indices of files.
+        tagged File: FileTag,
+        /// Map from files to something.
+        map: Files,
+        /// Set of files.
+        btree set: FileSet,
+        /// Partition of files into classes.
+        union find: FileClasses,
+    }
+}
+
+use idx::*;
+
+super::clients::clients_example! {}
+
+#[test]
+fn map() {
+    use alloc::{format, vec::Vec};
+    let mut files: Files<char> = "abc".chars().collect();
+    let idx: Vec<File> = files.indices().collect();
+    assert_eq! { files[idx[1]], 'b' }
+    assert_eq! { files[idx[1]..], ['b', 'c'] }
+    assert_eq! { files[..=idx[0]], ['a'] }
+    files[idx[2]] = 'C';
+    assert_eq! { files.replace(idx[0], 'A'), 'a' }
+    files.swap(idx[0], idx[1]);
+    assert_eq! { files.iter().collect::<alloc::string::String>(), "bAC" }
+    assert_eq! { files.last(), Some((idx[2], &'C')) }
+    assert_eq! {
+        files.select(&idx[1..]).map(|(_, c)| *c).collect::<Vec<_>>(),
+        ['A', 'C'],
+    }
+    assert_eq! { format!("{} {:?}", idx[2], idx[2]), "2 File { val: 2 }" }
+    assert_eq! { format!("{:?}", files), "Files { vec: ['b', 'A', 'C'] }" }
+    assert_eq! { files.clone(), files }
+    assert!(idx[0] < idx[1] && idx[1] == 1);
+    assert_eq! { Into::<usize>::into(idx[2]), 2 }
+    assert_eq! {
+        core::mem::size_of::<Files<char>>(),
+        core::mem::size_of::<alloc::vec::Vec<char>>()
+    }
+    assert_eq! { core::mem::size_of::<File>(), core::mem::size_of::<usize>() }
+}
+
+#[test]
+#[should_panic(expected = "index Client(3) out of bounds for Clients of length 2")]
+fn map_out_of_bounds() {
+    let mut clients = Clients::new();
+    let c_0 = clients.push('a');
+    let mut more = Clients::new();
+    for _ in 0..4 {
+        more.push(c_0);
+    }
+    clients.push('b');
+    let _ = clients[more.last_index().unwrap()];
+}
+
+#[test]
+#[cfg(all(feature = "zerocopy", not(feature = "strict")))]
+fn zerocopy() {
+    use alloc::vec;
+    use zerocopy::{FromBytes, IntoBytes};
+
+    let size = core::mem::size_of::<usize>();
+    let owners: Files<Client> = vec![Client::new(3), Client::new(0), Client::new(7)]
+        .into_iter()
+        .collect();
+    let bytes = owners.as_vec().as_bytes();
+    assert_eq! { bytes.len(), 3 * size }
+    assert_eq! { Client::read_from_bytes(&bytes[..size]).unwrap(), Client::new(3) }
+    assert_eq! { Client::new(7).as_bytes(), 7usize.as_bytes() }
+    let read = <[Client]>::ref_from_bytes(bytes).unwrap();
+    assert_eq! { read, owners.as_vec().as_slice() }
+    assert!(<[Client]>::ref_from_bytes(&bytes[..bytes.len() - 1]).is_err());
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn serde() {
+    let mut owners: Files<Client> = Files::new();
+    let mut clients = Clients::new();
+    let c_0 = clients.push(7u8);
+    let c_1 = clients.push(3);
+    owners.push(c_1);
+    owners.push(c_0);
+
+    assert_eq! { serde_json::to_string(&c_1).unwrap(), "1" }
+    assert_eq! { serde_json::to_string(&clients).unwrap(), "[7,3]" }
+    assert_eq! { serde_json::to_string(&owners).unwrap(), "[1,0]" }
+
+    #[cfg(not(feature = "strict"))]
+    {
+        let read: Files<Client> = serde_json::from_str("[1,0]").unwrap();
+        assert_eq! { read, owners }
+        let read: Clients<u8> = serde_json::from_str("[7,3]").unwrap();
+        assert_eq! { read, clients }
+        assert_eq! { serde_json::from_str::<Client>("1").unwrap(), c_1 }
+        assert!(serde_json::from_str::<Client>("-1").is_err());
+    }
+}
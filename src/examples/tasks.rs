@@ -0,0 +1,214 @@
+//! Scheduling tasks that depend on each other, such as the steps of a build.
+//!
+//! ```rust
+//! use safe_index::examples::tasks::{idx::*, *};
+//!
+//! let mut build = BuildGraph::new();
+//! let fetch = build.add_task("fetch", None);
+//! let config = build.add_task("configure", Some(fetch));
+//! let lib = build.add_task("compile lib", vec![fetch, config]);
+//! let bin = build.add_task("compile bin", Some(lib));
+//! let test = build.add_task("test", vec![lib, bin]);
+//! let docs = build.add_task("docs", Some(config));
+//!
+//! let order = build.schedule().unwrap();
+//! assert_eq! { order, vec![fetch, config, lib, docs, bin, test] }
+//! let names: Vec<_> = order.iter().map(|task| build.tasks[*task].name.as_str()).collect();
+//! assert_eq! { names, ["fetch", "configure", "compile lib", "docs", "compile bin", "test"] }
+//!
+//! let needed: TaskSet = vec![fetch, config, lib].into_iter().collect();
+//! assert_eq! { build.closure(bin), needed }
+//! assert!(build.closure(fetch).is_empty());
+//!
+//! // Configuring now needs the docs, which need the configuration.
+//! assert!(build.add_dep(config, docs));
+//! let cycle: TaskSet = vec![config, docs].into_iter().collect();
+//! assert_eq! { build.schedule(), Err(cycle) }
+//! assert!(build.closure(docs).contains(&docs));
+//! ```
+
+use alloc::{collections::VecDeque, string::String, vec::Vec};
+
+/// Indices.
+pub mod idx {
+    new! {
+        /// Indices of tasks.
+        Task,
+        /// Map from tasks to something.
+        map: Tasks,
+        /// Set of tasks.
+        btree set: TaskSet,
+    }
+}
+
+use idx::*;
+
+/// Task information.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskInfo {
+    /// Name of the task.
+    pub name: String,
+    /// Tasks to run before this one.
+    pub deps: TaskSet,
+}
+
+/// Tasks and their dependencies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuildGraph {
+    /// Map from task indexes to task information.
+    pub tasks: Tasks<TaskInfo>,
+}
+impl Default for BuildGraph {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl BuildGraph {
+    /// Constructor.
+    pub fn new() -> BuildGraph {
+        BuildGraph {
+            tasks: Tasks::new(),
+        }
+    }
+
+    /// Adds a task depending on some tasks.
+    pub fn add_task<S, I>(&mut self, name: S, deps: I) -> Task
+    where
+        S: Into<String>,
+        I: IntoIterator<Item = Task>,
+    {
+        self.tasks.push(TaskInfo {
+            name: name.into(),
+            deps: deps.into_iter().collect(),
+        })
+    }
+
+    /// Makes `task` depend on `dep`, false if it already did.
+    pub fn add_dep(&mut self, task: Task, dep: Task) -> bool {
+        self.tasks[task].deps.insert(dep)
+    }
+
+    /// Tasks depending directly on each task.
+    pub fn dependents(&self) -> Tasks<TaskSet> {
+        let mut dependents: Tasks<TaskSet> = Tasks::new_like(&self.tasks);
+        for (task, info) in self.tasks.index_iter() {
+            for dep in &info.deps {
+                dependents[*dep].insert(task);
+            }
+        }
+        dependents
+    }
+
+    /// An order running each task after its dependencies, with Kahn's algorithm.
+    ///
+    /// Tasks are scheduled as soon as their dependencies are, ties are broken by index. Fails
+    /// with the tasks on a dependency cycle, or on a path between two cycles.
+    pub fn schedule(&self) -> Result<Vec<Task>, TaskSet> {
+        let dependents = self.dependents();
+        // Number of dependencies of each task not scheduled yet.
+        let mut waiting_on: Tasks<usize> = self.tasks.iter().map(|info| info.deps.len()).collect();
+        let mut ready: VecDeque<Task> = waiting_on
+            .index_iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(task, _)| task)
+            .collect();
+        let mut order = Vec::with_capacity(self.tasks.len());
+        while let Some(task) = ready.pop_front() {
+            order.push(task);
+            for dependent in &dependents[task] {
+                waiting_on[*dependent] -= 1;
+                if waiting_on[*dependent] == 0 {
+                    ready.push_back(*dependent);
+                }
+            }
+        }
+        if order.len() == self.tasks.len() {
+            return Ok(order);
+        }
+
+        // Tasks left are on cycles or depend on them, drop the ones no task left depends on.
+        let mut left: TaskSet = waiting_on
+            .index_iter()
+            .filter(|(_, count)| **count > 0)
+            .map(|(task, _)| task)
+            .collect();
+        let mut needed_by: Tasks<usize> = Tasks::new_like(&self.tasks);
+        for task in &left {
+            for dep in &self.tasks[*task].deps {
+                needed_by[*dep] += 1;
+            }
+        }
+        let mut unneeded: Vec<Task> = left
+            .iter()
+            .copied()
+            .filter(|task| needed_by[*task] == 0)
+            .collect();
+        while let Some(task) = unneeded.pop() {
+            left.remove(&task);
+            for dep in &self.tasks[task].deps {
+                needed_by[*dep] -= 1;
+                if needed_by[*dep] == 0 && left.contains(dep) {
+                    unneeded.push(*dep);
+                }
+            }
+        }
+        Err(left)
+    }
+
+    /// Tasks a task depends on, transitively.
+    ///
+    /// Contains `task` itself only if it is on a cycle.
+    pub fn closure(&self, task: Task) -> TaskSet {
+        let mut closure = TaskSet::new();
+        let mut todo: Vec<Task> = self.tasks[task].deps.iter().copied().collect();
+        while let Some(dep) = todo.pop() {
+            if closure.insert(dep) {
+                todo.extend(self.tasks[dep].deps.iter().copied());
+            }
+        }
+        closure
+    }
+}
+
+#[test]
+fn run() {
+    let mut build = BuildGraph::new();
+    let tasks: Vec<Task> = (0..6)
+        .map(|n| build.add_task(alloc::format!("task {}", n), None))
+        .collect();
+    // Every task depends on the next one.
+    for pair in tasks.windows(2) {
+        assert!(build.add_dep(pair[0], pair[1]));
+    }
+    assert!(!build.add_dep(tasks[0], tasks[1]));
+    let mut reversed = tasks.clone();
+    reversed.reverse();
+    assert_eq! { build.schedule(), Ok(reversed) }
+    assert_eq! { build.closure(tasks[3]), tasks[4..].iter().copied().collect() }
+    assert_eq! { build.dependents()[tasks[3]], Some(tasks[2]).into_iter().collect() }
+
+    // `task 2`, `task 3` and `task 4` are now on a cycle.
+    build.add_dep(tasks[4], tasks[2]);
+    assert_eq! { build.schedule(), Err(tasks[2..5].iter().copied().collect()) }
+    // `task 0` and `task 1` only depend on the cycle, `task 5` is not involved.
+    assert!(build
+        .closure(tasks[1])
+        .is_superset(&build.closure(tasks[2])));
+
+    // Two cycles with a task between them.
+    let mut build = BuildGraph::new();
+    let tasks: Vec<Task> = (0..5)
+        .map(|n| build.add_task(alloc::format!("task {}", n), None))
+        .collect();
+    for (task, dep) in [(0, 1), (1, 0), (2, 1), (3, 2), (3, 4), (4, 3)].iter() {
+        build.add_dep(tasks[*task], tasks[*dep]);
+    }
+    assert_eq! { build.schedule(), Err(tasks.iter().copied().collect()) }
+    // A self-dependency is a cycle.
+    let mut build = BuildGraph::new();
+    let task = build.add_task("task", None);
+    build.add_dep(task, task);
+    let after = build.add_task("after", Some(task));
+    assert_eq! { build.schedule(), Err(Some(task).into_iter().collect()) }
+    assert_eq! { build.closure(after), Some(task).into_iter().collect() }
+}
@@ -0,0 +1,173 @@
+//! Handles indexed heap code generation.
+
+/// Indexed heap code generation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! heap_codegen {
+    ($t:ident,
+        $(#[$meta:meta])*
+        $heap:ident
+        $($tail:tt)*
+    ) => {
+        $(#[$meta])*
+        ///
+        /// Max-heap of indices with updatable priorities, use [`core::cmp::Reverse`] priorities for a
+        /// min-heap.
+        #[derive(Debug, Clone)]
+        pub struct $heap<P> {
+            /// Binary heap of index-priority pairs.
+            heap: $crate::alloc::vec::Vec<($t, P)>,
+            /// Position of each index in `heap`, `usize::MAX` if absent.
+            pos: $crate::alloc::vec::Vec<usize>,
+        }
+
+        impl<P> core::default::Default for $heap<P> {
+            #[inline]
+            fn default() -> Self {
+                $heap {
+                    heap: $crate::alloc::vec::Vec::new(),
+                    pos: $crate::alloc::vec::Vec::new(),
+                }
+            }
+        }
+
+        impl<P: Ord> $heap<P> {
+            /// Creates an empty heap.
+            #[inline]
+            pub const fn new() -> Self {
+                $heap {
+                    heap: $crate::alloc::vec::Vec::new(),
+                    pos: $crate::alloc::vec::Vec::new(),
+                }
+            }
+            /// Number of indices in the heap.
+            #[inline]
+            pub fn len(&self) -> usize {
+                self.heap.len()
+            }
+            /// True if the heap is empty.
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.heap.is_empty()
+            }
+            /// Removes all the indices.
+            #[inline]
+            pub fn clear(&mut self) {
+                self.heap.clear();
+                self.pos.clear();
+            }
+            /// Position of an index in the heap.
+            #[inline]
+            fn pos_of(&self, idx: $t) -> Option<usize> {
+                self.pos.get(idx.val).copied().filter(|pos| *pos != usize::MAX)
+            }
+            /// True if an index is in the heap.
+            #[inline]
+            pub fn contains(&self, idx: impl core::borrow::Borrow<$t>) -> bool {
+                self.pos_of(*idx.borrow()).is_some()
+            }
+            /// Priority of an index, if it is in the heap.
+            #[inline]
+            pub fn priority(&self, idx: impl core::borrow::Borrow<$t>) -> Option<&P> {
+                self.pos_of(*idx.borrow()).map(|pos| &self.heap[pos].1)
+            }
+            /// Index with the highest priority.
+            #[inline]
+            pub fn peek(&self) -> Option<($t, &P)> {
+                self.heap.first().map(|(idx, prio)| (*idx, prio))
+            }
+            /// Inserts an index, or changes its priority if it is already there.
+            ///
+            /// Returns the previous priority of the index, if any.
+            pub fn push(&mut self, idx: $t, prio: P) -> Option<P> {
+                if self.contains(idx) {
+                    return self.change_priority(idx, prio);
+                }
+                if self.pos.len() <= idx.val {
+                    self.pos.resize(idx.val + 1, usize::MAX)
+                }
+                self.pos[idx.val] = self.heap.len();
+                self.heap.push((idx, prio));
+                self.sift_up(self.heap.len() - 1);
+                None
+            }
+            /// Changes the priority of an index, does nothing if the index is not in the heap.
+            ///
+            /// Returns the previous priority of the index, if any.
+            pub fn change_priority(&mut self, idx: $t, prio: P) -> Option<P> {
+                let pos = self.pos_of(idx)?;
+                let old = core::mem::replace(&mut self.heap[pos].1, prio);
+                if self.heap[pos].1 > old {
+                    self.sift_up(pos);
+                } else {
+                    self.sift_down(pos);
+                }
+                Some(old)
+            }
+            /// Removes the index with the highest priority.
+            pub fn pop(&mut self) -> Option<($t, P)> {
+                self.remove_at(0)
+            }
+            /// Removes an index, returns its priority if it was in the heap.
+            pub fn remove(&mut self, idx: impl core::borrow::Borrow<$t>) -> Option<P> {
+                let pos = self.pos_of(*idx.borrow())?;
+                self.remove_at(pos).map(|(_, prio)| prio)
+            }
+
+            /// Removes the pair at some position of the heap.
+            fn remove_at(&mut self, pos: usize) -> Option<($t, P)> {
+                if pos >= self.heap.len() {
+                    return None;
+                }
+                let last = self.heap.len() - 1;
+                self.swap(pos, last);
+                let (idx, prio) = self.heap.pop()?;
+                self.pos[idx.val] = usize::MAX;
+                if pos < self.heap.len() {
+                    // The pair moved to `pos` can go either way.
+                    self.sift_up(pos);
+                    self.sift_down(pos);
+                }
+                Some((idx, prio))
+            }
+            /// Swaps two positions of the heap.
+            #[inline]
+            fn swap(&mut self, a: usize, b: usize) {
+                self.heap.swap(a, b);
+                self.pos[self.heap[a].0.val] = a;
+                self.pos[self.heap[b].0.val] = b;
+            }
+            /// Moves the pair at some position up until its parent has a higher priority.
+            fn sift_up(&mut self, mut pos: usize) {
+                while pos > 0 {
+                    let parent = (pos - 1) / 2;
+                    if self.heap[pos].1 <= self.heap[parent].1 {
+                        break;
+                    }
+                    self.swap(pos, parent);
+                    pos = parent;
+                }
+            }
+            /// Moves the pair at some position down until its children have a lower priority.
+            fn sift_down(&mut self, mut pos: usize) {
+                loop {
+                    let (left, right) = (2 * pos + 1, 2 * pos + 2);
+                    let mut max = pos;
+                    if left < self.heap.len() && self.heap[left].1 > self.heap[max].1 {
+                        max = left
+                    }
+                    if right < self.heap.len() && self.heap[right].1 > self.heap[max].1 {
+                        max = right
+                    }
+                    if max == pos {
+                        break;
+                    }
+                    self.swap(pos, max);
+                    pos = max;
+                }
+            }
+        }
+
+        $crate::handle!{ $t $($tail)* }
+    };
+}
@@ -0,0 +1,467 @@
+//! Generic map over any index type.
+
+use core::marker::PhantomData;
+
+use crate::{alloc::vec::Vec, SafeIndex};
+
+/// Map from `I`s to `T`s, a wrapper around a vector.
+///
+/// Offers the API of the maps generated by the `map` option of [`new`](crate::new), but is
+/// implemented once for all the index types, which only need the index type to be generated.
+/// Converts to and from the generated maps of the same index type without copying.
+///
+/// ```rust
+/// use safe_index::IdxVec;
+/// safe_index::new! {
+///     /// Indices of clients.
+///     Client,
+///     /// Map from clients to something.
+///     map: Clients,
+/// }
+///
+/// let mut names: IdxVec<Client, &str> = IdxVec::new();
+/// let c_0 = names.push("client 0");
+/// let c_1 = names.push("client 1");
+/// assert_eq! { names[c_1], "client 1" }
+/// assert_eq! { names[c_0..].len(), 2 }
+///
+/// let names: Clients<&str> = names.into();
+/// assert_eq! { names[c_0], "client 0" }
+/// ```
+#[repr(transparent)]
+pub struct IdxVec<I, T> {
+    vec: Vec<T>,
+    idx: PhantomData<fn() -> I>,
+}
+
+impl<I: SafeIndex, T> IdxVec<I, T> {
+    /// Crate-private constructor.
+    #[inline]
+    pub(crate) const fn of(vec: Vec<T>) -> Self {
+        IdxVec {
+            vec,
+            idx: PhantomData,
+        }
+    }
+
+    /// Creates an empty map.
+    #[inline]
+    pub const fn new() -> Self {
+        Self::of(Vec::new())
+    }
+    /// Creates an empty map with some capacity.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::of(Vec::with_capacity(capacity))
+    }
+    /// Reserves some space for the map.
+    #[inline]
+    pub fn reserve(&mut self, capa: usize) {
+        self.vec.reserve(capa)
+    }
+    /// Shrinks the capacity of the map as much as possible.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.vec.shrink_to_fit()
+    }
+
+    /// Generates an index from a [`usize`] when it is a legal index.
+    #[inline]
+    pub fn index_from_usize(&self, n: usize) -> Option<I> {
+        if n < self.vec.len() {
+            Some(crate::idx_of(n))
+        } else {
+            None
+        }
+    }
+
+    /// Retrieves an entry in the map.
+    #[inline]
+    pub fn get(&self, idx: impl core::borrow::Borrow<I>) -> Option<&T> {
+        self.vec.get(idx.borrow().as_usize())
+    }
+    /// Retrieves an entry in the map.
+    #[inline]
+    pub fn get_mut(&mut self, idx: impl core::borrow::Borrow<I>) -> Option<&mut T> {
+        self.vec.get_mut(idx.borrow().as_usize())
+    }
+    /// True if the index is a legal index of the map.
+    #[inline]
+    pub fn contains_index(&self, idx: I) -> bool {
+        idx.as_usize() < self.vec.len()
+    }
+    /// Replaces the element at some index, yields the old one.
+    #[inline]
+    #[track_caller]
+    pub fn replace(&mut self, idx: I, elem: T) -> T {
+        core::mem::replace(&mut self[idx], elem)
+    }
+    /// Swaps two elements, panics if either index is out of bounds.
+    #[inline]
+    #[track_caller]
+    pub fn swap(&mut self, a: I, b: I) {
+        let len = self.vec.len();
+        for idx in [a, b] {
+            if idx.as_usize() >= len {
+                crate::oob::index(I::NAME, idx.as_usize(), "IdxVec", len)
+            }
+        }
+        self.vec.swap(a.as_usize(), b.as_usize())
+    }
+
+    /// Number of elements in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+    /// True if the map is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+    /// Capacity of the map.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.vec.capacity()
+    }
+
+    crate::non_strict! {
+        /// The next free index (wrapped `self.len()`).
+        #[inline]
+        pub fn next_index(&self) -> I {
+            crate::idx_of(self.len())
+        }
+    }
+    /// Index of the last element in the map.
+    #[inline]
+    pub fn last_index(&self) -> Option<I> {
+        let len = self.len();
+        if len > 0 {
+            Some(crate::idx_of(len - 1))
+        } else {
+            None
+        }
+    }
+    /// Last entry of the map.
+    #[inline]
+    pub fn last(&self) -> Option<(I, &T)> {
+        let idx = self.last_index()?;
+        Some((idx, &self.vec[idx.as_usize()]))
+    }
+
+    /// Pushes an element, yields its index.
+    #[inline]
+    pub fn push(&mut self, elem: T) -> I {
+        let idx = crate::idx_of(self.len());
+        self.vec.push(elem);
+        idx
+    }
+    /// Pushes an element built from its index, yields this index.
+    #[inline]
+    pub fn push_idx(&mut self, new_elem: impl FnOnce(I) -> T) -> I {
+        let idx = crate::idx_of(self.len());
+        self.vec.push(new_elem(idx));
+        idx
+    }
+    /// Pops an element.
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        self.vec.pop()
+    }
+    /// Clears the map, keeps the capacity.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.vec.clear()
+    }
+
+    /// Iterator over all the indices.
+    #[inline]
+    pub fn indices(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = I> + ExactSizeIterator + core::iter::FusedIterator {
+        (0..self.len()).map(crate::idx_of)
+    }
+    /// Set of all the indices of the map.
+    #[inline]
+    pub fn keys_as_set(&self) -> crate::alloc::collections::BTreeSet<I> {
+        self.indices().collect()
+    }
+
+    /// Ref-iterator over the elements.
+    #[inline]
+    pub fn iter(&self) -> core::slice::Iter<'_, T> {
+        self.vec.iter()
+    }
+    /// Ref-mut-iterator over the elements.
+    #[inline]
+    pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, T> {
+        self.vec.iter_mut()
+    }
+    /// Ref-iterator over the index/element pairs.
+    #[inline]
+    pub fn index_iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (I, &T)> + ExactSizeIterator + core::iter::FusedIterator
+    {
+        self.vec
+            .iter()
+            .enumerate()
+            .map(|(val, elem)| (crate::idx_of(val), elem))
+    }
+    /// Ref-mut-iterator over the index/element pairs.
+    #[inline]
+    pub fn index_iter_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = (I, &mut T)> + ExactSizeIterator + core::iter::FusedIterator
+    {
+        self.vec
+            .iter_mut()
+            .enumerate()
+            .map(|(val, elem)| (crate::idx_of(val), elem))
+    }
+    /// Own-iterator over the index/element pairs.
+    #[inline]
+    pub fn into_index_iter(
+        self,
+    ) -> impl DoubleEndedIterator<Item = (I, T)> + ExactSizeIterator + core::iter::FusedIterator
+    {
+        self.vec
+            .into_iter()
+            .enumerate()
+            .map(|(val, elem)| (crate::idx_of(val), elem))
+    }
+
+    /// Iterator over the entries at some indices, in the order of the indices.
+    ///
+    /// Panics if an index is out of bounds, like indexing does.
+    #[inline]
+    pub fn select<'a, Is>(&'a self, indices: Is) -> impl Iterator<Item = (I, &'a T)> + 'a
+    where
+        Is: IntoIterator,
+        Is::IntoIter: 'a,
+        Is::Item: core::borrow::Borrow<I>,
+    {
+        indices.into_iter().map(move |idx| {
+            let idx = *core::borrow::Borrow::<I>::borrow(&idx);
+            (idx, &self[idx])
+        })
+    }
+
+    /// Underlying slice.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        &self.vec
+    }
+    /// Underlying mutable slice.
+    #[inline]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        &mut self.vec
+    }
+    /// Underlying vector.
+    #[inline]
+    pub fn as_vec(&self) -> &Vec<T> {
+        &self.vec
+    }
+    /// Turns the map into its underlying vector.
+    #[inline]
+    pub fn into_vec(self) -> Vec<T> {
+        self.vec
+    }
+}
+
+impl<I: SafeIndex, T: Clone> IdxVec<I, T> {
+    /// Creates a map with `size` copies of `elem`.
+    #[inline]
+    pub fn of_elems(elem: T, size: usize) -> Self {
+        Self::of(crate::alloc::vec![elem; size])
+    }
+    /// Overwrites all the elements of the map with `value`.
+    #[inline]
+    pub fn fill(&mut self, value: T) {
+        self.vec.fill(value)
+    }
+}
+
+crate::non_strict! {
+    impl<I: SafeIndex, T> From<Vec<T>> for IdxVec<I, T> {
+        #[inline]
+        fn from(vec: Vec<T>) -> Self {
+            Self::of(vec)
+        }
+    }
+    impl<I: SafeIndex, T> core::ops::Deref for IdxVec<I, T> {
+        type Target = Vec<T>;
+        #[inline]
+        fn deref(&self) -> &Vec<T> {
+            &self.vec
+        }
+    }
+}
+
+impl<I: SafeIndex, T> Default for IdxVec<I, T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<I: SafeIndex, T: Clone> Clone for IdxVec<I, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self::of(self.vec.clone())
+    }
+}
+impl<I: SafeIndex, T: core::fmt::Debug> core::fmt::Debug for IdxVec<I, T> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fmt.debug_struct("IdxVec").field("vec", &self.vec).finish()
+    }
+}
+impl<I: SafeIndex, T: PartialEq> PartialEq for IdxVec<I, T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.vec == other.vec
+    }
+}
+impl<I: SafeIndex, T: Eq> Eq for IdxVec<I, T> {}
+impl<I: SafeIndex, T: PartialOrd> PartialOrd for IdxVec<I, T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.vec.partial_cmp(&other.vec)
+    }
+}
+impl<I: SafeIndex, T: Ord> Ord for IdxVec<I, T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.vec.cmp(&other.vec)
+    }
+}
+impl<I: SafeIndex, T: core::hash::Hash> core::hash::Hash for IdxVec<I, T> {
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.vec.hash(state)
+    }
+}
+
+impl<I: SafeIndex, T> IntoIterator for IdxVec<I, T> {
+    type Item = T;
+    type IntoIter = crate::alloc::vec::IntoIter<T>;
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.vec.into_iter()
+    }
+}
+impl<'a, I: SafeIndex, T> IntoIterator for &'a IdxVec<I, T> {
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<'a, I: SafeIndex, T> IntoIterator for &'a mut IdxVec<I, T> {
+    type Item = &'a mut T;
+    type IntoIter = core::slice::IterMut<'a, T>;
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+impl<I: SafeIndex, T> crate::SafeMap for IdxVec<I, T> {
+    type Idx = I;
+    type Elem = T;
+    #[inline]
+    fn len(&self) -> usize {
+        self.vec.len()
+    }
+    #[inline]
+    fn push(&mut self, elem: T) -> I {
+        IdxVec::push(self, elem)
+    }
+    #[inline]
+    fn get(&self, idx: I) -> Option<&T> {
+        self.vec.get(idx.as_usize())
+    }
+    #[inline]
+    fn get_mut(&mut self, idx: I) -> Option<&mut T> {
+        self.vec.get_mut(idx.as_usize())
+    }
+    #[inline]
+    fn index_from_usize(&self, n: usize) -> Option<I> {
+        IdxVec::index_from_usize(self, n)
+    }
+}
+
+impl<I: SafeIndex, T> core::iter::FromIterator<T> for IdxVec<I, T> {
+    #[inline]
+    fn from_iter<Iter: IntoIterator<Item = T>>(iter: Iter) -> Self {
+        Self::of(iter.into_iter().collect())
+    }
+}
+
+impl<I: SafeIndex, T> core::ops::Index<I> for IdxVec<I, T> {
+    type Output = T;
+    #[inline]
+    #[track_caller]
+    fn index(&self, idx: I) -> &T {
+        match self.vec.get(idx.as_usize()) {
+            Some(elem) => elem,
+            None => crate::oob::index(I::NAME, idx.as_usize(), "IdxVec", self.vec.len()),
+        }
+    }
+}
+impl<I: SafeIndex, T> core::ops::IndexMut<I> for IdxVec<I, T> {
+    #[inline]
+    #[track_caller]
+    fn index_mut(&mut self, idx: I) -> &mut T {
+        let len = self.vec.len();
+        match self.vec.get_mut(idx.as_usize()) {
+            Some(elem) => elem,
+            None => crate::oob::index(I::NAME, idx.as_usize(), "IdxVec", len),
+        }
+    }
+}
+impl<'a, I: SafeIndex, T> core::ops::Index<&'a I> for IdxVec<I, T> {
+    type Output = T;
+    #[inline]
+    #[track_caller]
+    fn index(&self, idx: &'a I) -> &T {
+        &self[*idx]
+    }
+}
+impl<'a, I: SafeIndex, T> core::ops::IndexMut<&'a I> for IdxVec<I, T> {
+    #[inline]
+    #[track_caller]
+    fn index_mut(&mut self, idx: &'a I) -> &mut T {
+        &mut self[*idx]
+    }
+}
+
+/// Generates `Index` and `IndexMut` implementations over some typed ranges.
+macro_rules! range_index {
+    ($($range:ty),* $(,)?) => {$(
+        impl<I: SafeIndex, T> core::ops::Index<$range> for IdxVec<I, T> {
+            type Output = [T];
+            #[inline]
+            #[track_caller]
+            fn index(&self, range: $range) -> &[T] {
+                let range = crate::oob::range::<I>(I::NAME, "IdxVec", self.vec.len(), &range);
+                &self.vec[range]
+            }
+        }
+        impl<I: SafeIndex, T> core::ops::IndexMut<$range> for IdxVec<I, T> {
+            #[inline]
+            #[track_caller]
+            fn index_mut(&mut self, range: $range) -> &mut [T] {
+                let range = crate::oob::range::<I>(I::NAME, "IdxVec", self.vec.len(), &range);
+                &mut self.vec[range]
+            }
+        }
+    )*};
+}
+range_index! {
+    core::ops::Range<I>,
+    core::ops::RangeInclusive<I>,
+    core::ops::RangeFrom<I>,
+    core::ops::RangeTo<I>,
+    core::ops::RangeToInclusive<I>,
+    core::ops::RangeFull,
+}
@@ -0,0 +1,208 @@
+//! Handles interner code generation.
+
+/// Interner code generation.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+macro_rules! interner_codegen {
+    ($t:ident,
+        $(#[$meta:meta])*
+        $interner:ident
+        $($tail:tt)*
+    ) => {
+        $(#[$meta])*
+        ///
+        /// Interns values: the first time a value is interned it gets the next free index, interning
+        /// an equal value later yields the same index.
+        #[derive(Debug, Clone)]
+        pub struct $interner<T> {
+            /// Values in index order.
+            vec: $crate::alloc::vec::Vec<T>,
+            /// Index of each value.
+            indices: $crate::__hash::HashMap<T, $t>,
+        }
+
+        impl<T> core::default::Default for $interner<T> {
+            #[inline]
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<T> $interner<T> {
+            /// Creates an empty interner.
+            #[inline]
+            pub fn new() -> Self {
+                $interner {
+                    vec: $crate::alloc::vec::Vec::new(),
+                    indices: $crate::__hash::HashMap::default(),
+                }
+            }
+
+            /// Number of values interned.
+            #[inline]
+            pub fn len(&self) -> usize {
+                self.vec.len()
+            }
+            /// True if no value was interned.
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.vec.is_empty()
+            }
+
+            /// Value of an index, `None` if the index is out of bounds.
+            #[inline]
+            pub fn try_resolve(&self, idx: $t) -> Option<&T> {
+                self.vec.get(idx.val)
+            }
+            /// Value of an index, panics if the index is out of bounds.
+            #[inline]
+            #[track_caller]
+            pub fn resolve(&self, idx: $t) -> &T {
+                &self[idx]
+            }
+            /// Values interned, as a slice in index order.
+            #[inline]
+            pub fn as_slice(&self) -> &[T] {
+                &self.vec
+            }
+
+            /// Iterator over all the indices.
+            #[inline]
+            pub fn indices(&self) ->
+                impl core::iter::DoubleEndedIterator<Item = $t>
+                + core::iter::ExactSizeIterator
+                + core::iter::FusedIterator
+            {
+                (0..self.len()).map(|val| $t { val })
+            }
+            /// Iterator over the values, in index order.
+            #[inline]
+            pub fn iter(&self) -> core::slice::Iter<'_, T> {
+                self.vec.iter()
+            }
+            /// Iterator over the index/value pairs, in index order.
+            #[inline]
+            pub fn index_iter<'a>(&'a self) ->
+                impl core::iter::DoubleEndedIterator<Item = ($t, &'a T)>
+                + core::iter::ExactSizeIterator
+                + core::iter::FusedIterator
+            where T: 'a {
+                self.vec.iter().enumerate().map(|(val, elm)| ($t { val }, elm))
+            }
+            /// Turns the interner into the vector of its values, in index order.
+            #[inline]
+            pub fn into_vec(self) -> $crate::alloc::vec::Vec<T> {
+                self.vec
+            }
+        }
+
+        impl<T: core::hash::Hash + Eq + Clone> $interner<T> {
+            /// Interns a value, yields its index.
+            ///
+            /// The index is the one the value (or an equal value) was given if it was interned
+            /// already, the next free index otherwise.
+            pub fn intern(&mut self, value: T) -> $t {
+                if let Some(idx) = self.indices.get(&value) {
+                    return *idx;
+                }
+                let idx = $t { val: self.vec.len() };
+                self.indices.insert(value.clone(), idx);
+                self.vec.push(value);
+                idx
+            }
+            /// Index of a value, `None` if it was never interned.
+            #[inline]
+            pub fn get<Q>(&self, value: &Q) -> Option<$t>
+            where
+                T: core::borrow::Borrow<Q>,
+                Q: core::hash::Hash + Eq + ?Sized,
+            {
+                self.indices.get(value).copied()
+            }
+            /// True if a value was interned.
+            #[inline]
+            pub fn contains<Q>(&self, value: &Q) -> bool
+            where
+                T: core::borrow::Borrow<Q>,
+                Q: core::hash::Hash + Eq + ?Sized,
+            {
+                self.indices.contains_key(value)
+            }
+        }
+
+        impl<T: PartialEq> PartialEq for $interner<T> {
+            /// Compares the values in index order, which determine the indices.
+            #[inline]
+            fn eq(&self, other: &Self) -> bool {
+                self.vec == other.vec
+            }
+        }
+        impl<T: Eq> Eq for $interner<T> {}
+
+        impl<T> core::ops::Index<$t> for $interner<T> {
+            type Output = T;
+            #[inline]
+            #[track_caller]
+            fn index(&self, idx: $t) -> &T {
+                match self.vec.get(idx.val) {
+                    Some(value) => value,
+                    None => $crate::oob::index(
+                        stringify!($t), idx.val, stringify!($interner), self.vec.len()
+                    ),
+                }
+            }
+        }
+        impl<'a, T> core::ops::Index<&'a $t> for $interner<T> {
+            type Output = T;
+            #[inline]
+            #[track_caller]
+            fn index(&self, idx: &'a $t) -> &T {
+                &self[*idx]
+            }
+        }
+
+        impl<'a, T> core::iter::IntoIterator for &'a $interner<T> {
+            type Item = &'a T;
+            type IntoIter = core::slice::Iter<'a, T>;
+            #[inline]
+            fn into_iter(self) -> core::slice::Iter<'a, T> {
+                self.iter()
+            }
+        }
+        impl<T: core::hash::Hash + Eq + Clone> core::iter::FromIterator<T> for $interner<T> {
+            fn from_iter<I: core::iter::IntoIterator<Item = T>>(iter: I) -> Self {
+                let mut interner = Self::new();
+                interner.extend(iter);
+                interner
+            }
+        }
+        impl<T: core::hash::Hash + Eq + Clone> core::iter::Extend<T> for $interner<T> {
+            fn extend<I: core::iter::IntoIterator<Item = T>>(&mut self, iter: I) {
+                for value in iter {
+                    self.intern(value);
+                }
+            }
+        }
+
+        $crate::handle!{ $t $($tail)* }
+    };
+}
+/// Interner code generation, without the hash maps interners rely on.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(not(any(feature = "std", feature = "hashbrown")))]
+macro_rules! interner_codegen {
+    ($t:ident,
+        $(#[$meta:meta])*
+        $interner:ident
+        $($tail:tt)*
+    ) => {
+        compile_error!(concat!(
+            "`interner: ",
+            stringify!($interner),
+            "` requires the `std` or `hashbrown` feature of safe_index",
+        ));
+        $crate::handle!{ $t $($tail)* }
+    };
+}
@@ -0,0 +1,195 @@
+//! Handles interval set code generation.
+
+/// Interval set code generation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! interval_set_codegen {
+    ($t:ident,
+        $(#[$meta:meta])*
+        $set:ident
+        $($tail:tt)*
+    ) => {
+        $(#[$meta])*
+        #[derive(Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $set {
+            /// Maps the start of each interval to its (exclusive) end.
+            ///
+            /// Intervals are non-empty, disjoint and never adjacent.
+            intervals: $crate::alloc::collections::BTreeMap<$t, $t>,
+        }
+
+        impl $set {
+            /// Creates an empty set.
+            #[inline]
+            pub const fn new() -> Self {
+                $set {
+                    intervals: $crate::alloc::collections::BTreeMap::new(),
+                }
+            }
+            /// True if the set is empty.
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.intervals.is_empty()
+            }
+            /// Number of indices in the set.
+            #[inline]
+            pub fn covered_len(&self) -> usize {
+                self.intervals.iter().map(|(start, end)| end.val - start.val).sum()
+            }
+            /// Removes all the indices.
+            #[inline]
+            pub fn clear(&mut self) {
+                self.intervals.clear()
+            }
+            /// True if the set contains an index.
+            pub fn contains(&self, idx: impl core::borrow::Borrow<$t>) -> bool {
+                let idx = idx.borrow();
+                self.intervals
+                    .range(..=idx)
+                    .next_back()
+                    .map_or(false, |(_, end)| idx < end)
+            }
+            /// Iterator over the maximal intervals of the set, in ascending order.
+            pub fn iter_ranges(&self) -> impl DoubleEndedIterator<Item = core::ops::Range<$t>>
+                + ExactSizeIterator
+                + '_
+            {
+                self.intervals.iter().map(|(start, end)| *start..*end)
+            }
+            /// Iterator over the indices of the set, in ascending order.
+            pub fn iter(&self) -> impl Iterator<Item = $t> + '_ {
+                self.intervals
+                    .iter()
+                    .flat_map(|(start, end)| (start.val..end.val).map(|val| $t { val }))
+            }
+            /// Inserts all the indices of a range, merging it with the intervals it overlaps or
+            /// touches.
+            pub fn insert_range(&mut self, range: impl Into<core::ops::Range<$t>>) {
+                let core::ops::Range { mut start, mut end } = range.into();
+                if start >= end {
+                    return;
+                }
+                if let Some((prev_start, prev_end)) = self.intervals.range(..start).next_back() {
+                    if *prev_end >= start {
+                        start = *prev_start;
+                        end = core::cmp::max(end, *prev_end);
+                    }
+                }
+                while let Some((next_start, next_end)) = self.intervals.range(start..=end).next() {
+                    let (next_start, next_end) = (*next_start, *next_end);
+                    self.intervals.remove(&next_start);
+                    end = core::cmp::max(end, next_end);
+                }
+                self.intervals.insert(start, end);
+            }
+            /// Removes all the indices of a range, splitting the intervals it cuts.
+            pub fn remove_range(&mut self, range: impl Into<core::ops::Range<$t>>) {
+                let core::ops::Range { start, end } = range.into();
+                if start >= end {
+                    return;
+                }
+                if let Some((prev_start, prev_end)) = self.intervals.range(..start).next_back() {
+                    let (prev_start, prev_end) = (*prev_start, *prev_end);
+                    if prev_end > start {
+                        self.intervals.insert(prev_start, start);
+                        if prev_end > end {
+                            self.intervals.insert(end, prev_end);
+                            return;
+                        }
+                    }
+                }
+                while let Some((next_start, next_end)) = self.intervals.range(start..end).next() {
+                    let (next_start, next_end) = (*next_start, *next_end);
+                    self.intervals.remove(&next_start);
+                    if next_end > end {
+                        self.intervals.insert(end, next_end);
+                    }
+                }
+            }
+            $crate::rand_only! {
+                /// Uniformly random index of the set, `None` if the set is empty.
+                ///
+                /// Linear in the number of intervals.
+                pub fn choose<R: $crate::rand::Rng + ?Sized>(&self, rng: &mut R) -> Option<$t> {
+                    let len = self.covered_len();
+                    if len == 0 {
+                        return None;
+                    }
+                    let mut n = rng.gen_range(0..len);
+                    for (start, end) in &self.intervals {
+                        if n < end.val - start.val {
+                            return Some($t { val: start.val + n });
+                        }
+                        n -= end.val - start.val;
+                    }
+                    unreachable!()
+                }
+                /// `k` distinct uniformly random indices of the set in ascending order, all of them
+                /// if `k >= self.covered_len()`.
+                pub fn sample<R: $crate::rand::Rng + ?Sized>(
+                    &self,
+                    rng: &mut R,
+                    k: usize,
+                ) -> $crate::alloc::vec::Vec<$t> {
+                    $crate::sample_iter(self.iter(), self.covered_len(), rng, k)
+                }
+            }
+            /// Adds all the indices of `other` to `self`.
+            pub fn union_with(&mut self, other: &Self) {
+                for range in other.iter_ranges() {
+                    self.insert_range(range)
+                }
+            }
+            /// Removes all the indices of `self` that are not in `other`.
+            pub fn intersect_with(&mut self, other: &Self) {
+                let mut res = Self::new();
+                let mut others = other.intervals.iter().peekable();
+                for (start, end) in &self.intervals {
+                    // Skip the intervals of `other` ending before this one.
+                    while others.next_if(|(_, other_end)| *other_end <= start).is_some() {}
+                    for (other_start, other_end) in others.clone() {
+                        if other_start >= end {
+                            break;
+                        }
+                        res.intervals.insert(
+                            core::cmp::max(*start, *other_start),
+                            core::cmp::min(*end, *other_end),
+                        );
+                    }
+                }
+                *self = res
+            }
+            /// Removes all the indices of `other` from `self`.
+            pub fn difference_with(&mut self, other: &Self) {
+                for range in other.iter_ranges() {
+                    self.remove_range(range)
+                }
+            }
+        }
+
+        impl core::fmt::Debug for $set {
+            /// Formats the set as `{0..3, 7..9}`.
+            fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+                fmt.debug_set()
+                    .entries(self.intervals.iter().map(|(start, end)| start.val..end.val))
+                    .finish()
+            }
+        }
+        impl<R: Into<core::ops::Range<$t>>> core::iter::FromIterator<R> for $set {
+            fn from_iter<I: core::iter::IntoIterator<Item = R>>(iter: I) -> Self {
+                let mut set = Self::new();
+                set.extend(iter);
+                set
+            }
+        }
+        impl<R: Into<core::ops::Range<$t>>> core::iter::Extend<R> for $set {
+            fn extend<I: core::iter::IntoIterator<Item = R>>(&mut self, iter: I) {
+                for range in iter {
+                    self.insert_range(range)
+                }
+            }
+        }
+
+        $crate::handle!{ $t $($tail)* }
+    };
+}
@@ -31,9 +31,151 @@
 //! add these:
 //!
 //! - `map <Map>`: creates a wrapper named `<Map>` around a vector, indexed by `Idx`.
-//! - `btree set <Set>`: alias type for a binary tree set of `Idx`s.
+//! - `map <Map> of <Elem>`: creates a map named `<Map>` from `Idx` to `<Elem>`s, without a type
+//!   parameter; it dereferences to the generic [`IdxVec`] and converts to and from it.
+//! - `small map <Map>[N]`: same as `map`, but stores up to `N` elements inline without allocating;
+//!   requires the `smallvec` feature.
+//! - `array map <Map>[N]`: creates a map named `<Map>` storing at most `N` elements in an array,
+//!   which never allocates and only relies on `core`.
+//! - `slice <Slice>`: creates a wrapper named `<Slice>` around a slice, indexed by `Idx`. Built
+//!   from the elements of the `map` of the same invocation with `<Slice>::from_map`, or from any
+//!   slice with `<Slice>::from_slice` in non-strict mode.
+//! - `range <Range>`: creates a range type named `<Range>` iterating over `Idx`s, built with
+//!   `<Range>::new(start, end)` or `start.up_to(end)`; can only appear once since it adds methods
+//!   to `Idx`.
+//! - `heap <Heap>`: creates a priority queue named `<Heap>` of `Idx`s, with updatable priorities.
+//! - `union find <Partition>`: creates a union-find structure named `<Partition>`, a partition of
+//!   `Idx`s into disjoint sets that can be merged, and queried with path compression; its
+//!   `new_like` constructor takes the `map` of the same invocation.
+//! - `interner <Interner>`: creates a structure named `<Interner>` giving an `Idx` to each distinct
+//!   value it is given, and the value of each `Idx`; requires the `std` or `hashbrown` feature.
+//! - `sparse map <Map>`: creates a map named `<Map>` where removing an element leaves a hole
+//!   instead of shifting the following indices, holes are squeezed out on demand.
+//! - `deque map <Map>`: creates a double-ended queue named `<Map>`, elements are pushed at the back
+//!   with increasing indices and evicted from the front without changing the other indices.
+//! - `shared map <Map>`: creates a read-only map named `<Map>` behind an `Arc`, whose clones share
+//!   the elements, for sharing a map between threads once built. With a `map`, maps have an
+//!   `into_shared` method and convert to shared maps.
+//! - `slot map <Map>`: creates a map named `<Map>` whose elements are accessed through
+//!   [`SlotHandle`]s, which detect accesses to removed elements.
+//! - `counter <Counter>`: creates a generator of fresh `Idx`s named `<Counter>`, for indices
+//!   allocated before any collection exists; it is a legal source of indices in strict mode.
+//! - `atomic counter <Counter>`: same as `counter`, but thread-safe and usable in `static`s.
+//! - `btree set <Set>`: alias type for a binary tree set of `Idx`s; with the `rand` feature,
+//!   `ChooseIndex` picks random members.
+//! - `btree set struct <Set>`: creates a wrapper named `<Set>` around a binary tree set of `Idx`s,
+//!   which unlike the alias can have inherent methods and foreign trait implementations.
 //! - `btree map <Map>`: alias type for a binary tree map from `Idx` to something.
+//! - `btree map struct <Map>`: creates a wrapper named `<Map>` around a binary tree map from `Idx`
+//!   to something, with the same method names as `map` (`index_iter`, `last_index`...).
+//! - `bit set <Set>`: creates a growable bit set named `<Set>`, compact and fast for dense sets of
+//!   `Idx`s.
+//! - `interval set <Set>`: creates a set named `<Set>` storing `Idx`s as disjoint, coalesced
+//!   ranges.
+//! - `hash set <Set>`: alias type for a hash set of `Idx`s, with an optional hasher parameter;
+//!   requires the `std` or `hashbrown` feature. With the `rand` feature, `ChooseIndex` picks
+//!   random members.
+//! - `hash map <Map>`: alias type for a hash map from `Idx` to something, with an optional hasher
+//!   parameter; requires the `std` or `hashbrown` feature.
+//! - `bounded <Bounded>[N]`: creates an index type named `<Bounded>` for the indices smaller than
+//!   `N`, validated once by `<Bounded>::try_from_idx` and converting to `Idx` for free. With `with
+//!   map: <Map>`, also creates a map named `<Map>` holding exactly `N` elements, that `<Bounded>`s
+//!   index without bound checks. In strict mode, bounded indices only come from `Idx`s, so
+//!   `<Bounded>::try_new`, `<Bounded>::all` and the map's `from_fn` and `index_iter` are not
+//!   available.
+//! - `base: 1`: not a type, adds `to_external` and `from_external` methods to `Idx` converting
+//!   it to and from a one-based index, for file formats and tools counting from one. Internal
+//!   storage and addressing stay zero-based, so the offset lives in these two methods only.
+//! - `full: <Prefix>`: not a type, shorthand for `map: <Prefix>Map`, `btree set: <Prefix>BSet`,
+//!   `btree map: <Prefix>BMap` and `range: <Prefix>Range`, the options most index families need;
+//!   must be the first option and requires the `macros` feature, like the `full` argument of the
+//!   `index` attribute.
 //!
+//! Attributes written before the index type or an option, doc comments included, are forwarded to
+//! the type it defines. Generated structures can thus have more derives, `cfg_attr`s or
+//! `#[non_exhaustive]`, as long as they do not clash with the traits they already implement; the
+//! options defining aliases (`btree set`, `hash map`...) only accept the attributes type aliases
+//! accept.
+//!
+//! The `cfg`s of an option are the exception: they apply to everything the option generates,
+//! including its implementations, the methods it adds to the index type (such as `up_to` for a
+//! `range`) and the code shared with other options, so `#[cfg(feature = "dot")] map: DotIds` only
+//! exists when your crate's `dot` feature is active. `cfg_attr`s go to the type like other
+//! attributes.
+//!
+//! Index types always implement `Debug`, `Clone`, `Copy`, `PartialEq`, `Eq`, `PartialOrd`, `Ord`
+//! and `Hash`, and `Default` unless the `strict` feature is active (see `petgraph` below):
+//! deriving one of them again is a compile error naming the trait. Other derives, such as serde's
+//! or your own, are fine and see the private `usize` field of the index type like any derive on a
+//! struct would.
+//!
+//! Maps migrate from and to other typed index crates without copying. With the `index_vec`
+//! feature, index types implement `index_vec::Idx` and maps convert from and to
+//! `IndexVec<Client, T>`; like `petgraph`, this is an explicit opt-in to the `Idx::from_usize`
+//! constructor in strict mode. With the `typed-index-collections` feature, maps convert to
+//! `TiVec<Client, T>`, and from it unless the `strict` feature is active as for vectors.
+//!
+//! With the `num-traits` feature, index types implement `Zero`, `One`, `Bounded`, `CheckedAdd`,
+//! `CheckedSub`, `SaturatingAdd` and `SaturatingSub` from num-traits, as well as the `Sub` and
+//! `Mul` operators these traits require, all behaving like the same operations on `usize`. They
+//! all create indices, so they are not available in strict mode.
+//!
+//! With the `zerocopy` feature, index types are `#[repr(transparent)]` wrappers around a `usize`
+//! deriving zerocopy's `FromBytes`, `IntoBytes`, `Immutable` and `KnownLayout`, and slices of
+//! maps over zerocopy elements are viewed from and as raw bytes with `from_bytes` and
+//! `as_bytes`. Index types cannot be `Unaligned` since `usize` is not. Reading an index from
+//! bytes creates it, so this is an explicit opt-in in strict mode; `from_bytes` still requires
+//! non-strict mode like `from_slice`. The derives refer to `::safe_index::zerocopy`, so safe_index
+//! must be a dependency named `safe_index`.
+//!
+//! safe_index is `no_std` and so is the code `new` generates, which only relies on `core` and
+//! `alloc`: it works in `#![no_std]` crates with an allocator. Only the `hash set`, `hash map` and
+//! `interner` options, and the conversions between maps and hash maps, require the `std` feature or
+//! the `hashbrown` one. They use std's `HashSet` and `HashMap` with the `std` feature, and hashbrown's
+//! with its default hasher otherwise: std wins when both features are active, so that enabling
+//! `hashbrown` never changes the types of an `std` build. The optional hasher parameter works the
+//! same either way, so the same `new` invocation compiles with or without `std`.
+//!
+//! With the `petgraph` feature, index types implement petgraph's `IndexType`, so that
+//! `Graph<N, E, Directed, Client>` uses `Client`s as node indices directly, and its node indices
+//! convert back to `Client`s with `Client::from`. Edge indices do not: they index edges, not
+//! clients, `edge.index()` yields their `usize`. `IndexType::new` creates indices from any `usize`
+//! and `IndexType` requires `Default`: activating `petgraph` is an explicit opt-in to both,
+//! including in strict mode.
+//!
+//! With the `borsh` feature, index types and `map`s implement borsh's `BorshSerialize`, and
+//! `BorshDeserialize` unless the `strict` feature is active. They use borsh's canonical encoding of
+//! `usize` and `Vec`: a little-endian `u64` for indices, and a little-endian `u32` length followed
+//! by the elements for maps.
+//!
+//! With the `schemars` feature, index types and `map`s implement schemars' `JsonSchema`: indices
+//! are non-negative integers and maps are arrays of their elements. Schema names include the name
+//! of the types, such as `Client` or `Clients_of_string`.
+//!
+//! A `map` stores a value for every index and is the fastest option when most indices have one.
+//! When only a few indices carry a value, a `btree map` or a `hash map` saves memory: prefer the
+//! former when iterating in index order matters, the latter for faster lookups.
+//!
+//! Tables indexed by two index families, say clients and files, are handled by [`matrix`] which
+//! creates a dense matrix indexed by `(Client, File)` pairs, and by [`relation`] which creates a
+//! many-to-many relation between clients and files. Both families and the tables between them can
+//! be declared in a single invocation of [`new`].
+//!
+//! Each `new` invocation generates its own index type and collections. When there are many index
+//! families, declaring them with `tagged <Idx>: <Tag>` instead of `<Idx>` makes `<Idx>` an alias of
+//! the generic [`tagged::Idx`] over a new tag type `<Tag>`: all families then share one
+//! implementation, see the [`tagged`] module.
+//!
+//! All index types implement [`SafeIndex`] and maps implement [`SafeMap`], which lets generic code
+//! handle any index family, see [`examples::clients::position`]. [`IdxVec`] is a map over any index
+//! type: unlike the `map` option, it is implemented once for all index families.
+//!
+//! With the `macros` feature, the [`index`] attribute is an alternative to `new` that checks its
+//! options and reports misuses on the faulty option: `#[safe_index::index(map = "Clients",
+//! btree_set = "ClientSet")] pub struct Client;` is the same as `new!{ Client, map: Clients, btree
+//! set: ClientSet }`. Its `full` argument derives conventional names from the index type:
+//! `#[safe_index::index(full)] pub struct Client;` generates `ClientMap`, `ClientBSet`,
+//! `ClientBMap` and `ClientRange`.
 //!
 //! See the [`examples` module] and the example below for illustrations of the `new` macro.
 //!
@@ -124,42 +266,45 @@
 //! }
 //! ```
 //!
+//! Keeping the files of the clients and the clients of the files in sync by hand is error-prone
+//! though, so the actual example stores this many-to-many relation in a single [`relation`].
+//!
 //! The full code is available [here][clients src], and you can see it used in the documentation of
 //! [`examples::clients`]. Here are a few functions on `Data` to (hopefully) show that `Client` and
 //! `File` behave as (and in fact are) `usize` indexes.
 //!
 //! ```rust
-//! # use safe_index::examples::clients::{idx::*, ClientInfo, FileInfo};
+//! # use safe_index::examples::clients::{idx::*, ClientFiles, ClientInfo, FileInfo};
 //! /// Aggregates clients and files info.
 //! pub struct Data {
 //!     /// Map from client indexes to client information.
 //!     pub clients: Clients<ClientInfo>,
 //!     /// Map from file indexes to file information.
 //!     pub files: Files<FileInfo>,
+//!     /// Relates clients and the files about them.
+//!     pub links: ClientFiles,
 //! }
 //! impl Data {
-//!     /// Adds a file, updates the clients concerned.
-//!     pub fn add_file(&mut self, file: FileInfo) -> File {
+//!     /// Adds a file about some clients.
+//!     pub fn add_file<I>(&mut self, file: FileInfo, clients: I) -> File
+//!     where
+//!         I: IntoIterator<Item = Client>,
+//!     {
 //!         let idx = self.files.push(file);
-//!         let file = &self.files[idx];
-//!         for client in &file.clients {
-//!             let is_new = self.clients[*client].files.insert(idx);
-//!             debug_assert! { is_new }
-//!         }
+//!         self.links
+//!             .insert_all(clients.into_iter().map(|client| (client, idx)));
 //!         idx
 //!     }
 //!
-//!     /// Adds a client to a file.
-//!     pub fn add_client_to_file(&mut self, client: Client, file: File) {
-//!         let is_new = self.files[file].clients.insert(client);
-//!         debug_assert! { is_new }
-//!         let is_new = self.clients[client].files.insert(file);
-//!         debug_assert! { is_new }
+//!     /// Adds a client to a file, false if the file was already about the client.
+//!     pub fn add_client_to_file(&mut self, client: Client, file: File) -> bool {
+//!         self.links.insert(client, file)
 //!     }
 //! }
 //! ```
 //!
 //! [`new`]: ../../macro.new.html (new macro)
+//! [`index`]: attr.index.html (index attribute)
 //! [`examples` module]: examples/index.html (safe_index examples)
 //! [`examples::clients`]: examples/clients/index.html (clients example)
 //! [clients src]: examples/clients.rs.html (Code of the clients example)
@@ -168,8 +313,101 @@
 #![no_std]
 
 pub extern crate alloc;
+#[cfg(feature = "std")]
+pub extern crate std;
+
+#[cfg(feature = "rayon")]
+pub extern crate rayon;
+
+#[cfg(feature = "serde")]
+pub extern crate serde;
+
+#[cfg(feature = "rand")]
+pub extern crate rand;
+
+#[cfg(feature = "smallvec")]
+pub extern crate smallvec;
+
+#[cfg(feature = "petgraph")]
+pub extern crate petgraph;
+
+#[cfg(feature = "borsh")]
+pub extern crate borsh;
+
+#[cfg(feature = "schemars")]
+pub extern crate schemars;
+
+#[cfg(feature = "index_vec")]
+pub extern crate index_vec;
+
+#[cfg(feature = "typed-index-collections")]
+pub extern crate typed_index_collections;
+
+#[cfg(feature = "num-traits")]
+pub extern crate num_traits;
+
+#[cfg(feature = "zerocopy")]
+pub extern crate zerocopy;
+
+#[cfg(feature = "hashbrown")]
+pub extern crate hashbrown;
+
+/// Hash collections behind the `hash set` and `hash map` options: std's if the `std` feature is
+/// active, hashbrown's otherwise.
+#[doc(hidden)]
+#[cfg(feature = "std")]
+pub mod __hash {
+    pub use std::collections::hash_map::RandomState as DefaultHashBuilder;
+    pub use std::collections::{HashMap, HashSet};
+}
+/// Hash collections behind the `hash set` and `hash map` options: std's if the `std` feature is
+/// active, hashbrown's otherwise.
+#[doc(hidden)]
+#[cfg(all(feature = "hashbrown", not(feature = "std")))]
+pub mod __hash {
+    pub use hashbrown::{DefaultHashBuilder, HashMap, HashSet};
+}
 
+// Lets the code generated by the `index` attribute, by `full` and by the zerocopy derives refer to
+// `::safe_index` inside this crate.
+#[cfg(any(feature = "macros", feature = "zerocopy"))]
+extern crate self as safe_index;
+#[doc(hidden)]
+#[cfg(feature = "macros")]
+pub use safe_index_macros::__new_full;
+/// Attribute alternative to [`new`], requires the `macros` feature.
+#[cfg(feature = "macros")]
+pub use safe_index_macros::index;
+
+mod array_map;
+mod bit_set;
+mod bounded;
+mod btree_map;
+mod counter;
+mod deque_map;
+mod heap;
+mod idx_vec;
+mod interner;
+mod interval_set;
 mod map;
+mod matrix;
+#[doc(hidden)]
+pub mod oob;
+mod range;
+mod relation;
+mod set;
+mod shared_map;
+mod slice;
+mod slot_map;
+mod small_map;
+mod sparse_map;
+pub mod tagged;
+mod union_find;
+
+pub use bounded::Below;
+pub use idx_vec::IdxVec;
+#[doc(hidden)]
+pub use map::ExtractIf;
 
 /// Discards its input if the `strict` feature is active.
 #[macro_export]
@@ -200,6 +438,557 @@ macro_rules! non_strict {
     };
 }
 
+/// Discards its input if the `std` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(feature = "std")]
+macro_rules! std_only {
+    ( $($stuff:tt)* ) => {
+        $($stuff)*
+    };
+}
+/// Discards its input if the `std` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(not(feature = "std"))]
+macro_rules! std_only {
+    ( $($stuff:tt)* ) => {};
+}
+
+/// Discards its input if neither the `std` nor the `hashbrown` feature is active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+macro_rules! hash_only {
+    ( $($stuff:tt)* ) => {
+        $($stuff)*
+    };
+}
+/// Discards its input if neither the `std` nor the `hashbrown` feature is active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(not(any(feature = "std", feature = "hashbrown")))]
+macro_rules! hash_only {
+    ( $($stuff:tt)* ) => {};
+}
+
+/// Discards its input if the `rayon` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(feature = "rayon")]
+macro_rules! rayon_only {
+    ( $($stuff:tt)* ) => {
+        $($stuff)*
+    };
+}
+/// Discards its input if the `rayon` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(not(feature = "rayon"))]
+macro_rules! rayon_only {
+    ( $($stuff:tt)* ) => {};
+}
+
+/// Discards its input if the `serde` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(feature = "serde")]
+macro_rules! serde_only {
+    ( $($stuff:tt)* ) => {
+        $($stuff)*
+    };
+}
+/// Discards its input if the `serde` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(not(feature = "serde"))]
+macro_rules! serde_only {
+    ( $($stuff:tt)* ) => {};
+}
+
+/// Discards its input if the `rand` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(feature = "rand")]
+macro_rules! rand_only {
+    ( $($stuff:tt)* ) => {
+        $($stuff)*
+    };
+}
+/// Discards its input if the `rand` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(not(feature = "rand"))]
+macro_rules! rand_only {
+    ( $($stuff:tt)* ) => {};
+}
+
+/// Discards its input if the `petgraph` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(feature = "petgraph")]
+macro_rules! petgraph_only {
+    ( $($stuff:tt)* ) => {
+        $($stuff)*
+    };
+}
+/// Discards its input if the `petgraph` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(not(feature = "petgraph"))]
+macro_rules! petgraph_only {
+    ( $($stuff:tt)* ) => {};
+}
+
+/// Discards its input if the `borsh` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(feature = "borsh")]
+macro_rules! borsh_only {
+    ( $($stuff:tt)* ) => {
+        $($stuff)*
+    };
+}
+/// Discards its input if the `borsh` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(not(feature = "borsh"))]
+macro_rules! borsh_only {
+    ( $($stuff:tt)* ) => {};
+}
+
+/// Discards its input if the `schemars` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(feature = "schemars")]
+macro_rules! schemars_only {
+    ( $($stuff:tt)* ) => {
+        $($stuff)*
+    };
+}
+/// Discards its input if the `schemars` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(not(feature = "schemars"))]
+macro_rules! schemars_only {
+    ( $($stuff:tt)* ) => {};
+}
+
+/// Discards its input if the `index_vec` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(feature = "index_vec")]
+macro_rules! index_vec_only {
+    ( $($stuff:tt)* ) => {
+        $($stuff)*
+    };
+}
+/// Discards its input if the `index_vec` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(not(feature = "index_vec"))]
+macro_rules! index_vec_only {
+    ( $($stuff:tt)* ) => {};
+}
+
+/// Discards its input if the `typed-index-collections` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(feature = "typed-index-collections")]
+macro_rules! typed_index_collections_only {
+    ( $($stuff:tt)* ) => {
+        $($stuff)*
+    };
+}
+/// Discards its input if the `typed-index-collections` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(not(feature = "typed-index-collections"))]
+macro_rules! typed_index_collections_only {
+    ( $($stuff:tt)* ) => {};
+}
+
+/// Discards its input if the `num-traits` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(feature = "num-traits")]
+macro_rules! num_traits_only {
+    ( $($stuff:tt)* ) => {
+        $($stuff)*
+    };
+}
+/// Discards its input if the `num-traits` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(not(feature = "num-traits"))]
+macro_rules! num_traits_only {
+    ( $($stuff:tt)* ) => {};
+}
+
+/// Derives zerocopy's traits on the index struct it wraps if the `zerocopy` feature is active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(feature = "zerocopy")]
+macro_rules! index_struct {
+    ( $($item:tt)* ) => {
+        #[derive(
+            $crate::zerocopy::FromBytes,
+            $crate::zerocopy::IntoBytes,
+            $crate::zerocopy::Immutable,
+            $crate::zerocopy::KnownLayout,
+        )]
+        #[zerocopy(crate = "::safe_index::zerocopy")]
+        $($item)*
+    };
+}
+/// Derives zerocopy's traits on the index struct it wraps if the `zerocopy` feature is active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(not(feature = "zerocopy"))]
+macro_rules! index_struct {
+    ( $($item:tt)* ) => {
+        $($item)*
+    };
+}
+
+/// Discards its input if the `zerocopy` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(feature = "zerocopy")]
+macro_rules! zerocopy_only {
+    ( $($stuff:tt)* ) => {
+        $($stuff)*
+    };
+}
+/// Discards its input if the `zerocopy` feature is not active.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(not(feature = "zerocopy"))]
+macro_rules! zerocopy_only {
+    ( $($stuff:tt)* ) => {};
+}
+
+/// Discards its input if the `strict` feature is active, unless the `petgraph` one is too:
+/// petgraph's `IndexType` requires `Default`.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(any(not(feature = "strict"), feature = "petgraph"))]
+macro_rules! default_idx {
+    ( $($stuff:tt)* ) => {
+        $($stuff)*
+    };
+}
+/// Discards its input if the `strict` feature is active, unless the `petgraph` one is too:
+/// petgraph's `IndexType` requires `Default`.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(all(feature = "strict", not(feature = "petgraph")))]
+macro_rules! default_idx {
+    ( $($stuff:tt)* ) => {};
+}
+
+/// Error produced when building a dense map from a collection missing some index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MissingIndex<Idx> {
+    /// First index missing.
+    pub idx: Idx,
+}
+impl<Idx: core::fmt::Display> core::fmt::Display for MissingIndex<Idx> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(fmt, "missing index {}", self.idx)
+    }
+}
+#[cfg(feature = "std")]
+impl<Idx: core::fmt::Debug + core::fmt::Display> std::error::Error for MissingIndex<Idx> {}
+
+/// Error produced when an index is not the one expected, typically when extending a dense map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct UnexpectedIndex<Idx> {
+    /// Index expected.
+    pub expected: Idx,
+    /// Index found.
+    pub found: Idx,
+}
+impl<Idx: core::fmt::Display> core::fmt::Display for UnexpectedIndex<Idx> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            fmt,
+            "expected index {}, found {}",
+            self.expected, self.found
+        )
+    }
+}
+#[cfg(feature = "std")]
+impl<Idx: core::fmt::Debug + core::fmt::Display> std::error::Error for UnexpectedIndex<Idx> {}
+
+/// Error produced when building a range whose start is greater than its end.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InvalidRange<Idx> {
+    /// Start of the range.
+    pub start: Idx,
+    /// End of the range.
+    pub end: Idx,
+}
+impl<Idx: core::fmt::Display> core::fmt::Display for InvalidRange<Idx> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(
+            fmt,
+            "invalid range {}..{}, start is greater than end",
+            self.start, self.end
+        )
+    }
+}
+#[cfg(feature = "std")]
+impl<Idx: core::fmt::Debug + core::fmt::Display> std::error::Error for InvalidRange<Idx> {}
+
+/// Bounds of a range, turned into an empty range if they are inverted.
+///
+/// Used for btree range queries, since `BTreeMap::range` and `BTreeSet::range` panic on inverted
+/// bounds.
+#[doc(hidden)]
+pub fn non_inverted_bounds<Idx: Ord + Copy>(
+    range: &impl core::ops::RangeBounds<Idx>,
+) -> (core::ops::Bound<Idx>, core::ops::Bound<Idx>) {
+    use core::ops::Bound::*;
+    let (start, end) = (range.start_bound().cloned(), range.end_bound().cloned());
+    match (start, end) {
+        (Included(s), Included(e)) | (Included(s), Excluded(e)) | (Excluded(s), Included(e))
+            if s > e =>
+        {
+            (Included(s), Excluded(s))
+        }
+        (Excluded(s), Excluded(e)) if s >= e => (Included(s), Excluded(s)),
+        bounds => bounds,
+    }
+}
+
+/// Picks the elements of an iterator at `k` distinct random positions below `len`, in iteration
+/// order.
+///
+/// Picks all of them if `k >= len`.
+#[cfg(feature = "rand")]
+#[doc(hidden)]
+pub fn sample_iter<I: Iterator, R: rand::Rng + ?Sized>(
+    iter: I,
+    len: usize,
+    rng: &mut R,
+    k: usize,
+) -> alloc::vec::Vec<I::Item> {
+    let mut positions = rand::seq::index::sample(rng, len, core::cmp::min(k, len)).into_vec();
+    positions.sort_unstable();
+    let mut positions = positions.into_iter().peekable();
+    iter.enumerate()
+        .filter(|(pos, _)| positions.next_if_eq(pos).is_some())
+        .map(|(_, elem)| elem)
+        .take(core::cmp::min(k, len))
+        .collect()
+}
+
+/// Random members of the `btree set` and `hash set` aliases, requires the `rand` feature.
+///
+/// The aliases are std (or hashbrown) types and cannot have inherent methods, the newtype sets
+/// have `choose` and `sample` directly. Neither set has random access: both methods are linear in
+/// the size of the set.
+#[cfg(feature = "rand")]
+pub trait ChooseIndex {
+    /// Type of the indices of the set.
+    type Idx;
+    /// Uniformly random index of the set, `None` if the set is empty.
+    fn choose<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Option<Self::Idx>;
+    /// `k` distinct uniformly random indices of the set in iteration order, all of them if
+    /// `k >= self.len()`.
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R, k: usize) -> alloc::vec::Vec<Self::Idx>;
+}
+#[cfg(feature = "rand")]
+impl<I: SafeIndex> ChooseIndex for alloc::collections::BTreeSet<I> {
+    type Idx = I;
+    fn choose<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Option<I> {
+        if self.is_empty() {
+            None
+        } else {
+            self.iter().nth(rng.gen_range(0..self.len())).copied()
+        }
+    }
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R, k: usize) -> alloc::vec::Vec<I> {
+        sample_iter(self.iter().copied(), self.len(), rng, k)
+    }
+}
+#[cfg(all(feature = "rand", any(feature = "std", feature = "hashbrown")))]
+impl<I: SafeIndex, S> ChooseIndex for __hash::HashSet<I, S> {
+    type Idx = I;
+    fn choose<R: rand::Rng + ?Sized>(&self, rng: &mut R) -> Option<I> {
+        if self.is_empty() {
+            None
+        } else {
+            self.iter().nth(rng.gen_range(0..self.len())).copied()
+        }
+    }
+    fn sample<R: rand::Rng + ?Sized>(&self, rng: &mut R, k: usize) -> alloc::vec::Vec<I> {
+        sample_iter(self.iter().copied(), self.len(), rng, k)
+    }
+}
+
+/// Wraps a [`usize`], only used by collections on legal indices.
+#[inline]
+pub(crate) fn idx_of<I: SafeIndex>(val: usize) -> I {
+    assert_eq! { core::mem::size_of::<I>(), core::mem::size_of::<usize>() }
+    // Sound: implementors of `SafeIndex` are `#[repr(transparent)]` over a `usize`.
+    unsafe { core::mem::transmute_copy::<usize, I>(&val) }
+}
+
+/// Implemented by all the index types generated by [`new`], for code generic over index families.
+///
+/// Only `as_usize` is available in strict mode: `ZERO` and `from_usize` create indices out of thin
+/// air, which is what strict mode forbids.
+///
+/// # Safety
+///
+/// Implementors are `#[repr(transparent)]` wrappers around the `usize` `as_usize` yields: generic
+/// collections such as [`IdxVec`] create indices from their `usize` directly. [`new`] implements
+/// this trait for the index types it generates, other implementations are neither needed nor
+/// supported.
+pub unsafe trait SafeIndex:
+    Copy + Ord + core::hash::Hash + core::fmt::Debug + core::fmt::Display + Into<usize>
+{
+    /// Name of the index type, for error messages.
+    #[doc(hidden)]
+    const NAME: &'static str;
+    /// Index zero.
+    #[cfg(not(feature = "strict"))]
+    const ZERO: Self;
+    /// Wraps a [`usize`].
+    #[cfg(not(feature = "strict"))]
+    fn from_usize(val: usize) -> Self;
+    /// Underlying [`usize`].
+    fn as_usize(&self) -> usize;
+}
+
+/// Implemented by the `map` and `small map` collections and by [`tagged::Map`], for code generic
+/// over maps.
+pub trait SafeMap {
+    /// Index type of the map.
+    type Idx: SafeIndex;
+    /// Type of the elements of the map.
+    type Elem;
+    /// Number of elements in the map.
+    fn len(&self) -> usize;
+    /// True if the map is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    /// Pushes an element, yields its index.
+    fn push(&mut self, elem: Self::Elem) -> Self::Idx;
+    /// Retrieves an entry in the map.
+    fn get(&self, idx: Self::Idx) -> Option<&Self::Elem>;
+    /// Retrieves an entry in the map.
+    fn get_mut(&mut self, idx: Self::Idx) -> Option<&mut Self::Elem>;
+    /// Generates an index from a [`usize`] when it is a legal index.
+    fn index_from_usize(&self, n: usize) -> Option<Self::Idx>;
+}
+
+/// Bulk insertion of a range of indices, implemented by btree sets of indices.
+pub trait InsertRange<Range> {
+    /// Inserts all the indices of a range.
+    fn insert_range(&mut self, range: Range);
+}
+
+/// Iterator over every `step`-th index of a range, see `<Range>::step_by_typed`.
+///
+/// Unlike [`core::iter::StepBy`], this type is `Copy` and iterates from the back in constant time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SteppedRange<R> {
+    range: R,
+    step: usize,
+}
+impl<R> SteppedRange<R> {
+    /// Constructor, panics if `step` is zero.
+    #[doc(hidden)]
+    #[track_caller]
+    pub fn new(range: R, step: usize) -> Self {
+        assert!(step != 0, "range step must be non-zero");
+        Self { range, step }
+    }
+    /// Step of the iterator.
+    #[inline]
+    pub fn step(&self) -> usize {
+        self.step
+    }
+}
+impl<R: Iterator> Iterator for SteppedRange<R> {
+    type Item = R::Item;
+    #[inline]
+    fn next(&mut self) -> Option<R::Item> {
+        self.nth(0)
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let steps = |len: usize| {
+            if len == 0 {
+                0
+            } else {
+                (len - 1) / self.step + 1
+            }
+        };
+        let (lo, hi) = self.range.size_hint();
+        (steps(lo), hi.map(steps))
+    }
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<R::Item> {
+        let res = self.range.nth(n.saturating_mul(self.step))?;
+        // Skip the elements between `res` and the next one.
+        if self.step > 1 {
+            self.range.nth(self.step - 2);
+        }
+        Some(res)
+    }
+}
+impl<R: DoubleEndedIterator + ExactSizeIterator> DoubleEndedIterator for SteppedRange<R> {
+    #[inline]
+    fn next_back(&mut self) -> Option<R::Item> {
+        let len = self.range.len();
+        if len == 0 {
+            None
+        } else {
+            self.range.nth_back((len - 1) % self.step)
+        }
+    }
+}
+impl<R: ExactSizeIterator> ExactSizeIterator for SteppedRange<R> {}
+impl<R: core::iter::FusedIterator> core::iter::FusedIterator for SteppedRange<R> {}
+
+/// Handle of an element of a slot map: the index of its slot and the generation of the slot when
+/// the element was inserted.
+///
+/// A handle is stale once its element is removed, even if the slot is reused later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SlotHandle<Idx> {
+    idx: Idx,
+    generation: u32,
+}
+impl<Idx: Copy> SlotHandle<Idx> {
+    /// Constructor.
+    #[doc(hidden)]
+    #[inline]
+    pub const fn new(idx: Idx, generation: u32) -> Self {
+        Self { idx, generation }
+    }
+    /// Index of the slot of the handle.
+    #[inline]
+    pub const fn index(&self) -> Idx {
+        self.idx
+    }
+    /// Generation of the slot of the handle.
+    #[inline]
+    pub const fn generation(&self) -> u32 {
+        self.generation
+    }
+}
+impl<Idx: core::fmt::Display> core::fmt::Display for SlotHandle<Idx> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(fmt, "{}@{}", self.idx, self.generation)
+    }
+}
+
 /// Generates an alias type for [`alloc::collections::BTreeSet`] of indices.
 #[macro_export]
 #[doc(hidden)]
@@ -214,80 +1003,994 @@ macro_rules! btree_set_codegen {
     };
 }
 
-/// Generates an alias type for [`alloc::collections::BTreeMap`] of indices.
+/// Generates an alias type for a hash set of indices, std's or hashbrown's.
 #[macro_export]
 #[doc(hidden)]
-macro_rules! btree_map_codegen {
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+macro_rules! hash_set_codegen {
     { $t:ident,
         $(#[$meta:meta])*
-        $map:ident $($tail:tt)*
+        $set:ident $($tail:tt)*
     } => {
         $(#[$meta])*
-        pub type $map<T> = $crate::alloc::collections::BTreeMap<$t, T> ;
+        pub type $set<S = $crate::__hash::DefaultHashBuilder> =
+            $crate::__hash::HashSet<$t, S>;
         $crate::handle!{ $t $($tail)* }
     };
 }
-
-/// Handles some user input and decides what to do.
+/// Generates an alias type for a hash set of indices, std's or hashbrown's.
 #[macro_export]
 #[doc(hidden)]
-macro_rules! handle {
-    { $t:ident, $(#[$meta:meta])* btree set: $($tail:tt)* } => {
-        $crate::btree_set_codegen! { $t, $(#[$meta])* $($tail)* }
-    };
-    { $t:ident, $(#[$meta:meta])* btree map: $($tail:tt)* } => {
-        $crate::btree_map_codegen! { $t, $(#[$meta])* $($tail)* }
-    };
-    { $t:ident, $(#[$meta:meta])* map: $($tail:tt)* } => {
-        $crate::map_codegen! { $t, $(#[$meta])* $($tail)* }
-    };
-    { $t:ident $(,)? } => {};
-
-    { $t:ident with iter: $iter:ident $($tail:tt)* } => {
-        compile_error!(concat!(
-            "maps do not have dedicated iterator structures anymore, remove `with iter: ",
-            stringify!($iter),
-            "` from your input",
-        ));
-    };
-    { $t:ident, range: $range:ident $($tail:tt)* } => {
-        compile_error!(concat!(
-            "`range` does not exist anymore, use `..` and `..=` operators instead and remove `range: ",
-            stringify!($range),
-            "` from your input",
-        ));
-    };
-    { $t:ident, $token:tt $($tail:tt)* } => {
-        compile_error!(concat!(
-            "expected `btree set`, `btree map` or `map` but found unexpected token `",
-            stringify!($token),
-            "`",
-        ));
-    };
-    { $t:ident $token:tt $($tail:tt)* } => {
+#[cfg(not(any(feature = "std", feature = "hashbrown")))]
+macro_rules! hash_set_codegen {
+    { $t:ident,
+        $(#[$meta:meta])*
+        $set:ident $($tail:tt)*
+    } => {
+        $(#[$meta])*
         compile_error!(concat!(
-            "expected comma, found unexpected token `",
-            stringify!($token),
-            "`",
+            "`hash set: ",
+            stringify!($set),
+            "` requires the `std` or `hashbrown` feature of safe_index",
         ));
+        $crate::handle!{ $t $($tail)* }
     };
 }
 
-/// Wraps a `usize` into a struct (zero-cost). Also generates the relevant collections indexed by
+/// Generates an alias type for a hash map from indices, std's or hashbrown's.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+macro_rules! hash_map_codegen {
+    { $t:ident,
+        $(#[$meta:meta])*
+        $map:ident $($tail:tt)*
+    } => {
+        $(#[$meta])*
+        pub type $map<T, S = $crate::__hash::DefaultHashBuilder> =
+            $crate::__hash::HashMap<$t, T, S>;
+        $crate::handle!{ $t $($tail)* }
+    };
+}
+/// Generates an alias type for a hash map from indices, std's or hashbrown's.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(not(any(feature = "std", feature = "hashbrown")))]
+macro_rules! hash_map_codegen {
+    { $t:ident,
+        $(#[$meta:meta])*
+        $map:ident $($tail:tt)*
+    } => {
+        $(#[$meta])*
+        compile_error!(concat!(
+            "`hash map: ",
+            stringify!($map),
+            "` requires the `std` or `hashbrown` feature of safe_index",
+        ));
+        $crate::handle!{ $t $($tail)* }
+    };
+}
+
+/// Generates an alias type for [`alloc::collections::BTreeMap`] of indices.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! btree_map_codegen {
+    { $t:ident,
+        $(#[$meta:meta])*
+        $map:ident $($tail:tt)*
+    } => {
+        $(#[$meta])*
+        pub type $map<T> = $crate::alloc::collections::BTreeMap<$t, T> ;
+        $crate::handle!{ $t $($tail)* }
+    };
+}
+
+/// Generates the code involving several items of the same `new!` invocation.
+///
+/// Scans the input of `new!` and records the names of the first range, map, btree set, btree set
+/// newtype, btree map newtype, sparse map, shared map, slice and union find declared, as
+/// `[<Range>] [<Map>] [<Set>] [<SetStruct>] [<BMap>] [<Sparse>] [<Shared>] [<Slice>] [<UnionFind>]`;
+/// `<Set>` is the first of the btree sets and btree set newtypes, it can be `<SetStruct>`. The
+/// `cfg`s of an option follow its name, and guard the code involving it. Malformed input is
+/// skipped, [`handle`] reports it.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! cross_codegen {
+    { @range_map $t:ident [$range:ident $(#$c1:tt)*] [$map:ident $(#$c2:tt)*] } => {
+        $(#$c1)* $(#$c2)*
+        $crate::range_map_codegen! { $t, $range, $map }
+    };
+    { @range_set $t:ident [$range:ident $(#$c1:tt)*] [$set:ident $(#$c2:tt)*] } => {
+        $(#$c1)* $(#$c2)*
+        $crate::range_set_codegen! { $t, $range, $set }
+    };
+    { @range_set_struct $t:ident [$range:ident $(#$c1:tt)*] [$set:ident $(#$c2:tt)*] } => {
+        $(#$c1)* $(#$c2)*
+        $crate::range_set_struct_codegen! { $t, $range, $set }
+    };
+    { @map_set $t:ident [$map:ident $(#$c1:tt)*] [$set:ident $(#$c2:tt)*] } => {
+        $(#$c1)* $(#$c2)*
+        $crate::map_set_codegen! { $t, $map, $set }
+    };
+    { @map_set_struct $t:ident [$map:ident $(#$c1:tt)*] [$set:ident $(#$c2:tt)*] } => {
+        $(#$c1)* $(#$c2)*
+        $crate::map_set_struct_codegen! { $t, $map, $set }
+    };
+    { @btree_map_map $t:ident [$btree_map:ident $(#$c1:tt)*] [$map:ident $(#$c2:tt)*] } => {
+        $(#$c1)* $(#$c2)*
+        $crate::btree_map_map_codegen! { $t, $btree_map, $map }
+    };
+    { @map_sparse_map $t:ident [$map:ident $(#$c1:tt)*] [$sparse:ident $(#$c2:tt)*] } => {
+        $(#$c1)* $(#$c2)*
+        $crate::map_sparse_map_codegen! { $t, $map, $sparse }
+    };
+    { @map_shared $t:ident [$map:ident $(#$c1:tt)*] [$shared:ident $(#$c2:tt)*] } => {
+        $(#$c1)* $(#$c2)*
+        $crate::map_shared_map_codegen! { $t, $map, $shared }
+    };
+    { @map_slice $t:ident [$map:ident $(#$c1:tt)*] [$slice:ident $(#$c2:tt)*] } => {
+        $(#$c1)* $(#$c2)*
+        $crate::map_slice_codegen! { $t, $map, $slice }
+    };
+    { @map_union_find $t:ident [$map:ident $(#$c1:tt)*] [$uf:ident $(#$c2:tt)*] } => {
+        $(#$c1)* $(#$c2)*
+        $crate::map_union_find_codegen! { $t, $map, $uf }
+    };
+    { @$pair:ident $($stuff:tt)* } => {};
+
+    // Drops the attributes of the options but their `cfg`s, moved in front of the option: the
+    // cross implementations of an option only exist when it does.
+    {
+        $t:ident $range:tt $map:tt $set:tt $sset:tt $bmap:tt $sparse:tt $shared:tt $slice:tt $uf:tt
+        , #[cfg($($cfg:tt)*)] $($tail:tt)*
+    } => {
+        $crate::cross_codegen! {
+            $t $range $map $set $sset $bmap $sparse $shared $slice $uf , @cfg [#[cfg($($cfg)*)]] $($tail)*
+        }
+    };
+    {
+        $t:ident $range:tt $map:tt $set:tt $sset:tt $bmap:tt $sparse:tt $shared:tt $slice:tt $uf:tt
+        , @cfg [$($cfgs:tt)*] #[cfg($($cfg:tt)*)] $($tail:tt)*
+    } => {
+        $crate::cross_codegen! {
+            $t $range $map $set $sset $bmap $sparse $shared $slice $uf , @cfg [$($cfgs)* #[cfg($($cfg)*)]] $($tail)*
+        }
+    };
+    {
+        $t:ident $range:tt $map:tt $set:tt $sset:tt $bmap:tt $sparse:tt $shared:tt $slice:tt $uf:tt
+        , @cfg $cfgs:tt #[$($attr:tt)*] $($tail:tt)*
+    } => {
+        $crate::cross_codegen! { $t $range $map $set $sset $bmap $sparse $shared $slice $uf , @cfg $cfgs $($tail)* }
+    };
+    {
+        $t:ident $range:tt $map:tt $set:tt $sset:tt $bmap:tt $sparse:tt $shared:tt $slice:tt $uf:tt
+        , #[$($attr:tt)*] $($tail:tt)*
+    } => {
+        $crate::cross_codegen! { $t $range $map $set $sset $bmap $sparse $shared $slice $uf , $($tail)* }
+    };
+    {
+        $t:ident [] $map:tt $set:tt $sset:tt $bmap:tt $sparse:tt $shared:tt $slice:tt $uf:tt
+        , $(@cfg [$(#$cfg:tt)*])? range: $range:ident $($tail:tt)*
+    } => {
+        $crate::cross_codegen! { $t [$range $($(#$cfg)*)?] $map $set $sset $bmap $sparse $shared $slice $uf $($tail)* }
+    };
+    {
+        $t:ident $range:tt $map:tt $set:tt $sset:tt $bmap:tt $sparse:tt $shared:tt $slice:tt $uf:tt
+        , $(@cfg [$(#$cfg:tt)*])? map: $fixed:ident of $($tail:tt)*
+    } => {
+        $crate::cross_codegen! { $t $range $map $set $sset $bmap $sparse $shared $slice $uf $($tail)* }
+    };
+    {
+        $t:ident $range:tt [] $set:tt $sset:tt $bmap:tt $sparse:tt $shared:tt $slice:tt $uf:tt
+        , $(@cfg [$(#$cfg:tt)*])? map: $map:ident $($tail:tt)*
+    } => {
+        $crate::cross_codegen! { $t $range [$map $($(#$cfg)*)?] $set $sset $bmap $sparse $shared $slice $uf $($tail)* }
+    };
+    {
+        $t:ident $range:tt $map:tt [] $sset:tt $bmap:tt $sparse:tt $shared:tt $slice:tt $uf:tt
+        , $(@cfg [$(#$cfg:tt)*])? btree set: $set:ident $($tail:tt)*
+    } => {
+        $crate::cross_codegen! { $t $range $map [$set $($(#$cfg)*)?] $sset $bmap $sparse $shared $slice $uf $($tail)* }
+    };
+    {
+        $t:ident $range:tt $map:tt [] [] $bmap:tt $sparse:tt $shared:tt $slice:tt $uf:tt
+        , $(@cfg [$(#$cfg:tt)*])? btree set struct: $set:ident $($tail:tt)*
+    } => {
+        $crate::cross_codegen! {
+            $t $range $map [$set $($(#$cfg)*)?] [$set $($(#$cfg)*)?] $bmap $sparse $shared $slice $uf
+            $($tail)*
+        }
+    };
+    {
+        $t:ident $range:tt $map:tt $set:tt [] $bmap:tt $sparse:tt $shared:tt $slice:tt $uf:tt
+        , $(@cfg [$(#$cfg:tt)*])? btree set struct: $sset:ident $($tail:tt)*
+    } => {
+        $crate::cross_codegen! {
+            $t $range $map $set [$sset $($(#$cfg)*)?] $bmap $sparse $shared $slice $uf $($tail)*
+        }
+    };
+    {
+        $t:ident $range:tt $map:tt $set:tt $sset:tt [] $sparse:tt $shared:tt $slice:tt $uf:tt
+        , $(@cfg [$(#$cfg:tt)*])? btree map struct: $bmap:ident $($tail:tt)*
+    } => {
+        $crate::cross_codegen! {
+            $t $range $map $set $sset [$bmap $($(#$cfg)*)?] $sparse $shared $slice $uf $($tail)*
+        }
+    };
+    {
+        $t:ident $range:tt $map:tt $set:tt $sset:tt $bmap:tt [] $shared:tt $slice:tt $uf:tt
+        , $(@cfg [$(#$cfg:tt)*])? sparse map: $sparse:ident $($tail:tt)*
+    } => {
+        $crate::cross_codegen! {
+            $t $range $map $set $sset $bmap [$sparse $($(#$cfg)*)?] $shared $slice $uf $($tail)*
+        }
+    };
+    {
+        $t:ident $range:tt $map:tt $set:tt $sset:tt $bmap:tt $sparse:tt [] $slice:tt $uf:tt
+        , $(@cfg [$(#$cfg:tt)*])? shared map: $shared:ident $($tail:tt)*
+    } => {
+        $crate::cross_codegen! {
+            $t $range $map $set $sset $bmap $sparse [$shared $($(#$cfg)*)?] $slice $uf $($tail)*
+        }
+    };
+    {
+        $t:ident $range:tt $map:tt $set:tt $sset:tt $bmap:tt $sparse:tt $shared:tt [] $uf:tt
+        , $(@cfg [$(#$cfg:tt)*])? slice: $slice:ident $($tail:tt)*
+    } => {
+        $crate::cross_codegen! {
+            $t $range $map $set $sset $bmap $sparse $shared [$slice $($(#$cfg)*)?] $uf $($tail)*
+        }
+    };
+    {
+        $t:ident $range:tt $map:tt $set:tt $sset:tt $bmap:tt $sparse:tt $shared:tt $slice:tt []
+        , $(@cfg [$(#$cfg:tt)*])? union find: $uf:ident $($tail:tt)*
+    } => {
+        $crate::cross_codegen! {
+            $t $range $map $set $sset $bmap $sparse $shared $slice [$uf $($(#$cfg)*)?] $($tail)*
+        }
+    };
+    {
+        $t:ident $range:tt $map:tt $set:tt $sset:tt $bmap:tt $sparse:tt $shared:tt $slice:tt $uf:tt
+        , $(@cfg [$(#$cfg:tt)*])? $($kind:ident)+ : $name:ident $($tail:tt)*
+    } => {
+        $crate::cross_codegen! { $t $range $map $set $sset $bmap $sparse $shared $slice $uf $($tail)* }
+    };
+    { $t:ident $range:tt $map:tt $set:tt $sset:tt $bmap:tt $sparse:tt $shared:tt $slice:tt $uf:tt $token:tt $($tail:tt)* } => {
+        $crate::cross_codegen! { $t $range $map $set $sset $bmap $sparse $shared $slice $uf $($tail)* }
+    };
+
+    { $t:ident $range:tt $map:tt $set:tt $sset:tt $bmap:tt $sparse:tt $shared:tt $slice:tt $uf:tt } => {
+        $crate::cross_codegen! { @range_map $t $range $map }
+        $crate::cross_codegen! { @range_set $t $range $set }
+        $crate::cross_codegen! { @range_set_struct $t $range $sset }
+        $crate::cross_codegen! { @map_set $t $map $set }
+        $crate::cross_codegen! { @map_set_struct $t $map $sset }
+        $crate::cross_codegen! { @btree_map_map $t $bmap $map }
+        $crate::cross_codegen! { @map_sparse_map $t $map $sparse }
+        $crate::cross_codegen! { @map_shared $t $map $shared }
+        $crate::cross_codegen! { @map_slice $t $map $slice }
+        $crate::cross_codegen! { @map_union_find $t $map $uf }
+    };
+}
+
+/// Handles some user input and decides what to do.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! handle {
+    { @option $t:ident, $(#[$meta:meta])* btree set struct: $($tail:tt)* } => {
+        $crate::btree_set_struct_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* btree set: $($tail:tt)* } => {
+        $crate::btree_set_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* bit set: $($tail:tt)* } => {
+        $crate::bit_set_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* interval set: $($tail:tt)* } => {
+        $crate::interval_set_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* hash set: $($tail:tt)* } => {
+        $crate::hash_set_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* hash map: $($tail:tt)* } => {
+        $crate::hash_map_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* btree map struct: $($tail:tt)* } => {
+        $crate::btree_map_struct_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* btree map: $($tail:tt)* } => {
+        $crate::btree_map_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* array map: $($tail:tt)* } => {
+        $crate::array_map_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* small map: $($tail:tt)* } => {
+        $crate::small_map_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* map: $map:ident of $($tail:tt)* } => {
+        $crate::fixed_map_codegen! { $t, $(#[$meta])* $map of $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* map: $($tail:tt)* } => {
+        $crate::map_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* slice: $($tail:tt)* } => {
+        $crate::slice_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* interner: $($tail:tt)* } => {
+        $crate::interner_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* counter: $($tail:tt)* } => {
+        $crate::counter_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* atomic counter: $($tail:tt)* } => {
+        $crate::atomic_counter_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* deque map: $($tail:tt)* } => {
+        $crate::deque_map_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* sparse map: $($tail:tt)* } => {
+        $crate::sparse_map_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* shared map: $($tail:tt)* } => {
+        $crate::shared_map_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* slot map: $($tail:tt)* } => {
+        $crate::slot_map_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* union find: $($tail:tt)* } => {
+        $crate::union_find_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* heap: $($tail:tt)* } => {
+        $crate::heap_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* bounded: $($tail:tt)* } => {
+        $crate::bounded_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, $(#[$meta:meta])* range: $($tail:tt)* } => {
+        $crate::range_codegen! { $t, $(#[$meta])* $($tail)* }
+    };
+    { @option $t:ident, base: 1 $($tail:tt)* } => {
+        $crate::one_based_codegen! { $t }
+        $crate::handle! { $t $($tail)* }
+    };
+    { @option $t:ident, base: $base:tt $($tail:tt)* } => {
+        compile_error!(concat!(
+            "external indices can only be one-based, expected `base: 1` but found `base: ",
+            stringify!($base),
+            "`",
+        ));
+    };
+    { $t:ident $(,)? } => {};
+    { $t:ident, #[cfg($($cfg:tt)*)] $($tail:tt)* } => {
+        $crate::handle! { @attrs $t [[$($cfg)*]] [] $($tail)* }
+    };
+    { $t:ident, #[$($attr:tt)*] $($tail:tt)* } => {
+        $crate::handle! { @attrs $t [] [[$($attr)*]] $($tail)* }
+    };
+    { $t:ident, $($tail:tt)* } => {
+        $crate::handle! { @attrs $t [] [] $($tail)* }
+    };
+
+    // Collects the attributes of an option, separating `cfg`s from the others.
+    { @attrs $t:ident [$($cfgs:tt)*] $attrs:tt #[cfg($($cfg:tt)*)] $($tail:tt)* } => {
+        $crate::handle! { @attrs $t [$($cfgs)* [$($cfg)*]] $attrs $($tail)* }
+    };
+    { @attrs $t:ident $cfgs:tt [$($attrs:tt)*] #[$($attr:tt)*] $($tail:tt)* } => {
+        $crate::handle! { @attrs $t $cfgs [$($attrs)* [$($attr)*]] $($tail)* }
+    };
+    // Each option is handled alone, under its `cfg`s if any so that all its items disappear when
+    // they do not hold, next to the following options.
+    {
+        @attrs $t:ident [$([$($cfg:tt)*])*] [$([$($attr:tt)*])*]
+        base : $base:tt $(, $($tail:tt)*)?
+    } => {
+        $(#[cfg($($cfg)*)])*
+        $crate::handle! { @option $t, $(#[$($attr)*])* base : $base }
+        $crate::handle! { $t $(, $($tail)*)? }
+    };
+    // Names are identifiers, `base` above is the only option taking a literal.
+    { @attrs $t:ident $cfgs:tt $attrs:tt $($kind:ident)+ : $lit:literal $($tail:tt)* } => {
+        compile_error!(concat!(
+            "expected a name after `",
+            stringify!($($kind)+),
+            ":` but found literal `",
+            stringify!($lit),
+            "`, names are identifiers as in `",
+            stringify!($($kind)+),
+            ": Name`",
+        ));
+    };
+    {
+        @attrs $t:ident [$([$($cfg:tt)*])*] [$([$($attr:tt)*])*]
+        $($kind:ident)+ : $name:ident [$($cap:tt)*]
+        with $($with_kind:ident)+ : $with_name:ident $(, $($tail:tt)*)?
+    } => {
+        $(#[cfg($($cfg)*)])*
+        $crate::handle! {
+            @option $t, $(#[$($attr)*])* $($kind)+ : $name [$($cap)*]
+            with $($with_kind)+ : $with_name
+        }
+        $crate::handle! { $t $(, $($tail)*)? }
+    };
+    {
+        @attrs $t:ident [$([$($cfg:tt)*])*] [$([$($attr:tt)*])*]
+        $($kind:ident)+ : $name:ident [$($cap:tt)*] $(, $($tail:tt)*)?
+    } => {
+        $(#[cfg($($cfg)*)])*
+        $crate::handle! { @option $t, $(#[$($attr)*])* $($kind)+ : $name [$($cap)*] }
+        $crate::handle! { $t $(, $($tail)*)? }
+    };
+    {
+        @attrs $t:ident [$([$($cfg:tt)*])*] [$([$($attr:tt)*])*]
+        $($kind:ident)+ : $name:ident of $elem:ty $(, $($tail:tt)*)?
+    } => {
+        $(#[cfg($($cfg)*)])*
+        $crate::handle! { @option $t, $(#[$($attr)*])* $($kind)+ : $name of $elem }
+        $crate::handle! { $t $(, $($tail)*)? }
+    };
+    {
+        @attrs $t:ident [$([$($cfg:tt)*])*] [$([$($attr:tt)*])*]
+        $($kind:ident)+ : $name:tt $(, $($tail:tt)*)?
+    } => {
+        $(#[cfg($($cfg)*)])*
+        $crate::handle! { @option $t, $(#[$($attr)*])* $($kind)+ : $name }
+        $crate::handle! { $t $(, $($tail)*)? }
+    };
+    // Common mistakes, reported before reaching the codegen macros.
+    { @attrs $t:ident $cfgs:tt $attrs:tt $($kind:ident)+ : $(, $($tail:tt)*)? } => {
+        compile_error!(concat!(
+            "expected a name after `",
+            stringify!($($kind)+),
+            ":`, for instance `",
+            stringify!($($kind)+),
+            ": Name`",
+        ));
+    };
+    { @attrs $t:ident $cfgs:tt [$([$($attr:tt)*])*] base : $($tail:tt)* } => {
+        $crate::handle! { @option $t, $(#[$($attr)*])* base : $($tail)* }
+    };
+    {
+        @attrs $t:ident $cfgs:tt [$([$($attr:tt)*])*]
+        $($kind:ident)+ : $name:ident with $($tail:tt)*
+    } => {
+        $crate::handle! { @option $t, $(#[$($attr)*])* $($kind)+ : $name with $($tail)* }
+    };
+    {
+        @attrs $t:ident $cfgs:tt $attrs:tt
+        $($kind:ident)+ : $name:ident of $(, $($tail:tt)*)?
+    } => {
+        compile_error!(concat!(
+            "expected the type of the elements after `",
+            stringify!($($kind)+),
+            ": ",
+            stringify!($name),
+            " of`, for instance `",
+            stringify!($($kind)+),
+            ": ",
+            stringify!($name),
+            " of String`",
+        ));
+    };
+    {
+        @attrs $t:ident $cfgs:tt $attrs:tt
+        $($kind:ident)+ : $name:ident : $($tail:tt)*
+    } => {
+        compile_error!(concat!(
+            "unexpected `:` after `",
+            stringify!($($kind)+),
+            ": ",
+            stringify!($name),
+            "`, options are separated by commas",
+        ));
+    };
+    {
+        @attrs $t:ident $cfgs:tt $attrs:tt
+        $($kind:ident)+ : $name:ident #[$($attr:tt)*] $($tail:tt)*
+    } => {
+        compile_error!(concat!(
+            "expected `,` after `",
+            stringify!($($kind)+),
+            ": ",
+            stringify!($name),
+            "` but found `",
+            stringify!(#[$($attr)*]),
+            "`, attributes and doc comments go right before the option they apply to",
+        ));
+    };
+    {
+        @attrs $t:ident $cfgs:tt $attrs:tt
+        $($kind:ident)+ : $name:ident $next:ident $($tail:tt)*
+    } => {
+        compile_error!(concat!(
+            "expected `,` after `",
+            stringify!($($kind)+),
+            ": ",
+            stringify!($name),
+            "` but found `",
+            stringify!($next),
+            "`, options are separated by commas",
+        ));
+    };
+    { @attrs $t:ident $cfgs:tt $attrs:tt $($word:ident)+ $(, $($tail:tt)*)? } => {
+        compile_error!(concat!(
+            "expected `:` between an option and its name, as in `map: Name`, but found `",
+            stringify!($($word)+),
+            "`",
+        ));
+    };
+    { @attrs $t:ident [] [] , $($tail:tt)* } => {
+        compile_error!("expected an option but found `,`, remove the extra comma");
+    };
+    {
+        @attrs $t:ident [$([$($cfg:tt)*])*] [$([$($attr:tt)*])*] $(, $($tail:tt)*)?
+    } => {
+        compile_error!(concat!(
+            "expected an option after `",
+            stringify!($(#[cfg($($cfg)*)])* $(#[$($attr)*])*),
+            "`, attributes and doc comments go right before the option they apply to",
+        ));
+    };
+    { @attrs $t:ident $cfgs:tt [$([$($attr:tt)*])*] $($tail:tt)* } => {
+        $crate::handle! { @option $t, $(#[$($attr)*])* $($tail)* }
+    };
+
+    { $t:ident with iter: $iter:ident $($tail:tt)* } => {
+        compile_error!(concat!(
+            "maps do not have dedicated iterator structures anymore, remove `with iter: ",
+            stringify!($iter),
+            "` from your input",
+        ));
+    };
+    { @option $t:ident, $(#[$meta:meta])* full: $($tail:tt)* } => {
+        compile_error!(
+            "`full` must be the first option, right after the index type, and has no attributes"
+        );
+    };
+    { @option $t:ident, $token:tt $($tail:tt)* } => {
+        compile_error!(concat!(
+            "expected `btree set`, `btree set struct`, `bit set`, `interval set`, `hash set`, ",
+            "`btree map`, `btree map struct`, `hash map`, `map`, `small map`, `array map`, ",
+            "`shared map`, `slot map`, `sparse map`, `deque map`, `slice`, `range`, `heap`, ",
+            "`union find`, `interner`, `counter`, `atomic counter`, `bounded` or `base` ",
+            "but found unexpected token `",
+            stringify!($token),
+            "`",
+        ));
+    };
+    { $t:ident $token:tt $($tail:tt)* } => {
+        compile_error!(concat!(
+            "expected comma, found unexpected token `",
+            stringify!($token),
+            "`",
+        ));
+    };
+}
+
+/// Wraps a `usize` into a struct (zero-cost). Also generates the relevant collections indexed by
 /// the wrapper.
 ///
 /// See the [module-level documentation](index.html) for more.
+///
+/// Several related index families can be declared in one invocation by giving the options of each
+/// family between braces. Such an invocation can also declare the [`matrix`] and [`relation`]
+/// types between these families, ended by a semicolon. The row slice of a `matrix` must be declared
+/// in the same module, typically by the same invocation:
+///
+/// ```rust
+/// safe_index::new! {
+///     /// Indices of clients.
+///     Client {
+///         /// Map from clients to something.
+///         map: Clients,
+///     }
+///     /// Indices of files.
+///     File {
+///         /// Map from files to something.
+///         map: Files,
+///         /// Slice of things indexed by files.
+///         slice: FileSlice,
+///     }
+///     /// Matrix from clients and files to something.
+///     matrix ClientFileMatrix<Client, File>, row: FileSlice;
+///     /// Relates clients and files.
+///     relation ClientFiles: Client <-> File;
+/// }
+///
+/// let mut clients = Clients::new();
+/// let c_0 = clients.push("client 0");
+/// let mut files = Files::new();
+/// let f_0 = files.push("file 0");
+///
+/// let mut owns = ClientFileMatrix::new(clients.len(), files.len(), false);
+/// owns[(c_0, f_0)] = true;
+/// let mut links = ClientFiles::new();
+/// links.insert(c_0, f_0);
+/// assert!(owns[(c_0, f_0)] && links.contains(c_0, f_0));
+/// ```
+///
+/// Starting with `mod <name>;` wraps all the items in a module `<name>`, with the visibility and
+/// attributes written before `mod`. Writing `mod <name>, pub use;` also re-exports the index types
+/// next to the module, with the visibility written before `use`:
+///
+/// ```rust
+/// safe_index::new! {
+///     /// Client indices and collections.
+///     pub mod client_idx, pub use;
+///     /// Indices of clients.
+///     Client,
+///     /// Map from clients to something.
+///     map: Clients,
+/// }
+///
+/// let mut clients = client_idx::Clients::new();
+/// let c_0: Client = clients.push("client 0");
+/// assert_eq! { clients[c_0], "client 0" }
+/// ```
 #[macro_export]
 macro_rules! new {
+    (
+        $(#[$mod_meta:meta])*
+        $vis:vis mod $mod:ident, $use_vis:vis use;
+        $($tail:tt)*
+    ) => (
+        $crate::new! { $(#[$mod_meta])* $vis mod $mod; $($tail)* }
+        $crate::use_indices! { $use_vis $mod $($tail)* }
+    );
+    (
+        $(#[$mod_meta:meta])*
+        $vis:vis mod $mod:ident;
+        $($tail:tt)*
+    ) => (
+        $(#[$mod_meta])*
+        $vis mod $mod {
+            #[allow(unused_imports)]
+            use super::*;
+            $crate::new! { $($tail)* }
+        }
+    );
+    (
+        $(#[$meta:meta])*
+        tagged $t:ident : $tag:ident { $($options:tt)* }
+        $($families:tt)*
+    ) => (
+        $crate::families! { $(#[$meta])* tagged $t: $tag { $($options)* } $($families)* }
+    );
     (
         $(#[$meta:meta])*
+        $t:ident { $($options:tt)* }
+        $($families:tt)*
+    ) => (
+        $crate::families! { $(#[$meta])* $t { $($options)* } $($families)* }
+    );
+    (
+        $(#[$meta:meta])*
+        tagged $t:ident : $tag:ident
+        $($tail:tt)*
+    ) => (
+        $crate::check_options! {
+            tagged_codegen { $(#[$meta])* $t $tag $($tail)* } [] [$t [index type] $tag [tag type]]
+            $($tail)*
+        }
+    );
+    (
+        $(#[$($attr:tt)*])*
+        $t:ident, full: $prefix:ident
+        $(, $($tail:tt)*)?
+    ) => (
+        $crate::full_codegen! { [$(#[$($attr)*])* $t] $prefix [$($($tail)*)?] }
+    );
+    (
+        $(#[$($attr:tt)*])*
         $t:ident
         $($tail:tt)*
     ) => (
-        $(#[$meta])*
-        #[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Hash)]
-        pub struct $t {
-            val: usize
+        $crate::check_derives! {
+            [$([$($attr)*])*]
+            { index_codegen { $(#[$($attr)*])* $t $($tail)* } [] [$t [index type]] $($tail)* }
+        }
+    ) ;
+}
+
+/// Expands `full: <Prefix>` through the `macros` feature, which derives the names.
+#[cfg(feature = "macros")]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! full_codegen {
+    { [$($idx:tt)*] $prefix:ident [$($tail:tt)*] } => {
+        $crate::__new_full! { [$($idx)*] $prefix [$($tail)*] }
+    };
+}
+/// Expands `full: <Prefix>` through the `macros` feature, which derives the names.
+#[cfg(not(feature = "macros"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! full_codegen {
+    { [$($idx:tt)*] $prefix:ident [$($tail:tt)*] } => {
+        compile_error!(concat!(
+            "`full: ",
+            stringify!($prefix),
+            "` requires the `macros` feature of safe_index, which derives the names",
+        ));
+    };
+}
+
+/// Re-exports the index types declared in an invocation of [`new`] wrapped in a module.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! use_indices {
+    { $vis:vis $mod:ident } => {};
+    {
+        $vis:vis $mod:ident
+        $(#[$meta:meta])* matrix $matrix:ident<$row:ident, $col:ident>, row: $slice:ident;
+        $($families:tt)*
+    } => {
+        $crate::use_indices! { $vis $mod $($families)* }
+    };
+    {
+        $vis:vis $mod:ident
+        $(#[$meta:meta])* relation $rel:ident : $left:ident <-> $right:ident;
+        $($families:tt)*
+    } => {
+        $crate::use_indices! { $vis $mod $($families)* }
+    };
+    {
+        $vis:vis $mod:ident
+        $(#[$meta:meta])* tagged $t:ident : $tag:ident { $($options:tt)* }
+        $($families:tt)*
+    } => {
+        $vis use $mod::$t;
+        $crate::use_indices! { $vis $mod $($families)* }
+    };
+    {
+        $vis:vis $mod:ident
+        $(#[$meta:meta])* $t:ident { $($options:tt)* }
+        $($families:tt)*
+    } => {
+        $vis use $mod::$t;
+        $crate::use_indices! { $vis $mod $($families)* }
+    };
+    { $vis:vis $mod:ident $(#[$meta:meta])* tagged $t:ident $($tail:tt)* } => {
+        $vis use $mod::$t;
+    };
+    { $vis:vis $mod:ident $(#[$meta:meta])* $t:ident $($tail:tt)* } => {
+        $vis use $mod::$t;
+    };
+}
+
+/// Handles several index families declared in one invocation of [`new`].
+#[macro_export]
+#[doc(hidden)]
+macro_rules! families {
+    () => {};
+    (
+        $(#[$meta:meta])*
+        matrix $matrix:ident<$row:ident, $col:ident>, row: $slice:ident;
+        $($families:tt)*
+    ) => {
+        $crate::matrix! { $(#[$meta])* $matrix<$row, $col>, row: $slice }
+        $crate::families! { $($families)* }
+    };
+    (
+        $(#[$meta:meta])*
+        relation $rel:ident : $left:ident <-> $right:ident;
+        $($families:tt)*
+    ) => {
+        $crate::relation! { $(#[$meta])* $rel: $left <-> $right }
+        $crate::families! { $($families)* }
+    };
+    (
+        $(#[$meta:meta])*
+        tagged $t:ident : $tag:ident { $($options:tt)* }
+        $($families:tt)*
+    ) => {
+        $crate::new! { $(#[$meta])* tagged $t: $tag, $($options)* }
+        $crate::families! { $($families)* }
+    };
+    (
+        $(#[$meta:meta])*
+        $t:ident { $($options:tt)* }
+        $($families:tt)*
+    ) => {
+        $crate::new! { $(#[$meta])* $t, $($options)* }
+        $crate::families! { $($families)* }
+    };
+    ($token:tt $($tail:tt)*) => {
+        compile_error!(concat!(
+            "expected an index family `<Idx> { <options> }`, a `matrix` or a `relation`, found \
+            unexpected token `",
+            stringify!($token),
+            "`",
+        ));
+    };
+}
+
+/// Checks the derives on the index type of [`new`], then checks the options.
+///
+/// Fails on derives of traits the index type already implements.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! check_derives {
+    { [] { $($then:tt)* } } => {
+        $crate::check_options! { $($then)* }
+    };
+    { [[derive($($derives:tt)*)] $($attrs:tt)*] $then:tt } => {
+        $crate::check_derives! { @derive [$($derives)*] [$($attrs)*] $then }
+    };
+    { [$attr:tt $($attrs:tt)*] $then:tt } => {
+        $crate::check_derives! { [$($attrs)*] $then }
+    };
+
+    { @derive [] $attrs:tt $then:tt } => {
+        $crate::check_derives! { $attrs $then }
+    };
+    {
+        @derive
+        [$derive:ident $(, $($derives:tt)*)?] $attrs:tt $then:tt
+    } => {
+        $crate::check_derive! { $derive [$($($derives)*)?] $attrs $then }
+    };
+    {
+        @derive
+        [$derive:path $(, $($derives:tt)*)?] $attrs:tt $then:tt
+    } => {
+        $crate::check_derives! { @derive [$($($derives)*)?] $attrs $then }
+    };
+}
+
+/// Fails if the index type of [`new`] already implements the trait of a derive.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! check_derive {
+    (Debug $($tail:tt)*) => { $crate::derive_conflict! { Debug } };
+    (Clone $($tail:tt)*) => { $crate::derive_conflict! { Clone } };
+    (Copy $($tail:tt)*) => { $crate::derive_conflict! { Copy } };
+    (PartialEq $($tail:tt)*) => { $crate::derive_conflict! { PartialEq } };
+    (Eq $($tail:tt)*) => { $crate::derive_conflict! { Eq } };
+    (PartialOrd $($tail:tt)*) => { $crate::derive_conflict! { PartialOrd } };
+    (Ord $($tail:tt)*) => { $crate::derive_conflict! { Ord } };
+    (Hash $($tail:tt)*) => { $crate::derive_conflict! { Hash } };
+    (Default $($tail:tt)*) => { $crate::check_derive_default! { $($tail)* } };
+    ($derive:ident $derives:tt $attrs:tt $then:tt) => {
+        $crate::check_derives! { @derive $derives $attrs $then }
+    };
+}
+
+/// Error for a derive of a trait the index type of [`new`] already implements.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! derive_conflict {
+    ($derive:ident) => {
+        compile_error!(concat!(
+            "index types always implement `",
+            stringify!($derive),
+            "`, remove it from `derive(...)`",
+        ));
+    };
+}
+
+/// Index types implement `Default` unless the `strict` feature is active without `petgraph`.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(any(not(feature = "strict"), feature = "petgraph"))]
+macro_rules! check_derive_default {
+    ($($tail:tt)*) => {
+        compile_error!(
+            "index types implement `Default` as zero unless safe_index's `strict` feature is \
+            active without `petgraph`, remove it from `derive(...)`"
+        );
+    };
+}
+/// Index types implement `Default` unless the `strict` feature is active without `petgraph`.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(all(feature = "strict", not(feature = "petgraph")))]
+macro_rules! check_derive_default {
+    ($derives:tt $attrs:tt $then:tt) => {
+        $crate::check_derives! { @derive $derives $attrs $then }
+    };
+}
+
+/// Checks the options of [`new`] before generating anything, then generates the index type with
+/// the codegen macro given as first argument.
+///
+/// Fails if `range` appears more than once: its range type adds methods to the index type. Also
+/// collects the names of the index type and of the options for [`check_names`].
+#[macro_export]
+#[doc(hidden)]
+macro_rules! check_options {
+    {
+        $codegen:ident $input:tt [$first:ident] $names:tt
+        , $(#[$meta:meta])* range: $second:ident $($tail:tt)*
+    } => {
+        compile_error!(concat!(
+            "`range` can only appear once, found `range: ",
+            stringify!($first),
+            "` and `range: ",
+            stringify!($second),
+            "`",
+        ));
+    };
+    {
+        $codegen:ident $input:tt [] [$($names:tt)*]
+        , $(#[$meta:meta])* range: $range:ident $($tail:tt)*
+    } => {
+        $crate::check_options! { $codegen $input [$range] [$($names)* $range [range]] $($tail)* }
+    };
+    {
+        $codegen:ident $input:tt $range:tt [$($names:tt)*]
+        , $(#[$meta:meta])* $($kind:ident)+ : $name:ident $($tail:tt)*
+    } => {
+        $crate::check_options! {
+            $codegen $input $range [$($names)* $name [$($kind)+]] $($tail)*
+        }
+    };
+    {
+        $codegen:ident $input:tt $range:tt [$($names:tt)*]
+        with $($kind:ident)+ : $name:ident $($tail:tt)*
+    } => {
+        $crate::check_options! {
+            $codegen $input $range [$($names)* $name [$($kind)+]] $($tail)*
+        }
+    };
+    { $codegen:ident $input:tt $range:tt $names:tt $token:tt $($tail:tt)* } => {
+        $crate::check_options! { $codegen $input $range $names $($tail)* }
+    };
+    { $codegen:ident $input:tt $range:tt $names:tt } => {
+        $crate::check_names! { ($) $codegen $input $names }
+    };
+}
+
+/// Fails if two names given to [`new`] are the same, otherwise generates the index type.
+///
+/// For each name, defines a local macro with an arm for each of the following names and applies it
+/// to the name, which keeps the expansion depth linear in the number of names.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! check_names {
+    { ($d:tt) $codegen:ident { $($input:tt)* } [] } => {
+        $crate::$codegen! { $($input)* }
+    };
+    {
+        ($d:tt) $codegen:ident $input:tt
+        [$name:ident $what:tt $($other_name:ident $other_what:tt)*]
+    } => {
+        macro_rules! __safe_index_check_name {
+            $(
+                ($other_name) => {
+                    compile_error!(concat!(
+                        "`",
+                        stringify!($name),
+                        "` used for both the ",
+                        stringify! $what,
+                        " and the ",
+                        stringify! $other_what,
+                    ));
+                };
+            )*
+            ($d name:ident) => {
+                $crate::check_names! { ($d) $codegen $input [$($other_name $other_what)*] }
+            };
+        }
+        __safe_index_check_name! { $name }
+    };
+}
+
+/// Index type code generation, see [`new`].
+#[macro_export]
+#[doc(hidden)]
+macro_rules! index_codegen {
+    (
+        $(#[$meta:meta])*
+        $t:ident
+        $($tail:tt)*
+    ) => (
+        $crate::index_struct! {
+            $(#[$meta])*
+            #[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Hash)]
+            #[repr(transparent)]
+            pub struct $t {
+                val: usize
+            }
         }
 
         impl $t {
@@ -295,7 +1998,7 @@ macro_rules! new {
                 /// Wraps a [`usize`].
                 #[inline]
                 pub const fn new(val: usize) -> Self {
-                    $t { val: val }
+                    $t { val }
                 }
             }
             $crate::non_strict! {
@@ -326,18 +2029,55 @@ macro_rules! new {
                     self.val -= 1
                 }
             }
+            $crate::non_strict! {
+                /// Iterator over this index and the ones after it, in ascending order.
+                ///
+                /// Stops after the maximum index instead of overflowing.
+                #[inline]
+                pub fn successors(self) ->
+                    impl core::iter::FusedIterator<Item = Self> + core::clone::Clone
+                {
+                    (self.val..=usize::MAX).map(|val| $t { val })
+                }
+            }
+            $crate::non_strict! {
+                /// Iterator over all the indices in ascending order, see `successors`.
+                #[inline]
+                pub fn zero_onwards() ->
+                    impl core::iter::FusedIterator<Item = Self> + core::clone::Clone
+                {
+                    $t { val: 0 }.successors()
+                }
+            }
             /// Underlying index accessor.
             #[inline]
             pub const fn get(& self) -> usize {
                 self.val
             }
         }
+        // Sound: `$t` is `#[repr(transparent)]` over its `usize`.
+        unsafe impl $crate::SafeIndex for $t {
+            const NAME: &'static str = stringify!($t);
+            $crate::non_strict! {
+                const ZERO: Self = $t { val: 0 };
+                #[inline]
+                fn from_usize(val: usize) -> Self {
+                    $t { val }
+                }
+            }
+            #[inline]
+            fn as_usize(&self) -> usize {
+                self.val
+            }
+        }
+        #[allow(clippy::from_over_into)]
         impl core::convert::Into<usize> for $t {
             #[inline]
             fn into(self) -> usize {
                 self.val
             }
         }
+        #[allow(clippy::from_over_into)]
         impl<'a> core::convert::Into<usize> for &'a $t {
             #[inline]
             fn into(self) -> usize {
@@ -398,15 +2138,193 @@ macro_rules! new {
                     self.val += rhs.into()
                 }
             }
+        }
+        $crate::num_traits_only! {
+            $crate::non_strict! {
+                impl core::ops::Sub for $t {
+                    type Output = $t;
+                    #[inline]
+                    fn sub(self, rhs: $t) -> $t {
+                        $t { val: self.val - rhs.val }
+                    }
+                }
+                impl core::ops::Mul for $t {
+                    type Output = $t;
+                    #[inline]
+                    fn mul(self, rhs: $t) -> $t {
+                        $t { val: self.val * rhs.val }
+                    }
+                }
+                impl $crate::num_traits::Zero for $t {
+                    #[inline]
+                    fn zero() -> Self {
+                        $t { val: 0 }
+                    }
+                    #[inline]
+                    fn is_zero(&self) -> bool {
+                        self.val == 0
+                    }
+                }
+                impl $crate::num_traits::One for $t {
+                    #[inline]
+                    fn one() -> Self {
+                        $t { val: 1 }
+                    }
+                }
+                impl $crate::num_traits::Bounded for $t {
+                    #[inline]
+                    fn min_value() -> Self {
+                        $t { val: 0 }
+                    }
+                    #[inline]
+                    fn max_value() -> Self {
+                        $t { val: usize::MAX }
+                    }
+                }
+                impl $crate::num_traits::CheckedAdd for $t {
+                    #[inline]
+                    fn checked_add(&self, rhs: &Self) -> Option<Self> {
+                        self.val.checked_add(rhs.val).map(|val| $t { val })
+                    }
+                }
+                impl $crate::num_traits::CheckedSub for $t {
+                    #[inline]
+                    fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+                        self.val.checked_sub(rhs.val).map(|val| $t { val })
+                    }
+                }
+                impl $crate::num_traits::SaturatingAdd for $t {
+                    #[inline]
+                    fn saturating_add(&self, rhs: &Self) -> Self {
+                        $t { val: self.val.saturating_add(rhs.val) }
+                    }
+                }
+                impl $crate::num_traits::SaturatingSub for $t {
+                    #[inline]
+                    fn saturating_sub(&self, rhs: &Self) -> Self {
+                        $t { val: self.val.saturating_sub(rhs.val) }
+                    }
+                }
+            }
+        }
+        $crate::default_idx! {
             impl Default for $t {
                 #[inline]
                 fn default() -> Self {
-                    Self::zero()
+                    $t { val: 0 }
+                }
+            }
+        }
+        $crate::petgraph_only! {
+            // Sound: `index` yields the value given to `new`.
+            unsafe impl $crate::petgraph::graph::IndexType for $t {
+                #[inline]
+                fn new(val: usize) -> Self {
+                    $t { val }
+                }
+                #[inline]
+                fn index(&self) -> usize {
+                    self.val
+                }
+                #[inline]
+                fn max() -> Self {
+                    $t { val: usize::MAX }
+                }
+            }
+            impl core::convert::From<$crate::petgraph::graph::NodeIndex<$t>> for $t {
+                #[inline]
+                fn from(node: $crate::petgraph::graph::NodeIndex<$t>) -> Self {
+                    $t { val: node.index() }
+                }
+            }
+        }
+        $crate::borsh_only! {
+            impl $crate::borsh::BorshSerialize for $t {
+                /// Serializes the index as its `usize`, a little-endian `u64`.
+                #[inline]
+                fn serialize<W: $crate::borsh::io::Write>(
+                    &self,
+                    writer: &mut W,
+                ) -> $crate::borsh::io::Result<()> {
+                    $crate::borsh::BorshSerialize::serialize(&self.val, writer)
+                }
+            }
+            $crate::non_strict! {
+                impl $crate::borsh::BorshDeserialize for $t {
+                    /// Deserializes a little-endian `u64`, fails if it does not fit in a `usize`.
+                    #[inline]
+                    fn deserialize_reader<R: $crate::borsh::io::Read>(
+                        reader: &mut R,
+                    ) -> $crate::borsh::io::Result<Self> {
+                        let val = <usize as $crate::borsh::BorshDeserialize>::deserialize_reader(reader)?;
+                        Ok($t { val })
+                    }
+                }
+            }
+        }
+        $crate::index_vec_only! {
+            impl $crate::index_vec::Idx for $t {
+                #[inline]
+                fn from_usize(val: usize) -> Self {
+                    $t { val }
+                }
+                #[inline]
+                fn index(self) -> usize {
+                    self.val
+                }
+            }
+        }
+        $crate::schemars_only! {
+            impl $crate::schemars::JsonSchema for $t {
+                fn schema_name() -> $crate::alloc::borrow::Cow<'static, str> {
+                    stringify!($t).into()
+                }
+                fn schema_id() -> $crate::alloc::borrow::Cow<'static, str> {
+                    concat!(module_path!(), "::", stringify!($t)).into()
+                }
+                /// Non-negative integer as wide as a `usize`.
+                fn json_schema(
+                    _: &mut $crate::schemars::SchemaGenerator,
+                ) -> $crate::schemars::Schema {
+                    $crate::schemars::json_schema!({
+                        "type": "integer",
+                        "format": $crate::alloc::format!("uint{}", usize::BITS),
+                        "minimum": 0,
+                    })
                 }
             }
         }
         $crate::handle!{ $t $($tail)* }
+        $crate::cross_codegen!{ $t [] [] [] [] [] [] [] [] [] $($tail)* }
     ) ;
 }
 
+/// One-based external indices code generation, see [`new`].
+#[macro_export]
+#[doc(hidden)]
+macro_rules! one_based_codegen {
+    ($t:ident) => {
+        impl $t {
+            /// One-based version of the index, for displaying or exporting it.
+            ///
+            /// The index itself and the collections it indexes stay zero-based.
+            #[inline]
+            pub const fn to_external(self) -> usize {
+                self.val + 1
+            }
+            $crate::non_strict! {
+                /// Index corresponding to a one-based external index, `None` on zero.
+                #[inline]
+                pub const fn from_external(external: usize) -> Option<Self> {
+                    if external == 0 {
+                        None
+                    } else {
+                        Some($t { val: external - 1 })
+                    }
+                }
+            }
+        }
+    };
+}
+
 pub mod examples;
@@ -11,6 +11,7 @@ macro_rules! map_codegen {
     ) => {
         $(#[$meta])*
         #[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[repr(transparent)]
         pub struct $map<T> {
             vec: $crate::alloc::vec::Vec<T>
         }
@@ -23,6 +24,7 @@ macro_rules! map_codegen {
             }
         }
 
+        #[allow(clippy::len_without_is_empty)]
         impl<T> $map<T> {
             /// Creates an empty map.
             #[inline]
@@ -35,6 +37,32 @@ macro_rules! map_codegen {
                 $map { vec: $crate::alloc::vec::Vec::with_capacity(capacity) }
             }
 
+            $crate::non_strict! {
+                /// Views a vector as a map, without copying.
+                #[inline]
+                pub fn from_vec_ref(vec: &$crate::alloc::vec::Vec<T>) -> &Self {
+                    // Sound: `Self` is `#[repr(transparent)]` over its vector.
+                    unsafe { &*(vec as *const $crate::alloc::vec::Vec<T> as *const Self) }
+                }
+            }
+            $crate::non_strict! {
+                /// Views a vector as a mutable map, without copying.
+                #[inline]
+                pub fn from_vec_mut(vec: &mut $crate::alloc::vec::Vec<T>) -> &mut Self {
+                    // Sound: `Self` is `#[repr(transparent)]` over its vector.
+                    unsafe { &mut *(vec as *mut $crate::alloc::vec::Vec<T> as *mut Self) }
+                }
+            }
+
+            /// Creates a map with an element for each element of another map.
+            ///
+            /// Both maps have the same length, meaning the indices of `other` are legal for the
+            /// new map.
+            #[inline]
+            pub fn new_like_with<U>(other: &$map<U>, mut f: impl FnMut($t, &U) -> T) -> Self {
+                $map { vec: other.index_iter().map(|(idx, elem)| f(idx, elem)).collect() }
+            }
+
             /// Reserves some space for the map.
             #[inline]
             pub fn reserve(&mut self, capa: usize) {
@@ -53,13 +81,84 @@ macro_rules! map_codegen {
 
             /// Retrieves an entry in the map.
             #[inline]
-            pub fn get(&self, idx: $t) -> Option<&T> {
-                self.vec.get(idx.get())
+            pub fn get(&self, idx: impl core::borrow::Borrow<$t>) -> Option<&T> {
+                self.vec.get(idx.borrow().get())
             }
             /// Retrieves an entry in the map.
             #[inline]
-            pub fn get_mut(&mut self, idx: $t) -> Option<&mut T> {
-                self.vec.get_mut(idx.get())
+            pub fn get_mut(&mut self, idx: impl core::borrow::Borrow<$t>) -> Option<&mut T> {
+                self.vec.get_mut(idx.borrow().get())
+            }
+
+            /// Replaces the element at some index, yields the previous element.
+            ///
+            /// Panics if the index is out of bounds, like indexing does.
+            #[inline]
+            #[track_caller]
+            pub fn replace(&mut self, idx: $t, elem: T) -> T {
+                core::mem::replace(&mut self[idx], elem)
+            }
+
+            /// Retrieves an entry in the map, without bounds checking.
+            ///
+            /// Bounds are still checked in debug builds.
+            ///
+            /// # Safety
+            ///
+            /// Calling this method with an out-of-bounds index is *[undefined behavior]* even if
+            /// the resulting reference is not used.
+            ///
+            /// [undefined behavior]: https://doc.rust-lang.org/reference/behavior-considered-undefined.html
+            #[inline]
+            pub unsafe fn get_unchecked(&self, idx: $t) -> &T {
+                debug_assert!(idx.val < self.vec.len());
+                self.vec.get_unchecked(idx.val)
+            }
+            /// Retrieves an entry in the map, without bounds checking.
+            ///
+            /// Bounds are still checked in debug builds.
+            ///
+            /// # Safety
+            ///
+            /// Calling this method with an out-of-bounds index is *[undefined behavior]* even if
+            /// the resulting reference is not used.
+            ///
+            /// [undefined behavior]: https://doc.rust-lang.org/reference/behavior-considered-undefined.html
+            #[inline]
+            pub unsafe fn get_unchecked_mut(&mut self, idx: $t) -> &mut T {
+                debug_assert!(idx.val < self.vec.len());
+                self.vec.get_unchecked_mut(idx.val)
+            }
+
+            /// Retrieves a range of entries in the map.
+            ///
+            /// Accepts the same typed ranges as indexing, but returns `None` when the range is
+            /// inverted or out of bounds.
+            #[inline]
+            pub fn get_range(&self, range: impl core::ops::RangeBounds<$t>) -> Option<&[T]> {
+                self.vec.get((
+                    range.start_bound().map(|idx| idx.get()),
+                    range.end_bound().map(|idx| idx.get()),
+                ))
+            }
+            /// Retrieves a range of entries in the map.
+            ///
+            /// Accepts the same typed ranges as indexing, but returns `None` when the range is
+            /// inverted or out of bounds.
+            #[inline]
+            pub fn get_range_mut(
+                &mut self,
+                range: impl core::ops::RangeBounds<$t>,
+            ) -> Option<&mut [T]> {
+                self.vec.get_mut((
+                    range.start_bound().map(|idx| idx.get()),
+                    range.end_bound().map(|idx| idx.get()),
+                ))
+            }
+            /// True if `idx` is a legal index for this map.
+            #[inline]
+            pub fn contains_index(&self, idx: $t) -> bool {
+                idx.val < self.vec.len()
             }
 
             /// Retrieves the last entry in the map.
@@ -83,6 +182,11 @@ macro_rules! map_codegen {
             pub fn capacity(& self) -> usize {
                 self.vec.capacity()
             }
+            /// Number of elements the map can hold without reallocating.
+            #[inline]
+            pub fn remaining_capacity(& self) -> usize {
+                self.vec.capacity() - self.vec.len()
+            }
 
             $crate::non_strict! {
                 /// The next free index (wrapped `self.len()`).
@@ -107,6 +211,17 @@ macro_rules! map_codegen {
                 self.vec.push(elem);
                 idx
             }
+            /// Pushes an element if the map has enough capacity, yields its index.
+            ///
+            /// Never allocates: if the map is full, the element is given back as an error.
+            #[inline]
+            pub fn push_within_capacity(&mut self, elem: T) -> Result<$t, T> {
+                if self.vec.len() < self.vec.capacity() {
+                    Ok(self.push(elem))
+                } else {
+                    Err(elem)
+                }
+            }
             /// Pushes an element generated by a function taking the element's index as input.
             ///
             /// This is useful if you want to store the `T`-element's index inside the element,
@@ -117,7 +232,7 @@ macro_rules! map_codegen {
                 self.vec.push(new_elem(idx));
                 idx
             }
-            /// Same as [`push_idx`], but the builder returns a result.
+            /// Same as [`Self::push_idx`], but the builder returns a result.
             #[inline]
             pub fn try_push_idx<E>(&mut self, new_elem: impl FnOnce($t) -> Result<T, E>) -> Result<$t, E> {
                 let idx = $t { val: self.len() };
@@ -125,6 +240,23 @@ macro_rules! map_codegen {
                 Ok(idx)
             }
 
+            /// Pushes index/element pairs, fails if an index is not the next free index.
+            ///
+            /// Pairs before the faulty one are pushed. See also the `Extend` implementation.
+            pub fn try_extend_pairs(
+                &mut self,
+                pairs: impl core::iter::IntoIterator<Item = ($t, T)>,
+            ) -> Result<(), $crate::UnexpectedIndex<$t>> {
+                for (idx, elem) in pairs {
+                    let expected = $t { val: self.len() };
+                    if idx != expected {
+                        return Err($crate::UnexpectedIndex { expected, found: idx });
+                    }
+                    self.vec.push(elem)
+                }
+                Ok(())
+            }
+
             $crate::non_strict! {
                 /// Pops an element.
                 ///
@@ -151,21 +283,43 @@ macro_rules! map_codegen {
             }
             /// Iterator over all the indices.
             #[inline]
-            pub fn indices(&self) -> impl core::iter::Iterator<Item = $t> {
-                (0..self.len()).into_iter().map(|i| $t { val: i })
+            pub fn indices(&self) ->
+                impl core::iter::DoubleEndedIterator<Item = $t>
+                + core::iter::ExactSizeIterator
+                + core::iter::FusedIterator
+            {
+                (0..self.len()).map(|i| $t { val: i })
+            }
+
+            /// Set of all the indices of the map.
+            ///
+            /// This is the type of the `btree set` alias, if one was declared.
+            #[inline]
+            pub fn keys_as_set(&self) -> $crate::alloc::collections::BTreeSet<$t> {
+                self.indices().collect()
+            }
+            /// Set of the indices of the elements verifying a predicate.
+            ///
+            /// This is the type of the `btree set` alias, if one was declared.
+            #[inline]
+            pub fn indices_matching(
+                &self,
+                mut pred: impl FnMut(&T) -> bool,
+            ) -> $crate::alloc::collections::BTreeSet<$t> {
+                self.index_iter().filter(|(_, elem)| pred(elem)).map(|(idx, _)| idx).collect()
             }
 
             /// Ref-iterator over the elements.
             #[inline]
-            pub fn iter(& self) -> core::slice::Iter<T> {
+            pub fn iter(& self) -> core::slice::Iter<'_, T> {
                 self.vec.iter()
             }
             /// Ref-iterator over the index/element pairs.
             #[inline]
             pub fn index_iter<'a>(&'a self) ->
-                impl core::iter::Iterator<Item = ($t, &'a T)>
-                + core::iter::DoubleEndedIterator
+                impl core::iter::DoubleEndedIterator<Item = ($t, &'a T)>
                 + core::iter::ExactSizeIterator
+                + core::iter::FusedIterator
             where T: 'a {
                 self.vec.iter().enumerate().map(|(idx, elm)| (
                     $t { val: idx }, elm
@@ -174,9 +328,9 @@ macro_rules! map_codegen {
             /// Ref-mut-iterator over the index/element pairs.
             #[inline]
             pub fn index_iter_mut<'a>(&'a mut self) ->
-                impl core::iter::Iterator<Item = ($t, &'a mut T)>
-                + core::iter::DoubleEndedIterator
+                impl core::iter::DoubleEndedIterator<Item = ($t, &'a mut T)>
                 + core::iter::ExactSizeIterator
+                + core::iter::FusedIterator
             where T: 'a {
                 self.vec.iter_mut().enumerate().map(|(idx, elm)| (
                     $t { val: idx }, elm
@@ -185,9 +339,9 @@ macro_rules! map_codegen {
             /// Own-iterator over the index/element pairs.
             #[inline]
             pub fn into_index_iter(self) ->
-                impl core::iter::Iterator<Item = ($t, T)>
-                + core::iter::DoubleEndedIterator
+                impl core::iter::DoubleEndedIterator<Item = ($t, T)>
                 + core::iter::ExactSizeIterator
+                + core::iter::FusedIterator
             {
                 self.vec.into_iter().enumerate().map(|(idx, elm)| (
                     $t { val: idx }, elm
@@ -195,10 +349,79 @@ macro_rules! map_codegen {
             }
             /// Ref-mut-iterator over the elements.
             #[inline]
-            pub fn iter_mut(&mut self) -> core::slice::IterMut<T> {
+            pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, T> {
                 self.vec.iter_mut()
             }
 
+            /// Fills the map with values generated by `f`.
+            #[inline]
+            pub fn fill_with(&mut self, f: impl FnMut() -> T) {
+                self.vec.fill_with(f)
+            }
+
+            /// Iterator over the entries at some indices, in the order of the indices.
+            ///
+            /// Panics if an index is out of bounds, like indexing does.
+            #[inline]
+            pub fn select<'a, I>(
+                &'a self,
+                indices: I,
+            ) -> impl core::iter::Iterator<Item = ($t, &'a T)> + 'a
+            where
+                I: core::iter::IntoIterator,
+                I::IntoIter: 'a,
+                I::Item: core::borrow::Borrow<$t>,
+            {
+                indices.into_iter().map(move |idx| {
+                    let idx = *core::borrow::Borrow::<$t>::borrow(&idx);
+                    (idx, &self[idx])
+                })
+            }
+            /// Index of the last element verifying a predicate, scanning from the end.
+            #[inline]
+            pub fn rposition(&self, pred: impl FnMut(&T) -> bool) -> Option<$t> {
+                self.vec.iter().rposition(pred).map(|val| $t { val })
+            }
+            /// Last entry verifying a predicate, scanning from the end.
+            #[inline]
+            pub fn rfind_indexed(&self, mut pred: impl FnMut($t, &T) -> bool) -> Option<($t, &T)> {
+                self.index_iter().rev().find(|(idx, elem)| pred(*idx, elem))
+            }
+
+            /// Applies `f` to the entries at some indices, typically from a set of indices.
+            ///
+            /// Panics if an index is out of bounds, like indexing does.
+            #[inline]
+            pub fn for_each_in<I>(&mut self, indices: I, mut f: impl FnMut($t, &mut T))
+            where
+                I: core::iter::IntoIterator,
+                I::Item: core::borrow::Borrow<$t>,
+            {
+                for idx in indices {
+                    let idx = *core::borrow::Borrow::<$t>::borrow(&idx);
+                    f(idx, &mut self[idx])
+                }
+            }
+            /// Applies a fallible `f` to the entries at some indices, stops at the first error.
+            ///
+            /// Panics if an index is out of bounds, like indexing does.
+            #[inline]
+            pub fn try_for_each_in<I, E>(
+                &mut self,
+                indices: I,
+                mut f: impl FnMut($t, &mut T) -> Result<(), E>,
+            ) -> Result<(), E>
+            where
+                I: core::iter::IntoIterator,
+                I::Item: core::borrow::Borrow<$t>,
+            {
+                for idx in indices {
+                    let idx = *core::borrow::Borrow::<$t>::borrow(&idx);
+                    f(idx, &mut self[idx])?
+                }
+                Ok(())
+            }
+
             /// Shrinks the capacity as much as possible.
             #[inline]
             pub fn shrink_to_fit(&mut self) {
@@ -222,6 +445,187 @@ macro_rules! map_codegen {
                 }
             }
 
+            $crate::non_strict! {
+                /// Retains the elements for which `f` returns `true`, `f` can mutate them.
+                ///
+                /// This function is unsafe for the logics of safe indices. This function voids indices
+                /// previously created and should be used with great care, see
+                /// [`Self::retain_indexed_mut`] to repair them.
+                #[inline]
+                pub fn retain_mut(&mut self, f: impl FnMut(&mut T) -> bool) {
+                    self.vec.retain_mut(f)
+                }
+            }
+            $crate::non_strict! {
+                /// Retains the elements for which `f` returns `true`, `f` can mutate them.
+                ///
+                /// Function `f` receives the index of the element *before* anything was removed.
+                /// Returns a map from old indices to new indices, `None` for elements that were
+                /// removed, so that side tables can be fixed.
+                #[inline]
+                pub fn retain_indexed_mut(
+                    &mut self,
+                    mut f: impl FnMut($t, &mut T) -> bool,
+                ) -> $map<Option<$t>> {
+                    let mut remap = $map::with_capacity(self.len());
+                    let (mut old, mut new) = (0, 0);
+                    self.vec.retain_mut(|elem| {
+                        let keep = f($t { val: old }, elem);
+                        old += 1;
+                        if keep {
+                            remap.vec.push(Some($t { val: new }));
+                            new += 1;
+                        } else {
+                            remap.vec.push(None);
+                        }
+                        keep
+                    });
+                    remap
+                }
+            }
+
+            $crate::non_strict! {
+                /// Lazily removes and yields the elements for which `pred` returns `true`.
+                ///
+                /// Same semantics as `Vec::extract_if`: elements are removed as the iterator
+                /// advances, and the remaining elements are shifted back when it is dropped. Both
+                /// `pred` and the iterator use the index of the element *before* extraction began,
+                /// which is not a legal index for this map anymore.
+                ///
+                /// This function is unsafe for the logics of safe indices. This function voids indices
+                /// previously created and should be used with great care.
+                #[inline]
+                pub fn extract_if<'a>(
+                    &'a mut self,
+                    mut pred: impl FnMut($t, &mut T) -> bool + 'a,
+                ) -> impl core::iter::Iterator<Item = ($t, T)> + 'a
+                where T: 'a {
+                    $crate::ExtractIf::new(&mut self.vec, move |idx, elem| pred($t { val: idx }, elem))
+                        .map(|(idx, elem)| ($t { val: idx }, elem))
+                }
+            }
+
+            $crate::non_strict! {
+                /// Replaces a range of the map with some elements, yields the removed elements.
+                ///
+                /// The range can be any of the typed ranges the map can be indexed with. Indices
+                /// after the range are shifted by the difference between the number of elements
+                /// inserted and the number of elements removed, which is returned alongside the
+                /// removed elements so that side tables can be fixed. Same as `Vec::splice`,
+                /// elements are removed even if the iterator is not consumed.
+                ///
+                /// This function is unsafe for the logics of safe indices. This function voids indices
+                /// previously created and should be used with great care.
+                pub fn splice<I>(
+                    &mut self,
+                    range: impl core::ops::RangeBounds<$t>,
+                    replace_with: I,
+                ) -> (isize, impl core::iter::Iterator<Item = T> + '_)
+                where I: core::iter::IntoIterator<Item = T> {
+                    use core::ops::Bound::*;
+                    let start = match range.start_bound() {
+                        Included(idx) => idx.val,
+                        Excluded(idx) => idx.val + 1,
+                        Unbounded => 0,
+                    };
+                    let end = match range.end_bound() {
+                        Included(idx) => idx.val + 1,
+                        Excluded(idx) => idx.val,
+                        Unbounded => self.len(),
+                    };
+                    let replace_with: $crate::alloc::vec::Vec<T> = replace_with.into_iter().collect();
+                    let shift = replace_with.len() as isize - end.saturating_sub(start) as isize;
+                    (shift, self.vec.splice(start..end, replace_with))
+                }
+            }
+
+            /// Sorts the map with a key extraction function, calling `f` once per element.
+            ///
+            /// The sort is stable. Returns the permutation applied to the map: a map from the
+            /// index of each element before the sort to its index after the sort, so that side
+            /// tables can be fixed.
+            ///
+            /// Keys are computed once and stored in a single buffer, which is also used to apply
+            /// the permutation in place.
+            pub fn sort_by_cached_key<K: Ord>(&mut self, mut f: impl FnMut(&T) -> K) -> $map<$t> {
+                let len = self.len();
+                let mut keys: $crate::alloc::vec::Vec<(K, usize)> = self.vec
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, elem)| (f(elem), idx))
+                    .collect();
+                // Stable since ties are broken by the original index.
+                keys.sort_unstable();
+
+                let mut perm = $map::of_elems($t { val: 0 }, len);
+                for (new, (_, old)) in keys.iter().enumerate() {
+                    perm.vec[*old] = $t { val: new };
+                }
+
+                // Apply the permutation: element at `new` must be the one at `keys[new].1`.
+                for start in 0..len {
+                    let mut current = start;
+                    loop {
+                        let source = keys[current].1;
+                        keys[current].1 = current;
+                        if source == start {
+                            break;
+                        }
+                        self.vec.swap(current, source);
+                        current = source;
+                    }
+                }
+
+                perm
+            }
+
+            /// Turns the map into a binary tree map from indices to elements.
+            #[inline]
+            pub fn into_btree_map(self) -> $crate::alloc::collections::BTreeMap<$t, T> {
+                self.into_index_iter().collect()
+            }
+            /// Builds a map from a binary tree map whose keys are exactly the indices `0..len`.
+            ///
+            /// Fails on the first index missing from the keys otherwise.
+            pub fn from_dense_btree_map(
+                map: $crate::alloc::collections::BTreeMap<$t, T>,
+            ) -> Result<Self, $crate::MissingIndex<$t>> {
+                let mut vec = $crate::alloc::vec::Vec::with_capacity(map.len());
+                for (idx, elem) in map {
+                    if idx.val != vec.len() {
+                        return Err($crate::MissingIndex { idx: $t { val: vec.len() } });
+                    }
+                    vec.push(elem);
+                }
+                Ok($map { vec })
+            }
+            $crate::hash_only! {
+                /// Turns the map into a hash map from indices to elements.
+                #[inline]
+                pub fn into_hash_map(self) -> $crate::__hash::HashMap<$t, T> {
+                    self.into_index_iter().collect()
+                }
+            }
+            $crate::hash_only! {
+                /// Builds a map from a hash map whose keys are exactly the indices `0..len`.
+                ///
+                /// Fails on the first index missing from the keys otherwise.
+                pub fn from_dense_hash_map<S>(
+                    mut map: $crate::__hash::HashMap<$t, T, S>,
+                ) -> Result<Self, $crate::MissingIndex<$t>>
+                where S: core::hash::BuildHasher {
+                    let len = map.len();
+                    let mut vec = $crate::alloc::vec::Vec::with_capacity(len);
+                    for val in 0..len {
+                        match map.remove(&$t { val }) {
+                            Some(elem) => vec.push(elem),
+                            None => return Err($crate::MissingIndex { idx: $t { val } }),
+                        }
+                    }
+                    Ok($map { vec })
+                }
+            }
+
             /// Splits the map into the elements before and after some index.
             ///
             /// More precisely, returns a tuple of
@@ -247,6 +651,65 @@ macro_rules! map_codegen {
                 );
                 (before, &self.vec[idx.val], after)
             }
+
+            /// Reorders the map so that the element at `n` is the one that would be there if the
+            /// map was sorted by `f`.
+            ///
+            /// Delegates to [`slice::select_nth_unstable_by_key`][select]: all elements before `n`
+            /// are smaller or equal to the pivot, all elements after it are greater or equal.
+            /// Returns the elements before `n`, the pivot and its index, and the elements after
+            /// `n`.
+            ///
+            /// **NB:** this changes the order of the elements, any index previously obtained for
+            /// this map may point to a different element afterwards.
+            ///
+            /// [select]: https://doc.rust-lang.org/std/primitive.slice.html#method.select_nth_unstable_by_key
+            #[inline]
+            pub fn select_nth_unstable_by_key<K: Ord>(
+                &mut self,
+                n: $t,
+                f: impl FnMut(&T) -> K,
+            ) -> (&mut [T], ($t, &mut T), &mut [T]) {
+                let (before, pivot, after) = self.vec.select_nth_unstable_by_key(n.get(), f);
+                (before, (n, pivot), after)
+            }
+
+            /// Iterator over the runs of consecutive elements related by `eq`.
+            ///
+            /// Two consecutive elements `a` and `b` are in the same run iff `eq(a, b)`. Yields
+            /// the index of the first element of each run, and the run itself.
+            #[inline]
+            pub fn runs_by<'a>(
+                &'a self,
+                mut eq: impl FnMut(&T, &T) -> bool + 'a,
+            ) -> impl core::iter::Iterator<Item = ($t, &'a [T])> + 'a
+            where T: 'a {
+                let mut start = 0;
+                core::iter::from_fn(move || {
+                    let vec = &self.vec;
+                    if start >= vec.len() {
+                        return None;
+                    }
+                    let mut end = start + 1;
+                    while end < vec.len() && eq(&vec[end - 1], &vec[end]) {
+                        end += 1
+                    }
+                    let run = ($t { val: start }, &vec[start..end]);
+                    start = end;
+                    Some(run)
+                })
+            }
+            /// Iterator over the runs of consecutive elements with the same key.
+            ///
+            /// See [`Self::runs_by`].
+            #[inline]
+            pub fn runs_by_key<'a, K: PartialEq>(
+                &'a self,
+                mut f: impl FnMut(&T) -> K + 'a,
+            ) -> impl core::iter::Iterator<Item = ($t, &'a [T])> + 'a
+            where T: 'a {
+                self.runs_by(move |a, b| f(a) == f(b))
+            }
         }
 
         impl<T: Clone> $map<T> {
@@ -255,6 +718,312 @@ macro_rules! map_codegen {
             pub fn of_elems(elem: T, size: usize) -> Self {
                 $map { vec: $crate::alloc::vec![ elem ; size ] }
             }
+
+            /// Builds a new map from the elements at some indices, in the order of the indices.
+            ///
+            /// Indices can appear several times, in any order. Also returns a map from the
+            /// indices of the new map to the index of the element they come from in `self`.
+            ///
+            /// Panics if an index is out of bounds, like indexing does.
+            pub fn gather<I>(&self, indices: I) -> (Self, $map<$t>)
+            where
+                I: core::iter::IntoIterator,
+                I::Item: core::borrow::Borrow<$t>,
+            {
+                let indices = indices.into_iter();
+                let capa = indices.size_hint().0;
+                let (mut elems, mut sources) = ($map::with_capacity(capa), $map::with_capacity(capa));
+                for (idx, elem) in self.select(indices) {
+                    elems.push(elem.clone());
+                    sources.push(idx);
+                }
+                (elems, sources)
+            }
+
+            /// Clones the elements of a range of the map to the range starting at `dest`.
+            ///
+            /// Counterpart of [`Self::copy_within`] for elements that are only `Clone`, with the
+            /// same semantics when ranges overlap.
+            ///
+            /// Panics if either range is inverted or out of bounds.
+            #[track_caller]
+            pub fn clone_within(&mut self, src: impl core::ops::RangeBounds<$t>, dest: $t) {
+                let src = $crate::oob::range::<$t>(
+                    stringify!($t), stringify!($map), self.vec.len(), &src
+                );
+                let count = src.end - src.start;
+                let _ = $crate::oob::range::<$t>(
+                    stringify!($t),
+                    stringify!($map),
+                    self.vec.len(),
+                    &(dest..$t { val: dest.val + count }),
+                );
+                if src.start < dest.val {
+                    for i in (0..count).rev() {
+                        let elem = self.vec[src.start + i].clone();
+                        self.vec[dest.val + i] = elem;
+                    }
+                } else {
+                    for i in 0..count {
+                        let elem = self.vec[src.start + i].clone();
+                        self.vec[dest.val + i] = elem;
+                    }
+                }
+            }
+
+            /// Clones the map into a binary tree map from indices to elements.
+            #[inline]
+            pub fn to_btree_map(&self) -> $crate::alloc::collections::BTreeMap<$t, T> {
+                self.index_iter().map(|(idx, elem)| (idx, elem.clone())).collect()
+            }
+            $crate::hash_only! {
+                /// Clones the map into a hash map from indices to elements.
+                #[inline]
+                pub fn to_hash_map(&self) -> $crate::__hash::HashMap<$t, T> {
+                    self.index_iter().map(|(idx, elem)| (idx, elem.clone())).collect()
+                }
+            }
+
+            /// Fills the map with clones of `value`.
+            #[inline]
+            pub fn fill(&mut self, value: T) {
+                self.vec.fill(value)
+            }
+            /// Fills a range of the map with clones of `value`.
+            ///
+            /// The range can be any of the typed ranges the map can be indexed with.
+            #[inline]
+            pub fn fill_range<R>(&mut self, range: R, value: T)
+            where Self: core::ops::IndexMut<R, Output = [T]> {
+                self[range].fill(value)
+            }
+        }
+
+        $crate::borsh_only! {
+            impl<T: $crate::borsh::BorshSerialize> $crate::borsh::BorshSerialize for $map<T> {
+                /// Serializes the map as a sequence, its length as a little-endian `u32` followed
+                /// by the elements.
+                #[inline]
+                fn serialize<W: $crate::borsh::io::Write>(
+                    &self,
+                    writer: &mut W,
+                ) -> $crate::borsh::io::Result<()> {
+                    $crate::borsh::BorshSerialize::serialize(&self.vec, writer)
+                }
+            }
+            $crate::non_strict! {
+                impl<T: $crate::borsh::BorshDeserialize> $crate::borsh::BorshDeserialize
+                for $map<T> {
+                    /// Deserializes a sequence, element `i` goes to index `i`.
+                    #[inline]
+                    fn deserialize_reader<R: $crate::borsh::io::Read>(
+                        reader: &mut R,
+                    ) -> $crate::borsh::io::Result<Self> {
+                        let vec = $crate::borsh::BorshDeserialize::deserialize_reader(reader)?;
+                        Ok($map { vec })
+                    }
+                }
+            }
+        }
+
+        $crate::index_vec_only! {
+            impl<T> core::convert::From<$map<T>> for $crate::index_vec::IndexVec<$t, T> {
+                /// Moves the elements of the map, without copying.
+                #[inline]
+                fn from(map: $map<T>) -> Self {
+                    $crate::index_vec::IndexVec::from_vec(map.vec)
+                }
+            }
+            impl<T> core::convert::From<$crate::index_vec::IndexVec<$t, T>> for $map<T> {
+                /// Moves the elements of the vector, without copying.
+                #[inline]
+                fn from(vec: $crate::index_vec::IndexVec<$t, T>) -> Self {
+                    $map { vec: vec.raw }
+                }
+            }
+        }
+        $crate::typed_index_collections_only! {
+            impl<T> core::convert::From<$map<T>>
+            for $crate::typed_index_collections::TiVec<$t, T> {
+                /// Moves the elements of the map, without copying.
+                #[inline]
+                fn from(map: $map<T>) -> Self {
+                    map.vec.into()
+                }
+            }
+            $crate::non_strict! {
+                impl<T> core::convert::From<$crate::typed_index_collections::TiVec<$t, T>>
+                for $map<T> {
+                    /// Moves the elements of the vector, without copying.
+                    #[inline]
+                    fn from(vec: $crate::typed_index_collections::TiVec<$t, T>) -> Self {
+                        $map { vec: vec.raw }
+                    }
+                }
+            }
+        }
+        $crate::schemars_only! {
+            impl<T: $crate::schemars::JsonSchema> $crate::schemars::JsonSchema for $map<T> {
+                fn schema_name() -> $crate::alloc::borrow::Cow<'static, str> {
+                    $crate::alloc::format!("{}_of_{}", stringify!($map), T::schema_name()).into()
+                }
+                fn schema_id() -> $crate::alloc::borrow::Cow<'static, str> {
+                    $crate::alloc::format!(
+                        "{}::{}<{}>", module_path!(), stringify!($map), T::schema_id()
+                    ).into()
+                }
+                /// Array of the elements, element `i` is the one of index `i`.
+                fn json_schema(
+                    generator: &mut $crate::schemars::SchemaGenerator,
+                ) -> $crate::schemars::Schema {
+                    $crate::schemars::json_schema!({
+                        "type": "array",
+                        "description": concat!("Map indexed by `", stringify!($t), "`."),
+                        "items": generator.subschema_for::<T>(),
+                    })
+                }
+            }
+        }
+
+        $crate::rayon_only! {
+            impl<T: Sync> $map<T> {
+                /// Sorts the map in parallel with a key extraction function.
+                ///
+                /// The sort is stable. Returns the permutation applied to the map: a map from the
+                /// index of each element before the sort to its index after the sort.
+                ///
+                /// The original indices are sorted in parallel by the key of their element, the
+                /// permutation is then applied in place.
+                pub fn par_sort_by_key<K: Ord + Send>(
+                    &mut self,
+                    f: impl Fn(&T) -> K + Sync,
+                ) -> $map<$t> {
+                    self.par_sort_with(|vec, olds| {
+                        $crate::rayon::slice::ParallelSliceMut::par_sort_by_key(olds, |&old| f(&vec[old]))
+                    })
+                }
+                /// Sorts the map in parallel with a key extraction function.
+                ///
+                /// The sort is unstable. Returns the permutation applied to the map: a map from the
+                /// index of each element before the sort to its index after the sort.
+                ///
+                /// The original indices are sorted in parallel by the key of their element, the
+                /// permutation is then applied in place.
+                pub fn par_sort_unstable_by_key<K: Ord + Send>(
+                    &mut self,
+                    f: impl Fn(&T) -> K + Sync,
+                ) -> $map<$t> {
+                    self.par_sort_with(|vec, olds| {
+                        $crate::rayon::slice::ParallelSliceMut::par_sort_unstable_by_key(
+                            olds, |&old| f(&vec[old])
+                        )
+                    })
+                }
+
+                /// Sorts the original indices of the elements using `sort`, then applies the
+                /// permutation in place.
+                ///
+                /// The elements are not moved until `sort` returns, so the map stays complete if
+                /// it panics.
+                fn par_sort_with(
+                    &mut self,
+                    sort: impl FnOnce(&[T], &mut [usize]),
+                ) -> $map<$t> {
+                    let len = self.len();
+                    let mut olds: $crate::alloc::vec::Vec<usize> = (0..len).collect();
+                    sort(&self.vec, &mut olds);
+
+                    let mut perm = $map::of_elems($t { val: 0 }, len);
+                    for (new, old) in olds.iter().enumerate() {
+                        perm.vec[*old] = $t { val: new };
+                    }
+
+                    // Apply the permutation: element at `new` must be the one at `olds[new]`.
+                    for start in 0..len {
+                        let mut current = start;
+                        loop {
+                            let source = olds[current];
+                            olds[current] = current;
+                            if source == start {
+                                break;
+                            }
+                            self.vec.swap(current, source);
+                            current = source;
+                        }
+                    }
+
+                    perm
+                }
+            }
+        }
+
+        impl<T: Copy> $map<T> {
+            /// Copies the elements of a range of the map to the range starting at `dest`.
+            ///
+            /// The range can be any of the typed ranges the map can be indexed with. Delegates to
+            /// `slice::copy_within`, so the ranges can overlap.
+            ///
+            /// Panics if either range is inverted or out of bounds.
+            #[inline]
+            #[track_caller]
+            pub fn copy_within(&mut self, src: impl core::ops::RangeBounds<$t>, dest: $t) {
+                let src = $crate::oob::range::<$t>(
+                    stringify!($t), stringify!($map), self.vec.len(), &src
+                );
+                let _ = $crate::oob::range::<$t>(
+                    stringify!($t),
+                    stringify!($map),
+                    self.vec.len(),
+                    &(dest..$t { val: dest.val + (src.end - src.start) }),
+                );
+                self.vec.copy_within(src, dest.val)
+            }
+        }
+
+        impl<T: Default> $map<T> {
+            /// Takes the element at some index, leaving the default value in its place.
+            ///
+            /// Panics if the index is out of bounds, like indexing does.
+            #[inline]
+            #[track_caller]
+            pub fn take(&mut self, idx: $t) -> T {
+                core::mem::take(&mut self[idx])
+            }
+            /// Creates a map of default elements with the same length as another map.
+            ///
+            /// The indices of `other` are legal for the new map.
+            #[inline]
+            pub fn new_like<U>(other: &$map<U>) -> Self {
+                $map { vec: other.iter().map(|_| T::default()).collect() }
+            }
+        }
+
+        impl<T: PartialEq> $map<T> {
+            /// Iterator over the entries that differ between two maps.
+            ///
+            /// Yields the index and the element of each map, if any: all indices past the length
+            /// of the shorter map differ.
+            #[inline]
+            pub fn diff<'a>(
+                &'a self,
+                other: &'a Self,
+            ) -> impl core::iter::Iterator<Item = ($t, Option<&'a T>, Option<&'a T>)> + 'a {
+                (0..core::cmp::max(self.len(), other.len())).filter_map(move |val| {
+                    let (mine, theirs) = (self.vec.get(val), other.vec.get(val));
+                    if mine == theirs {
+                        None
+                    } else {
+                        Some(($t { val }, mine, theirs))
+                    }
+                })
+            }
+            /// Set of the indices of the entries that differ between two maps.
+            ///
+            /// All indices past the length of the shorter map differ.
+            #[inline]
+            pub fn diff_indices(&self, other: &Self) -> $crate::alloc::collections::BTreeSet<$t> {
+                self.diff(other).map(|(idx, _, _)| idx).collect()
+            }
         }
 
         impl<T> core::iter::IntoIterator for $map<T> {
@@ -278,6 +1047,44 @@ macro_rules! map_codegen {
                 self.iter_mut()
             }
         }
+        impl<T> core::convert::From<$map<T>> for $crate::IdxVec<$t, T> {
+            #[inline]
+            fn from(map: $map<T>) -> Self {
+                // Collecting a vector's `IntoIter` into a vector reuses its buffer.
+                map.vec.into_iter().collect()
+            }
+        }
+        impl<T> core::convert::From<$crate::IdxVec<$t, T>> for $map<T> {
+            #[inline]
+            fn from(map: $crate::IdxVec<$t, T>) -> Self {
+                $map { vec: map.into_vec() }
+            }
+        }
+        impl<T> $crate::SafeMap for $map<T> {
+            type Idx = $t;
+            type Elem = T;
+            #[inline]
+            fn len(&self) -> usize {
+                self.vec.len()
+            }
+            #[inline]
+            fn push(&mut self, elem: T) -> $t {
+                $map::push(self, elem)
+            }
+            #[inline]
+            fn get(&self, idx: $t) -> Option<&T> {
+                self.vec.get(idx.val)
+            }
+            #[inline]
+            fn get_mut(&mut self, idx: $t) -> Option<&mut T> {
+                self.vec.get_mut(idx.val)
+            }
+            #[inline]
+            fn index_from_usize(&self, n: usize) -> Option<$t> {
+                $map::index_from_usize(self, n)
+            }
+        }
+
         impl<T> core::iter::FromIterator<T> for $map<T> {
             fn from_iter<
                 I: core::iter::IntoIterator<Item = T>
@@ -285,55 +1092,61 @@ macro_rules! map_codegen {
                 $map { vec: iter.into_iter().collect() }
             }
         }
+        /// Panics if an index is not the next free index, see `try_extend_pairs`.
+        impl<T> core::iter::Extend<($t, T)> for $map<T> {
+            fn extend<I: core::iter::IntoIterator<Item = ($t, T)>>(&mut self, pairs: I) {
+                if let Err(e) = self.try_extend_pairs(pairs) {
+                    panic!("{}", e)
+                }
+            }
+        }
         impl<T> core::ops::Index<$t> for $map<T> {
             type Output = T ;
+            #[inline]
+            #[track_caller]
             fn index(& self, index: $t) -> & T {
-                & self.vec[ index.get() ]
-            }
-        }
-        impl<T> core::ops::Index<core::ops::RangeFrom<$t>> for $map<T> {
-            type Output = [T];
-            fn index(& self, core::ops::RangeFrom { start }: core::ops::RangeFrom<$t>) -> &[T] {
-                & self.vec[ start.get() .. ]
-            }
-        }
-        impl<T> core::ops::Index<core::ops::Range<$t>> for $map<T> {
-            type Output = [T];
-            fn index(& self, core::ops::Range { start, end }: core::ops::Range<$t>) -> &[T] {
-                & self.vec[ start.get() .. end.get() ]
-            }
-        }
-        impl<T> core::ops::Index<core::ops::RangeInclusive<$t>> for $map<T> {
-            type Output = [T];
-            fn index(& self, range: core::ops::RangeInclusive<$t>) -> &[T] {
-                & self.vec[ range.start().get() ..= range.end().get() ]
+                match self.vec.get(index.val) {
+                    Some(elem) => elem,
+                    None => $crate::oob::index(
+                        stringify!($t), index.val, stringify!($map), self.vec.len()
+                    ),
+                }
             }
         }
-        impl<T> core::ops::Index<core::ops::RangeFull> for $map<T> {
-            type Output = [T];
-            fn index(& self, _: core::ops::RangeFull) -> &[T] {
-                & self.vec[..]
+        impl<T> core::ops::IndexMut<$t> for $map<T> {
+            #[inline]
+            #[track_caller]
+            fn index_mut(&mut self, index: $t) -> &mut T {
+                let len = self.vec.len();
+                match self.vec.get_mut(index.val) {
+                    Some(elem) => elem,
+                    None => $crate::oob::index(stringify!($t), index.val, stringify!($map), len),
+                }
             }
         }
-        impl<T> core::ops::Index<core::ops::RangeTo<$t>> for $map<T> {
-            type Output = [T];
-            fn index(& self, core::ops::RangeTo { end }: core::ops::RangeTo<$t>) -> &[T] {
-                & self.vec[..end.get()]
+        impl<'a, T> core::ops::Index<&'a $t> for $map<T> {
+            type Output = T ;
+            #[inline]
+            #[track_caller]
+            fn index(& self, index: &'a $t) -> & T {
+                & self[ *index ]
             }
         }
-        impl<T> core::ops::Index<core::ops::RangeToInclusive<$t>> for $map<T> {
-            type Output = [T];
-            fn index(
-                & self,
-                core::ops::RangeToInclusive { end }: core::ops::RangeToInclusive<$t>
-            ) -> &[T] {
-                & self.vec[..=end.get()]
+        impl<'a, T> core::ops::IndexMut<&'a $t> for $map<T> {
+            #[inline]
+            #[track_caller]
+            fn index_mut(&mut self, index: &'a $t) -> &mut T {
+                &mut self[ *index ]
             }
         }
-        impl<T> core::ops::IndexMut<$t> for $map<T> {
-            fn index_mut(&mut self, index: $t) -> &mut T {
-                &mut self.vec[ index.get() ]
-            }
+        $crate::map_range_index_codegen! {
+            $t, $map,
+            core::ops::Range<$t>,
+            core::ops::RangeInclusive<$t>,
+            core::ops::RangeFrom<$t>,
+            core::ops::RangeTo<$t>,
+            core::ops::RangeToInclusive<$t>,
+            core::ops::RangeFull,
         }
 
         $crate::non_strict! {
@@ -388,3 +1201,217 @@ macro_rules! map_codegen {
         $crate::handle!{ $t $($tail)* }
     };
 }
+
+/// Generates `Index` and `IndexMut` implementations over some typed ranges for a map.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! map_range_index_codegen {
+    ($t:ident, $map:ident, $($range:ty),* $(,)?) => {$(
+        impl<T> core::ops::Index<$range> for $map<T> {
+            type Output = [T];
+            #[inline]
+            #[track_caller]
+            fn index(& self, range: $range) -> &[T] {
+                let range = $crate::oob::range::<$t>(
+                    stringify!($t), stringify!($map), self.vec.len(), &range
+                );
+                & self.vec[range]
+            }
+        }
+        impl<T> core::ops::IndexMut<$range> for $map<T> {
+            #[inline]
+            #[track_caller]
+            fn index_mut(&mut self, range: $range) -> &mut [T] {
+                let range = $crate::oob::range::<$t>(
+                    stringify!($t), stringify!($map), self.vec.len(), &range
+                );
+                &mut self.vec[range]
+            }
+        }
+    )*};
+}
+
+/// Fixed element type map code generation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! fixed_map_codegen {
+    ($t:ident,
+        $(#[$meta:meta])*
+        $map:ident of $elem:ty
+        $(, $($tail:tt)*)?
+    ) => {
+        $(#[$meta])*
+        ///
+        /// Map from indices to
+        #[doc = concat!("`", stringify!($elem), "`s.")]
+        /// Dereferences to [`IdxVec`]($crate::IdxVec) for the API of dense maps.
+        #[repr(transparent)]
+        pub struct $map {
+            map: $crate::IdxVec<$t, $elem>,
+        }
+
+        impl $map {
+            /// Creates an empty map.
+            #[inline]
+            pub const fn new() -> Self {
+                $map { map: $crate::IdxVec::new() }
+            }
+            /// Creates an empty map with some capacity.
+            #[inline]
+            pub fn with_capacity(capacity: usize) -> Self {
+                $map { map: $crate::IdxVec::with_capacity(capacity) }
+            }
+            /// Turns the map into its generic form.
+            #[inline]
+            pub fn into_generic(self) -> $crate::IdxVec<$t, $elem> {
+                self.map
+            }
+        }
+
+        impl core::default::Default for $map {
+            #[inline]
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+        impl core::ops::Deref for $map {
+            type Target = $crate::IdxVec<$t, $elem>;
+            #[inline]
+            fn deref(&self) -> &Self::Target {
+                &self.map
+            }
+        }
+        impl core::ops::DerefMut for $map {
+            #[inline]
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.map
+            }
+        }
+        impl core::convert::From<$crate::IdxVec<$t, $elem>> for $map {
+            #[inline]
+            fn from(map: $crate::IdxVec<$t, $elem>) -> Self {
+                $map { map }
+            }
+        }
+        impl core::convert::From<$map> for $crate::IdxVec<$t, $elem> {
+            #[inline]
+            fn from(map: $map) -> Self {
+                map.map
+            }
+        }
+
+        impl core::iter::FromIterator<$elem> for $map {
+            #[inline]
+            fn from_iter<I: core::iter::IntoIterator<Item = $elem>>(iter: I) -> Self {
+                $map { map: iter.into_iter().collect() }
+            }
+        }
+        impl core::iter::IntoIterator for $map {
+            type Item = $elem;
+            type IntoIter = $crate::alloc::vec::IntoIter<$elem>;
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.map.into_iter()
+            }
+        }
+        impl<'a> core::iter::IntoIterator for &'a $map {
+            type Item = &'a $elem;
+            type IntoIter = core::slice::Iter<'a, $elem>;
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.map.iter()
+            }
+        }
+        impl<'a> core::iter::IntoIterator for &'a mut $map {
+            type Item = &'a mut $elem;
+            type IntoIter = core::slice::IterMut<'a, $elem>;
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.map.iter_mut()
+            }
+        }
+
+        $crate::handle!{ $t $(, $($tail)*)? }
+    };
+}
+
+/// Iterator behind the `extract_if` method of maps, yields elements with their original position.
+///
+/// Kept elements are shifted back as the iterator advances, the ones not visited yet are shifted
+/// back when it is dropped. The length of the vector is zero in the meantime, so leaking the
+/// iterator leaks the elements instead of dropping them twice.
+#[doc(hidden)]
+pub struct ExtractIf<'a, T, F> {
+    /// Vector elements are extracted from.
+    vec: &'a mut alloc::vec::Vec<T>,
+    /// Position of the next element to visit.
+    read: usize,
+    /// Number of elements kept so far, also the position of the next element kept.
+    write: usize,
+    /// Length of the vector before extraction began.
+    len: usize,
+    /// Extracts an element when it returns `true`.
+    pred: F,
+}
+impl<'a, T, F> ExtractIf<'a, T, F>
+where
+    F: FnMut(usize, &mut T) -> bool,
+{
+    /// Constructor.
+    #[inline]
+    pub fn new(vec: &'a mut alloc::vec::Vec<T>, pred: F) -> Self {
+        let len = vec.len();
+        // Sound: the elements are still initialized, they are handed back in `drop`.
+        unsafe { vec.set_len(0) }
+        ExtractIf {
+            vec,
+            read: 0,
+            write: 0,
+            len,
+            pred,
+        }
+    }
+}
+impl<T, F> Iterator for ExtractIf<'_, T, F>
+where
+    F: FnMut(usize, &mut T) -> bool,
+{
+    type Item = (usize, T);
+    fn next(&mut self) -> Option<(usize, T)> {
+        let base = self.vec.as_mut_ptr();
+        while self.read < self.len {
+            let idx = self.read;
+            // Sound: the element at `read` is initialized and not aliased, if `pred` panics it is
+            // shifted back in `drop` since `read` has not moved yet.
+            let extract = (self.pred)(idx, unsafe { &mut *base.add(idx) });
+            self.read += 1;
+            if extract {
+                // Sound: the element is initialized, and is not visited nor shifted back anymore.
+                return Some((idx, unsafe { base.add(idx).read() }));
+            }
+            if idx != self.write {
+                // Sound: `write < idx`, the element at `write` was moved out or shifted already.
+                unsafe { core::ptr::copy_nonoverlapping(base.add(idx), base.add(self.write), 1) }
+            }
+            self.write += 1;
+        }
+        None
+    }
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.len - self.read))
+    }
+}
+impl<T, F> Drop for ExtractIf<'_, T, F> {
+    fn drop(&mut self) {
+        let tail = self.len - self.read;
+        let base = self.vec.as_mut_ptr();
+        if self.read != self.write {
+            // Sound: the `tail` elements from `read` are initialized, the ones before `write`
+            // are the kept elements, and the ones in between were moved out or shifted.
+            unsafe { core::ptr::copy(base.add(self.read), base.add(self.write), tail) }
+        }
+        // Sound: the first `write + tail` elements are initialized.
+        unsafe { self.vec.set_len(self.write + tail) }
+    }
+}
@@ -0,0 +1,202 @@
+//! Handles matrix code generation.
+
+/// Creates a dense, row-major matrix indexed by pairs of indices from two index families.
+///
+/// The syntax is `<Matrix><<Row>, <Col>>, row: <ColSlice>` where `<Row>` and `<Col>` are index
+/// types and `<ColSlice>` is the `slice` type of `<Col>`: rows of the matrix are `<ColSlice>`s.
+///
+/// The matrix builds its rows with the private constructors of `<ColSlice>`, so `matrix!` must be
+/// invoked in the same module as the `new!` declaring `<ColSlice>`.
+///
+/// ```rust
+/// safe_index::new! {
+///     /// Indices of clients.
+///     Client,
+///     /// Map from clients to something.
+///     map: Clients,
+/// }
+/// safe_index::new! {
+///     /// Indices of files.
+///     File,
+///     /// Map from files to something.
+///     map: Files,
+///     /// Slice of things indexed by files.
+///     slice: FileSlice,
+/// }
+/// safe_index::matrix! {
+///     /// Matrix from clients and files to something.
+///     ClientFileMatrix<Client, File>, row: FileSlice
+/// }
+///
+/// let clients: Clients<_> = vec!["client 0", "client 1"].into_iter().collect();
+/// let files: Files<_> = vec!["file 0", "file 1", "file 2"].into_iter().collect();
+///
+/// let mut owns = ClientFileMatrix::new(clients.len(), files.len(), false);
+/// let (c_1, f_2) = (clients.last_index().unwrap(), files.last_index().unwrap());
+/// owns[(c_1, f_2)] = true;
+///
+/// assert!(owns[(c_1, f_2)]);
+/// assert_eq! { owns.row(c_1).as_slice(), [false, false, true] }
+/// assert_eq! { owns.rows().filter(|row| row.iter().any(|b| *b)).count(), 1 }
+/// ```
+#[macro_export]
+macro_rules! matrix {
+    (
+        $(#[$meta:meta])*
+        $matrix:ident<$row:ident, $col:ident>, row: $slice:ident $(,)?
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub struct $matrix<T> {
+            /// Number of rows.
+            rows: usize,
+            /// Number of columns.
+            cols: usize,
+            /// Elements, row after row.
+            vec: $crate::alloc::vec::Vec<T>,
+        }
+
+        impl<T> $matrix<T> {
+            /// Creates a matrix with `rows` rows and `cols` columns, all filled with `fill`.
+            ///
+            /// Panics if the number of elements overflows.
+            #[track_caller]
+            pub fn new(rows: usize, cols: usize, fill: T) -> Self
+            where
+                T: Clone,
+            {
+                let len = rows
+                    .checked_mul(cols)
+                    .expect("matrix dimensions overflow");
+                $matrix {
+                    rows,
+                    cols,
+                    vec: $crate::alloc::vec![fill; len],
+                }
+            }
+
+            /// Number of rows.
+            #[inline]
+            pub const fn row_count(&self) -> usize {
+                self.rows
+            }
+            /// Number of columns.
+            #[inline]
+            pub const fn col_count(&self) -> usize {
+                self.cols
+            }
+            /// Dimensions of the matrix, as `(rows, columns)`.
+            #[inline]
+            pub const fn dims(&self) -> (usize, usize) {
+                (self.rows, self.cols)
+            }
+            /// True if the matrix has no elements.
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.vec.is_empty()
+            }
+
+            /// Offset of a row in `vec`, panics if the row is out of bounds.
+            #[inline]
+            #[track_caller]
+            fn row_offset(&self, row: $row) -> usize {
+                if row.get() >= self.rows {
+                    $crate::oob::index(
+                        stringify!($row),
+                        row.get(),
+                        concat!("the rows of ", stringify!($matrix)),
+                        self.rows,
+                    )
+                }
+                row.get() * self.cols
+            }
+            /// Offset of an element in `vec`, panics if the row or the column is out of bounds.
+            #[inline]
+            #[track_caller]
+            fn offset(&self, row: $row, col: $col) -> usize {
+                let row_offset = self.row_offset(row);
+                if col.get() >= self.cols {
+                    $crate::oob::index(
+                        stringify!($col),
+                        col.get(),
+                        concat!("the columns of ", stringify!($matrix)),
+                        self.cols,
+                    )
+                }
+                row_offset + col.get()
+            }
+
+            /// Retrieves an element, `None` if the row or the column is out of bounds.
+            #[inline]
+            pub fn get(&self, row: $row, col: $col) -> Option<&T> {
+                if row.get() < self.rows && col.get() < self.cols {
+                    self.vec.get(row.get() * self.cols + col.get())
+                } else {
+                    None
+                }
+            }
+            /// Retrieves an element, `None` if the row or the column is out of bounds.
+            #[inline]
+            pub fn get_mut(&mut self, row: $row, col: $col) -> Option<&mut T> {
+                if row.get() < self.rows && col.get() < self.cols {
+                    self.vec.get_mut(row.get() * self.cols + col.get())
+                } else {
+                    None
+                }
+            }
+
+            /// A row of the matrix, panics if the row is out of bounds.
+            #[inline]
+            #[track_caller]
+            pub fn row(&self, row: $row) -> &$slice<T> {
+                let start = self.row_offset(row);
+                $slice::of_slice(&self.vec[start..start + self.cols])
+            }
+            /// A mutable row of the matrix, panics if the row is out of bounds.
+            #[inline]
+            #[track_caller]
+            pub fn row_mut(&mut self, row: $row) -> &mut $slice<T> {
+                let start = self.row_offset(row);
+                $slice::of_slice_mut(&mut self.vec[start..start + self.cols])
+            }
+            /// Iterator over the rows of the matrix, in order.
+            pub fn rows(
+                &self,
+            ) -> impl core::iter::DoubleEndedIterator<Item = &$slice<T>>
+                   + core::iter::ExactSizeIterator
+                   + core::iter::FusedIterator {
+                let (vec, cols) = (&self.vec, self.cols);
+                (0..self.rows).map(move |row| {
+                    let start = row * cols;
+                    $slice::of_slice(&vec[start..start + cols])
+                })
+            }
+
+            /// Overwrites all the elements of the matrix with `value`.
+            #[inline]
+            pub fn fill(&mut self, value: T)
+            where
+                T: Clone,
+            {
+                self.vec.fill(value)
+            }
+        }
+
+        impl<T> core::ops::Index<($row, $col)> for $matrix<T> {
+            type Output = T;
+            #[inline]
+            #[track_caller]
+            fn index(&self, (row, col): ($row, $col)) -> &T {
+                &self.vec[self.offset(row, col)]
+            }
+        }
+        impl<T> core::ops::IndexMut<($row, $col)> for $matrix<T> {
+            #[inline]
+            #[track_caller]
+            fn index_mut(&mut self, (row, col): ($row, $col)) -> &mut T {
+                let offset = self.offset(row, col);
+                &mut self.vec[offset]
+            }
+        }
+    };
+}
@@ -0,0 +1,93 @@
+//! Out-of-bounds checks and panics for the generated collections.
+//!
+//! Panics are produced by cold functions so that the happy path stays small.
+
+use core::ops::{Bound, RangeBounds};
+
+/// Displays a range of indices as `Idx(start)..Idx(end)`.
+struct RangeFmt {
+    idx: &'static str,
+    start: Bound<usize>,
+    end: Bound<usize>,
+}
+impl core::fmt::Display for RangeFmt {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self.start {
+            Bound::Included(start) => write!(fmt, "{}({})", self.idx, start)?,
+            Bound::Excluded(start) => write!(fmt, "{}({}) (excluded)", self.idx, start)?,
+            Bound::Unbounded => (),
+        }
+        match self.end {
+            Bound::Included(end) => write!(fmt, "..={}({})", self.idx, end),
+            Bound::Excluded(end) => write!(fmt, "..{}({})", self.idx, end),
+            Bound::Unbounded => write!(fmt, ".."),
+        }
+    }
+}
+
+/// Panics for an index out of the bounds of a collection.
+#[cold]
+#[inline(never)]
+#[track_caller]
+pub fn index(idx: &'static str, val: usize, coll: &'static str, len: usize) -> ! {
+    panic!(
+        "index {}({}) out of bounds for {} of length {}",
+        idx, val, coll, len
+    )
+}
+
+/// Panics for a range that is inverted or out of the bounds of a collection.
+#[cold]
+#[inline(never)]
+#[track_caller]
+fn range_panic(
+    idx: &'static str,
+    start: Bound<usize>,
+    end: Bound<usize>,
+    inverted: bool,
+    coll: &'static str,
+    len: usize,
+) -> ! {
+    let range = RangeFmt { idx, start, end };
+    if inverted {
+        panic!(
+            "range {} is inverted, indexing {} of length {}",
+            range, coll, len
+        )
+    } else {
+        panic!(
+            "range {} out of bounds for {} of length {}",
+            range, coll, len
+        )
+    }
+}
+
+/// Turns a range of indices into a legal range of `usize`s for a collection of length `len`.
+///
+/// Panics if the range is inverted or out of bounds.
+#[inline]
+#[track_caller]
+pub fn range<I: Copy + Into<usize>>(
+    idx: &'static str,
+    coll: &'static str,
+    len: usize,
+    range: &impl RangeBounds<I>,
+) -> core::ops::Range<usize> {
+    let start = range.start_bound().map(|i| (*i).into());
+    let end = range.end_bound().map(|i| (*i).into());
+    let lo = match start {
+        Bound::Included(lo) => Some(lo),
+        Bound::Excluded(lo) => lo.checked_add(1),
+        Bound::Unbounded => Some(0),
+    };
+    let hi = match end {
+        Bound::Included(hi) => hi.checked_add(1),
+        Bound::Excluded(hi) => Some(hi),
+        Bound::Unbounded => Some(len),
+    };
+    match (lo, hi) {
+        (Some(lo), Some(hi)) if lo <= hi && hi <= len => lo..hi,
+        (Some(lo), Some(hi)) if lo > hi => range_panic(idx, start, end, true, coll, len),
+        _ => range_panic(idx, start, end, false, coll, len),
+    }
+}
@@ -0,0 +1,513 @@
+//! Handles range code generation.
+
+/// Range code generation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! range_codegen {
+    ($t:ident,
+        $(#[$meta:meta])*
+        $range:ident
+        $($tail:tt)*
+    ) => {
+        $(#[$meta])*
+        #[derive(Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct $range {
+            start: $t,
+            end: $t,
+        }
+
+        impl $range {
+            /// Creates a range from `start` (inclusive) to `end` (exclusive).
+            ///
+            /// If `start > end`, the range is silently empty; use [`Self::try_new`] to reject such
+            /// bounds.
+            #[inline]
+            pub const fn new(start: $t, end: $t) -> Self {
+                $range { start, end }
+            }
+            $crate::non_strict! {
+                /// Creates a range of `len` indices starting at `start`.
+                ///
+                /// Panics if `start + len` overflows.
+                #[inline]
+                #[track_caller]
+                pub const fn with_len(start: $t, len: usize) -> Self {
+                    match start.val.checked_add(len) {
+                        Some(val) => $range { start, end: $t { val } },
+                        None => panic!("range length overflows the index type"),
+                    }
+                }
+            }
+            /// Creates a range from `start` (inclusive) to `end` (exclusive), fails if `start > end`.
+            #[inline]
+            pub const fn try_new(start: $t, end: $t) -> Result<Self, $crate::InvalidRange<$t>> {
+                if start.val <= end.val {
+                    Ok($range { start, end })
+                } else {
+                    Err($crate::InvalidRange { start, end })
+                }
+            }
+            /// Start of the range (inclusive).
+            #[inline]
+            pub const fn start(&self) -> $t {
+                self.start
+            }
+            /// End of the range (exclusive).
+            #[inline]
+            pub const fn end(&self) -> $t {
+                self.end
+            }
+            /// Start of the range and its length.
+            #[inline]
+            pub const fn len_pair(self) -> ($t, usize) {
+                (self.start, self.len())
+            }
+            /// Number of indices in the range, zero if `start >= end`.
+            #[inline]
+            pub const fn len(&self) -> usize {
+                self.end.val.saturating_sub(self.start.val)
+            }
+            /// True if the range contains no index.
+            #[inline]
+            pub const fn is_empty(&self) -> bool {
+                self.start.val >= self.end.val
+            }
+            /// True if `start <= idx < end`.
+            #[inline]
+            pub const fn contains(&self, idx: $t) -> bool {
+                self.start.val <= idx.val && idx.val < self.end.val
+            }
+            /// True if all the indices of `inner` are in `self`.
+            ///
+            /// An empty `inner` range is contained in any range, including empty ones.
+            #[inline]
+            pub const fn contains_range(&self, inner: Self) -> bool {
+                inner.is_empty() || (self.start.val <= inner.start.val && inner.end.val <= self.end.val)
+            }
+            /// True if all the indices of `self` are in `outer`, see [`Self::contains_range`].
+            #[inline]
+            pub const fn is_subrange_of(&self, outer: &Self) -> bool {
+                outer.contains_range(*self)
+            }
+            /// Indices in both `self` and `other`, `None` if there are none.
+            ///
+            /// Touching ranges such as `0..2` and `2..4` do not intersect, and neither do empty
+            /// ranges.
+            #[inline]
+            pub const fn intersect(self, other: Self) -> Option<Self> {
+                let start = if self.start.val < other.start.val { other.start } else { self.start };
+                let end = if self.end.val < other.end.val { self.end } else { other.end };
+                if start.val < end.val {
+                    Some($range { start, end })
+                } else {
+                    None
+                }
+            }
+            /// Smallest range containing both `self` and `other`.
+            ///
+            /// Empty ranges are ignored: the hull of an empty range and `other` is `other`, and the
+            /// hull of two empty ranges is `self`.
+            #[inline]
+            pub const fn hull(self, other: Self) -> Self {
+                if other.is_empty() {
+                    self
+                } else if self.is_empty() {
+                    other
+                } else {
+                    let start = if self.start.val < other.start.val { self.start } else { other.start };
+                    let end = if self.end.val < other.end.val { other.end } else { self.end };
+                    $range { start, end }
+                }
+            }
+            /// True if `self` and `other` have at least one index in common.
+            #[inline]
+            pub const fn overlaps(&self, other: &Self) -> bool {
+                self.intersect(*other).is_some()
+            }
+            /// Splits the range into at most `n` contiguous, non-empty sub-ranges of near-equal size.
+            ///
+            /// The first `len % n` sub-ranges have one more index than the others. Yields `len`
+            /// sub-ranges if `n > len`, and nothing if the range is empty. Panics if `n` is zero.
+            #[track_caller]
+            pub fn split_into(self, n: usize) -> impl Iterator<Item = $range> {
+                assert!(n != 0, "cannot split a range into zero sub-ranges");
+                let len = self.len();
+                let count = core::cmp::min(n, len);
+                let (base, rem) = if count == 0 { (0, 0) } else { (len / count, len % count) };
+                let mut start = self.start;
+                let mut chunk = 0;
+                core::iter::from_fn(move || {
+                    if chunk < count {
+                        let end = $t { val: start.val + base + if chunk < rem { 1 } else { 0 } };
+                        let res = $range::new(start, end);
+                        start = end;
+                        chunk += 1;
+                        Some(res)
+                    } else {
+                        None
+                    }
+                })
+            }
+            /// Splits the range into contiguous sub-ranges of `chunk_len` indices.
+            ///
+            /// The last sub-range is shorter if `chunk_len` does not divide the length of the range.
+            /// Panics if `chunk_len` is zero.
+            #[track_caller]
+            pub fn chunks(self, chunk_len: usize) -> impl Iterator<Item = $range> {
+                assert!(chunk_len != 0, "cannot split a range into chunks of length zero");
+                let mut rest = self;
+                core::iter::from_fn(move || {
+                    if rest.is_empty() {
+                        None
+                    } else {
+                        let end = $t { val: rest.start.val + core::cmp::min(chunk_len, rest.len()) };
+                        let res = $range::new(rest.start, end);
+                        rest.start = end;
+                        Some(res)
+                    }
+                })
+            }
+            /// Iterator over every `step`-th index of the range, starting at `start`.
+            ///
+            /// Panics if `step` is zero.
+            #[inline]
+            #[track_caller]
+            pub fn step_by_typed(self, step: usize) -> $crate::SteppedRange<$range> {
+                $crate::SteppedRange::new(self, step)
+            }
+        }
+
+        impl $t {
+            /// Range from `self` (inclusive) to `end` (exclusive).
+            #[inline]
+            pub const fn up_to(self, end: $t) -> $range {
+                $range::new(self, end)
+            }
+            $crate::non_strict! {
+                /// Range of `len` indices starting at `self`.
+                ///
+                /// Panics if `self + len` overflows.
+                #[inline]
+                #[track_caller]
+                pub const fn span(self, len: usize) -> $range {
+                    $range::with_len(self, len)
+                }
+            }
+            /// Descending iterator from `self` (exclusive) down to `end` (inclusive).
+            ///
+            /// Yields the same indices as `end.up_to(self)` in reverse order, nothing if `self <=
+            /// end`.
+            #[inline]
+            pub fn down_to(self, end: $t) -> core::iter::Rev<$range> {
+                core::iter::Iterator::rev($range::new(end, self))
+            }
+        }
+
+        impl core::convert::From<core::ops::Range<$t>> for $range {
+            /// Same as [`Self::new`], an inverted range yields an empty range.
+            #[inline]
+            fn from(range: core::ops::Range<$t>) -> Self {
+                $range::new(range.start, range.end)
+            }
+        }
+        impl core::convert::From<$range> for core::ops::Range<$t> {
+            /// An inverted range yields an empty range at `start`.
+            #[inline]
+            fn from(range: $range) -> Self {
+                let end = if range.start.val < range.end.val { range.end } else { range.start };
+                range.start..end
+            }
+        }
+        $crate::non_strict! {
+            impl core::convert::From<core::ops::Range<usize>> for $range {
+                /// Same as [`Self::new`], an inverted range yields an empty range.
+                #[inline]
+                fn from(range: core::ops::Range<usize>) -> Self {
+                    $range::new($t { val: range.start }, $t { val: range.end })
+                }
+            }
+        }
+
+        impl core::ops::RangeBounds<$t> for $range {
+            #[inline]
+            fn start_bound(&self) -> core::ops::Bound<&$t> {
+                core::ops::Bound::Included(&self.start)
+            }
+            #[inline]
+            fn end_bound(&self) -> core::ops::Bound<&$t> {
+                core::ops::Bound::Excluded(&self.end)
+            }
+        }
+
+        impl core::fmt::Display for $range {
+            /// Formats the range as `start..end`.
+            fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+                write!(fmt, "{}..{}", self.start, self.end)
+            }
+        }
+        impl core::fmt::Debug for $range {
+            /// Formats the range as `start..end`.
+            fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+                core::fmt::Display::fmt(self, fmt)
+            }
+        }
+
+        $crate::serde_only! {
+            impl $crate::serde::Serialize for $range {
+                /// Serializes the range as a `[start, end]` array.
+                fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+                where
+                    S: $crate::serde::Serializer,
+                {
+                    $crate::serde::Serialize::serialize(&[self.start.val, self.end.val], serializer)
+                }
+            }
+            $crate::non_strict! {
+                impl<'de> $crate::serde::Deserialize<'de> for $range {
+                    /// Deserializes a `[start, end]` array, fails if `start > end`.
+                    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+                    where
+                        D: $crate::serde::Deserializer<'de>,
+                    {
+                        let [start, end] =
+                            <[usize; 2] as $crate::serde::Deserialize>::deserialize(deserializer)?;
+                        $range::try_new($t { val: start }, $t { val: end })
+                            .map_err(<D::Error as $crate::serde::de::Error>::custom)
+                    }
+                }
+            }
+        }
+
+        impl<'a> core::iter::IntoIterator for &'a $range {
+            type Item = $t;
+            type IntoIter = $range;
+            /// Fresh copy of the range, leaves `self` untouched.
+            #[inline]
+            fn into_iter(self) -> $range {
+                *self
+            }
+        }
+
+        $crate::rayon_only! {
+            impl $crate::rayon::iter::IntoParallelIterator for $range {
+                type Item = $t;
+                type Iter = $crate::rayon::iter::Map<
+                    $crate::rayon::range::Iter<usize>,
+                    fn(usize) -> $t,
+                >;
+                /// Indexed parallel iterator over the indices of the range.
+                fn into_par_iter(self) -> Self::Iter {
+                    let end = core::cmp::max(self.start.val, self.end.val);
+                    $crate::rayon::iter::ParallelIterator::map(
+                        $crate::rayon::iter::IntoParallelIterator::into_par_iter(self.start.val..end),
+                        (|val| $t { val }) as fn(usize) -> $t,
+                    )
+                }
+            }
+        }
+
+        impl core::iter::Iterator for $range {
+            type Item = $t;
+            #[inline]
+            fn next(&mut self) -> Option<$t> {
+                if !$range::is_empty(self) {
+                    let res = self.start;
+                    self.start.val += 1;
+                    Some(res)
+                } else {
+                    None
+                }
+            }
+            #[inline]
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                let len = $range::len(self);
+                (len, Some(len))
+            }
+            #[inline]
+            fn nth(&mut self, n: usize) -> Option<$t> {
+                match self.start.val.checked_add(n) {
+                    Some(val) if val < self.end.val => {
+                        self.start.val = val + 1;
+                        Some($t { val })
+                    }
+                    _ => {
+                        self.start.val = core::cmp::max(self.start.val, self.end.val);
+                        None
+                    }
+                }
+            }
+            #[inline]
+            fn count(self) -> usize {
+                $range::len(&self)
+            }
+            #[inline]
+            fn last(mut self) -> Option<$t> {
+                self.next_back()
+            }
+            #[inline]
+            fn min(mut self) -> Option<$t> {
+                self.next()
+            }
+            #[inline]
+            fn max(mut self) -> Option<$t> {
+                self.next_back()
+            }
+        }
+        impl core::iter::DoubleEndedIterator for $range {
+            #[inline]
+            fn next_back(&mut self) -> Option<$t> {
+                if !$range::is_empty(self) {
+                    self.end.val -= 1;
+                    Some(self.end)
+                } else {
+                    None
+                }
+            }
+            #[inline]
+            fn nth_back(&mut self, n: usize) -> Option<$t> {
+                match self.end.val.checked_sub(n).and_then(|val| val.checked_sub(1)) {
+                    Some(val) if val >= self.start.val => {
+                        self.end.val = val;
+                        Some($t { val })
+                    }
+                    _ => {
+                        self.end.val = core::cmp::min(self.start.val, self.end.val);
+                        None
+                    }
+                }
+            }
+        }
+        impl core::iter::ExactSizeIterator for $range {}
+        impl core::iter::FusedIterator for $range {}
+
+        $crate::handle!{ $t $($tail)* }
+    };
+}
+
+/// Range code generation involving a map.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! range_map_codegen {
+    ($t:ident, $range:ident, $map:ident) => {
+        impl $range {
+            /// Collects `f(idx)` for all `idx` in the range into a map.
+            ///
+            /// Panics if the range does not start at zero, since the indices of the map would not
+            /// match the ones of the range.
+            #[inline]
+            #[track_caller]
+            pub fn map_collect<T>(self, f: impl FnMut($t) -> T) -> $map<T> {
+                if self.start.val != 0 {
+                    panic!(
+                        "cannot collect range {} into a {}, it does not start at zero",
+                        self,
+                        stringify!($map),
+                    )
+                }
+                $map {
+                    vec: core::iter::Iterator::collect(core::iter::Iterator::map(self, f)),
+                }
+            }
+        }
+
+        impl<T> $map<T> {
+            /// Slice of the elements of a range, panics if the range is out of bounds.
+            #[inline]
+            #[track_caller]
+            pub fn slice(&self, range: $range) -> &[T] {
+                &self[range]
+            }
+            /// Mutable slice of the elements of a range, panics if the range is out of bounds.
+            #[inline]
+            #[track_caller]
+            pub fn slice_mut(&mut self, range: $range) -> &mut [T] {
+                &mut self[range]
+            }
+        }
+        impl<T> core::ops::Index<$range> for $map<T> {
+            type Output = [T];
+            /// Panics if the range is out of bounds, an inverted range yields an empty slice.
+            #[inline]
+            #[track_caller]
+            fn index(&self, range: $range) -> &[T] {
+                &self[core::ops::Range::<$t>::from(range)]
+            }
+        }
+        impl<T> core::ops::IndexMut<$range> for $map<T> {
+            /// Panics if the range is out of bounds, an inverted range yields an empty slice.
+            #[inline]
+            #[track_caller]
+            fn index_mut(&mut self, range: $range) -> &mut [T] {
+                &mut self[core::ops::Range::<$t>::from(range)]
+            }
+        }
+    };
+}
+
+/// Range code generation involving a btree set.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! range_set_codegen {
+    ($t:ident, $range:ident, $set:ident) => {
+        impl $range {
+            /// Set of the indices of the range.
+            #[inline]
+            pub fn to_set(self) -> $set {
+                core::iter::Iterator::collect(self)
+            }
+        }
+        impl $crate::InsertRange<$range> for $set {
+            /// Builds a set from the range in bulk and merges it into `self`, cheaper than inserting
+            /// each index separately.
+            #[inline]
+            fn insert_range(&mut self, range: $range) {
+                if !range.is_empty() {
+                    self.append(&mut range.to_set())
+                }
+            }
+        }
+    };
+}
+
+/// Range code generation involving a btree set newtype.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! range_set_struct_codegen {
+    ($t:ident, $range:ident, $set:ident) => {
+        impl $set {
+            /// Iterator over the maximal runs of consecutive indices of the set, in ascending order.
+            ///
+            /// Ranges exclude their end, so index `usize::MAX` cannot be part of a range: the run
+            /// stops right before it and it is skipped.
+            pub fn iter_ranges(&self) -> impl Iterator<Item = $range> + '_ {
+                let mut iter = self.set.iter().peekable();
+                core::iter::from_fn(move || {
+                    let start = *iter.next()?;
+                    let mut end = $t {
+                        val: start.val.checked_add(1)?,
+                    };
+                    while end.val < usize::MAX && iter.next_if(|idx| idx.val == end.val).is_some() {
+                        end.val += 1
+                    }
+                    Some($range::new(start, end))
+                })
+            }
+            /// The set as a range, if it is non-empty and contiguous.
+            ///
+            /// Never a range if the set contains index `usize::MAX`, see [`Self::iter_ranges`].
+            pub fn as_single_range(&self) -> Option<$range> {
+                let (first, last) = (self.set.first()?, self.set.last()?);
+                if last.val - first.val == self.set.len() - 1 {
+                    Some($range::new(
+                        *first,
+                        $t {
+                            val: last.val.checked_add(1)?,
+                        },
+                    ))
+                } else {
+                    None
+                }
+            }
+        }
+    };
+}
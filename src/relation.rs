@@ -0,0 +1,191 @@
+//! Handles relation code generation.
+
+/// Creates a many-to-many relation between two index families, queryable in both directions.
+///
+/// The syntax is `<Relation>: <Left> <-> <Right>` where `<Left>` and `<Right>` are index types.
+/// The relation stores the right indices related to each left index and the other way around, and
+/// keeps both directions consistent.
+///
+/// ```rust
+/// safe_index::new! {
+///     /// Indices of clients.
+///     Client,
+///     /// Map from clients to something.
+///     map: Clients,
+/// }
+/// safe_index::new! {
+///     /// Indices of files.
+///     File,
+///     /// Map from files to something.
+///     map: Files,
+/// }
+/// safe_index::relation! {
+///     /// Relates clients and files.
+///     ClientFiles: Client <-> File
+/// }
+///
+/// let mut clients = Clients::new();
+/// let (c_0, c_1) = (clients.push("client 0"), clients.push("client 1"));
+/// let mut files = Files::new();
+/// let (f_0, f_1) = (files.push("file 0"), files.push("file 1"));
+///
+/// let mut rel = ClientFiles::new();
+/// assert!(rel.insert(c_0, f_1));
+/// assert!(!rel.insert(c_0, f_1));
+/// assert_eq! { rel.insert_all(vec![(c_1, f_1), (c_1, f_0)]), 2 }
+/// assert_eq! { rel.len(), 3 }
+///
+/// assert_eq! { rel.right_of(c_1).collect::<Vec<_>>(), [f_0, f_1] }
+/// assert_eq! { rel.left_of(f_1).collect::<Vec<_>>(), [c_0, c_1] }
+/// assert!(rel.remove(c_0, f_1));
+/// assert_eq! { rel.left_of(f_1).collect::<Vec<_>>(), [c_1] }
+/// assert_eq! { rel.right_of(c_0).count(), 0 }
+/// ```
+#[macro_export]
+macro_rules! relation {
+    (
+        $(#[$meta:meta])*
+        $rel:ident : $left:ident <-> $right:ident $(,)?
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+        pub struct $rel {
+            /// Right indices related to each left index, never empty.
+            rights: $crate::alloc::collections::BTreeMap<
+                $left, $crate::alloc::collections::BTreeSet<$right>
+            >,
+            /// Left indices related to each right index, never empty.
+            lefts: $crate::alloc::collections::BTreeMap<
+                $right, $crate::alloc::collections::BTreeSet<$left>
+            >,
+            /// Number of pairs in the relation.
+            len: usize,
+        }
+
+        impl $rel {
+            /// Creates an empty relation.
+            #[inline]
+            pub const fn new() -> Self {
+                $rel {
+                    rights: $crate::alloc::collections::BTreeMap::new(),
+                    lefts: $crate::alloc::collections::BTreeMap::new(),
+                    len: 0,
+                }
+            }
+
+            /// Number of pairs in the relation.
+            #[inline]
+            pub const fn len(&self) -> usize {
+                self.len
+            }
+            /// True if the relation has no pairs.
+            #[inline]
+            pub const fn is_empty(&self) -> bool {
+                self.len == 0
+            }
+            /// Removes all the pairs.
+            #[inline]
+            pub fn clear(&mut self) {
+                self.rights.clear();
+                self.lefts.clear();
+                self.len = 0
+            }
+
+            /// True if `left` and `right` are related.
+            #[inline]
+            pub fn contains(&self, left: $left, right: $right) -> bool {
+                self.rights
+                    .get(&left)
+                    .is_some_and(|rights| rights.contains(&right))
+            }
+
+            /// Relates `left` and `right`, false if they already were.
+            pub fn insert(&mut self, left: $left, right: $right) -> bool {
+                let is_new = self.rights.entry(left).or_default().insert(right);
+                if is_new {
+                    let also_new = self.lefts.entry(right).or_default().insert(left);
+                    debug_assert! { also_new }
+                    self.len += 1
+                }
+                is_new
+            }
+            /// Relates all the pairs of an iterator, returns the number of new pairs.
+            pub fn insert_all(
+                &mut self,
+                pairs: impl core::iter::IntoIterator<Item = ($left, $right)>,
+            ) -> usize {
+                let mut count = 0;
+                for (left, right) in pairs {
+                    if self.insert(left, right) {
+                        count += 1
+                    }
+                }
+                count
+            }
+
+            /// Unrelates `left` and `right`, false if they were not related.
+            pub fn remove(&mut self, left: $left, right: $right) -> bool {
+                let rights = match self.rights.get_mut(&left) {
+                    Some(rights) => rights,
+                    None => return false,
+                };
+                if !rights.remove(&right) {
+                    return false;
+                }
+                if rights.is_empty() {
+                    self.rights.remove(&left);
+                }
+                let lefts = self
+                    .lefts
+                    .get_mut(&right)
+                    .expect("inconsistent relation");
+                let was_there = lefts.remove(&left);
+                debug_assert! { was_there }
+                if lefts.is_empty() {
+                    self.lefts.remove(&right);
+                }
+                self.len -= 1;
+                true
+            }
+
+            /// Iterator over the left indices related to `right`, in order.
+            #[inline]
+            pub fn left_of(&self, right: $right) -> impl core::iter::Iterator<Item = $left> + '_ {
+                self.lefts
+                    .get(&right)
+                    .into_iter()
+                    .flat_map(|lefts| lefts.iter().copied())
+            }
+            /// Iterator over the right indices related to `left`, in order.
+            #[inline]
+            pub fn right_of(&self, left: $left) -> impl core::iter::Iterator<Item = $right> + '_ {
+                self.rights
+                    .get(&left)
+                    .into_iter()
+                    .flat_map(|rights| rights.iter().copied())
+            }
+
+            /// Iterator over the pairs of the relation, in order.
+            #[inline]
+            pub fn iter(&self) -> impl core::iter::Iterator<Item = ($left, $right)> + '_ {
+                self.rights
+                    .iter()
+                    .flat_map(|(left, rights)| rights.iter().map(move |right| (*left, *right)))
+            }
+        }
+
+        impl core::iter::FromIterator<($left, $right)> for $rel {
+            fn from_iter<I: core::iter::IntoIterator<Item = ($left, $right)>>(iter: I) -> Self {
+                let mut rel = Self::new();
+                rel.insert_all(iter);
+                rel
+            }
+        }
+        impl core::iter::Extend<($left, $right)> for $rel {
+            #[inline]
+            fn extend<I: core::iter::IntoIterator<Item = ($left, $right)>>(&mut self, iter: I) {
+                self.insert_all(iter);
+            }
+        }
+    };
+}
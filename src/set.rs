@@ -0,0 +1,278 @@
+//! Handles btree set newtype code generation.
+
+/// Btree set newtype code generation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! btree_set_struct_codegen {
+    ($t:ident,
+        $(#[$meta:meta])*
+        $set:ident
+        $($tail:tt)*
+    ) => {
+        $(#[$meta])*
+        #[derive(Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[repr(transparent)]
+        pub struct $set {
+            set: $crate::alloc::collections::BTreeSet<$t>,
+        }
+
+        impl $set {
+            /// Creates an empty set.
+            #[inline]
+            pub const fn new() -> Self {
+                $set {
+                    set: $crate::alloc::collections::BTreeSet::new(),
+                }
+            }
+            /// Number of indices in the set.
+            #[inline]
+            pub fn len(&self) -> usize {
+                self.set.len()
+            }
+            /// True if the set is empty.
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.set.is_empty()
+            }
+            /// Inserts an index, returns `true` if it was not already there.
+            #[inline]
+            pub fn insert(&mut self, idx: $t) -> bool {
+                self.set.insert(idx)
+            }
+            /// Removes an index, returns `true` if it was there.
+            #[inline]
+            pub fn remove(&mut self, idx: impl core::borrow::Borrow<$t>) -> bool {
+                self.set.remove(idx.borrow())
+            }
+            /// True if the set contains an index.
+            #[inline]
+            pub fn contains(&self, idx: impl core::borrow::Borrow<$t>) -> bool {
+                self.set.contains(idx.borrow())
+            }
+            /// Removes all the indices.
+            #[inline]
+            pub fn clear(&mut self) {
+                self.set.clear()
+            }
+            /// Moves all the indices of `other` into `self`, leaving `other` empty.
+            #[inline]
+            pub fn append(&mut self, other: &mut Self) {
+                self.set.append(&mut other.set)
+            }
+            /// Iterator over the indices, in ascending order.
+            #[inline]
+            pub fn iter(&self) -> $crate::alloc::collections::btree_set::Iter<'_, $t> {
+                self.set.iter()
+            }
+            /// Iterator over the indices of the set in a range, in ascending order.
+            ///
+            /// Inverted ranges, such as `b..a` with `a < b`, are empty.
+            pub fn range_query(
+                &self,
+                range: impl core::ops::RangeBounds<$t>,
+            ) -> core::iter::Copied<$crate::alloc::collections::btree_set::Range<'_, $t>> {
+                self.set.range($crate::non_inverted_bounds(&range)).copied()
+            }
+            /// Smallest index of the set in a range.
+            #[inline]
+            pub fn first_in(&self, range: impl core::ops::RangeBounds<$t>) -> Option<$t> {
+                self.range_query(range).next()
+            }
+            /// Greatest index of the set in a range.
+            #[inline]
+            pub fn last_in(&self, range: impl core::ops::RangeBounds<$t>) -> Option<$t> {
+                self.range_query(range).next_back()
+            }
+            /// Iterator over the indices in `0..end` that are not in the set, in ascending order.
+            ///
+            /// Members of the set greater than or equal to `end` are ignored.
+            pub fn absent_up_to(&self, end: $t) -> impl Iterator<Item = $t> + '_ {
+                let mut members = self.set.range(..end).peekable();
+                (0..end.val).map(|val| $t { val }).filter(move |idx| {
+                    members.next_if_eq(&idx).is_none()
+                })
+            }
+            /// Set of the indices in `0..end` that are not in the set.
+            ///
+            /// Members of the set greater than or equal to `end` are ignored.
+            #[inline]
+            pub fn complement_up_to(&self, end: $t) -> Self {
+                self.absent_up_to(end).collect()
+            }
+            /// `k`-th smallest index of the set, starting at zero.
+            ///
+            /// Linear in `k`.
+            #[inline]
+            pub fn select(&self, k: usize) -> Option<$t> {
+                self.set.iter().nth(k).copied()
+            }
+            /// Number of indices of the set smaller than `idx`.
+            ///
+            /// Linear in the result.
+            #[inline]
+            pub fn rank(&self, idx: impl core::borrow::Borrow<$t>) -> usize {
+                self.set.range(..idx.borrow()).count()
+            }
+            /// Position of an index among the indices of the set in ascending order, if it is in the
+            /// set.
+            #[inline]
+            pub fn position_of(&self, idx: impl core::borrow::Borrow<$t>) -> Option<usize> {
+                let idx = idx.borrow();
+                if self.set.contains(idx) {
+                    Some(self.rank(idx))
+                } else {
+                    None
+                }
+            }
+            $crate::rand_only! {
+                /// Uniformly random index of the set, `None` if the set is empty.
+                ///
+                /// Linear in the size of the set.
+                pub fn choose<R: $crate::rand::Rng + ?Sized>(&self, rng: &mut R) -> Option<$t> {
+                    if self.set.is_empty() {
+                        None
+                    } else {
+                        self.select(rng.gen_range(0..self.set.len()))
+                    }
+                }
+                /// `k` distinct uniformly random indices of the set in ascending order, all of them
+                /// if `k >= self.len()`.
+                ///
+                /// Linear in the size of the set.
+                pub fn sample<R: $crate::rand::Rng + ?Sized>(
+                    &self,
+                    rng: &mut R,
+                    k: usize,
+                ) -> $crate::alloc::vec::Vec<$t> {
+                    $crate::sample_iter(self.set.iter().copied(), self.set.len(), rng, k)
+                }
+            }
+            /// Underlying btree set.
+            #[inline]
+            pub fn as_btree_set(&self) -> &$crate::alloc::collections::BTreeSet<$t> {
+                &self.set
+            }
+            /// Underlying btree set.
+            #[inline]
+            pub fn into_btree_set(self) -> $crate::alloc::collections::BTreeSet<$t> {
+                self.set
+            }
+        }
+
+        impl core::convert::From<$crate::alloc::collections::BTreeSet<$t>> for $set {
+            #[inline]
+            fn from(set: $crate::alloc::collections::BTreeSet<$t>) -> Self {
+                $set { set }
+            }
+        }
+        impl core::fmt::Debug for $set {
+            /// Formats the set as `{0, 3, 7}`.
+            fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+                fmt.debug_set()
+                    .entries(self.set.iter().map(|idx| idx.val))
+                    .finish()
+            }
+        }
+
+        impl core::iter::FromIterator<$t> for $set {
+            #[inline]
+            fn from_iter<I: core::iter::IntoIterator<Item = $t>>(iter: I) -> Self {
+                $set {
+                    set: iter.into_iter().collect(),
+                }
+            }
+        }
+        impl<'a> core::iter::FromIterator<&'a $t> for $set {
+            #[inline]
+            fn from_iter<I: core::iter::IntoIterator<Item = &'a $t>>(iter: I) -> Self {
+                $set {
+                    set: iter.into_iter().cloned().collect(),
+                }
+            }
+        }
+        impl core::iter::Extend<$t> for $set {
+            #[inline]
+            fn extend<I: core::iter::IntoIterator<Item = $t>>(&mut self, iter: I) {
+                self.set.extend(iter)
+            }
+        }
+        impl<'a> core::iter::Extend<&'a $t> for $set {
+            #[inline]
+            fn extend<I: core::iter::IntoIterator<Item = &'a $t>>(&mut self, iter: I) {
+                self.set.extend(iter)
+            }
+        }
+        impl core::iter::IntoIterator for $set {
+            type Item = $t;
+            type IntoIter = $crate::alloc::collections::btree_set::IntoIter<$t>;
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.set.into_iter()
+            }
+        }
+        impl<'a> core::iter::IntoIterator for &'a $set {
+            type Item = &'a $t;
+            type IntoIter = $crate::alloc::collections::btree_set::Iter<'a, $t>;
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.set.iter()
+            }
+        }
+
+        $crate::handle!{ $t $($tail)* }
+    };
+}
+
+/// Btree set code generation involving a map.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! map_set_codegen {
+    ($t:ident, $map:ident, $set:ident) => {
+        impl $map<bool> {
+            /// Set of the indices mapped to `true`.
+            pub fn to_set(&self) -> $set {
+                self.index_iter()
+                    .filter(|(_, member)| **member)
+                    .map(|(idx, _)| idx)
+                    .collect()
+            }
+        }
+    };
+}
+
+/// Btree set newtype code generation involving a map.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! map_set_struct_codegen {
+    ($t:ident, $map:ident, $set:ident) => {
+        impl $set {
+            /// Map of length `len` from indices to `member` if they are in the set, `non_member`
+            /// otherwise.
+            ///
+            /// Panics if the set contains an index greater than or equal to `len`.
+            #[track_caller]
+            pub fn to_map_with<T: Clone>(&self, len: usize, member: T, non_member: T) -> $map<T> {
+                let mut map = $map::of_elems(non_member, len);
+                for idx in &self.set {
+                    map[idx] = member.clone()
+                }
+                map
+            }
+            /// Map of length `len` from indices to `true` if they are in the set, `false` otherwise.
+            ///
+            /// Panics if the set contains an index greater than or equal to `len`.
+            #[inline]
+            #[track_caller]
+            pub fn to_membership_map(&self, len: usize) -> $map<bool> {
+                self.to_map_with(len, true, false)
+            }
+            /// Set of the indices of `map` that are not in the set.
+            ///
+            /// Members of the set that are not indices of `map` are ignored.
+            #[inline]
+            pub fn complement_in<T>(&self, map: &$map<T>) -> Self {
+                self.complement_up_to($t { val: map.len() })
+            }
+        }
+    };
+}
@@ -0,0 +1,196 @@
+//! Handles shared map code generation.
+
+/// Shared map code generation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! shared_map_codegen {
+    (@range $t:ident, $shared:ident, $($range:ty),* $(,)?) => {$(
+        impl<T> core::ops::Index<$range> for $shared<T> {
+            type Output = [T];
+            #[inline]
+            #[track_caller]
+            fn index(&self, range: $range) -> &[T] {
+                let range = $crate::oob::range::<$t>(
+                    stringify!($t), stringify!($shared), self.elems.len(), &range
+                );
+                &self.elems[range]
+            }
+        }
+    )*};
+
+    ($t:ident,
+        $(#[$meta:meta])*
+        $shared:ident
+        $($tail:tt)*
+    ) => {
+        $(#[$meta])*
+        ///
+        /// Read-only map behind an [`Arc`]($crate::alloc::sync::Arc), cloning it is cheap and does
+        /// not copy the elements. Shareable between threads when the elements are.
+        #[derive(Debug, PartialEq, Eq, Hash)]
+        pub struct $shared<T> {
+            elems: $crate::alloc::sync::Arc<[T]>,
+        }
+
+        impl<T> core::clone::Clone for $shared<T> {
+            #[inline]
+            fn clone(&self) -> Self {
+                $shared { elems: self.elems.clone() }
+            }
+        }
+        impl<T> core::default::Default for $shared<T> {
+            #[inline]
+            fn default() -> Self {
+                $shared { elems: $crate::alloc::vec::Vec::new().into() }
+            }
+        }
+
+        impl<T> $shared<T> {
+            /// Number of elements in the map.
+            #[inline]
+            pub fn len(&self) -> usize {
+                self.elems.len()
+            }
+            /// True if the map is empty.
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.elems.is_empty()
+            }
+            /// Number of clones of the map, itself included, sharing the same elements.
+            #[inline]
+            pub fn share_count(&self) -> usize {
+                $crate::alloc::sync::Arc::strong_count(&self.elems)
+            }
+            /// True if two maps share the same elements.
+            #[inline]
+            pub fn ptr_eq(&self, other: &Self) -> bool {
+                $crate::alloc::sync::Arc::ptr_eq(&self.elems, &other.elems)
+            }
+
+            /// Generates an index from a [`usize`] when it is a legal index.
+            #[inline]
+            pub fn index_from_usize(&self, n: usize) -> Option<$t> {
+                if n < self.elems.len() {
+                    Some($t { val: n })
+                } else {
+                    None
+                }
+            }
+            /// Index of the last element in the map.
+            #[inline]
+            pub fn last_index(&self) -> Option<$t> {
+                self.elems.len().checked_sub(1).map(|val| $t { val })
+            }
+            /// Retrieves an element in the map.
+            #[inline]
+            pub fn get(&self, idx: impl core::borrow::Borrow<$t>) -> Option<&T> {
+                self.elems.get(idx.borrow().val)
+            }
+            /// Last element in the map, with its index.
+            #[inline]
+            pub fn last(&self) -> Option<($t, &T)> {
+                self.last_index().map(|idx| (idx, &self.elems[idx.val]))
+            }
+
+            /// Slice of the elements.
+            #[inline]
+            pub fn as_slice(&self) -> &[T] {
+                &self.elems
+            }
+            /// Ref-iterator over the elements.
+            #[inline]
+            pub fn iter(&self) -> core::slice::Iter<'_, T> {
+                self.elems.iter()
+            }
+            /// Iterator over the indices of the map.
+            #[inline]
+            pub fn indices(&self) -> impl core::iter::DoubleEndedIterator<Item = $t>
+                + core::iter::ExactSizeIterator
+                + core::iter::FusedIterator
+            {
+                (0..self.elems.len()).map(|val| $t { val })
+            }
+            /// Ref-iterator over the index/element pairs.
+            #[inline]
+            pub fn index_iter<'a>(&'a self) ->
+                impl core::iter::DoubleEndedIterator<Item = ($t, &'a T)>
+                + core::iter::ExactSizeIterator
+                + core::iter::FusedIterator
+            where T: 'a {
+                self.elems.iter().enumerate().map(|(val, elem)| ($t { val }, elem))
+            }
+        }
+
+        impl<T> core::ops::Index<$t> for $shared<T> {
+            type Output = T;
+            #[inline]
+            #[track_caller]
+            fn index(&self, index: $t) -> &T {
+                match self.elems.get(index.val) {
+                    Some(elem) => elem,
+                    None => $crate::oob::index(
+                        stringify!($t), index.val, stringify!($shared), self.elems.len()
+                    ),
+                }
+            }
+        }
+        impl<'a, T> core::ops::Index<&'a $t> for $shared<T> {
+            type Output = T;
+            #[inline]
+            #[track_caller]
+            fn index(&self, index: &'a $t) -> &T {
+                &self[*index]
+            }
+        }
+        $crate::shared_map_codegen! {
+            @range $t, $shared,
+            core::ops::Range<$t>,
+            core::ops::RangeInclusive<$t>,
+            core::ops::RangeFrom<$t>,
+            core::ops::RangeTo<$t>,
+            core::ops::RangeToInclusive<$t>,
+            core::ops::RangeFull,
+        }
+
+        impl<'a, T> core::iter::IntoIterator for &'a $shared<T> {
+            type Item = &'a T;
+            type IntoIter = core::slice::Iter<'a, T>;
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.elems.iter()
+            }
+        }
+
+        $crate::handle!{ $t $($tail)* }
+    };
+}
+
+/// Map and shared map code generation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! map_shared_map_codegen {
+    ($t:ident, $map:ident, $shared:ident) => {
+        impl<T> $map<T> {
+            /// Turns the map into a shared map, the elements move once to the shared allocation.
+            #[inline]
+            pub fn into_shared(self) -> $shared<T> {
+                self.into()
+            }
+        }
+        impl<T> core::convert::From<$map<T>> for $shared<T> {
+            #[inline]
+            fn from(map: $map<T>) -> Self {
+                $shared {
+                    elems: map.vec.into(),
+                }
+            }
+        }
+        impl<T: core::clone::Clone> $shared<T> {
+            /// Copies the elements into a map, which can be modified.
+            #[inline]
+            pub fn to_map(&self) -> $map<T> {
+                self.elems.iter().cloned().collect()
+            }
+        }
+    };
+}
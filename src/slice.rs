@@ -0,0 +1,221 @@
+//! Handles slice code generation.
+
+/// Slice code generation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! slice_codegen {
+    ($t:ident,
+        $(#[$meta:meta])*
+        $slice:ident
+        $($tail:tt)*
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[repr(transparent)]
+        pub struct $slice<T> {
+            slice: [T]
+        }
+
+        impl<T> $slice<T> {
+            $crate::non_strict! {
+                /// Views a slice as a typed slice, without copying.
+                #[inline]
+                pub fn from_slice(slice: &[T]) -> &Self {
+                    Self::of_slice(slice)
+                }
+            }
+            $crate::non_strict! {
+                /// Views a mutable slice as a mutable typed slice, without copying.
+                #[inline]
+                pub fn from_slice_mut(slice: &mut [T]) -> &mut Self {
+                    Self::of_slice_mut(slice)
+                }
+            }
+            /// Views a slice as a typed slice, private so that the collections generated alongside
+            /// the slice can use it in strict mode.
+            #[inline]
+            fn of_slice(slice: &[T]) -> &Self {
+                // Sound: `Self` is `#[repr(transparent)]` over `[T]`.
+                unsafe { &*(slice as *const [T] as *const Self) }
+            }
+            /// Views a mutable slice as a mutable typed slice, private so that the collections
+            /// generated alongside the slice can use it in strict mode.
+            #[inline]
+            fn of_slice_mut(slice: &mut [T]) -> &mut Self {
+                // Sound: `Self` is `#[repr(transparent)]` over `[T]`.
+                unsafe { &mut *(slice as *mut [T] as *mut Self) }
+            }
+
+            /// Number of elements in the slice.
+            #[inline]
+            pub const fn len(&self) -> usize {
+                self.slice.len()
+            }
+            /// True if the slice is empty.
+            #[inline]
+            pub const fn is_empty(&self) -> bool {
+                self.slice.is_empty()
+            }
+            /// Underlying slice.
+            #[inline]
+            pub const fn as_slice(&self) -> &[T] {
+                &self.slice
+            }
+
+            /// Retrieves an entry in the slice.
+            #[inline]
+            pub fn get(&self, idx: impl core::borrow::Borrow<$t>) -> Option<&T> {
+                self.slice.get(idx.borrow().get())
+            }
+            /// Retrieves an entry in the slice.
+            #[inline]
+            pub fn get_mut(&mut self, idx: impl core::borrow::Borrow<$t>) -> Option<&mut T> {
+                self.slice.get_mut(idx.borrow().get())
+            }
+
+            /// Iterator over all the indices.
+            #[inline]
+            pub fn indices(&self) ->
+                impl core::iter::DoubleEndedIterator<Item = $t>
+                + core::iter::ExactSizeIterator
+                + core::iter::FusedIterator
+            {
+                (0..self.len()).map(|i| $t { val: i })
+            }
+            /// Ref-iterator over the elements.
+            #[inline]
+            pub fn iter(&self) -> core::slice::Iter<'_, T> {
+                self.slice.iter()
+            }
+            /// Ref-mut-iterator over the elements.
+            #[inline]
+            pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, T> {
+                self.slice.iter_mut()
+            }
+            /// Ref-iterator over the index/element pairs.
+            #[inline]
+            pub fn index_iter<'a>(&'a self) ->
+                impl core::iter::DoubleEndedIterator<Item = ($t, &'a T)>
+                + core::iter::ExactSizeIterator
+                + core::iter::FusedIterator
+            where T: 'a {
+                self.slice.iter().enumerate().map(|(idx, elm)| (
+                    $t { val: idx }, elm
+                ))
+            }
+        }
+
+        $crate::zerocopy_only! {
+            impl<T> $slice<T>
+            where
+                T: $crate::zerocopy::FromBytes + $crate::zerocopy::Immutable,
+            {
+                $crate::non_strict! {
+                    /// Views raw bytes as a typed slice, without copying.
+                    ///
+                    /// Fails if `bytes` is not aligned for `T` or its length is not a multiple of
+                    /// the size of `T`.
+                    #[inline]
+                    pub fn from_bytes(
+                        bytes: &[u8],
+                    ) -> Result<&Self, $crate::zerocopy::CastError<&[u8], [T]>> {
+                        <[T] as $crate::zerocopy::FromBytes>::ref_from_bytes(bytes)
+                            .map(Self::of_slice)
+                    }
+                }
+            }
+            impl<T> $slice<T>
+            where
+                T: $crate::zerocopy::IntoBytes + $crate::zerocopy::Immutable,
+            {
+                /// Views the slice as raw bytes, without copying.
+                #[inline]
+                pub fn as_bytes(&self) -> &[u8] {
+                    $crate::zerocopy::IntoBytes::as_bytes(&self.slice)
+                }
+            }
+        }
+
+        impl<'a, T> core::iter::IntoIterator for &'a $slice<T> {
+            type Item = &'a T ;
+            type IntoIter = core::slice::Iter<'a, T> ;
+            fn into_iter(self) -> core::slice::Iter<'a, T> {
+                self.iter()
+            }
+        }
+        impl<'a, T> core::iter::IntoIterator for &'a mut $slice<T> {
+            type Item = &'a mut T ;
+            type IntoIter = core::slice::IterMut<'a, T> ;
+            fn into_iter(self) -> core::slice::IterMut<'a, T> {
+                self.iter_mut()
+            }
+        }
+        impl<T> core::ops::Index<$t> for $slice<T> {
+            type Output = T ;
+            #[inline]
+            #[track_caller]
+            fn index(& self, index: $t) -> & T {
+                match self.slice.get(index.val) {
+                    Some(elem) => elem,
+                    None => $crate::oob::index(
+                        stringify!($t), index.val, stringify!($slice), self.slice.len()
+                    ),
+                }
+            }
+        }
+        impl<T> core::ops::IndexMut<$t> for $slice<T> {
+            #[inline]
+            #[track_caller]
+            fn index_mut(&mut self, index: $t) -> &mut T {
+                let len = self.slice.len();
+                match self.slice.get_mut(index.val) {
+                    Some(elem) => elem,
+                    None => $crate::oob::index(stringify!($t), index.val, stringify!($slice), len),
+                }
+            }
+        }
+        impl<'a, T> core::ops::Index<&'a $t> for $slice<T> {
+            type Output = T ;
+            #[inline]
+            #[track_caller]
+            fn index(& self, index: &'a $t) -> & T {
+                & self[ *index ]
+            }
+        }
+        impl<'a, T> core::ops::IndexMut<&'a $t> for $slice<T> {
+            #[inline]
+            #[track_caller]
+            fn index_mut(&mut self, index: &'a $t) -> &mut T {
+                &mut self[ *index ]
+            }
+        }
+
+        $crate::handle!{ $t $($tail)* }
+    };
+}
+
+/// Slice construction from the map of the same `new!` invocation.
+///
+/// Only takes the map generated alongside the slice, whose indices are legal in strict mode.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! map_slice_codegen {
+    ($t:ident, $map:ident, $slice:ident) => {
+        impl<T> $slice<T> {
+            /// Views the elements of a map as a typed slice, without copying.
+            ///
+            /// Available in strict mode: the indices of the slice are the ones of the map.
+            #[inline]
+            pub fn from_map(map: &$map<T>) -> &Self {
+                Self::of_slice(&map.vec)
+            }
+            /// Views the elements of a map as a mutable typed slice, without copying.
+            ///
+            /// Available in strict mode: the indices of the slice are the ones of the map.
+            #[inline]
+            pub fn from_map_mut(map: &mut $map<T>) -> &mut Self {
+                Self::of_slice_mut(&mut map.vec)
+            }
+        }
+    };
+}
@@ -0,0 +1,217 @@
+//! Handles slot map code generation.
+
+/// Slot map code generation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! slot_map_codegen {
+    ($t:ident,
+        $(#[$meta:meta])*
+        $slots:ident
+        $($tail:tt)*
+    ) => {
+        $(#[$meta])*
+        ///
+        /// Slot map: inserting an element yields a [`SlotHandle`]($crate::SlotHandle), made of the
+        /// index of the slot storing the element and of the generation of this slot. Removing an
+        /// element bumps the generation of its slot, so that accessing a removed element through
+        /// its (stale) handle fails even after the slot is reused.
+        ///
+        /// Compared to a `map`, each slot stores a generation and removed elements leave a free slot
+        /// behind for later insertions, and each access checks the generation of the slot. Prefer a
+        /// `map` when elements are never removed.
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub struct $slots<T> {
+            /// Generation and element of each slot.
+            slots: $crate::alloc::vec::Vec<(u32, Option<T>)>,
+            /// Free slots, reused last-in first-out.
+            free: $crate::alloc::vec::Vec<usize>,
+            /// Number of elements.
+            len: usize,
+        }
+
+        impl<T> core::default::Default for $slots<T> {
+            #[inline]
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<T> $slots<T> {
+            /// Creates an empty slot map.
+            #[inline]
+            pub const fn new() -> Self {
+                $slots {
+                    slots: $crate::alloc::vec::Vec::new(),
+                    free: $crate::alloc::vec::Vec::new(),
+                    len: 0,
+                }
+            }
+            /// Creates an empty slot map with some capacity.
+            #[inline]
+            pub fn with_capacity(capacity: usize) -> Self {
+                $slots {
+                    slots: $crate::alloc::vec::Vec::with_capacity(capacity),
+                    free: $crate::alloc::vec::Vec::new(),
+                    len: 0,
+                }
+            }
+
+            /// Number of elements in the slot map.
+            #[inline]
+            pub const fn len(&self) -> usize {
+                self.len
+            }
+            /// True if the slot map has no elements.
+            #[inline]
+            pub const fn is_empty(&self) -> bool {
+                self.len == 0
+            }
+            /// Number of slots, occupied or free.
+            #[inline]
+            pub fn slot_count(&self) -> usize {
+                self.slots.len()
+            }
+
+            /// Inserts an element, yields its handle.
+            ///
+            /// Reuses the last slot freed if any.
+            pub fn insert(&mut self, elem: T) -> $crate::SlotHandle<$t> {
+                self.len += 1;
+                if let Some(val) = self.free.pop() {
+                    let slot = &mut self.slots[val];
+                    debug_assert! { slot.1.is_none() }
+                    slot.1 = Some(elem);
+                    $crate::SlotHandle::new($t { val }, slot.0)
+                } else {
+                    let val = self.slots.len();
+                    self.slots.push((0, Some(elem)));
+                    $crate::SlotHandle::new($t { val }, 0)
+                }
+            }
+
+            /// Removes an element, `None` if the handle is stale.
+            pub fn remove(&mut self, handle: $crate::SlotHandle<$t>) -> Option<T> {
+                let val = handle.index().val;
+                let slot = self.slots.get_mut(val)?;
+                if slot.0 != handle.generation() {
+                    return None;
+                }
+                let elem = slot.1.take()?;
+                slot.0 = slot.0.wrapping_add(1);
+                self.free.push(val);
+                self.len -= 1;
+                Some(elem)
+            }
+            /// Removes all the elements, all the handles become stale.
+            pub fn clear(&mut self) {
+                for (val, slot) in self.slots.iter_mut().enumerate() {
+                    if slot.1.take().is_some() {
+                        slot.0 = slot.0.wrapping_add(1);
+                        self.free.push(val);
+                    }
+                }
+                self.len = 0
+            }
+
+            /// Retrieves an element, `None` if the handle is stale.
+            #[inline]
+            pub fn get(&self, handle: $crate::SlotHandle<$t>) -> Option<&T> {
+                match self.slots.get(handle.index().val) {
+                    Some((generation, elem)) if *generation == handle.generation() => {
+                        elem.as_ref()
+                    }
+                    _ => None,
+                }
+            }
+            /// Retrieves an element, `None` if the handle is stale.
+            #[inline]
+            pub fn get_mut(&mut self, handle: $crate::SlotHandle<$t>) -> Option<&mut T> {
+                match self.slots.get_mut(handle.index().val) {
+                    Some((generation, elem)) if *generation == handle.generation() => {
+                        elem.as_mut()
+                    }
+                    _ => None,
+                }
+            }
+            /// True if the handle is not stale.
+            #[inline]
+            pub fn contains(&self, handle: $crate::SlotHandle<$t>) -> bool {
+                self.get(handle).is_some()
+            }
+
+            /// Iterator over the handles of the elements, in index order.
+            #[inline]
+            pub fn handles(&self) -> impl core::iter::Iterator<Item = $crate::SlotHandle<$t>> + '_ {
+                self.iter().map(|(handle, _)| handle)
+            }
+            /// Iterator over the elements and their handles, in index order.
+            #[inline]
+            pub fn iter(
+                &self,
+            ) -> impl core::iter::Iterator<Item = ($crate::SlotHandle<$t>, &T)> + '_ {
+                self.slots
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(val, (generation, elem))| {
+                        elem.as_ref()
+                            .map(|elem| ($crate::SlotHandle::new($t { val }, *generation), elem))
+                    })
+            }
+            /// Mutable iterator over the elements and their handles, in index order.
+            #[inline]
+            pub fn iter_mut(
+                &mut self,
+            ) -> impl core::iter::Iterator<Item = ($crate::SlotHandle<$t>, &mut T)> + '_ {
+                self.slots
+                    .iter_mut()
+                    .enumerate()
+                    .filter_map(|(val, (generation, elem))| {
+                        let generation = *generation;
+                        elem.as_mut()
+                            .map(|elem| ($crate::SlotHandle::new($t { val }, generation), elem))
+                    })
+            }
+            /// Iterator over the elements, in index order.
+            #[inline]
+            pub fn values(&self) -> impl core::iter::Iterator<Item = &T> + '_ {
+                self.slots.iter().filter_map(|(_, elem)| elem.as_ref())
+            }
+        }
+
+        impl<T> core::ops::Index<$crate::SlotHandle<$t>> for $slots<T> {
+            type Output = T;
+            #[inline]
+            #[track_caller]
+            fn index(&self, handle: $crate::SlotHandle<$t>) -> &T {
+                match self.get(handle) {
+                    Some(elem) => elem,
+                    None => panic!(
+                        "handle {}({})@{} is stale in {}",
+                        stringify!($t),
+                        handle.index().val,
+                        handle.generation(),
+                        stringify!($slots),
+                    ),
+                }
+            }
+        }
+        impl<T> core::ops::IndexMut<$crate::SlotHandle<$t>> for $slots<T> {
+            #[inline]
+            #[track_caller]
+            fn index_mut(&mut self, handle: $crate::SlotHandle<$t>) -> &mut T {
+                match self.get_mut(handle) {
+                    Some(elem) => elem,
+                    None => panic!(
+                        "handle {}({})@{} is stale in {}",
+                        stringify!($t),
+                        handle.index().val,
+                        handle.generation(),
+                        stringify!($slots),
+                    ),
+                }
+            }
+        }
+
+        $crate::handle!{ $t $($tail)* }
+    };
+}
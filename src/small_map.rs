@@ -0,0 +1,328 @@
+//! Handles small map code generation.
+
+/// Small map code generation.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(feature = "smallvec")]
+macro_rules! small_map_codegen {
+    ($t:ident,
+        $(#[$meta:meta])*
+        $map:ident [$n:expr]
+        $($tail:tt)*
+    ) => {
+        $(#[$meta])*
+        ///
+        /// Map storing up to
+        #[doc = stringify!($n)]
+        /// elements inline, without allocating, backed by a
+        /// [`SmallVec`]($crate::smallvec::SmallVec).
+        #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $map<T> {
+            vec: $crate::smallvec::SmallVec<[T; $n]>
+        }
+
+        impl<T> core::default::Default for $map<T> {
+            #[inline]
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<T> $map<T> {
+            /// Number of elements the map stores without allocating.
+            pub const INLINE_CAPACITY: usize = $n;
+
+            /// Creates an empty map.
+            #[inline]
+            pub fn new() -> Self {
+                $map { vec: $crate::smallvec::SmallVec::new() }
+            }
+            /// Creates an empty map with some capacity, allocates if it exceeds the inline capacity.
+            #[inline]
+            pub fn with_capacity(capacity: usize) -> Self {
+                $map { vec: $crate::smallvec::SmallVec::with_capacity(capacity) }
+            }
+
+            /// Number of elements in the map.
+            #[inline]
+            pub fn len(&self) -> usize {
+                self.vec.len()
+            }
+            /// True if the map is empty.
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.vec.is_empty()
+            }
+            /// Capacity of the map.
+            #[inline]
+            pub fn capacity(&self) -> usize {
+                self.vec.capacity()
+            }
+            /// True if the elements were moved to the heap, *i.e.* the map has more elements than
+            /// its inline capacity, or had at some point.
+            #[inline]
+            pub fn spilled(&self) -> bool {
+                self.vec.spilled()
+            }
+
+            /// Generates an index from a [`usize`] when it is a legal index.
+            #[inline]
+            pub fn index_from_usize(&self, n: usize) -> Option<$t> {
+                if n < self.vec.len() {
+                    Some($t { val: n })
+                } else {
+                    None
+                }
+            }
+            /// Index of the last element in the map.
+            #[inline]
+            pub fn last_index(&self) -> Option<$t> {
+                let len = self.len();
+                if len > 0 { Some($t { val: len - 1 }) } else { None }
+            }
+
+            /// Retrieves an entry in the map.
+            #[inline]
+            pub fn get(&self, idx: impl core::borrow::Borrow<$t>) -> Option<&T> {
+                self.vec.get(idx.borrow().val)
+            }
+            /// Retrieves an entry in the map.
+            #[inline]
+            pub fn get_mut(&mut self, idx: impl core::borrow::Borrow<$t>) -> Option<&mut T> {
+                self.vec.get_mut(idx.borrow().val)
+            }
+
+            /// Pushes an element, yields its index.
+            #[inline]
+            pub fn push(&mut self, elem: T) -> $t {
+                let idx = $t { val: self.vec.len() };
+                self.vec.push(elem);
+                idx
+            }
+            /// Removes the last element, yields it with its index.
+            #[inline]
+            pub fn pop(&mut self) -> Option<($t, T)> {
+                let elem = self.vec.pop()?;
+                Some(($t { val: self.vec.len() }, elem))
+            }
+            /// Removes all the elements, keeps the capacity.
+            #[inline]
+            pub fn clear(&mut self) {
+                self.vec.clear()
+            }
+
+            /// Underlying slice.
+            #[inline]
+            pub fn as_slice(&self) -> &[T] {
+                &self.vec
+            }
+            /// Underlying mutable slice.
+            #[inline]
+            pub fn as_mut_slice(&mut self) -> &mut [T] {
+                &mut self.vec
+            }
+            /// Turns the map into a vector, without allocating if the elements were spilled.
+            #[inline]
+            pub fn into_vec(self) -> $crate::alloc::vec::Vec<T> {
+                self.vec.into_vec()
+            }
+
+            /// Iterator over all the indices.
+            #[inline]
+            pub fn indices(&self) ->
+                impl core::iter::DoubleEndedIterator<Item = $t>
+                + core::iter::ExactSizeIterator
+                + core::iter::FusedIterator
+            {
+                (0..self.len()).map(|val| $t { val })
+            }
+            /// Ref-iterator over the elements.
+            #[inline]
+            pub fn iter(&self) -> core::slice::Iter<'_, T> {
+                self.vec.iter()
+            }
+            /// Ref-mut-iterator over the elements.
+            #[inline]
+            pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, T> {
+                self.vec.iter_mut()
+            }
+            /// Ref-iterator over the index/element pairs.
+            #[inline]
+            pub fn index_iter<'a>(&'a self) ->
+                impl core::iter::DoubleEndedIterator<Item = ($t, &'a T)>
+                + core::iter::ExactSizeIterator
+                + core::iter::FusedIterator
+            where T: 'a {
+                self.vec.iter().enumerate().map(|(val, elem)| ($t { val }, elem))
+            }
+            /// Ref-mut-iterator over the index/element pairs.
+            #[inline]
+            pub fn index_iter_mut<'a>(&'a mut self) ->
+                impl core::iter::DoubleEndedIterator<Item = ($t, &'a mut T)>
+                + core::iter::ExactSizeIterator
+                + core::iter::FusedIterator
+            where T: 'a {
+                self.vec.iter_mut().enumerate().map(|(val, elem)| ($t { val }, elem))
+            }
+        }
+
+        impl<T> $crate::SafeMap for $map<T> {
+            type Idx = $t;
+            type Elem = T;
+            #[inline]
+            fn len(&self) -> usize {
+                self.vec.len()
+            }
+            #[inline]
+            fn push(&mut self, elem: T) -> $t {
+                $map::push(self, elem)
+            }
+            #[inline]
+            fn get(&self, idx: $t) -> Option<&T> {
+                self.vec.get(idx.val)
+            }
+            #[inline]
+            fn get_mut(&mut self, idx: $t) -> Option<&mut T> {
+                self.vec.get_mut(idx.val)
+            }
+            #[inline]
+            fn index_from_usize(&self, n: usize) -> Option<$t> {
+                $map::index_from_usize(self, n)
+            }
+        }
+
+        impl<T> core::iter::FromIterator<T> for $map<T> {
+            #[inline]
+            fn from_iter<I: core::iter::IntoIterator<Item = T>>(iter: I) -> Self {
+                $map { vec: iter.into_iter().collect() }
+            }
+        }
+        impl<T> core::iter::Extend<T> for $map<T> {
+            #[inline]
+            fn extend<I: core::iter::IntoIterator<Item = T>>(&mut self, iter: I) {
+                self.vec.extend(iter)
+            }
+        }
+        impl<T> core::iter::IntoIterator for $map<T> {
+            type Item = T;
+            type IntoIter = $crate::smallvec::IntoIter<[T; $n]>;
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.vec.into_iter()
+            }
+        }
+        impl<'a, T> core::iter::IntoIterator for &'a $map<T> {
+            type Item = &'a T;
+            type IntoIter = core::slice::Iter<'a, T>;
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter()
+            }
+        }
+        impl<'a, T> core::iter::IntoIterator for &'a mut $map<T> {
+            type Item = &'a mut T;
+            type IntoIter = core::slice::IterMut<'a, T>;
+            #[inline]
+            fn into_iter(self) -> Self::IntoIter {
+                self.iter_mut()
+            }
+        }
+
+        impl<T> core::ops::Index<$t> for $map<T> {
+            type Output = T;
+            #[inline]
+            #[track_caller]
+            fn index(&self, index: $t) -> &T {
+                match self.vec.get(index.val) {
+                    Some(elem) => elem,
+                    None => $crate::oob::index(
+                        stringify!($t), index.val, stringify!($map), self.vec.len()
+                    ),
+                }
+            }
+        }
+        impl<T> core::ops::IndexMut<$t> for $map<T> {
+            #[inline]
+            #[track_caller]
+            fn index_mut(&mut self, index: $t) -> &mut T {
+                let len = self.vec.len();
+                match self.vec.get_mut(index.val) {
+                    Some(elem) => elem,
+                    None => $crate::oob::index(stringify!($t), index.val, stringify!($map), len),
+                }
+            }
+        }
+        impl<'a, T> core::ops::Index<&'a $t> for $map<T> {
+            type Output = T;
+            #[inline]
+            #[track_caller]
+            fn index(&self, index: &'a $t) -> &T {
+                &self[*index]
+            }
+        }
+        impl<'a, T> core::ops::IndexMut<&'a $t> for $map<T> {
+            #[inline]
+            #[track_caller]
+            fn index_mut(&mut self, index: &'a $t) -> &mut T {
+                &mut self[*index]
+            }
+        }
+        $crate::map_range_index_codegen! {
+            $t, $map,
+            core::ops::Range<$t>,
+            core::ops::RangeInclusive<$t>,
+            core::ops::RangeFrom<$t>,
+            core::ops::RangeTo<$t>,
+            core::ops::RangeToInclusive<$t>,
+            core::ops::RangeFull,
+        }
+
+        $crate::handle!{ $t $($tail)* }
+    };
+    ($t:ident,
+        $(#[$meta:meta])*
+        $map:ident
+        $($tail:tt)*
+    ) => {
+        compile_error!(concat!(
+            "`small map: ",
+            stringify!($map),
+            "` expects a capacity after the name, for instance `small map: ",
+            stringify!($map),
+            "[4]`",
+        ));
+    };
+}
+
+/// Small map code generation.
+#[macro_export]
+#[doc(hidden)]
+#[cfg(not(feature = "smallvec"))]
+macro_rules! small_map_codegen {
+    ($t:ident,
+        $(#[$meta:meta])*
+        $map:ident [$n:expr]
+        $($tail:tt)*
+    ) => {
+        $(#[$meta])*
+        compile_error!(concat!(
+            "`small map: ",
+            stringify!($map),
+            "` requires the `smallvec` feature of safe_index",
+        ));
+        $crate::handle!{ $t $($tail)* }
+    };
+    ($t:ident,
+        $(#[$meta:meta])*
+        $map:ident
+        $($tail:tt)*
+    ) => {
+        compile_error!(concat!(
+            "`small map: ",
+            stringify!($map),
+            "` expects a capacity after the name, for instance `small map: ",
+            stringify!($map),
+            "[4]`",
+        ));
+    };
+}
@@ -0,0 +1,242 @@
+//! Handles sparse map code generation.
+
+/// Sparse map code generation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! sparse_map_codegen {
+    ($t:ident,
+        $(#[$meta:meta])*
+        $sparse:ident
+        $($tail:tt)*
+    ) => {
+        $(#[$meta])*
+        ///
+        /// Map where removing an element leaves a hole instead of shifting the indices of the
+        /// following elements. Holes are squeezed out by `compact`, available when a `map` is
+        /// declared alongside the sparse map.
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub struct $sparse<T> {
+            /// Elements, `None` for holes.
+            vec: $crate::alloc::vec::Vec<Option<T>>,
+            /// Number of elements that are not holes.
+            present: usize,
+        }
+
+        impl<T> core::default::Default for $sparse<T> {
+            #[inline]
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl<T> $sparse<T> {
+            /// Creates an empty sparse map.
+            #[inline]
+            pub const fn new() -> Self {
+                $sparse {
+                    vec: $crate::alloc::vec::Vec::new(),
+                    present: 0,
+                }
+            }
+            /// Creates an empty sparse map with some capacity.
+            #[inline]
+            pub fn with_capacity(capacity: usize) -> Self {
+                $sparse {
+                    vec: $crate::alloc::vec::Vec::with_capacity(capacity),
+                    present: 0,
+                }
+            }
+
+            /// Number of indices of the map, holes included.
+            #[inline]
+            pub fn len(&self) -> usize {
+                self.vec.len()
+            }
+            /// Number of elements of the map, holes excluded.
+            #[inline]
+            pub const fn len_present(&self) -> usize {
+                self.present
+            }
+            /// True if the map has no indices, not even holes.
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.vec.is_empty()
+            }
+            /// Number of holes in the map.
+            #[inline]
+            pub fn hole_count(&self) -> usize {
+                self.vec.len() - self.present
+            }
+
+            /// Pushes an element, yields its index.
+            ///
+            /// The index is always fresh, holes are never reused.
+            #[inline]
+            pub fn push(&mut self, elem: T) -> $t {
+                let idx = $t { val: self.vec.len() };
+                self.vec.push(Some(elem));
+                self.present += 1;
+                idx
+            }
+            /// Removes an element and leaves a hole, `None` if `idx` is already a hole.
+            ///
+            /// Panics if the index is out of bounds.
+            #[inline]
+            #[track_caller]
+            pub fn remove(&mut self, idx: $t) -> Option<T> {
+                let elem = self.slot_mut(idx).take();
+                if elem.is_some() {
+                    self.present -= 1
+                }
+                elem
+            }
+            /// Puts an element at some index, yields the previous element, `None` if it was a hole.
+            ///
+            /// Panics if the index is out of bounds.
+            #[inline]
+            #[track_caller]
+            pub fn insert(&mut self, idx: $t, elem: T) -> Option<T> {
+                let prev = self.slot_mut(idx).replace(elem);
+                if prev.is_none() {
+                    self.present += 1
+                }
+                prev
+            }
+            /// Slot of an index, panics if the index is out of bounds.
+            #[inline]
+            #[track_caller]
+            fn slot_mut(&mut self, idx: $t) -> &mut Option<T> {
+                let len = self.vec.len();
+                match self.vec.get_mut(idx.val) {
+                    Some(slot) => slot,
+                    None => $crate::oob::index(stringify!($t), idx.val, stringify!($sparse), len),
+                }
+            }
+
+            /// Retrieves an element, `None` if the index is a hole or out of bounds.
+            #[inline]
+            pub fn get(&self, idx: impl core::borrow::Borrow<$t>) -> Option<&T> {
+                self.vec.get(idx.borrow().val).and_then(Option::as_ref)
+            }
+            /// Retrieves an element, `None` if the index is a hole or out of bounds.
+            #[inline]
+            pub fn get_mut(&mut self, idx: impl core::borrow::Borrow<$t>) -> Option<&mut T> {
+                self.vec.get_mut(idx.borrow().val).and_then(Option::as_mut)
+            }
+            /// True if the index has an element.
+            #[inline]
+            pub fn contains_index(&self, idx: impl core::borrow::Borrow<$t>) -> bool {
+                self.get(idx).is_some()
+            }
+
+            /// Iterator over the indices that have an element.
+            #[inline]
+            pub fn indices_present(&self) -> impl core::iter::DoubleEndedIterator<Item = $t> + '_ {
+                self.iter_present().map(|(idx, _)| idx)
+            }
+            /// Iterator over the index/element pairs, holes excluded.
+            #[inline]
+            pub fn iter_present(
+                &self,
+            ) -> impl core::iter::DoubleEndedIterator<Item = ($t, &T)> + '_ {
+                self.vec
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(val, elem)| elem.as_ref().map(|elem| ($t { val }, elem)))
+            }
+            /// Mutable iterator over the index/element pairs, holes excluded.
+            #[inline]
+            pub fn iter_present_mut(
+                &mut self,
+            ) -> impl core::iter::DoubleEndedIterator<Item = ($t, &mut T)> + '_ {
+                self.vec
+                    .iter_mut()
+                    .enumerate()
+                    .filter_map(|(val, elem)| elem.as_mut().map(|elem| ($t { val }, elem)))
+            }
+        }
+
+        impl<T> core::ops::Index<$t> for $sparse<T> {
+            type Output = T;
+            #[inline]
+            #[track_caller]
+            fn index(&self, idx: $t) -> &T {
+                match self.vec.get(idx.val) {
+                    Some(Some(elem)) => elem,
+                    Some(None) => panic!(
+                        "index {}({}) has no element in {}",
+                        stringify!($t),
+                        idx.val,
+                        stringify!($sparse),
+                    ),
+                    None => $crate::oob::index(
+                        stringify!($t), idx.val, stringify!($sparse), self.vec.len()
+                    ),
+                }
+            }
+        }
+        impl<T> core::ops::IndexMut<$t> for $sparse<T> {
+            #[inline]
+            #[track_caller]
+            fn index_mut(&mut self, idx: $t) -> &mut T {
+                let len = self.vec.len();
+                match self.vec.get_mut(idx.val) {
+                    Some(Some(elem)) => elem,
+                    Some(None) => panic!(
+                        "index {}({}) has no element in {}",
+                        stringify!($t),
+                        idx.val,
+                        stringify!($sparse),
+                    ),
+                    None => $crate::oob::index(stringify!($t), idx.val, stringify!($sparse), len),
+                }
+            }
+        }
+
+        impl<T> core::iter::FromIterator<T> for $sparse<T> {
+            fn from_iter<I: core::iter::IntoIterator<Item = T>>(iter: I) -> Self {
+                let vec: $crate::alloc::vec::Vec<_> = iter.into_iter().map(Some).collect();
+                $sparse {
+                    present: vec.len(),
+                    vec,
+                }
+            }
+        }
+
+        $crate::handle!{ $t $($tail)* }
+    };
+}
+
+/// Sparse map code generation involving a map.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! map_sparse_map_codegen {
+    ($t:ident, $map:ident, $sparse:ident) => {
+        impl<T> $sparse<T> {
+            $crate::non_strict! {
+                /// Squeezes the holes out of the map.
+                ///
+                /// Returns a map from old indices to new indices, `None` for holes, so that side
+                /// tables can be fixed.
+                ///
+                /// This function voids indices previously created and should be used with great
+                /// care.
+                pub fn compact(&mut self) -> $map<Option<$t>> {
+                    let mut remap = $map::with_capacity(self.vec.len());
+                    let mut new = 0;
+                    for elem in &self.vec {
+                        if elem.is_some() {
+                            remap.push(Some($t { val: new }));
+                            new += 1;
+                        } else {
+                            remap.push(None);
+                        }
+                    }
+                    self.vec.retain(Option::is_some);
+                    debug_assert_eq! { self.vec.len(), self.present }
+                    remap
+                }
+            }
+        }
+    };
+}
@@ -0,0 +1,1497 @@
+//! Generic indices and maps, shared by all the index families declared in tagged mode.
+//!
+//! In tagged mode, [`new`](crate::new) does not generate an index type and its collections for
+//! each index family. It declares a zero-sized tag type and aliases [`Idx`] and [`Map`] over this
+//! tag instead, which are implemented once for all families:
+//!
+//! ```rust
+//! safe_index::new! {
+//!     /// Indices of clients.
+//!     tagged Client: ClientTag,
+//!     /// Map from clients to something.
+//!     map: Clients,
+//!     /// Set of clients.
+//!     btree set: ClientSet,
+//! }
+//! safe_index::new! {
+//!     /// Indices of files.
+//!     tagged File: FileTag,
+//!     /// Map from files to something.
+//!     map: Files,
+//! }
+//!
+//! let mut clients = Clients::new();
+//! let c_0: Client = clients.push("client 0");
+//! let c_1 = clients.push("client 1");
+//! let set: ClientSet = clients.indices().collect();
+//! assert_eq! { clients[c_1], "client 1" }
+//! assert_eq! { set.len(), 2 }
+//!
+//! // `Client` and `File` are still distinct types.
+//! let mut files: Files<Vec<Client>> = Files::new();
+//! let f_0 = files.push(vec![c_0, c_1]);
+//! assert_eq! { files[f_0].len(), 2 }
+//! ```
+//!
+//! Tagged mode supports the `map`, `btree set`, `btree map`, `hash set`, `hash map`, `union find`
+//! and `interner` options, the others are rejected at compile time. The [tagged
+//! example](crate::examples::tagged) runs the clients example with tagged indices.
+
+use core::marker::PhantomData;
+
+use crate::alloc::vec::Vec;
+
+/// Implemented by the tags [`new`](crate::new) declares in tagged mode, names the family in
+/// debug output and error messages.
+pub trait Family {
+    /// Name of the index type.
+    const IDX: &'static str;
+    /// Name of the map type.
+    const MAP: &'static str;
+    /// Name of the union-find type.
+    const UNION_FIND: &'static str;
+    /// Name of the interner type.
+    const INTERNER: &'static str;
+}
+
+/// Index of the family tagged `Tag`, a wrapper around a `usize`.
+#[repr(transparent)]
+#[cfg_attr(
+    feature = "zerocopy",
+    derive(
+        zerocopy::FromBytes,
+        zerocopy::IntoBytes,
+        zerocopy::Immutable,
+        zerocopy::KnownLayout,
+    )
+)]
+pub struct Idx<Tag> {
+    val: usize,
+    tag: PhantomData<fn() -> Tag>,
+}
+
+impl<Tag> Idx<Tag> {
+    /// Private constructor.
+    #[inline]
+    const fn of(val: usize) -> Self {
+        Idx {
+            val,
+            tag: PhantomData,
+        }
+    }
+
+    crate::non_strict! {
+        /// Wraps a [`usize`].
+        #[inline]
+        pub const fn new(val: usize) -> Self {
+            Self::of(val)
+        }
+    }
+    crate::non_strict! {
+        /// Zero.
+        #[inline]
+        pub const fn zero() -> Self {
+            Self::of(0)
+        }
+    }
+    crate::non_strict! {
+        /// One.
+        #[inline]
+        pub const fn one() -> Self {
+            Self::of(1)
+        }
+    }
+    crate::non_strict! {
+        /// Increments the int.
+        #[inline]
+        pub fn inc(&mut self) {
+            self.val += 1
+        }
+    }
+    crate::non_strict! {
+        /// Decrements the int.
+        #[inline]
+        pub fn dec(&mut self) {
+            self.val -= 1
+        }
+    }
+    /// Underlying index accessor.
+    #[inline]
+    pub const fn get(&self) -> usize {
+        self.val
+    }
+}
+
+impl<Tag> Clone for Idx<Tag> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<Tag> Copy for Idx<Tag> {}
+impl<Tag> PartialEq for Idx<Tag> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.val == other.val
+    }
+}
+impl<Tag> Eq for Idx<Tag> {}
+impl<Tag> PartialOrd for Idx<Tag> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<Tag> Ord for Idx<Tag> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.val.cmp(&other.val)
+    }
+}
+impl<Tag> core::hash::Hash for Idx<Tag> {
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.val.hash(state)
+    }
+}
+impl<Tag: Family> core::fmt::Debug for Idx<Tag> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fmt.debug_struct(Tag::IDX).field("val", &self.val).finish()
+    }
+}
+impl<Tag> core::fmt::Display for Idx<Tag> {
+    #[inline]
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        write!(fmt, "{}", self.val)
+    }
+}
+impl<Tag> From<Idx<Tag>> for usize {
+    #[inline]
+    fn from(idx: Idx<Tag>) -> usize {
+        idx.val
+    }
+}
+impl<'a, Tag> From<&'a Idx<Tag>> for usize {
+    #[inline]
+    fn from(idx: &'a Idx<Tag>) -> usize {
+        idx.val
+    }
+}
+impl<Tag> core::ops::Deref for Idx<Tag> {
+    type Target = usize;
+    #[inline]
+    fn deref(&self) -> &usize {
+        &self.val
+    }
+}
+impl<Tag> PartialEq<usize> for Idx<Tag> {
+    #[inline]
+    fn eq(&self, int: &usize) -> bool {
+        self.val.eq(int)
+    }
+}
+impl<Tag> PartialOrd<usize> for Idx<Tag> {
+    #[inline]
+    fn partial_cmp(&self, int: &usize) -> Option<core::cmp::Ordering> {
+        self.val.partial_cmp(int)
+    }
+}
+// Sound: `Idx` is `#[repr(transparent)]` over its `usize`, the tag is zero-sized.
+unsafe impl<Tag: Family> crate::SafeIndex for Idx<Tag> {
+    const NAME: &'static str = Tag::IDX;
+    crate::non_strict! {
+        const ZERO: Self = Self::of(0);
+        #[inline]
+        fn from_usize(val: usize) -> Self {
+            Self::of(val)
+        }
+    }
+    #[inline]
+    fn as_usize(&self) -> usize {
+        self.val
+    }
+}
+crate::non_strict! {
+    impl<Tag, T: Into<usize>> core::ops::Add<T> for Idx<Tag> {
+        type Output = Self;
+        #[inline]
+        fn add(mut self, rhs: T) -> Self {
+            self.val += rhs.into();
+            self
+        }
+    }
+    impl<Tag> From<usize> for Idx<Tag> {
+        #[inline]
+        fn from(val: usize) -> Self {
+            Self::of(val)
+        }
+    }
+    impl<'a, Tag> From<&'a usize> for Idx<Tag> {
+        #[inline]
+        fn from(val: &'a usize) -> Self {
+            Self::of(*val)
+        }
+    }
+    impl<Tag, T: Into<usize>> core::ops::AddAssign<T> for Idx<Tag> {
+        #[inline]
+        fn add_assign(&mut self, rhs: T) {
+            self.val += rhs.into()
+        }
+    }
+}
+#[cfg(any(not(feature = "strict"), feature = "petgraph"))]
+impl<Tag> Default for Idx<Tag> {
+    #[inline]
+    fn default() -> Self {
+        Self::of(0)
+    }
+}
+// Sound: `index` yields the value given to `new`.
+#[cfg(feature = "petgraph")]
+unsafe impl<Tag: Family + 'static> petgraph::graph::IndexType for Idx<Tag> {
+    #[inline]
+    fn new(val: usize) -> Self {
+        Self::of(val)
+    }
+    #[inline]
+    fn index(&self) -> usize {
+        self.val
+    }
+    #[inline]
+    fn max() -> Self {
+        Self::of(usize::MAX)
+    }
+}
+#[cfg(feature = "petgraph")]
+impl<Tag: Family + 'static> From<petgraph::graph::NodeIndex<Idx<Tag>>> for Idx<Tag> {
+    #[inline]
+    fn from(node: petgraph::graph::NodeIndex<Idx<Tag>>) -> Self {
+        Self::of(node.index())
+    }
+}
+#[cfg(feature = "num-traits")]
+crate::non_strict! {
+    impl<Tag> core::ops::Sub for Idx<Tag> {
+        type Output = Self;
+        #[inline]
+        fn sub(self, rhs: Self) -> Self {
+            Self::of(self.val - rhs.val)
+        }
+    }
+    impl<Tag> core::ops::Mul for Idx<Tag> {
+        type Output = Self;
+        #[inline]
+        fn mul(self, rhs: Self) -> Self {
+            Self::of(self.val * rhs.val)
+        }
+    }
+    impl<Tag> num_traits::Zero for Idx<Tag> {
+        #[inline]
+        fn zero() -> Self {
+            Self::of(0)
+        }
+        #[inline]
+        fn is_zero(&self) -> bool {
+            self.val == 0
+        }
+    }
+    impl<Tag> num_traits::One for Idx<Tag> {
+        #[inline]
+        fn one() -> Self {
+            Self::of(1)
+        }
+    }
+    impl<Tag> num_traits::Bounded for Idx<Tag> {
+        #[inline]
+        fn min_value() -> Self {
+            Self::of(0)
+        }
+        #[inline]
+        fn max_value() -> Self {
+            Self::of(usize::MAX)
+        }
+    }
+    impl<Tag> num_traits::CheckedAdd for Idx<Tag> {
+        #[inline]
+        fn checked_add(&self, rhs: &Self) -> Option<Self> {
+            self.val.checked_add(rhs.val).map(Self::of)
+        }
+    }
+    impl<Tag> num_traits::CheckedSub for Idx<Tag> {
+        #[inline]
+        fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+            self.val.checked_sub(rhs.val).map(Self::of)
+        }
+    }
+    impl<Tag> num_traits::SaturatingAdd for Idx<Tag> {
+        #[inline]
+        fn saturating_add(&self, rhs: &Self) -> Self {
+            Self::of(self.val.saturating_add(rhs.val))
+        }
+    }
+    impl<Tag> num_traits::SaturatingSub for Idx<Tag> {
+        #[inline]
+        fn saturating_sub(&self, rhs: &Self) -> Self {
+            Self::of(self.val.saturating_sub(rhs.val))
+        }
+    }
+}
+#[cfg(feature = "serde")]
+impl<Tag> serde::Serialize for Idx<Tag> {
+    /// Serializes the index as its `usize`.
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&self.val, serializer)
+    }
+}
+#[cfg(all(feature = "serde", not(feature = "strict")))]
+impl<'de, Tag> serde::Deserialize<'de> for Idx<Tag> {
+    /// Deserializes a `usize`.
+    #[inline]
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        <usize as serde::Deserialize>::deserialize(deserializer).map(Self::of)
+    }
+}
+#[cfg(feature = "borsh")]
+impl<Tag> borsh::BorshSerialize for Idx<Tag> {
+    /// Serializes the index as its `usize`, a little-endian `u64`.
+    #[inline]
+    fn serialize<W: borsh::io::Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+        borsh::BorshSerialize::serialize(&self.val, writer)
+    }
+}
+#[cfg(all(feature = "borsh", not(feature = "strict")))]
+impl<Tag> borsh::BorshDeserialize for Idx<Tag> {
+    /// Deserializes a little-endian `u64`, fails if it does not fit in a `usize`.
+    #[inline]
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        <usize as borsh::BorshDeserialize>::deserialize_reader(reader).map(Self::of)
+    }
+}
+#[cfg(feature = "index_vec")]
+impl<Tag: Family + 'static> index_vec::Idx for Idx<Tag> {
+    #[inline]
+    fn from_usize(val: usize) -> Self {
+        Self::of(val)
+    }
+    #[inline]
+    fn index(self) -> usize {
+        self.val
+    }
+}
+#[cfg(feature = "schemars")]
+impl<Tag: Family> schemars::JsonSchema for Idx<Tag> {
+    fn schema_name() -> crate::alloc::borrow::Cow<'static, str> {
+        Tag::IDX.into()
+    }
+    fn schema_id() -> crate::alloc::borrow::Cow<'static, str> {
+        crate::alloc::format!("safe_index::tagged::Idx<{}>", core::any::type_name::<Tag>()).into()
+    }
+    /// Non-negative integer as wide as a `usize`.
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "integer",
+            "format": crate::alloc::format!("uint{}", usize::BITS),
+            "minimum": 0,
+        })
+    }
+}
+
+/// Map from the indices of the family tagged `Tag` to `T`s, a wrapper around a vector.
+#[repr(transparent)]
+pub struct Map<Tag, T> {
+    vec: Vec<T>,
+    tag: PhantomData<fn() -> Tag>,
+}
+
+impl<Tag: Family, T> Map<Tag, T> {
+    /// Private constructor.
+    #[inline]
+    const fn of(vec: Vec<T>) -> Self {
+        Map {
+            vec,
+            tag: PhantomData,
+        }
+    }
+
+    /// Creates an empty map.
+    #[inline]
+    pub const fn new() -> Self {
+        Self::of(Vec::new())
+    }
+    /// Creates an empty map with some capacity.
+    #[inline]
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self::of(Vec::with_capacity(capacity))
+    }
+    /// Reserves some space for the map.
+    #[inline]
+    pub fn reserve(&mut self, capa: usize) {
+        self.vec.reserve(capa)
+    }
+    /// Shrinks the capacity of the map as much as possible.
+    #[inline]
+    pub fn shrink_to_fit(&mut self) {
+        self.vec.shrink_to_fit()
+    }
+
+    /// Generates an index from a [`usize`] when it is a legal index.
+    #[inline]
+    pub fn index_from_usize(&self, n: usize) -> Option<Idx<Tag>> {
+        if n < self.vec.len() {
+            Some(Idx::of(n))
+        } else {
+            None
+        }
+    }
+
+    /// Retrieves an entry in the map.
+    #[inline]
+    pub fn get(&self, idx: impl core::borrow::Borrow<Idx<Tag>>) -> Option<&T> {
+        self.vec.get(idx.borrow().val)
+    }
+    /// Retrieves an entry in the map.
+    #[inline]
+    pub fn get_mut(&mut self, idx: impl core::borrow::Borrow<Idx<Tag>>) -> Option<&mut T> {
+        self.vec.get_mut(idx.borrow().val)
+    }
+    /// True if the index is a legal index of the map.
+    #[inline]
+    pub fn contains_index(&self, idx: Idx<Tag>) -> bool {
+        idx.val < self.vec.len()
+    }
+    /// Replaces the element at some index, yields the old one.
+    #[inline]
+    #[track_caller]
+    pub fn replace(&mut self, idx: Idx<Tag>, elem: T) -> T {
+        core::mem::replace(&mut self[idx], elem)
+    }
+    /// Swaps two elements, panics if either index is out of bounds.
+    #[inline]
+    #[track_caller]
+    pub fn swap(&mut self, a: Idx<Tag>, b: Idx<Tag>) {
+        let len = self.vec.len();
+        for idx in [a, b] {
+            if idx.val >= len {
+                crate::oob::index(Tag::IDX, idx.val, Tag::MAP, len)
+            }
+        }
+        self.vec.swap(a.val, b.val)
+    }
+
+    /// Number of elements in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+    /// True if the map is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+    /// Capacity of the map.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.vec.capacity()
+    }
+
+    crate::non_strict! {
+        /// The next free index (wrapped `self.len()`).
+        #[inline]
+        pub fn next_index(&self) -> Idx<Tag> {
+            Idx::of(self.len())
+        }
+    }
+    /// Index of the last element in the map.
+    #[inline]
+    pub fn last_index(&self) -> Option<Idx<Tag>> {
+        let len = self.len();
+        if len > 0 {
+            Some(Idx::of(len - 1))
+        } else {
+            None
+        }
+    }
+    /// Last entry of the map.
+    #[inline]
+    pub fn last(&self) -> Option<(Idx<Tag>, &T)> {
+        let idx = self.last_index()?;
+        Some((idx, &self.vec[idx.val]))
+    }
+
+    /// Pushes an element, yields its index.
+    #[inline]
+    pub fn push(&mut self, elem: T) -> Idx<Tag> {
+        let idx = Idx::of(self.len());
+        self.vec.push(elem);
+        idx
+    }
+    /// Pushes an element built from its index, yields this index.
+    #[inline]
+    pub fn push_idx(&mut self, new_elem: impl FnOnce(Idx<Tag>) -> T) -> Idx<Tag> {
+        let idx = Idx::of(self.len());
+        self.vec.push(new_elem(idx));
+        idx
+    }
+    /// Pops an element.
+    #[inline]
+    pub fn pop(&mut self) -> Option<T> {
+        self.vec.pop()
+    }
+    /// Clears the map, keeps the capacity.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.vec.clear()
+    }
+
+    /// Iterator over all the indices.
+    #[inline]
+    pub fn indices(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Idx<Tag>> + ExactSizeIterator + core::iter::FusedIterator
+    {
+        (0..self.len()).map(Idx::of)
+    }
+    /// Set of all the indices of the map.
+    #[inline]
+    pub fn keys_as_set(&self) -> crate::alloc::collections::BTreeSet<Idx<Tag>> {
+        self.indices().collect()
+    }
+
+    /// Ref-iterator over the elements.
+    #[inline]
+    pub fn iter(&self) -> core::slice::Iter<'_, T> {
+        self.vec.iter()
+    }
+    /// Ref-mut-iterator over the elements.
+    #[inline]
+    pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, T> {
+        self.vec.iter_mut()
+    }
+    /// Ref-iterator over the index/element pairs.
+    #[inline]
+    pub fn index_iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (Idx<Tag>, &T)> + ExactSizeIterator + core::iter::FusedIterator
+    {
+        self.vec
+            .iter()
+            .enumerate()
+            .map(|(val, elem)| (Idx::of(val), elem))
+    }
+    /// Ref-mut-iterator over the index/element pairs.
+    #[inline]
+    pub fn index_iter_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = (Idx<Tag>, &mut T)>
+           + ExactSizeIterator
+           + core::iter::FusedIterator {
+        self.vec
+            .iter_mut()
+            .enumerate()
+            .map(|(val, elem)| (Idx::of(val), elem))
+    }
+    /// Own-iterator over the index/element pairs.
+    #[inline]
+    pub fn into_index_iter(
+        self,
+    ) -> impl DoubleEndedIterator<Item = (Idx<Tag>, T)> + ExactSizeIterator + core::iter::FusedIterator
+    {
+        self.vec
+            .into_iter()
+            .enumerate()
+            .map(|(val, elem)| (Idx::of(val), elem))
+    }
+
+    /// Iterator over the entries at some indices, in the order of the indices.
+    ///
+    /// Panics if an index is out of bounds, like indexing does.
+    #[inline]
+    pub fn select<'a, I>(&'a self, indices: I) -> impl Iterator<Item = (Idx<Tag>, &'a T)> + 'a
+    where
+        I: IntoIterator,
+        I::IntoIter: 'a,
+        I::Item: core::borrow::Borrow<Idx<Tag>>,
+    {
+        indices.into_iter().map(move |idx| {
+            let idx = *core::borrow::Borrow::<Idx<Tag>>::borrow(&idx);
+            (idx, &self[idx])
+        })
+    }
+
+    /// Underlying vector.
+    #[inline]
+    pub fn as_vec(&self) -> &Vec<T> {
+        &self.vec
+    }
+    /// Turns the map into its underlying vector.
+    #[inline]
+    pub fn into_vec(self) -> Vec<T> {
+        self.vec
+    }
+}
+
+impl<Tag: Family, T: Clone> Map<Tag, T> {
+    /// Creates a map with `size` copies of `elem`.
+    #[inline]
+    pub fn of_elems(elem: T, size: usize) -> Self {
+        Self::of(crate::alloc::vec![elem; size])
+    }
+    /// Overwrites all the elements of the map with `value`.
+    #[inline]
+    pub fn fill(&mut self, value: T) {
+        self.vec.fill(value)
+    }
+}
+
+impl<Tag: Family, T: Default> Map<Tag, T> {
+    /// Creates a map of default elements with the same length as another map.
+    ///
+    /// The indices of `other` are legal for the new map.
+    #[inline]
+    pub fn new_like<U>(other: &Map<Tag, U>) -> Self {
+        Self::of(other.iter().map(|_| T::default()).collect())
+    }
+}
+
+crate::non_strict! {
+    impl<Tag: Family, T> From<Vec<T>> for Map<Tag, T> {
+        #[inline]
+        fn from(vec: Vec<T>) -> Self {
+            Self::of(vec)
+        }
+    }
+    impl<Tag: Family, T> core::ops::Deref for Map<Tag, T> {
+        type Target = Vec<T>;
+        #[inline]
+        fn deref(&self) -> &Vec<T> {
+            &self.vec
+        }
+    }
+}
+
+impl<Tag: Family, T> Default for Map<Tag, T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl<Tag: Family, T: Clone> Clone for Map<Tag, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self::of(self.vec.clone())
+    }
+}
+impl<Tag: Family, T: core::fmt::Debug> core::fmt::Debug for Map<Tag, T> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fmt.debug_struct(Tag::MAP).field("vec", &self.vec).finish()
+    }
+}
+impl<Tag: Family, T: PartialEq> PartialEq for Map<Tag, T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.vec == other.vec
+    }
+}
+impl<Tag: Family, T: Eq> Eq for Map<Tag, T> {}
+impl<Tag: Family, T: PartialOrd> PartialOrd for Map<Tag, T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        self.vec.partial_cmp(&other.vec)
+    }
+}
+impl<Tag: Family, T: Ord> Ord for Map<Tag, T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> core::cmp::Ordering {
+        self.vec.cmp(&other.vec)
+    }
+}
+impl<Tag: Family, T: core::hash::Hash> core::hash::Hash for Map<Tag, T> {
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.vec.hash(state)
+    }
+}
+
+impl<Tag: Family, T> IntoIterator for Map<Tag, T> {
+    type Item = T;
+    type IntoIter = crate::alloc::vec::IntoIter<T>;
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.vec.into_iter()
+    }
+}
+impl<'a, Tag: Family, T> IntoIterator for &'a Map<Tag, T> {
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+impl<'a, Tag: Family, T> IntoIterator for &'a mut Map<Tag, T> {
+    type Item = &'a mut T;
+    type IntoIter = core::slice::IterMut<'a, T>;
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+impl<Tag: Family, T> From<Map<Tag, T>> for crate::IdxVec<Idx<Tag>, T> {
+    #[inline]
+    fn from(map: Map<Tag, T>) -> Self {
+        crate::IdxVec::of(map.vec)
+    }
+}
+impl<Tag: Family, T> From<crate::IdxVec<Idx<Tag>, T>> for Map<Tag, T> {
+    #[inline]
+    fn from(map: crate::IdxVec<Idx<Tag>, T>) -> Self {
+        Self::of(map.into_vec())
+    }
+}
+impl<Tag: Family, T> crate::SafeMap for Map<Tag, T> {
+    type Idx = Idx<Tag>;
+    type Elem = T;
+    #[inline]
+    fn len(&self) -> usize {
+        self.vec.len()
+    }
+    #[inline]
+    fn push(&mut self, elem: T) -> Idx<Tag> {
+        Map::push(self, elem)
+    }
+    #[inline]
+    fn get(&self, idx: Idx<Tag>) -> Option<&T> {
+        self.vec.get(idx.val)
+    }
+    #[inline]
+    fn get_mut(&mut self, idx: Idx<Tag>) -> Option<&mut T> {
+        self.vec.get_mut(idx.val)
+    }
+    #[inline]
+    fn index_from_usize(&self, n: usize) -> Option<Idx<Tag>> {
+        Map::index_from_usize(self, n)
+    }
+}
+
+impl<Tag: Family, T> core::iter::FromIterator<T> for Map<Tag, T> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        Self::of(iter.into_iter().collect())
+    }
+}
+
+impl<Tag: Family, T> core::ops::Index<Idx<Tag>> for Map<Tag, T> {
+    type Output = T;
+    #[inline]
+    #[track_caller]
+    fn index(&self, idx: Idx<Tag>) -> &T {
+        match self.vec.get(idx.val) {
+            Some(elem) => elem,
+            None => crate::oob::index(Tag::IDX, idx.val, Tag::MAP, self.vec.len()),
+        }
+    }
+}
+impl<Tag: Family, T> core::ops::IndexMut<Idx<Tag>> for Map<Tag, T> {
+    #[inline]
+    #[track_caller]
+    fn index_mut(&mut self, idx: Idx<Tag>) -> &mut T {
+        let len = self.vec.len();
+        match self.vec.get_mut(idx.val) {
+            Some(elem) => elem,
+            None => crate::oob::index(Tag::IDX, idx.val, Tag::MAP, len),
+        }
+    }
+}
+impl<'a, Tag: Family, T> core::ops::Index<&'a Idx<Tag>> for Map<Tag, T> {
+    type Output = T;
+    #[inline]
+    #[track_caller]
+    fn index(&self, idx: &'a Idx<Tag>) -> &T {
+        &self[*idx]
+    }
+}
+impl<'a, Tag: Family, T> core::ops::IndexMut<&'a Idx<Tag>> for Map<Tag, T> {
+    #[inline]
+    #[track_caller]
+    fn index_mut(&mut self, idx: &'a Idx<Tag>) -> &mut T {
+        &mut self[*idx]
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<Tag, T: serde::Serialize> serde::Serialize for Map<Tag, T> {
+    /// Serializes the map as the sequence of its elements.
+    #[inline]
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&self.vec, serializer)
+    }
+}
+#[cfg(all(feature = "serde", not(feature = "strict")))]
+impl<'de, Tag, T: serde::Deserialize<'de>> serde::Deserialize<'de> for Map<Tag, T> {
+    /// Deserializes a sequence, element `i` goes to index `i`.
+    #[inline]
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let vec = serde::Deserialize::deserialize(deserializer)?;
+        Ok(Map {
+            vec,
+            tag: PhantomData,
+        })
+    }
+}
+#[cfg(feature = "borsh")]
+impl<Tag, T: borsh::BorshSerialize> borsh::BorshSerialize for Map<Tag, T> {
+    /// Serializes the map as a sequence, its length as a little-endian `u32` followed by the
+    /// elements.
+    #[inline]
+    fn serialize<W: borsh::io::Write>(&self, writer: &mut W) -> borsh::io::Result<()> {
+        borsh::BorshSerialize::serialize(&self.vec, writer)
+    }
+}
+#[cfg(all(feature = "borsh", not(feature = "strict")))]
+impl<Tag, T: borsh::BorshDeserialize> borsh::BorshDeserialize for Map<Tag, T> {
+    /// Deserializes a sequence, element `i` goes to index `i`.
+    #[inline]
+    fn deserialize_reader<R: borsh::io::Read>(reader: &mut R) -> borsh::io::Result<Self> {
+        let vec = borsh::BorshDeserialize::deserialize_reader(reader)?;
+        Ok(Map {
+            vec,
+            tag: PhantomData,
+        })
+    }
+}
+#[cfg(feature = "index_vec")]
+impl<Tag: Family + 'static, T> From<Map<Tag, T>> for index_vec::IndexVec<Idx<Tag>, T> {
+    /// Moves the elements of the map, without copying.
+    #[inline]
+    fn from(map: Map<Tag, T>) -> Self {
+        index_vec::IndexVec::from_vec(map.vec)
+    }
+}
+#[cfg(feature = "index_vec")]
+impl<Tag: Family + 'static, T> From<index_vec::IndexVec<Idx<Tag>, T>> for Map<Tag, T> {
+    /// Moves the elements of the vector, without copying.
+    #[inline]
+    fn from(vec: index_vec::IndexVec<Idx<Tag>, T>) -> Self {
+        Self::of(vec.raw)
+    }
+}
+#[cfg(feature = "typed-index-collections")]
+impl<Tag, T> From<Map<Tag, T>> for typed_index_collections::TiVec<Idx<Tag>, T> {
+    /// Moves the elements of the map, without copying.
+    #[inline]
+    fn from(map: Map<Tag, T>) -> Self {
+        map.vec.into()
+    }
+}
+#[cfg(all(feature = "typed-index-collections", not(feature = "strict")))]
+impl<Tag: Family, T> From<typed_index_collections::TiVec<Idx<Tag>, T>> for Map<Tag, T> {
+    /// Moves the elements of the vector, without copying.
+    #[inline]
+    fn from(vec: typed_index_collections::TiVec<Idx<Tag>, T>) -> Self {
+        Self::of(vec.raw)
+    }
+}
+#[cfg(feature = "schemars")]
+impl<Tag: Family, T: schemars::JsonSchema> schemars::JsonSchema for Map<Tag, T> {
+    fn schema_name() -> crate::alloc::borrow::Cow<'static, str> {
+        crate::alloc::format!("{}_of_{}", Tag::MAP, T::schema_name()).into()
+    }
+    fn schema_id() -> crate::alloc::borrow::Cow<'static, str> {
+        crate::alloc::format!(
+            "safe_index::tagged::Map<{}, {}>",
+            core::any::type_name::<Tag>(),
+            T::schema_id()
+        )
+        .into()
+    }
+    /// Array of the elements, element `i` is the one of index `i`.
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "type": "array",
+            "description": crate::alloc::format!("Map indexed by `{}`.", Tag::IDX),
+            "items": generator.subschema_for::<T>(),
+        })
+    }
+}
+
+/// Generates `Index` and `IndexMut` implementations over some typed ranges.
+macro_rules! range_index {
+    ($($range:ty),* $(,)?) => {$(
+        impl<Tag: Family, T> core::ops::Index<$range> for Map<Tag, T> {
+            type Output = [T];
+            #[inline]
+            #[track_caller]
+            fn index(&self, range: $range) -> &[T] {
+                let range = crate::oob::range::<Idx<Tag>>(Tag::IDX, Tag::MAP, self.vec.len(), &range);
+                &self.vec[range]
+            }
+        }
+        impl<Tag: Family, T> core::ops::IndexMut<$range> for Map<Tag, T> {
+            #[inline]
+            #[track_caller]
+            fn index_mut(&mut self, range: $range) -> &mut [T] {
+                let range = crate::oob::range::<Idx<Tag>>(Tag::IDX, Tag::MAP, self.vec.len(), &range);
+                &mut self.vec[range]
+            }
+        }
+    )*};
+}
+range_index! {
+    core::ops::Range<Idx<Tag>>,
+    core::ops::RangeInclusive<Idx<Tag>>,
+    core::ops::RangeFrom<Idx<Tag>>,
+    core::ops::RangeTo<Idx<Tag>>,
+    core::ops::RangeToInclusive<Idx<Tag>>,
+    core::ops::RangeFull,
+}
+
+/// Partition of the indices of the family tagged `Tag` into disjoint sets, with union by rank and
+/// path compression.
+///
+/// Not comparable with `==`, since two forests can represent the same partition.
+pub struct UnionFind<Tag> {
+    /// Parent of each index, roots are their own parent.
+    parent: Vec<usize>,
+    /// Upper bound on the height of the tree of each root.
+    rank: Vec<u8>,
+    /// Number of sets.
+    sets: usize,
+    tag: PhantomData<fn() -> Tag>,
+}
+
+impl<Tag: Family> UnionFind<Tag> {
+    /// Private constructor, each index smaller than `len` is alone in its set.
+    #[inline]
+    fn of_len(len: usize) -> Self {
+        UnionFind {
+            parent: (0..len).collect(),
+            rank: crate::alloc::vec![0; len],
+            sets: len,
+            tag: PhantomData,
+        }
+    }
+
+    /// Creates an empty partition.
+    #[inline]
+    pub const fn new_empty() -> Self {
+        UnionFind {
+            parent: Vec::new(),
+            rank: Vec::new(),
+            sets: 0,
+            tag: PhantomData,
+        }
+    }
+    crate::non_strict! {
+        /// Creates a partition where each index smaller than `len` is alone in its set.
+        #[inline]
+        pub fn new(len: usize) -> Self {
+            Self::of_len(len)
+        }
+    }
+    /// Creates a partition where each index of a map is alone in its set.
+    ///
+    /// Like [`Self::make_set`], this is a legal source of indices in strict mode, since the map's
+    /// indices are legal.
+    #[inline]
+    pub fn new_like<T>(map: &Map<Tag, T>) -> Self {
+        Self::of_len(map.len())
+    }
+    /// Number of indices in the partition.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+    /// True if the partition has no indices.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+    /// Number of disjoint sets.
+    #[inline]
+    pub fn set_count(&self) -> usize {
+        self.sets
+    }
+
+    /// Adds a new index in its own set.
+    ///
+    /// Like the `push` of a map, this is a legal source of indices in strict mode.
+    pub fn make_set(&mut self) -> Idx<Tag> {
+        let val = self.parent.len();
+        self.parent.push(val);
+        self.rank.push(0);
+        self.sets += 1;
+        Idx::of(val)
+    }
+
+    /// Root of the tree of an index, panics if the index is out of bounds.
+    #[inline]
+    #[track_caller]
+    fn root(&mut self, idx: Idx<Tag>) -> usize {
+        if idx.val >= self.parent.len() {
+            crate::oob::index(Tag::IDX, idx.val, Tag::UNION_FIND, self.parent.len())
+        }
+        let mut root = idx.val;
+        while self.parent[root] != root {
+            root = self.parent[root]
+        }
+        // Path compression.
+        let mut current = idx.val;
+        while current != root {
+            current = core::mem::replace(&mut self.parent[current], root)
+        }
+        root
+    }
+    /// Representative of the set of an index.
+    ///
+    /// Two indices are in the same set iff they have the same representative, which changes when
+    /// the set is merged with another one.
+    #[inline]
+    #[track_caller]
+    pub fn find(&mut self, idx: Idx<Tag>) -> Idx<Tag> {
+        Idx::of(self.root(idx))
+    }
+    /// True if two indices are in the same set.
+    #[inline]
+    #[track_caller]
+    pub fn same_set(&mut self, lft: Idx<Tag>, rgt: Idx<Tag>) -> bool {
+        self.root(lft) == self.root(rgt)
+    }
+    /// Merges the sets of two indices, false if they were already in the same set.
+    #[track_caller]
+    pub fn union(&mut self, lft: Idx<Tag>, rgt: Idx<Tag>) -> bool {
+        let (lft, rgt) = (self.root(lft), self.root(rgt));
+        if lft == rgt {
+            return false;
+        }
+        match self.rank[lft].cmp(&self.rank[rgt]) {
+            core::cmp::Ordering::Less => self.parent[lft] = rgt,
+            core::cmp::Ordering::Greater => self.parent[rgt] = lft,
+            core::cmp::Ordering::Equal => {
+                self.parent[rgt] = lft;
+                self.rank[lft] += 1;
+            }
+        }
+        self.sets -= 1;
+        true
+    }
+
+    /// Turns the partition into its sets, ordered by smallest index.
+    ///
+    /// The sets can be any collection of indices, typically the `btree set` of the family.
+    pub fn into_groups<S>(mut self) -> Vec<S>
+    where
+        S: core::iter::FromIterator<Idx<Tag>>,
+    {
+        let len = self.parent.len();
+        // Position of the group of each root in `groups`.
+        let mut group_of = crate::alloc::vec![usize::MAX; len];
+        let mut groups: Vec<Vec<Idx<Tag>>> = Vec::with_capacity(self.sets);
+        for val in 0..len {
+            let root = self.root(Idx::of(val));
+            if group_of[root] == usize::MAX {
+                group_of[root] = groups.len();
+                groups.push(Vec::new());
+            }
+            groups[group_of[root]].push(Idx::of(val))
+        }
+        groups
+            .into_iter()
+            .map(|group| group.into_iter().collect())
+            .collect()
+    }
+}
+
+impl<Tag: Family> Default for UnionFind<Tag> {
+    #[inline]
+    fn default() -> Self {
+        Self::new_empty()
+    }
+}
+impl<Tag> Clone for UnionFind<Tag> {
+    #[inline]
+    fn clone(&self) -> Self {
+        UnionFind {
+            parent: self.parent.clone(),
+            rank: self.rank.clone(),
+            sets: self.sets,
+            tag: PhantomData,
+        }
+    }
+}
+impl<Tag: Family> core::fmt::Debug for UnionFind<Tag> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fmt.debug_struct(Tag::UNION_FIND)
+            .field("parent", &self.parent)
+            .field("rank", &self.rank)
+            .field("sets", &self.sets)
+            .finish()
+    }
+}
+
+/// Interner of the family tagged `Tag`: the first time a value is interned it gets the next free
+/// index, interning an equal value later yields the same index.
+///
+/// Requires the `std` or `hashbrown` feature, like the `interner` option.
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+pub struct Interner<Tag, T> {
+    /// Values in index order.
+    vec: Vec<T>,
+    /// Index of each value.
+    indices: crate::__hash::HashMap<T, Idx<Tag>>,
+}
+
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+impl<Tag: Family, T> Interner<Tag, T> {
+    /// Creates an empty interner.
+    #[inline]
+    pub fn new() -> Self {
+        Interner {
+            vec: Vec::new(),
+            indices: crate::__hash::HashMap::default(),
+        }
+    }
+
+    /// Number of values interned.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.vec.len()
+    }
+    /// True if no value was interned.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.vec.is_empty()
+    }
+
+    /// Value of an index, `None` if the index is out of bounds.
+    #[inline]
+    pub fn try_resolve(&self, idx: Idx<Tag>) -> Option<&T> {
+        self.vec.get(idx.val)
+    }
+    /// Value of an index, panics if the index is out of bounds.
+    #[inline]
+    #[track_caller]
+    pub fn resolve(&self, idx: Idx<Tag>) -> &T {
+        &self[idx]
+    }
+    /// Values interned, as a slice in index order.
+    #[inline]
+    pub fn as_slice(&self) -> &[T] {
+        &self.vec
+    }
+
+    /// Iterator over all the indices.
+    #[inline]
+    pub fn indices(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = Idx<Tag>> + ExactSizeIterator + core::iter::FusedIterator
+    {
+        (0..self.len()).map(Idx::of)
+    }
+    /// Iterator over the values, in index order.
+    #[inline]
+    pub fn iter(&self) -> core::slice::Iter<'_, T> {
+        self.vec.iter()
+    }
+    /// Iterator over the index/value pairs, in index order.
+    #[inline]
+    pub fn index_iter(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (Idx<Tag>, &T)> + ExactSizeIterator + core::iter::FusedIterator
+    {
+        self.vec
+            .iter()
+            .enumerate()
+            .map(|(val, elem)| (Idx::of(val), elem))
+    }
+    /// Turns the interner into the vector of its values, in index order.
+    #[inline]
+    pub fn into_vec(self) -> Vec<T> {
+        self.vec
+    }
+}
+
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+impl<Tag: Family, T: core::hash::Hash + Eq + Clone> Interner<Tag, T> {
+    /// Interns a value, yields its index.
+    ///
+    /// The index is the one the value (or an equal value) was given if it was interned already,
+    /// the next free index otherwise.
+    pub fn intern(&mut self, value: T) -> Idx<Tag> {
+        if let Some(idx) = self.indices.get(&value) {
+            return *idx;
+        }
+        let idx = Idx::of(self.vec.len());
+        self.indices.insert(value.clone(), idx);
+        self.vec.push(value);
+        idx
+    }
+    /// Index of a value, `None` if it was never interned.
+    #[inline]
+    pub fn get<Q>(&self, value: &Q) -> Option<Idx<Tag>>
+    where
+        T: core::borrow::Borrow<Q>,
+        Q: core::hash::Hash + Eq + ?Sized,
+    {
+        self.indices.get(value).copied()
+    }
+    /// True if a value was interned.
+    #[inline]
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: core::borrow::Borrow<Q>,
+        Q: core::hash::Hash + Eq + ?Sized,
+    {
+        self.indices.contains_key(value)
+    }
+}
+
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+impl<Tag: Family, T> Default for Interner<Tag, T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+impl<Tag, T: Clone> Clone for Interner<Tag, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Interner {
+            vec: self.vec.clone(),
+            indices: self.indices.clone(),
+        }
+    }
+}
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+impl<Tag: Family, T: core::fmt::Debug> core::fmt::Debug for Interner<Tag, T> {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter) -> core::fmt::Result {
+        fmt.debug_struct(Tag::INTERNER)
+            .field("vec", &self.vec)
+            .finish()
+    }
+}
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+impl<Tag, T: PartialEq> PartialEq for Interner<Tag, T> {
+    /// Compares the values in index order, which determine the indices.
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.vec == other.vec
+    }
+}
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+impl<Tag, T: Eq> Eq for Interner<Tag, T> {}
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+impl<Tag: Family, T> core::ops::Index<Idx<Tag>> for Interner<Tag, T> {
+    type Output = T;
+    #[inline]
+    #[track_caller]
+    fn index(&self, idx: Idx<Tag>) -> &T {
+        match self.vec.get(idx.val) {
+            Some(value) => value,
+            None => crate::oob::index(Tag::IDX, idx.val, Tag::INTERNER, self.vec.len()),
+        }
+    }
+}
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+impl<'a, Tag: Family, T> core::ops::Index<&'a Idx<Tag>> for Interner<Tag, T> {
+    type Output = T;
+    #[inline]
+    #[track_caller]
+    fn index(&self, idx: &'a Idx<Tag>) -> &T {
+        &self[*idx]
+    }
+}
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+impl<'a, Tag, T> IntoIterator for &'a Interner<Tag, T> {
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.vec.iter()
+    }
+}
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+impl<Tag: Family, T: core::hash::Hash + Eq + Clone> core::iter::FromIterator<T>
+    for Interner<Tag, T>
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut interner = Self::new();
+        interner.extend(iter);
+        interner
+    }
+}
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+impl<Tag: Family, T: core::hash::Hash + Eq + Clone> core::iter::Extend<T> for Interner<Tag, T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.intern(value);
+        }
+    }
+}
+
+/// Tagged index type code generation, see [`new`](crate::new).
+#[macro_export]
+#[doc(hidden)]
+macro_rules! tagged_codegen {
+    (
+        $(#[$meta:meta])*
+        $t:ident $tag:ident
+        $($tail:tt)*
+    ) => {
+        #[doc = concat!("Tag of the [`", stringify!($t), "`] index family.")]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub enum $tag {}
+        $(#[$meta])*
+        pub type $t = $crate::tagged::Idx<$tag>;
+        $crate::tagged_handle!{
+            $t $tag [
+                concat!("Map<", stringify!($tag), ">"),
+                concat!("UnionFind<", stringify!($tag), ">"),
+                concat!("Interner<", stringify!($tag), ">")
+            ] $($tail)*
+        }
+    };
+}
+
+/// Handles the options of `new!` in tagged mode.
+///
+/// The bracketed expressions are the names of the map, union-find and interner types, for the
+/// [`Family`] implementation of the tag generated once all the options are handled.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! tagged_handle {
+    {
+        $t:ident $tag:ident [$map_name:expr, $uf_name:expr, $interner_name:expr],
+        $(#[$meta:meta])* map: $map:ident $($tail:tt)*
+    } => {
+        $(#[$meta])*
+        pub type $map<T> = $crate::tagged::Map<$tag, T>;
+        $crate::tagged_handle! {
+            $t $tag [stringify!($map), $uf_name, $interner_name] $($tail)*
+        }
+    };
+    {
+        $t:ident $tag:ident [$map_name:expr, $uf_name:expr, $interner_name:expr], $(#[$meta:meta])* btree set: $set:ident $($tail:tt)*
+    } => {
+        $crate::btree_set_codegen! { $t, $(#[$meta])* $set }
+        $crate::tagged_handle! { $t $tag [$map_name, $uf_name, $interner_name] $($tail)* }
+    };
+    {
+        $t:ident $tag:ident [$map_name:expr, $uf_name:expr, $interner_name:expr], $(#[$meta:meta])* btree map: $map:ident $($tail:tt)*
+    } => {
+        $crate::btree_map_codegen! { $t, $(#[$meta])* $map }
+        $crate::tagged_handle! { $t $tag [$map_name, $uf_name, $interner_name] $($tail)* }
+    };
+    {
+        $t:ident $tag:ident [$map_name:expr, $uf_name:expr, $interner_name:expr], $(#[$meta:meta])* hash set: $set:ident $($tail:tt)*
+    } => {
+        $crate::hash_set_codegen! { $t, $(#[$meta])* $set }
+        $crate::tagged_handle! { $t $tag [$map_name, $uf_name, $interner_name] $($tail)* }
+    };
+    {
+        $t:ident $tag:ident [$map_name:expr, $uf_name:expr, $interner_name:expr], $(#[$meta:meta])* hash map: $map:ident $($tail:tt)*
+    } => {
+        $crate::hash_map_codegen! { $t, $(#[$meta])* $map }
+        $crate::tagged_handle! { $t $tag [$map_name, $uf_name, $interner_name] $($tail)* }
+    };
+    {
+        $t:ident $tag:ident [$map_name:expr, $uf_name:expr, $interner_name:expr],
+        $(#[$meta:meta])* union find: $uf:ident $($tail:tt)*
+    } => {
+        $(#[$meta])*
+        pub type $uf = $crate::tagged::UnionFind<$tag>;
+        $crate::tagged_handle! {
+            $t $tag [$map_name, stringify!($uf), $interner_name] $($tail)*
+        }
+    };
+    {
+        $t:ident $tag:ident [$map_name:expr, $uf_name:expr, $interner_name:expr],
+        $(#[$meta:meta])* interner: $interner:ident $($tail:tt)*
+    } => {
+        $crate::tagged_interner_codegen! { $tag, $(#[$meta])* $interner }
+        $crate::tagged_handle! {
+            $t $tag [$map_name, $uf_name, stringify!($interner)] $($tail)*
+        }
+    };
+    { $t:ident $tag:ident [$map_name:expr, $uf_name:expr, $interner_name:expr] $(,)? } => {
+        impl $crate::tagged::Family for $tag {
+            const IDX: &'static str = stringify!($t);
+            const MAP: &'static str = $map_name;
+            const UNION_FIND: &'static str = $uf_name;
+            const INTERNER: &'static str = $interner_name;
+        }
+    };
+
+    { $t:ident $tag:ident [$map_name:expr, $uf_name:expr, $interner_name:expr], $(#[$meta:meta])* $kw:ident $($tail:tt)* } => {
+        $crate::tagged_handle! { @unsupported $kw $($tail)* }
+    };
+    { $t:ident $tag:ident [$map_name:expr, $uf_name:expr, $interner_name:expr], $token:tt $($tail:tt)* } => {
+        $crate::tagged_handle! { @unsupported $token }
+    };
+    { @unsupported $kw:ident $kw_2:ident $kw_3:ident : $($tail:tt)* } => {
+        $crate::tagged_handle! { @unsupported_option concat!(
+            stringify!($kw), " ", stringify!($kw_2), " ", stringify!($kw_3)
+        ) }
+    };
+    { @unsupported $kw:ident $kw_2:ident : $($tail:tt)* } => {
+        $crate::tagged_handle! { @unsupported_option concat!(
+            stringify!($kw), " ", stringify!($kw_2)
+        ) }
+    };
+    { @unsupported $kw:ident : $($tail:tt)* } => {
+        $crate::tagged_handle! { @unsupported_option stringify!($kw) }
+    };
+    { @unsupported_option $option:expr } => {
+        compile_error!(concat!(
+            "tagged indices do not support `",
+            $option,
+            "`, only `map`, `btree set`, `btree map`, `hash set`, `hash map`, `union find` and \
+            `interner`: declare the index \
+            without `tagged` to use it",
+        ));
+    };
+    { @unsupported $token:tt $($tail:tt)* } => {
+        compile_error!(concat!(
+            "expected a tagged index option, found unexpected token `",
+            stringify!($token),
+            "`",
+        ));
+    };
+    { $t:ident $tag:ident [$map_name:expr, $uf_name:expr, $interner_name:expr] $token:tt $($tail:tt)* } => {
+        compile_error!(concat!(
+            "expected comma, found unexpected token `",
+            stringify!($token),
+            "`",
+        ));
+    };
+}
+
+/// Tagged interner alias generation, requires the `std` or `hashbrown` feature.
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! tagged_interner_codegen {
+    ($tag:ident, $(#[$meta:meta])* $interner:ident) => {
+        $(#[$meta])*
+        pub type $interner<T> = $crate::tagged::Interner<$tag, T>;
+    };
+}
+
+/// Tagged interner alias generation, without the hash maps interners rely on.
+#[cfg(not(any(feature = "std", feature = "hashbrown")))]
+#[macro_export]
+#[doc(hidden)]
+macro_rules! tagged_interner_codegen {
+    ($tag:ident, $(#[$meta:meta])* $interner:ident) => {
+        // Carries the attributes of the option, so that the error goes away with its `cfg`s.
+        $(#[$meta])*
+        const _: () = {
+            compile_error!(concat!(
+                "`interner: ",
+                stringify!($interner),
+                "` requires the `std` or `hashbrown` feature of safe_index",
+            ));
+        };
+    };
+}
@@ -0,0 +1,164 @@
+//! Handles union-find code generation.
+
+/// Union-find code generation.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! union_find_codegen {
+    ($t:ident,
+        $(#[$meta:meta])*
+        $uf:ident
+        $($tail:tt)*
+    ) => {
+        $(#[$meta])*
+        ///
+        /// Partition of indices into disjoint sets, with union by rank and path compression.
+        ///
+        /// Not comparable with `==`, since two forests can represent the same partition.
+        #[derive(Debug, Clone, Default)]
+        pub struct $uf {
+            /// Parent of each index, roots are their own parent.
+            parent: $crate::alloc::vec::Vec<usize>,
+            /// Upper bound on the height of the tree of each root.
+            rank: $crate::alloc::vec::Vec<u8>,
+            /// Number of sets.
+            sets: usize,
+        }
+
+        impl $uf {
+            $crate::non_strict! {
+                /// Creates a partition where each index smaller than `len` is alone in its set.
+                pub fn new(len: usize) -> Self {
+                    $uf {
+                        parent: (0..len).collect(),
+                        rank: $crate::alloc::vec![0; len],
+                        sets: len,
+                    }
+                }
+            }
+            /// Number of indices in the partition.
+            #[inline]
+            pub fn len(&self) -> usize {
+                self.parent.len()
+            }
+            /// True if the partition has no indices.
+            #[inline]
+            pub fn is_empty(&self) -> bool {
+                self.parent.is_empty()
+            }
+            /// Number of disjoint sets.
+            #[inline]
+            pub fn set_count(&self) -> usize {
+                self.sets
+            }
+
+            /// Adds a new index in its own set.
+            ///
+            /// Like the `push` of a map, this is a legal source of indices in strict mode.
+            pub fn make_set(&mut self) -> $t {
+                let val = self.parent.len();
+                self.parent.push(val);
+                self.rank.push(0);
+                self.sets += 1;
+                $t { val }
+            }
+
+            /// Root of the tree of an index, panics if the index is out of bounds.
+            #[inline]
+            fn root(&mut self, idx: $t) -> usize {
+                if idx.val >= self.parent.len() {
+                    $crate::oob::index(stringify!($t), idx.val, stringify!($uf), self.parent.len())
+                }
+                let mut root = idx.val;
+                while self.parent[root] != root {
+                    root = self.parent[root]
+                }
+                // Path compression.
+                let mut current = idx.val;
+                while current != root {
+                    current = core::mem::replace(&mut self.parent[current], root)
+                }
+                root
+            }
+            /// Representative of the set of an index.
+            ///
+            /// Two indices are in the same set iff they have the same representative, which changes
+            /// when the set is merged with another one.
+            #[inline]
+            pub fn find(&mut self, idx: $t) -> $t {
+                $t { val: self.root(idx) }
+            }
+            /// True if two indices are in the same set.
+            #[inline]
+            pub fn same_set(&mut self, lft: $t, rgt: $t) -> bool {
+                self.root(lft) == self.root(rgt)
+            }
+            /// Merges the sets of two indices, false if they were already in the same set.
+            pub fn union(&mut self, lft: $t, rgt: $t) -> bool {
+                let (lft, rgt) = (self.root(lft), self.root(rgt));
+                if lft == rgt {
+                    return false;
+                }
+                match self.rank[lft].cmp(&self.rank[rgt]) {
+                    core::cmp::Ordering::Less => self.parent[lft] = rgt,
+                    core::cmp::Ordering::Greater => self.parent[rgt] = lft,
+                    core::cmp::Ordering::Equal => {
+                        self.parent[rgt] = lft;
+                        self.rank[lft] += 1;
+                    }
+                }
+                self.sets -= 1;
+                true
+            }
+
+            /// Turns the partition into its sets, ordered by smallest index.
+            ///
+            /// The sets can be any collection of indices, typically the set types generated by
+            /// `btree set`, `btree set struct` or `bit set`.
+            pub fn into_groups<S>(mut self) -> $crate::alloc::vec::Vec<S>
+            where
+                S: core::iter::FromIterator<$t>,
+            {
+                let len = self.parent.len();
+                // Position of the group of each root in `groups`.
+                let mut group_of = $crate::alloc::vec![usize::MAX; len];
+                let mut groups: $crate::alloc::vec::Vec<$crate::alloc::vec::Vec<$t>> =
+                    $crate::alloc::vec::Vec::with_capacity(self.sets);
+                for val in 0..len {
+                    let root = self.root($t { val });
+                    if group_of[root] == usize::MAX {
+                        group_of[root] = groups.len();
+                        groups.push($crate::alloc::vec::Vec::new());
+                    }
+                    groups[group_of[root]].push($t { val })
+                }
+                groups.into_iter().map(|group| group.into_iter().collect()).collect()
+            }
+        }
+
+        $crate::handle!{ $t $($tail)* }
+    };
+}
+
+/// Union-find construction from the map of the same `new!` invocation.
+///
+/// Only takes the map generated alongside the union-find, whose indices are legal in strict mode.
+#[macro_export]
+#[doc(hidden)]
+macro_rules! map_union_find_codegen {
+    ($t:ident, $map:ident, $uf:ident) => {
+        impl $uf {
+            /// Creates a partition where each index of a map is alone in its set.
+            ///
+            /// Like [`Self::make_set`], this is a legal source of indices in strict mode, since the
+            /// map's indices are legal.
+            pub fn new_like<T>(map: &$map<T>) -> Self {
+                let len = map.len();
+                $uf {
+                    parent: (0..len).collect(),
+                    rank: $crate::alloc::vec![0; len],
+                    sets: len,
+                }
+            }
+        }
+    };
+}
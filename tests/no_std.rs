@@ -0,0 +1,149 @@
+//! Generated code only relies on `core` and `alloc`.
+
+#![no_std]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+safe_index::new! {
+    /// Indices of clients.
+    Client,
+    /// Map from clients to something.
+    map: Clients,
+    /// Set of clients.
+    btree set: ClientSet,
+    /// Set of clients, as a struct.
+    btree set struct: ClientSetStruct,
+    /// Map from clients to something.
+    btree map: ClientMap,
+    /// Map from clients to something, as a struct.
+    btree map struct: ClientMapStruct,
+    /// Range of clients.
+    range: ClientRange,
+    /// Bit set of clients.
+    bit set: ClientBits,
+    /// Interval set of clients.
+    interval set: ClientIntervals,
+    /// Slice of clients.
+    slice: ClientSlice,
+    /// Heap of clients.
+    heap: ClientHeap,
+    /// Shared map.
+    shared map: ClientShared,
+    /// Partition of clients.
+    union find: ClientClasses,
+}
+
+safe_index::new! {
+    /// Indices of jobs.
+    Job,
+    /// Map from jobs to something.
+    map: Jobs,
+    /// Interner of jobs, hashbrown's hash map without `std`.
+    #[cfg(any(feature = "std", feature = "hashbrown"))]
+    interner: JobNames,
+    /// Sparse map.
+    sparse map: JobSparse,
+    /// Deque map.
+    deque map: JobDeque,
+    /// Slot map.
+    slot map: JobSlots,
+    /// Counter.
+    counter: JobCounter,
+    /// Array map.
+    array map: JobArray[4],
+    /// Bounded jobs.
+    bounded: SmallJob[4] with map: SmallJobs,
+    /// Hash set of jobs, hashbrown's without `std`.
+    #[cfg(any(feature = "std", feature = "hashbrown"))]
+    hash set: JobHSet,
+    /// Hash map from jobs to something, hashbrown's without `std`.
+    #[cfg(any(feature = "std", feature = "hashbrown"))]
+    hash map: JobHMap,
+    base: 1,
+}
+
+safe_index::new! {
+    /// Indices of files.
+    tagged File: FileTag,
+    /// Map from files to something.
+    map: Files,
+    /// Set of files.
+    btree set: FileSet,
+}
+
+#[test]
+fn no_std() {
+    let mut clients = Clients::new();
+    let c_0 = clients.push("a");
+    let c_1 = clients.push("b");
+    let c_2 = clients.push("c");
+
+    let set: ClientSet = clients.indices().collect();
+    assert_eq! { set.len(), 3 }
+    let mut set_struct = ClientSetStruct::new();
+    set_struct.insert(c_1);
+    assert!(set_struct.contains(c_1));
+    let mut map = ClientMap::new();
+    map.insert(c_2, 2);
+    assert_eq! { map.get(&c_2), Some(&2) }
+
+    let range = ClientRange::new(c_0, c_2);
+    let in_range: Vec<Client> = range.into_iter().collect();
+    assert_eq! { in_range, [c_0, c_1] }
+    assert_eq! { c_0.up_to(c_2).len(), 2 }
+
+    let bits: ClientBits = range.into_iter().collect();
+    assert_eq! { bits.len(), 2 }
+    let mut classes = ClientClasses::new_like(&clients);
+    classes.union(c_0, c_2);
+    assert!(classes.same_set(c_2, c_0));
+
+    let shared = clients.into_shared();
+    assert_eq! { shared[c_1], "b" }
+
+    let mut counter = JobCounter::new();
+    let (_, j_1) = (counter.next(), counter.next());
+    assert_eq! { j_1.to_external(), 2 }
+    let small = SmallJob::try_from_idx(j_1).unwrap();
+    let mut smalls = SmallJobs::filled(0);
+    smalls[small] = 7;
+    assert_eq! { smalls.as_slice(), [0, 7, 0, 0] }
+
+    let mut files = Files::new();
+    let f_0: File = files.push(c_0);
+    let files: FileSet = core::iter::once(f_0).collect();
+    assert_eq! { files.len(), 1 }
+}
+
+#[test]
+#[cfg(any(feature = "std", feature = "hashbrown"))]
+fn no_std_hash() {
+    let mut jobs = Jobs::new();
+    let j_0 = jobs.push("a");
+    let j_1 = jobs.push("b");
+    let set: JobHSet = jobs.indices().collect();
+    assert!(set.contains(&j_0) && set.contains(&j_1));
+    let mut map = JobHMap::new();
+    map.insert(j_1, 7);
+    assert_eq! { map.get(&j_1), Some(&7) }
+    assert_eq! { map.get(&j_0), None }
+    let mut names = JobNames::new();
+    assert_eq! { names.intern("a"), j_0 }
+}
+
+safe_index::relation! {
+    /// Relates clients and jobs.
+    ClientJobs: Client <-> Job
+}
+
+#[test]
+fn no_std_relation() {
+    let mut clients = Clients::new();
+    let mut jobs = Jobs::new();
+    let (c_0, j_0) = (clients.push(()), jobs.push(()));
+    let mut links = ClientJobs::new();
+    assert!(links.insert(c_0, j_0));
+    assert_eq! { links.right_of(c_0).collect::<Vec<_>>(), [j_0] }
+}
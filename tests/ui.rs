@@ -0,0 +1,53 @@
+//! Compile errors of [`new`](safe_index::new), of [`matrix`](safe_index::matrix) and of the `index`
+//! attribute.
+
+#[test]
+fn new() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/new/*.rs");
+}
+
+/// Matrices build their rows with the private constructors of the row slice.
+#[test]
+fn matrix() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/matrix/*.rs");
+}
+
+/// In strict mode, code outside the crate cannot build indices through `SafeIndex`.
+#[test]
+#[cfg(feature = "strict")]
+fn strict() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/strict/*.rs");
+}
+
+/// Index types implement `Default` unless `strict` is active.
+#[test]
+#[cfg(not(feature = "strict"))]
+fn derive_default() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/derive/*.rs");
+}
+
+/// `full` derives its names through the `macros` feature.
+#[test]
+#[cfg(feature = "macros")]
+fn full() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/full/*.rs");
+}
+
+#[test]
+#[cfg(not(feature = "macros"))]
+fn full_without_macros() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/full_without_macros/*.rs");
+}
+
+#[test]
+#[cfg(feature = "macros")]
+fn index_attribute() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/index/*.rs");
+}
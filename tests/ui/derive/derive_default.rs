@@ -0,0 +1,9 @@
+safe_index::new! {
+    /// Indices of clients.
+    #[derive(Default)]
+    Client,
+    /// Map from clients to something.
+    map: Clients,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+safe_index::new! {
+    /// Indices of clients.
+    Client,
+    full: Client,
+    /// Same name as the map `full` derives.
+    btree set struct: ClientMap,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+safe_index::new! {
+    /// Indices of clients.
+    Client,
+    /// Bit set of clients.
+    bit set: ClientBits,
+    full: Clients,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+safe_index::new! {
+    /// Indices of clients.
+    Client,
+    full: Client,
+    /// `full` already declares a range.
+    range: Clients,
+}
+
+fn main() {}
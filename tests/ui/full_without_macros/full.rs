@@ -0,0 +1,7 @@
+safe_index::new! {
+    /// Indices of clients.
+    Client,
+    full: Client,
+}
+
+fn main() {}
@@ -0,0 +1,4 @@
+#[safe_index::index(map = "2Clients", btree_set = ClientSet, range = "Client Range")]
+pub struct Client;
+
+fn main() {}
@@ -0,0 +1,4 @@
+#[safe_index::index(array_map = "ClientArray", map = "Clients[4]")]
+pub struct Client;
+
+fn main() {}
@@ -0,0 +1,4 @@
+#[safe_index::index(range = "ClientRange", map = "Clients", range = "OtherClientRange")]
+pub struct Client;
+
+fn main() {}
@@ -0,0 +1,7 @@
+#[safe_index::index(full, range = "OtherClientRange", map = "ClientBSet")]
+pub struct Client;
+
+#[safe_index::index(full = "Client[4]", map = "ClientMap")]
+pub struct File;
+
+fn main() {}
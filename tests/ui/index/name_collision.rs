@@ -0,0 +1,4 @@
+#[safe_index::index(map = "Client", btree_set = "ClientSet", btree_map = "ClientSet")]
+pub struct Client;
+
+fn main() {}
@@ -0,0 +1,4 @@
+#[safe_index::index(map = "Clients")]
+struct Client<T>(T);
+
+fn main() {}
@@ -0,0 +1,4 @@
+#[safe_index::index(map = "Clients", btree = "ClientSet")]
+pub struct Client;
+
+fn main() {}
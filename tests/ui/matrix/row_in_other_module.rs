@@ -0,0 +1,21 @@
+mod idx {
+    safe_index::new! {
+        /// Indices of clients.
+        Client,
+    }
+    safe_index::new! {
+        /// Indices of files.
+        File,
+        /// Slice of things indexed by files.
+        slice: FileSlice,
+    }
+}
+
+use idx::{Client, File, FileSlice};
+
+safe_index::matrix! {
+    /// Matrix from clients and files to something.
+    ClientFileMatrix<Client, File>, row: FileSlice
+}
+
+fn main() {}
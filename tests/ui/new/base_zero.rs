@@ -0,0 +1,9 @@
+safe_index::new! {
+    /// Nodes of a graph.
+    Node,
+    /// Map from nodes to something.
+    map: Nodes,
+    base: 0,
+}
+
+fn main() {}
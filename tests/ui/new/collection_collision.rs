@@ -0,0 +1,12 @@
+safe_index::new! {
+    /// Indices of clients.
+    Client,
+    /// Set of clients.
+    btree set: Clients,
+    /// Range of clients.
+    range: ClientRange,
+    /// Map from clients to something.
+    map: Clients,
+}
+
+fn main() {}
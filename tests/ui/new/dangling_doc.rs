@@ -0,0 +1,7 @@
+safe_index::new! {
+    Client,
+    map: Clients,
+    /// Set of clients.
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+safe_index::new! {
+    /// Indices of clients.
+    #[derive(serde::Serialize, Clone)]
+    Client,
+    /// Map from clients to something.
+    map: Clients,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+safe_index::new! {
+    Client,
+    map: Clients
+    /// Set of clients.
+    btree set: ClientSet,
+}
+
+fn main() {}
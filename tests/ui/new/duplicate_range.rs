@@ -0,0 +1,12 @@
+safe_index::new! {
+    /// Indices of clients.
+    Client,
+    /// Range of clients.
+    range: ClientRange,
+    /// Map from clients to something.
+    map: Clients,
+    /// Another range of clients.
+    range: OtherClientRange,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+safe_index::new! {
+    /// Indices of clients.
+    Client,
+    /// Map from clients to something.
+    map: Client,
+}
+
+fn main() {}
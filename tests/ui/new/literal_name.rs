@@ -0,0 +1,6 @@
+safe_index::new! {
+    Client,
+    map: "Clients",
+}
+
+fn main() {}
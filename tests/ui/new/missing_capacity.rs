@@ -0,0 +1,6 @@
+safe_index::new! {
+    Client,
+    array map: Clients,
+}
+
+fn main() {}
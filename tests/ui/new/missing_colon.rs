@@ -0,0 +1,6 @@
+safe_index::new! {
+    Client,
+    btree map ClientMap,
+}
+
+fn main() {}
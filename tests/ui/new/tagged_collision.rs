@@ -0,0 +1,8 @@
+safe_index::new! {
+    /// Indices of clients.
+    tagged Client: ClientTag,
+    /// Map from clients to something.
+    map: ClientTag,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+safe_index::new! {
+    /// Indices of clients.
+    tagged Client: ClientTag,
+    /// Map from clients to something.
+    map: Clients,
+    /// Set of clients, as a struct.
+    btree set struct: ClientSet,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+safe_index::new! {
+    Client,
+    map: Clients:
+    btree set: ClientSet,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+//! Strict mode leaves no way to build an index out of a `usize` through `SafeIndex`.
+
+safe_index::new! {
+    /// Indices of clients.
+    Client,
+    /// Map from clients to something.
+    map: Clients,
+}
+
+use safe_index::SafeIndex;
+
+fn main() {
+    let _ = Client::__of(42, safe_index::__Legal(()));
+    let _ = Client::from_usize(42);
+    let _ = Client::ZERO;
+    let _ = <Client as SafeIndex>::as_usize;
+}
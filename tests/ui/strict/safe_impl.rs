@@ -0,0 +1,31 @@
+//! A user implementation of `SafeIndex` must be `unsafe`, and gets nothing to build other indices.
+
+safe_index::new! {
+    /// Indices of clients.
+    Client,
+    /// Map from clients to something.
+    map: Clients,
+}
+
+/// Index type written by hand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Mine(usize);
+impl std::fmt::Display for Mine {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.0.fmt(fmt)
+    }
+}
+impl From<Mine> for usize {
+    fn from(mine: Mine) -> usize {
+        mine.0
+    }
+}
+
+impl safe_index::SafeIndex for Mine {
+    const NAME: &'static str = "Mine";
+    fn as_usize(&self) -> usize {
+        self.0
+    }
+}
+
+fn main() {}
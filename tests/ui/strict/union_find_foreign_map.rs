@@ -0,0 +1,36 @@
+//! `new_like` only takes the map of the same invocation, a foreign map could claim any length.
+
+safe_index::new! {
+    /// Indices of clients.
+    Client,
+    /// Map from clients to something.
+    map: Clients,
+    /// Partition of clients.
+    union find: ClientClasses,
+}
+
+/// Map pretending to have many elements.
+pub struct Liar;
+impl safe_index::SafeMap for Liar {
+    type Idx = Client;
+    type Elem = ();
+    fn len(&self) -> usize {
+        1000
+    }
+    fn push(&mut self, _: ()) -> Client {
+        unimplemented!()
+    }
+    fn get(&self, _: Client) -> Option<&()> {
+        None
+    }
+    fn get_mut(&mut self, _: Client) -> Option<&mut ()> {
+        None
+    }
+    fn index_from_usize(&self, _: usize) -> Option<Client> {
+        None
+    }
+}
+
+fn main() {
+    let _ = ClientClasses::new_like(&Liar);
+}